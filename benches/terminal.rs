@@ -0,0 +1,139 @@
+use bevy::ecs::event::Events;
+use bevy::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use leafwing_terminal::{
+    parse_command, AddTerminalCommand, CommandArgInfo, CommandInfo, PrintTerminalLine,
+    TerminalCommand, TerminalCommandQueue, TerminalConfiguration, TerminalCorePlugin,
+    TerminalState,
+};
+
+const COMMAND_LINES: [&str; 5] = [
+    "help",
+    "log \"hello world\" 10",
+    "spawn_enemy goblin 1_000 12.5 true",
+    "teleport 10 0 5",
+    "say 'single quoted string with \\\"escapes\\\"' FALSE .5e3",
+];
+
+fn parsing(c: &mut Criterion) {
+    let lines: Vec<_> = COMMAND_LINES.iter().cycle().take(10_000).collect();
+
+    c.bench_function("parse 10k command lines", |b| {
+        b.iter(|| {
+            for line in &lines {
+                let _ = black_box(parse_command(line));
+            }
+        })
+    });
+}
+
+fn help_text(c: &mut Criterion) {
+    let mut group = c.benchmark_group("help_text");
+    for num_args in [1, 10, 100] {
+        let info = CommandInfo {
+            name: "wide".to_string(),
+            description: Some("A command with\nmany arguments".to_string()),
+            args: (0..num_args)
+                .map(|i| CommandArgInfo {
+                    name: format!("arg_{i}"),
+                    ty: "string".to_string(),
+                    description: Some(format!("argument number {i}")),
+                    optional: i % 2 == 1,
+                    default: None,
+                })
+                .collect(),
+            ..Default::default()
+        };
+        group.bench_with_input(BenchmarkId::from_parameter(num_args), &info, |b, info| {
+            b.iter(|| black_box(info.help_text()))
+        });
+    }
+    group.finish();
+}
+
+fn receive_lines(c: &mut Criterion) {
+    c.bench_function("receive 100k lines", |b| {
+        b.iter_batched(
+            || {
+                let mut app = App::new();
+                app.add_plugins(MinimalPlugins)
+                    .add_plugin(TerminalCorePlugin);
+                let mut config = app.world.resource_mut::<TerminalConfiguration>();
+                config.print_queue_size = 0;
+                config.print_lines_per_frame = 0;
+                app.update();
+                let mut events = app.world.resource_mut::<Events<PrintTerminalLine>>();
+                for i in 0..100_000 {
+                    events.send(PrintTerminalLine::new(format!("line {i}")));
+                }
+                app
+            },
+            |mut app| app.update(),
+            BatchSize::PerIteration,
+        )
+    });
+}
+
+/// Keeps a note
+#[derive(TerminalCommand)]
+#[terminal_command(name = "note")]
+struct NoteCommand {
+    text: String,
+}
+
+fn note_command(mut note: TerminalCommand<NoteCommand>) {
+    if let Some(NoteCommand { text }) = note.take() {
+        black_box(text);
+    }
+}
+
+fn run_commands(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run_command");
+    for len in [16, 4_096, 1 << 20] {
+        let line = format!("note \"{}\"", "a".repeat(len));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &line, |b, line| {
+            b.iter_batched(
+                || {
+                    let mut app = App::new();
+                    app.add_plugins(MinimalPlugins)
+                        .add_plugin(TerminalCorePlugin)
+                        .add_terminal_command::<NoteCommand, _, _>(note_command);
+                    app.update();
+                    app.world
+                        .resource_mut::<TerminalCommandQueue>()
+                        .submit(line)
+                        .unwrap();
+                    app
+                },
+                |mut app| app.update(),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn history(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_history");
+    for history_size in [20, 500, 5_000] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(history_size),
+            &history_size,
+            |b, &history_size| {
+                let mut state = TerminalState::default();
+                b.iter(|| state.push_history("spawn_enemy goblin 10".to_string(), history_size))
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    parsing,
+    help_text,
+    receive_lines,
+    run_commands,
+    history
+);
+criterion_main!(benches);
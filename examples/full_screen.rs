@@ -1,22 +1,16 @@
 use bevy::prelude::*;
-use leafwing_terminal::{TerminalConfiguration, TerminalPlugin};
+use leafwing_terminal::prelude::*;
+use leafwing_terminal::TerminalLayout;
 
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugin(TerminalPlugin)
-        .add_system_to_stage(CoreStage::PreUpdate, sync_full_screen)
+        .insert_resource(TerminalConfiguration {
+            layout: TerminalLayout::TopDropDown {
+                height_fraction: 1.0,
+            },
+            ..Default::default()
+        })
         .run();
 }
-
-fn sync_full_screen(mut config: ResMut<TerminalConfiguration>, windows: Res<Windows>) {
-    if windows.is_changed() {
-        let window = windows.get_primary().unwrap();
-
-        config.left_pos = 0.0;
-        config.top_pos = 0.0;
-
-        config.width = window.width();
-        config.height = window.height();
-    }
-}
@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use leafwing_terminal::{reply, AddTerminalCommand, TerminalCommand, TerminalPlugin};
+use leafwing_terminal::prelude::*;
 
 fn main() {
     App::new()
@@ -22,6 +22,12 @@ struct LogCommand {
 fn log_command(mut log: TerminalCommand<LogCommand>) {
     if let Some(LogCommand { msg, num }) = log.take() {
         let repeat_count = num.unwrap_or(1);
+        if repeat_count > 100 {
+            reply_warn!(
+                log,
+                "printing {repeat_count} times, this might take a while"
+            );
+        }
 
         for _ in 0..repeat_count {
             reply!(log, "{msg}");
@@ -0,0 +1,28 @@
+use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use bevy::prelude::*;
+use leafwing_terminal::prelude::*;
+
+/// Fills the scrollback with 10k lines, logging the frame time, to check scrolling stays smooth.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(TerminalPlugin)
+        .insert_resource(TerminalConfiguration {
+            scrollback_size: 0,
+            ..Default::default()
+        })
+        .insert_resource(TerminalOpen(true))
+        .add_startup_system(fill_scrollback)
+        .run();
+}
+
+fn fill_scrollback(mut terminal_line: EventWriter<PrintTerminalLine>) {
+    for i in 0..10_000 {
+        terminal_line.send(PrintTerminalLine::new(format!("line {i}")));
+    }
+    terminal_line.send(PrintTerminalLine::new(
+        "a long line, wrapped over several rows: ".repeat(20),
+    ));
+}
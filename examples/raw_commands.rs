@@ -1,11 +1,12 @@
 use bevy::prelude::*;
-use leafwing_terminal::{TerminalCommandEntered, TerminalPlugin};
+use leafwing_terminal::prelude::*;
 
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugin(TerminalPlugin)
         .add_system(raw_commands)
+        .add_system(parse_failures)
         .run();
 }
 
@@ -14,3 +15,9 @@ fn raw_commands(mut terminal_commands: EventReader<TerminalCommandEntered>) {
         println!(r#"Entered command "{command}" with args {:#?}"#, args);
     }
 }
+
+fn parse_failures(mut parse_failures: EventReader<TerminalParseFailed>) {
+    for TerminalParseFailed { input, error } in parse_failures.iter() {
+        println!(r#"Failed to parse "{input}": {error}"#);
+    }
+}
@@ -0,0 +1,29 @@
+use bevy::prelude::*;
+use leafwing_terminal::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(TerminalPlugin)
+        .add_terminal_command::<RespawnCommand, _, _>(respawn_command)
+        .add_terminal_toolbar(|ui, toolbar| {
+            if ui.button("Respawn").clicked() {
+                toolbar.submit("respawn").unwrap();
+            }
+            if ui.button("Clear").clicked() {
+                toolbar.submit("clear").unwrap();
+            }
+        })
+        .run();
+}
+
+/// Respawns the player
+#[derive(TerminalCommand)]
+#[terminal_command(name = "respawn")]
+struct RespawnCommand;
+
+fn respawn_command(mut respawn: TerminalCommand<RespawnCommand>) {
+    if respawn.take().is_some() {
+        reply_ok!(respawn, "Respawned");
+    }
+}
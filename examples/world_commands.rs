@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+use leafwing_terminal::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(TerminalPlugin)
+        .add_startup_system(print_hint)
+        .run();
+}
+
+fn print_hint(mut terminal_line: EventWriter<PrintTerminalLine>) {
+    terminal_line.send(PrintTerminalLine::new(
+        r#"Try `spawn "scenes/crate.gltf" 0 1 0`, then `despawn` the printed entity with `--recursive`"#
+            .to_string(),
+    ));
+}
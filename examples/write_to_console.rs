@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use leafwing_terminal::{PrintTerminalLine, TerminalPlugin};
+use leafwing_terminal::prelude::*;
 
 fn main() {
     App::new()
@@ -1,31 +1,76 @@
 use better_bae::{FromAttributes, TryFromAttributes};
+use leafwing_terminal_parser::validate_command_name;
 use proc_macro::{Span, TokenStream};
-use quote::{quote, ToTokens};
+use quote::{quote, quote_spanned, ToTokens};
 use syn::{parse_macro_input, spanned::Spanned, DeriveInput};
 
 #[derive(Debug, Eq, PartialEq, FromAttributes)]
 #[bae("terminal_command")]
 struct TerminalCommandContainerAttr {
     name: syn::Lit,
+    cooldown: Option<syn::Lit>,
+    exclude_from_history: Option<()>,
+    destructive: Option<()>,
+    deprecated: Option<syn::Lit>,
 }
 
 /// Implement
 /// [`CommandName`](https://docs.rs/leafwing_terminal/latest/leafwing_terminal/trait.CommandName.html),
 /// [`CommandArgs`](https://docs.rs/leafwing_terminal/latest/leafwing_terminal/trait.CommandArgs.html) and
 /// [`CommandHelp`](https://docs.rs/leafwing_terminal/latest/leafwing_terminal/trait.CommandHelp.html)
-/// for a struct.
+/// for a struct, and
+/// [`IntoValues`](https://docs.rs/leafwing_terminal/latest/leafwing_terminal/trait.IntoValues.html)
+/// when all its fields implement `IntoValue`.
 ///
 /// Doc comments are used to provide argument and command help.
+/// Lines of a paragraph are joined, paragraphs stay separated by a blank line,
+/// and indented lines and fenced code blocks are kept as they are, so help doesn't wrap them.
 ///
 /// # Container Attributes
 ///
 /// - `#[terminal_command(name = "log")`
 ///
-///   Specify the terminal command name.
+///   Specify the terminal command name: an ASCII letter or `_`, followed by ASCII letters, digits and `_`.
+///
+/// - `#[terminal_command(cooldown = "2s")]`
+///
+///   Specify a minimum duration between invocations, in `ms`, `s` or `m`.
+///
+/// - `#[terminal_command(exclude_from_history)]`
+///
+///   Don't add invocations to the input history.
+///
+/// - `#[terminal_command(destructive)]`
+///
+///   Mark the command as changing or deleting state in a way that shouldn't happen unattended,
+///   see `UnfocusedPolicy::DenyDestructive`.
+///
+/// - `#[terminal_command(deprecated = "use 'spawn wave' instead")]`
+///
+///   Mark the command as deprecated, with a note on what to use instead.
+///   It still runs, after a warning with the note, and is left out of completion unless nothing else matches.
+///
+/// - `#[terminal_command(example("teleport 10 0 5", "move to the tower"))]`
+///
+///   List an example invocation in the help, with an optional description. Repeat it for more examples.
+///
+/// # Field Attributes
+///
+/// - `#[terminal_command(flatten)]`
+///
+///   Inline the arguments of a type deriving [`CommandArgGroup`](derive.CommandArgGroup.html),
+///   in place of this field.
+///
+/// - `#[terminal_command(default = "3")]`
+///
+///   Show the value an optional field stands for when it's left out, in help with `HelpFormat::show_defaults`.
+///   The field is still `None` when left out, the command falls back to the value itself.
 ///
 /// # Example
 ///
 /// ```
+/// use leafwing_terminal::TerminalCommand;
+///
 /// /// Prints given arguments to the terminal.
 /// #[derive(TerminalCommand)]
 /// #[terminal_command(name = "log")]
@@ -61,13 +106,28 @@ pub fn derive_terminal_command(input: TokenStream) -> TokenStream {
         }
     };
 
-    let attrs = match TerminalCommandContainerAttr::from_attributes(&ast.attrs) {
+    let (attrs, examples) = match split_examples(&ast.attrs) {
+        Ok(split) => split,
+        Err(err) => return err.into_compile_error().into(),
+    };
+
+    let attrs = match TerminalCommandContainerAttr::from_attributes(&attrs) {
         Ok(attrs) => attrs,
         Err(err) => return err.into_compile_error().into(),
     };
 
     let command_name = match attrs.name {
-        syn::Lit::Str(name) => name.value(),
+        syn::Lit::Str(name) => match validate_command_name(&name.value()) {
+            Ok(()) => name.value(),
+            Err(reason) => {
+                return syn::Error::new_spanned(
+                    name,
+                    format!("name can't be typed in the terminal because {reason}"),
+                )
+                .into_compile_error()
+                .into()
+            }
+        },
         _ => {
             return syn::Error::new_spanned(attrs.name, "name must be a string literal")
                 .into_compile_error()
@@ -75,13 +135,357 @@ pub fn derive_terminal_command(input: TokenStream) -> TokenStream {
         }
     };
 
-    let mut fields = Vec::with_capacity(named_fields.len());
+    let exclude_from_history = attrs.exclude_from_history.is_some();
+    let destructive = attrs.destructive.is_some();
+    let deprecated = match attrs.deprecated {
+        Some(syn::Lit::Str(note)) => quote! {
+            ::std::option::Option::Some(#note.to_string())
+        },
+        Some(note) => {
+            return syn::Error::new_spanned(note, "deprecated must be a string literal")
+                .into_compile_error()
+                .into()
+        }
+        None => quote! {
+            ::std::option::Option::None
+        },
+    };
+
+    let command_cooldown = match attrs.cooldown {
+        Some(syn::Lit::Str(cooldown)) => match parse_duration_secs(&cooldown.value()) {
+            Some(secs) => quote! {
+                Some(::std::time::Duration::from_secs_f64(#secs))
+            },
+            None => {
+                return syn::Error::new_spanned(
+                    cooldown,
+                    "cooldown must be a duration such as \"500ms\", \"2s\" or \"1m\"",
+                )
+                .into_compile_error()
+                .into()
+            }
+        },
+        Some(cooldown) => {
+            return syn::Error::new_spanned(cooldown, "cooldown must be a string literal")
+                .into_compile_error()
+                .into()
+        }
+        None => quote! {
+            None
+        },
+    };
+
+    let ExpandedFields {
+        parse,
+        push_values,
+        value_bounds,
+        arg_info,
+        checks,
+        min_args,
+        max_args,
+        ..
+    } = match expand_fields(&named_fields, false) {
+        Ok(fields) => fields,
+        Err(err) => return err,
+    };
+
+    let command_description = if let Some(description) = get_doc_description(&ast.attrs) {
+        quote! {
+            Some(#description.to_string())
+        }
+    } else {
+        quote! {
+            None
+        }
+    };
+    TokenStream::from(quote! {
+        #[automatically_derived]
+        impl leafwing_terminal::__private::CommandName for #ident {
+            fn command_name() -> &'static str {
+                #command_name
+            }
+        }
+
+        #[automatically_derived]
+        impl leafwing_terminal::__private::CommandArgs for #ident {
+            fn from_values(values: ::std::vec::Vec<leafwing_terminal::__private::ValueRawOwned>) -> ::std::result::Result<Self, leafwing_terminal::__private::FromValueError> {
+                let values = &mut values.into_iter();
+
+                Ok(#ident {
+                    #( #parse )*
+                })
+            }
+        }
+
+        #[automatically_derived]
+        impl leafwing_terminal::__private::IntoValues for #ident
+        where
+            #( #value_bounds, )*
+        {
+            #[allow(unused_variables)]
+            fn into_values(self) -> ::std::vec::Vec<leafwing_terminal::__private::ValueRawOwned> {
+                let mut values = ::std::vec::Vec::new();
+                {
+                    let values = &mut values;
+                    #( #push_values )*
+                }
+                values
+            }
+        }
+
+        #[automatically_derived]
+        impl leafwing_terminal::__private::CommandHelp for #ident {
+            fn command_help() -> ::std::option::Option<leafwing_terminal::__private::CommandInfo> {
+                ::std::option::Option::Some(leafwing_terminal::__private::CommandInfo {
+                    name: #command_name.to_string(),
+                    description: #command_description,
+                    args: #arg_info,
+                    cooldown: #command_cooldown,
+                    exclude_from_history: #exclude_from_history,
+                    destructive: #destructive,
+                    min_args: ::std::option::Option::Some(#min_args),
+                    max_args: #max_args,
+                    examples: ::std::vec![#( #examples ),*],
+                    deprecated: #deprecated,
+                })
+            }
+        }
+
+        #( #checks )*
+    })
+}
+
+/// Implement
+/// [`CommandArgGroup`](https://docs.rs/leafwing_terminal/latest/leafwing_terminal/trait.CommandArgGroup.html)
+/// for a struct, so commands can embed its fields with `#[terminal_command(flatten)]`.
+///
+/// [`IntoValue`](https://docs.rs/leafwing_terminal/latest/leafwing_terminal/trait.IntoValue.html)
+/// is implemented too when all its fields implement it, pushing the values of each field.
+///
+/// Fields are declared as in [`TerminalCommand`](derive.TerminalCommand.html),
+/// and doc comments are used to provide argument help.
+///
+/// # Example
+///
+/// ```
+/// use leafwing_terminal::{CommandArgGroup, TerminalCommand};
+///
+/// #[derive(CommandArgGroup)]
+/// struct PosArgs {
+///     /// X coordinate
+///     x: f64,
+///     /// Y coordinate
+///     y: f64,
+/// }
+///
+/// /// Teleports the player
+/// #[derive(TerminalCommand)]
+/// #[terminal_command(name = "teleport")]
+/// struct TeleportCommand {
+///     #[terminal_command(flatten)]
+///     pos: PosArgs,
+/// }
+/// ```
+#[proc_macro_derive(CommandArgGroup, attributes(terminal_command))]
+pub fn derive_command_arg_group(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    let ident = &ast.ident;
+
+    let named_fields = match ast.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => fields.named,
+        _ => {
+            return syn::Error::new(
+                Span::call_site().into(),
+                "only structs with named fields are supported",
+            )
+            .into_compile_error()
+            .into();
+        }
+    };
+
+    let ExpandedFields {
+        parse,
+        push_values,
+        value_bounds,
+        arg_info,
+        checks,
+        arg_count,
+        min_args,
+        max_args,
+    } = match expand_fields(&named_fields, true) {
+        Ok(fields) => fields,
+        Err(err) => return err,
+    };
+
+    TokenStream::from(quote! {
+        #[automatically_derived]
+        impl leafwing_terminal::__private::CommandArgGroup for #ident {
+            const ARG_COUNT: u8 = #arg_count;
+            const MIN_ARGS: usize = #min_args;
+            const MAX_ARGS: ::std::option::Option<usize> = #max_args;
+
+            #[allow(unused_variables)]
+            fn from_value_iter<I>(values: &mut I, arg_num: u8) -> ::std::result::Result<Self, leafwing_terminal::__private::FromValueError>
+            where
+                I: ::std::iter::Iterator<Item = leafwing_terminal::__private::ValueRawOwned>,
+            {
+                Ok(#ident {
+                    #( #parse )*
+                })
+            }
+
+            fn arg_info() -> ::std::vec::Vec<leafwing_terminal::__private::CommandArgInfo> {
+                #arg_info
+            }
+        }
+
+        #[automatically_derived]
+        impl leafwing_terminal::__private::IntoValue for #ident
+        where
+            #( #value_bounds, )*
+        {
+            #[allow(unused_variables)]
+            fn push_values(self, values: &mut ::std::vec::Vec<leafwing_terminal::__private::ValueRawOwned>) {
+                #( #push_values )*
+            }
+        }
+
+        #( #checks )*
+    })
+}
+
+#[derive(Debug, Eq, PartialEq, FromAttributes)]
+#[bae("terminal_command")]
+struct TerminalCommandFieldAttr {
+    flatten: Option<()>,
+    default: Option<syn::Lit>,
+}
+
+/// Code generated for the fields of a command or argument group.
+struct ExpandedFields {
+    /// `field: value,` for each field, parsed from `values`
+    parse: Vec<proc_macro2::TokenStream>,
+    /// Statements pushing the values of each field to `values`
+    push_values: Vec<proc_macro2::TokenStream>,
+    /// `IntoValue` bounds on the type of each field, under a binder so types which don't
+    /// implement it only leave `IntoValues` unimplemented instead of failing to compile
+    value_bounds: Vec<proc_macro2::TokenStream>,
+    /// `Vec<CommandArgInfo>` with the arguments of each field
+    arg_info: proc_macro2::TokenStream,
+    /// Constant assertions on the order of optional arguments in flattened groups
+    checks: Vec<proc_macro2::TokenStream>,
+    /// Number of arguments, as a `u8`
+    arg_count: proc_macro2::TokenStream,
+    /// Fewest arguments, as a `usize`
+    min_args: proc_macro2::TokenStream,
+    /// Most arguments, as an `Option<usize>`
+    max_args: proc_macro2::TokenStream,
+}
+
+/// Expand the fields of a command or argument group.
+///
+/// Arguments of a group are numbered from its `arg_num` parameter, those of a command from 0.
+fn expand_fields(
+    named_fields: &syn::punctuated::Punctuated<syn::Field, syn::Token![,]>,
+    group: bool,
+) -> Result<ExpandedFields, TokenStream> {
+    let mut parse = Vec::with_capacity(named_fields.len());
+    let mut push_values = Vec::with_capacity(named_fields.len());
+    let mut value_bounds = Vec::with_capacity(named_fields.len());
+    let mut arg_info = Vec::with_capacity(named_fields.len());
+    let mut checks = Vec::new();
+    let mut plain_count = 0u8;
+    let mut plain_min = 0usize;
+    let mut variadic = false;
+    let mut groups: Vec<&syn::Type> = Vec::new();
     let mut previous_optional = None;
-    for (i, syn::Field { ident, ty, .. }) in named_fields.iter().enumerate() {
-        let optional = is_ty_option(ty);
+
+    for syn::Field {
+        attrs, ident, ty, ..
+    } in named_fields
+    {
+        let field_attrs = match TerminalCommandFieldAttr::try_from_attributes(attrs) {
+            Ok(field_attrs) => field_attrs,
+            Err(err) => return Err(err.into_compile_error().into()),
+        };
+        let (flatten, default) = match field_attrs {
+            Some(attrs) => (attrs.flatten.is_some(), attrs.default),
+            None => (false, None),
+        };
+
+        push_values.push(quote_spanned! {ty.span()=>
+            leafwing_terminal::__private::IntoValue::push_values(self.#ident, &mut *values);
+        });
+        value_bounds.push(quote_spanned! {ty.span()=>
+            for<'__values> #ty: leafwing_terminal::__private::IntoValue
+        });
+
+        let index = quote! {
+            #plain_count #( + <#groups as leafwing_terminal::__private::CommandArgGroup>::ARG_COUNT )*
+        };
+        let index = if group {
+            quote!(arg_num.saturating_add(#index))
+        } else {
+            index
+        };
+
+        if flatten {
+            if is_ty_option(ty) || is_ty_vec(ty) {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "flattened fields can't be optional, make the fields of the group optional instead",
+                )
+                .into_compile_error()
+                .into());
+            }
+
+            let group_args = quote!(<#ty as leafwing_terminal::__private::CommandArgGroup>);
+            if previous_optional.is_some() {
+                checks.push(quote_spanned! {ty.span()=>
+                    const _: () = ::std::assert!(
+                        #group_args::MIN_ARGS == 0,
+                        "flattened group has required fields, but an optional field is defined above it - all optional fields must be placed last"
+                    );
+                });
+            }
+            for previous in &groups {
+                checks.push(quote_spanned! {ty.span()=>
+                    const _: () = ::std::assert!(
+                        <#previous as leafwing_terminal::__private::CommandArgGroup>::MIN_ARGS
+                            == <#previous as leafwing_terminal::__private::CommandArgGroup>::ARG_COUNT as usize
+                            || #group_args::MIN_ARGS == 0,
+                        "flattened group has required fields, but a group with optional fields is flattened above it - all optional fields must be placed last"
+                    );
+                });
+            }
+
+            if let Some(default) = default {
+                return Err(syn::Error::new_spanned(
+                    default,
+                    "flattened fields can't have a default, give the fields of the group defaults instead",
+                )
+                .into_compile_error()
+                .into());
+            }
+
+            parse.push(quote_spanned! {ty.span()=>
+                #ident: #group_args::from_value_iter(&mut *values, #index)?,
+            });
+            arg_info.push(quote_spanned! {ty.span()=>
+                #group_args::arg_info()
+            });
+            groups.push(ty);
+            continue;
+        }
+
+        let optional = is_ty_option(ty) || is_ty_vec(ty);
         if !optional {
             if let Some(previous_optional) = previous_optional {
-                return TokenStream::from_iter([
+                return Err(TokenStream::from_iter([
                     TokenStream::from(
                         syn::Error::new_spanned(
                             ty,
@@ -93,96 +497,184 @@ pub fn derive_terminal_command(input: TokenStream) -> TokenStream {
                         syn::Error::new(previous_optional, "all optional fields must be after required fields")
                             .into_compile_error()
                     ),
-                ]);
+                ]));
             }
+            for previous in &groups {
+                checks.push(quote_spanned! {ty.span()=>
+                    const _: () = ::std::assert!(
+                        <#previous as leafwing_terminal::__private::CommandArgGroup>::MIN_ARGS
+                            == <#previous as leafwing_terminal::__private::CommandArgGroup>::ARG_COUNT as usize,
+                        "field is required, but a group with optional fields is flattened above this field - all optional fields must be placed last"
+                    );
+                });
+            }
+            plain_min += 1;
         }
         if optional && previous_optional.is_none() {
             previous_optional = Some(ty.span());
         }
+        variadic |= is_ty_vec(ty);
+        plain_count += 1;
 
-        let index = i as u8;
+        parse.push(quote! {
+            #ident: <#ty as leafwing_terminal::__private::FromValue>::from_value_iter(&mut *values, #index)?,
+        });
 
-        let expanded = quote! {
-            #ident: <#ty as leafwing_terminal::FromValue>::from_value_iter(&mut values, #index)?,
+        let name = ident.as_ref().unwrap().to_string();
+        let ty_string = ty_to_string(ty)
+            .map(|ty_string| quote!(#ty_string))
+            .unwrap_or_else(|| quote!(stringify!(#ty)));
+        let arg_description = if let Some(description) = get_doc_description(attrs) {
+            quote! {
+                Some(#description.to_string())
+            }
+        } else {
+            quote! {
+                None
+            }
+        };
+        let arg_default = match default {
+            Some(default) if !optional => {
+                return Err(syn::Error::new_spanned(
+                    default,
+                    "only optional fields can have a default",
+                )
+                .into_compile_error()
+                .into());
+            }
+            Some(default) => match default_to_string(&default) {
+                Some(default) => quote! {
+                    ::std::option::Option::Some(#default.to_string())
+                },
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        default,
+                        "default must be a string, number or bool literal",
+                    )
+                    .into_compile_error()
+                    .into());
+                }
+            },
+            None => quote! {
+                ::std::option::Option::None
+            },
         };
-        fields.push(expanded);
+        arg_info.push(quote! {
+            ::std::iter::once(leafwing_terminal::__private::CommandArgInfo {
+                name: #name.to_string(),
+                ty: #ty_string.to_string(),
+                description: #arg_description,
+                optional: #optional,
+                default: #arg_default,
+            })
+        });
     }
 
-    let doc_comments = get_doc_comments(&ast.attrs);
-    let command_description = if !doc_comments.is_empty() {
-        let description = doc_comments.join("\n");
+    let arg_count = quote! {
+        #plain_count #( + <#groups as leafwing_terminal::__private::CommandArgGroup>::ARG_COUNT )*
+    };
+    let min_args = quote! {
+        #plain_min #( + <#groups as leafwing_terminal::__private::CommandArgGroup>::MIN_ARGS )*
+    };
+    let max_args = if variadic {
         quote! {
-            Some(#description.to_string())
+            ::std::option::Option::None
         }
     } else {
-        quote! {
-            None
-        }
-    };
-    let command_arg_info = named_fields.iter().map(
-        |syn::Field {
-             attrs, ident, ty, ..
-         }| {
-            let name = ident.as_ref().unwrap().to_string();
-            let ty_string = ty_to_string(ty)
-                .map(|ty_string| quote!(#ty_string))
-                .unwrap_or_else(|| quote!(stringify!(#ty)));
-            let doc_comments = get_doc_comments(attrs);
-            let arg_description = if !doc_comments.is_empty() {
-                let description = doc_comments.join("\n");
-                quote! {
-                    Some(#description.to_string())
-                }
-            } else {
+        let plain_count = plain_count as usize;
+        groups.iter().fold(
+            quote!(::std::option::Option::Some(#plain_count)),
+            |max, group| {
                 quote! {
-                    None
+                    match (#max, <#group as leafwing_terminal::__private::CommandArgGroup>::MAX_ARGS) {
+                        (::std::option::Option::Some(max), ::std::option::Option::Some(group)) => {
+                            ::std::option::Option::Some(max + group)
+                        }
+                        _ => ::std::option::Option::None,
+                    }
                 }
-            };
-            let optional = is_ty_option(ty);
+            },
+        )
+    };
+    let arg_info = quote! {
+        ::std::iter::empty::<leafwing_terminal::__private::CommandArgInfo>()
+            #( .chain(#arg_info) )*
+            .collect::<::std::vec::Vec<_>>()
+    };
 
-            quote! {
-                leafwing_terminal::CommandArgInfo {
-                    name: #name.to_string(),
-                    ty: #ty_string.to_string(),
-                    description: #arg_description,
-                    optional: #optional,
-                }
-            }
-        },
-    );
+    Ok(ExpandedFields {
+        parse,
+        push_values,
+        value_bounds,
+        arg_info,
+        checks,
+        arg_count,
+        min_args,
+        max_args,
+    })
+}
 
-    TokenStream::from(quote! {
-        #[automatically_derived]
-        impl leafwing_terminal::CommandName for #ident {
-            fn command_name() -> &'static str {
-                #command_name
-            }
+/// Split `example(...)` out of the `terminal_command` attributes, which bae can't parse,
+/// returning the remaining attributes and a `CommandExample` for each example.
+fn split_examples(
+    attrs: &[syn::Attribute],
+) -> syn::Result<(Vec<syn::Attribute>, Vec<proc_macro2::TokenStream>)> {
+    let mut remaining = Vec::with_capacity(attrs.len());
+    let mut examples = Vec::new();
+    for attr in attrs {
+        if !attr.path.is_ident("terminal_command") {
+            remaining.push(attr.clone());
+            continue;
         }
-
-        #[automatically_derived]
-        impl leafwing_terminal::CommandArgs for #ident {
-            fn from_values(values: &[leafwing_terminal::ValueRawOwned]) -> ::std::result::Result<Self, leafwing_terminal::FromValueError> {
-                let mut values = values.iter();
-
-                Ok(#ident {
-                    #( #fields )*
-                })
+        let list = match attr.parse_meta()? {
+            syn::Meta::List(list) => list,
+            _ => {
+                remaining.push(attr.clone());
+                continue;
             }
-        }
+        };
 
-        #[automatically_derived]
-        impl leafwing_terminal::CommandHelp for #ident {
-            fn command_help() -> ::std::option::Option<leafwing_terminal::CommandInfo> {
-                ::std::option::Option::Some(leafwing_terminal::CommandInfo {
-                    name: #command_name.to_string(),
-                    description: #command_description,
-                    args: vec![
-                        #( #command_arg_info, )*
-                    ],
-                })
-            }
+        let mut nested = Vec::with_capacity(list.nested.len());
+        for meta in list.nested {
+            let example = match meta {
+                syn::NestedMeta::Meta(syn::Meta::List(example))
+                    if example.path.is_ident("example") =>
+                {
+                    example
+                }
+                meta => {
+                    nested.push(meta);
+                    continue;
+                }
+            };
+            let literals: Vec<_> = example.nested.iter().collect();
+            let (command, description) = match literals.as_slice() {
+                [syn::NestedMeta::Lit(syn::Lit::Str(command))] => {
+                    (command, quote!(::std::option::Option::None))
+                }
+                [syn::NestedMeta::Lit(syn::Lit::Str(command)), syn::NestedMeta::Lit(syn::Lit::Str(description))] => {
+                    (command, quote!(::std::option::Option::Some(#description.to_string())))
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &example,
+                        "example must be a command line and an optional description, such as example(\"teleport 10 0 5\", \"move to the tower\")",
+                    ))
+                }
+            };
+            examples.push(quote! {
+                leafwing_terminal::__private::CommandExample {
+                    command: #command.to_string(),
+                    description: #description,
+                }
+            });
         }
-    })
+
+        let mut attr = attr.clone();
+        attr.tokens = quote!(( #( #nested ),* ));
+        remaining.push(attr);
+    }
+    Ok((remaining, examples))
 }
 
 fn get_doc_comments(attrs: &[syn::Attribute]) -> Vec<String> {
@@ -198,7 +690,7 @@ fn get_doc_comments(attrs: &[syn::Attribute]) -> Vec<String> {
                 .map(|segment| segment.ident == "doc")
                 .unwrap_or(false) =>
             {
-                acc.push(comment.value().trim().to_string())
+                acc.push(comment.value())
             }
             _ => {}
         }
@@ -207,6 +699,82 @@ fn get_doc_comments(attrs: &[syn::Attribute]) -> Vec<String> {
     })
 }
 
+/// The description given by doc comments, laid out for `CommandInfo::help_text`:
+/// lines of a paragraph are joined, paragraphs are separated by a blank line,
+/// and indented lines are kept as they are, as are the lines of fenced code blocks, indented by four spaces.
+fn get_doc_description(attrs: &[syn::Attribute]) -> Option<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut paragraph = String::new();
+    let mut fenced = false;
+    let comments = get_doc_comments(attrs);
+    // Not `lines`, which gives nothing for the empty comment of a blank `///` line
+    for comment in comments.iter().flat_map(|comment| comment.split('\n')) {
+        // `/// text` comes through as " text", anything past that one space is indentation
+        let line = comment.strip_prefix(' ').unwrap_or(comment).trim_end();
+        let fence = line.trim_start().starts_with("```");
+        if !fence && !fenced && !line.is_empty() && !line.starts_with(char::is_whitespace) {
+            if !paragraph.is_empty() {
+                paragraph.push(' ');
+            }
+            paragraph.push_str(line);
+            continue;
+        }
+
+        if !paragraph.is_empty() {
+            lines.push(std::mem::take(&mut paragraph));
+        }
+        if fence {
+            fenced = !fenced;
+        } else if line.is_empty() {
+            // A single blank line between paragraphs, none before the first
+            if fenced || lines.last().is_some_and(|last| !last.is_empty()) {
+                lines.push(String::new());
+            }
+        } else if fenced {
+            lines.push(format!("    {line}"));
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    if !paragraph.is_empty() {
+        lines.push(paragraph);
+    }
+    while lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// The text of a `default = ...` literal, without quotes around strings.
+fn default_to_string(lit: &syn::Lit) -> Option<String> {
+    match lit {
+        syn::Lit::Str(s) => Some(s.value()),
+        syn::Lit::Int(int) => Some(int.base10_digits().to_string()),
+        syn::Lit::Float(float) => Some(float.base10_digits().to_string()),
+        syn::Lit::Bool(b) => Some(b.value.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_duration_secs(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let (num, scale) = if let Some(num) = s.strip_suffix("ms") {
+        (num, 0.001)
+    } else if let Some(num) = s.strip_suffix('s') {
+        (num, 1.0)
+    } else if let Some(num) = s.strip_suffix('m') {
+        (num, 60.0)
+    } else {
+        return None;
+    };
+
+    num.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|num| num.is_finite() && *num >= 0.0)
+        .map(|num| num * scale)
+}
+
 fn is_ty_option(ty: &syn::Type) -> bool {
     let mut ty_string = ty.to_token_stream().to_string();
     ty_string.retain(|c| c != ' ');
@@ -217,11 +785,22 @@ fn is_ty_option(ty: &syn::Type) -> bool {
         || ty_string.starts_with("::std::option::Option<")
 }
 
-fn ty_to_string(ty: &syn::Type) -> Option<&'static str> {
+fn is_ty_vec(ty: &syn::Type) -> bool {
+    let mut ty_string = ty.to_token_stream().to_string();
+    ty_string.retain(|c| c != ' ');
+
+    ty_string.starts_with("Vec<")
+        || ty_string.starts_with("vec::Vec<")
+        || ty_string.starts_with("std::vec::Vec<")
+        || ty_string.starts_with("::std::vec::Vec<")
+}
+
+fn ty_to_string(ty: &syn::Type) -> Option<String> {
     let mut ty_string = ty.to_token_stream().to_string();
     ty_string.retain(|c| c != ' ');
 
     let optional = is_ty_option(ty);
+    let vec = is_ty_vec(ty);
 
     let inner_ty = if optional {
         ty_string
@@ -231,18 +810,38 @@ fn ty_to_string(ty: &syn::Type) -> Option<&'static str> {
             .trim_start_matches("Option")
             .trim_start_matches('<')
             .trim_end_matches('>')
+    } else if vec {
+        ty_string
+            .trim_start_matches("::std::vec::Vec")
+            .trim_start_matches("std::vec::Vec")
+            .trim_start_matches("vec::Vec")
+            .trim_start_matches("Vec")
+            .trim_start_matches('<')
+            .trim_end_matches('>')
     } else {
         &ty_string
     };
 
-    match inner_ty {
-        "String" | "string::String" | "std::string::String" | "::std::string::String" => {
-            Some("string")
-        }
+    let name = match inner_ty {
+        "String" | "string::String" | "std::string::String" | "::std::string::String" => "string",
         "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128"
-        | "usize" => Some("int"),
-        "f32" | "f64" => Some("float"),
-        "bool" => Some("bool"),
-        _ => None,
-    }
+        | "usize" => "int",
+        "f32" | "f64" => "float",
+        "bool" => "bool",
+        "KeyCode" | "keyboard::KeyCode" | "bevy::input::keyboard::KeyCode" => "key",
+        "SocketAddr" | "net::SocketAddr" | "std::net::SocketAddr" => "addr",
+        "IpAddr" | "net::IpAddr" | "std::net::IpAddr" => "ip",
+        "ByName" => "name",
+        "AssetPathArg" => "path",
+        "Entity" | "entity::Entity" | "bevy::prelude::Entity" | "bevy::ecs::entity::Entity" => {
+            "entity"
+        }
+        "Value" | "ValueRawOwned" if vec => "value",
+        _ => return None,
+    };
+    Some(if vec {
+        format!("{name}...")
+    } else {
+        name.to_string()
+    })
 }
@@ -0,0 +1,315 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::parse::{parse_command_name, parse_escaped_char, parse_escaped_whitespace, parse_value};
+use crate::ValueRaw;
+
+/// Why a line couldn't be parsed.
+///
+/// Positions are byte offsets into the parsed line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The line is empty or only whitespace
+    EmptyInput,
+    /// The line doesn't start with a command name, like `2fast` or `"say"`
+    InvalidCommandName {
+        /// The word in place of the command name
+        span: Range<usize>,
+    },
+    /// A quoted string isn't closed
+    UnterminatedString {
+        /// Position of the opening quote
+        start: usize,
+    },
+    /// A backslash in a quoted string isn't followed by a known escape, like `\q`
+    InvalidEscape {
+        /// The backslash and the character after it
+        span: Range<usize>,
+    },
+    /// A word starting like a number which isn't one, like `10x` or `1.5.2`
+    InvalidNumber {
+        /// The whole word
+        span: Range<usize>,
+    },
+    /// A character which can't appear where it is, like `$` or a `'` inside a `"` string
+    UnexpectedChar {
+        /// The character
+        char: char,
+        /// Position of the character
+        pos: usize,
+    },
+}
+
+impl ParseError {
+    /// The part of the line the error is about, empty for [`ParseError::EmptyInput`].
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            ParseError::EmptyInput => 0..0,
+            ParseError::InvalidCommandName { span }
+            | ParseError::InvalidEscape { span }
+            | ParseError::InvalidNumber { span } => span.clone(),
+            ParseError::UnterminatedString { start } => *start..*start + 1,
+            ParseError::UnexpectedChar { char, pos } => *pos..*pos + char.len_utf8(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::EmptyInput => write!(f, "expected a command"),
+            ParseError::InvalidCommandName { span } => {
+                write!(f, "expected a command name at position {}", span.start)
+            }
+            ParseError::UnterminatedString { start } => {
+                write!(f, "unterminated string starting at position {start}")
+            }
+            ParseError::InvalidEscape { span } => {
+                write!(f, "invalid escape sequence at position {}", span.start)
+            }
+            ParseError::InvalidNumber { span } => {
+                write!(f, "invalid number at position {}", span.start)
+            }
+            ParseError::UnexpectedChar { char, pos } => {
+                write!(f, "unexpected {char:?} at position {pos}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Finds why `line` doesn't parse as a command by retracing the grammar of `parse_full_command`.
+pub(crate) fn command_error(line: &str) -> Option<ParseError> {
+    if line.trim().is_empty() {
+        return Some(ParseError::EmptyInput);
+    }
+    let start = skip_spaces(line, 0);
+    match parse_command_name(&line[start..]) {
+        Ok((rest, _)) => args_error(line, line.len() - rest.len(), true),
+        Err(_) => Some(ParseError::InvalidCommandName {
+            span: start..word_end(line, start),
+        }),
+    }
+}
+
+/// Finds why the arguments from `pos` don't parse by retracing the grammar of `parse_value_list`.
+///
+/// With `separated`, the first argument must be preceded by whitespace like the ones after it.
+pub(crate) fn args_error(line: &str, mut pos: usize, mut separated: bool) -> Option<ParseError> {
+    loop {
+        let start = skip_spaces(line, pos);
+        let next = line[start..].chars().next()?;
+        if separated && start == pos {
+            return Some(ParseError::UnexpectedChar { char: next, pos });
+        }
+
+        pos = match next {
+            '"' | '\'' => match string_end(line, start) {
+                Ok(end) => end,
+                Err(err) => return Some(err),
+            },
+            _ => match parse_value(&line[start..]) {
+                Ok((rest, value)) => {
+                    let end = line.len() - rest.len();
+                    let number = matches!(value, ValueRaw::Int(..) | ValueRaw::Float(..));
+                    if number && !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+                        return Some(ParseError::InvalidNumber {
+                            span: start..word_end(line, start),
+                        });
+                    }
+                    end
+                }
+                Err(_) => {
+                    return Some(ParseError::UnexpectedChar {
+                        char: next,
+                        pos: start,
+                    })
+                }
+            },
+        };
+        separated = true;
+    }
+}
+
+/// The position after the closing quote of the string starting at `start`, or why it isn't closed.
+fn string_end(line: &str, start: usize) -> Result<usize, ParseError> {
+    let quote = line[start..].chars().next().unwrap_or('"');
+    let mut pos = start + quote.len_utf8();
+    while let Some(c) = line[pos..].chars().next() {
+        let rest = &line[pos..];
+        pos += match c {
+            '\\' => match (parse_escaped_char(rest), parse_escaped_whitespace(rest)) {
+                (Ok((after, _)), _) | (_, Ok((after, _))) => rest.len() - after.len(),
+                _ => match rest[1..].chars().next() {
+                    Some(escaped) => {
+                        return Err(ParseError::InvalidEscape {
+                            span: pos..pos + 1 + escaped.len_utf8(),
+                        })
+                    }
+                    None => break,
+                },
+            },
+            '"' | '\'' if c == quote => return Ok(pos + 1),
+            '"' | '\'' => return Err(ParseError::UnexpectedChar { char: c, pos }),
+            _ => c.len_utf8(),
+        };
+    }
+    Err(ParseError::UnterminatedString { start })
+}
+
+/// The position after the spaces and tabs at `pos`.
+fn skip_spaces(line: &str, pos: usize) -> usize {
+    line.len() - line[pos..].trim_start_matches([' ', '\t']).len()
+}
+
+/// The position of the first whitespace at or after `pos`.
+fn word_end(line: &str, pos: usize) -> usize {
+    line[pos..]
+        .find(char::is_whitespace)
+        .map_or(line.len(), |end| pos + end)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::ParseError;
+    use crate::{parse_arg_str, parse_terminal_command};
+
+    fn error(line: &str) -> ParseError {
+        parse_terminal_command(line).unwrap_err()
+    }
+
+    #[test]
+    fn it_reports_empty_input() {
+        assert_eq!(error(""), ParseError::EmptyInput);
+        assert_eq!(error("   \t"), ParseError::EmptyInput);
+    }
+
+    #[test]
+    fn it_reports_invalid_command_names() {
+        assert_eq!(
+            error("2fast 10"),
+            ParseError::InvalidCommandName { span: 0..5 }
+        );
+        assert_eq!(
+            error("  \"say\" hi"),
+            ParseError::InvalidCommandName { span: 2..7 }
+        );
+        assert_eq!(error("$"), ParseError::InvalidCommandName { span: 0..1 });
+    }
+
+    #[test]
+    fn it_reports_unterminated_strings() {
+        assert_eq!(
+            error(r#"say "hello"#),
+            ParseError::UnterminatedString { start: 4 }
+        );
+        assert_eq!(
+            error(r#"say "hi" 'there"#),
+            ParseError::UnterminatedString { start: 9 }
+        );
+        assert_eq!(
+            error(r#"say "escaped \""#),
+            ParseError::UnterminatedString { start: 4 }
+        );
+        assert_eq!(
+            error(r#"say "trailing \"#),
+            ParseError::UnterminatedString { start: 4 }
+        );
+        assert_eq!(
+            error("say \"multi\nline"),
+            ParseError::UnterminatedString { start: 4 }
+        );
+    }
+
+    #[test]
+    fn it_reports_invalid_escapes() {
+        assert_eq!(
+            error(r#"say "\q""#),
+            ParseError::InvalidEscape { span: 5..7 }
+        );
+        assert_eq!(
+            error(r#"say "\u{110000}""#),
+            ParseError::InvalidEscape { span: 5..7 }
+        );
+        assert_eq!(
+            error(r#"say "\é""#),
+            ParseError::InvalidEscape { span: 5..8 }
+        );
+    }
+
+    #[test]
+    fn it_reports_invalid_numbers() {
+        assert_eq!(error("give 10x"), ParseError::InvalidNumber { span: 5..8 });
+        assert_eq!(
+            error("move 1.5.2 0"),
+            ParseError::InvalidNumber { span: 5..10 }
+        );
+        assert_eq!(error("scale .5f"), ParseError::InvalidNumber { span: 6..9 });
+    }
+
+    #[test]
+    fn it_reports_unexpected_chars() {
+        assert_eq!(
+            error("say $last"),
+            ParseError::UnexpectedChar { char: '$', pos: 4 }
+        );
+        assert_eq!(
+            error("say hi;"),
+            ParseError::UnexpectedChar { char: ';', pos: 6 }
+        );
+        assert_eq!(
+            error(r#"say"hi""#),
+            ParseError::UnexpectedChar { char: '"', pos: 3 }
+        );
+        assert_eq!(
+            error(r#"say "it's""#),
+            ParseError::UnexpectedChar { char: '\'', pos: 7 }
+        );
+        assert_eq!(
+            error("say hi\nthere"),
+            ParseError::UnexpectedChar { char: '\n', pos: 6 }
+        );
+        assert_eq!(
+            error("toggle true_"),
+            ParseError::UnexpectedChar { char: '_', pos: 11 }
+        );
+    }
+
+    #[test]
+    fn it_reports_argument_errors() {
+        assert_eq!(parse_arg_str("  "), Ok(Vec::new()));
+        assert_eq!(
+            parse_arg_str(r#"1 "two"#),
+            Err(ParseError::UnterminatedString { start: 2 })
+        );
+        assert_eq!(
+            parse_arg_str("$"),
+            Err(ParseError::UnexpectedChar { char: '$', pos: 0 })
+        );
+    }
+
+    #[test]
+    fn spans_cover_the_error() {
+        let line = r#"say "\q""#;
+        assert_eq!(&line[error(line).span()], r"\q");
+        let line = "give 10x";
+        assert_eq!(&line[error(line).span()], "10x");
+        let line = "say hé$";
+        assert_eq!(&line[error(line).span()], "$");
+        assert_eq!(error("").span(), 0..0);
+    }
+
+    #[test]
+    fn it_displays_errors() {
+        assert_eq!(
+            error(r#"say "hello"#).to_string(),
+            "unterminated string starting at position 4"
+        );
+        assert_eq!(error("say $").to_string(), "unexpected '$' at position 4");
+        assert_eq!(error("").to_string(), "expected a command");
+    }
+}
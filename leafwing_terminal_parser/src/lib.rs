@@ -1,7 +1,9 @@
 // pub use parse::parse_str;
+pub use error::ParseError;
 use nom_supreme::{error::ErrorTree, final_parser::final_parser};
 pub use value::{Value, ValueRaw, ValueRawOwned};
 
+mod error;
 mod parse;
 mod value;
 
@@ -13,12 +15,49 @@ pub struct TerminalCommand<'a> {
     pub args: Vec<ValueRaw<'a>>,
 }
 
-pub fn parse_arg_str(s: &str) -> Result<Vec<ValueRaw>, nom::error::Error<&str>> {
-    final_parser(parse::parse_value_list)(s)
+pub fn parse_arg_str(s: &str) -> Result<Vec<ValueRaw<'_>>, ParseError> {
+    final_parser(parse::parse_value_list)(s).map_err(|err: nom::error::Error<&str>| {
+        error::args_error(s, 0, false).unwrap_or_else(|| fallback_error(s, err))
+    })
 }
 
-pub fn parse_terminal_command(s: &str) -> Result<TerminalCommand, nom::error::Error<&str>> {
-    let (command, args) = final_parser(parse::parse_full_command)(s)?;
+pub fn parse_terminal_command(s: &str) -> Result<TerminalCommand<'_>, ParseError> {
+    let (command, args) =
+        final_parser(parse::parse_full_command)(s).map_err(|err: nom::error::Error<&str>| {
+            error::command_error(s).unwrap_or_else(|| fallback_error(s, err))
+        })?;
 
     Ok(TerminalCommand { command, args })
 }
+
+/// The character nom stopped at, for lines the grammar rejects in a way [`ParseError`] doesn't retrace.
+fn fallback_error(s: &str, err: nom::error::Error<&str>) -> ParseError {
+    match err.input.chars().next() {
+        Some(char) => ParseError::UnexpectedChar {
+            char,
+            pos: s.len() - err.input.len(),
+        },
+        None => ParseError::EmptyInput,
+    }
+}
+
+/// Checks that `name` can be typed as a command name:
+/// an ASCII letter or `_`, followed by ASCII letters, digits and `_`.
+///
+/// Returns why it can't be typed otherwise, such as "it contains whitespace".
+pub fn validate_command_name(name: &str) -> Result<(), String> {
+    if let Some(c) = name
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '_')
+    {
+        return Err(match c {
+            c if c.is_whitespace() => "it contains whitespace".to_string(),
+            c => format!("it contains '{c}'"),
+        });
+    }
+    match name.chars().next() {
+        None => Err("it is empty".to_string()),
+        Some(c) if c.is_ascii_digit() => Err(format!("it starts with the digit '{c}'")),
+        Some(_) => Ok(()),
+    }
+}
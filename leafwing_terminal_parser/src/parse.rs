@@ -1,7 +1,9 @@
 use nom::{
     branch::alt,
     bytes::complete::{is_not, tag, take_while_m_n},
-    character::complete::{alpha1, alphanumeric1, char, multispace1, one_of, space0, space1},
+    character::complete::{
+        alpha1, alphanumeric1, char, multispace1, one_of, satisfy, space0, space1,
+    },
     combinator::{map, map_opt, map_res, opt, recognize, value, verify},
     multi::{fold_many0, many0, many1, separated_list0},
     sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
@@ -41,7 +43,7 @@ fn parse_unicode(input: &str) -> IResult<&str, char> {
 }
 
 /// Parse an escaped character: \n, \t, \r, \u{00AC}, etc.
-fn parse_escaped_char(input: &str) -> IResult<&str, char> {
+pub(crate) fn parse_escaped_char(input: &str) -> IResult<&str, char> {
     preceded(
         char('\\'),
         // `alt` tries each parser in sequence, returning the result of
@@ -67,7 +69,7 @@ fn parse_escaped_char(input: &str) -> IResult<&str, char> {
 
 /// Parse a backslash, followed by any amount of whitespace. This is used later
 /// to discard any escaped whitespace.
-fn parse_escaped_whitespace(input: &str) -> IResult<&str, &str> {
+pub(crate) fn parse_escaped_whitespace(input: &str) -> IResult<&str, &str> {
     preceded(char('\\'), multispace1)(input)
 }
 
@@ -96,7 +98,7 @@ enum StringFragment<'a> {
 
 /// Combine parse_literal, parse_escaped_whitespace, and parse_escaped_char
 /// into a StringFragment.
-fn parse_fragment<'a>(input: &'a str) -> IResult<&str, StringFragment<'a>> {
+fn parse_fragment<'a>(input: &'a str) -> IResult<&'a str, StringFragment<'a>> {
     alt((
         // The `map` combinator runs a parser, then applies a function to the output
         // of that parser.
@@ -180,22 +182,24 @@ fn parse_bool(input: &str) -> IResult<&str, (bool, &str)> {
     ))(input)
 }
 
-fn parse_value(input: &str) -> IResult<&str, ValueRaw> {
+pub(crate) fn parse_value(input: &str) -> IResult<&str, ValueRaw<'_>> {
     alt((
         map(parse_string, ValueRaw::String),
         map(parse_float, |(num, raw)| ValueRaw::Float(num, raw)),
         map(parse_int, |(num, raw)| ValueRaw::Int(num, raw)),
         map(parse_bool, |(b, raw)| ValueRaw::Bool(b, raw)),
         map(
-            recognize(many1(one_of(
-                "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOP_-",
-            ))),
+            recognize(many1(alt((
+                one_of("0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_-"),
+                // Words in other scripts, including combining marks of decomposed characters
+                satisfy(|c| !c.is_ascii() && !c.is_whitespace()),
+            )))),
             |s: &str| ValueRaw::String(s.to_string()),
         ),
     ))(input)
 }
 
-pub fn parse_value_list(input: &str) -> IResult<&str, Vec<ValueRaw>> {
+pub fn parse_value_list(input: &str) -> IResult<&str, Vec<ValueRaw<'_>>> {
     delimited(space0, separated_list0(space1, parse_value), space0)(input)
 }
 
@@ -206,7 +210,7 @@ pub fn parse_command_name(input: &str) -> IResult<&str, &str> {
     ))(input)
 }
 
-pub fn parse_full_command(input: &str) -> IResult<&str, (&str, Vec<ValueRaw>)> {
+pub fn parse_full_command(input: &str) -> IResult<&str, (&str, Vec<ValueRaw<'_>>)> {
     delimited(
         space0,
         alt((
@@ -221,9 +225,12 @@ pub fn parse_full_command(input: &str) -> IResult<&str, (&str, Vec<ValueRaw>)> {
 mod tests {
     use pretty_assertions::assert_eq;
 
-    use crate::ValueRaw;
+    use crate::{validate_command_name, ValueRaw};
 
-    use super::{parse_bool, parse_float, parse_int, parse_string, parse_value, parse_value_list};
+    use super::{
+        parse_bool, parse_command_name, parse_float, parse_int, parse_string, parse_value,
+        parse_value_list,
+    };
 
     #[test]
     fn it_parses_strings() {
@@ -287,6 +294,23 @@ mod tests {
             parse_value(r#""hello world""#),
             Ok(("", ValueRaw::String("hello world".to_string())))
         );
+        assert_eq!(
+            parse_value("RShift"),
+            Ok(("", ValueRaw::String("RShift".to_string())))
+        );
+    }
+
+    #[test]
+    fn it_parses_non_ascii_words() {
+        assert_eq!(
+            parse_value("こんにちは 世界"),
+            Ok((" 世界", ValueRaw::String("こんにちは".to_string())))
+        );
+        // Decomposed characters keep their combining marks
+        assert_eq!(
+            parse_value("cafe\u{301}"),
+            Ok(("", ValueRaw::String("cafe\u{301}".to_string())))
+        );
     }
 
     #[test]
@@ -314,4 +338,47 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn it_validates_command_names() {
+        for name in ["help", "spawn_wave", "_debug", "level2"] {
+            assert_eq!(validate_command_name(name), Ok(()));
+        }
+
+        let invalid = [
+            ("give gold", "it contains whitespace"),
+            ("give\tgold", "it contains whitespace"),
+            ("\"quoted\"", "it contains '\"'"),
+            ("'quoted'", "it contains '''"),
+            ("run;", "it contains ';'"),
+            ("#comment", "it contains '#'"),
+            ("$last", "it contains '$'"),
+            ("net.connect", "it contains '.'"),
+            ("2fast", "it starts with the digit '2'"),
+            ("", "it is empty"),
+        ];
+        for (name, reason) in invalid {
+            assert_eq!(
+                validate_command_name(name),
+                Err(reason.to_string()),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn valid_command_names_parse_whole() {
+        for name in [
+            "help",
+            "spawn_wave",
+            "_debug",
+            "level2",
+            "give gold",
+            "run;",
+            "2fast",
+        ] {
+            let parses_whole = matches!(parse_command_name(name), Ok(("", _)));
+            assert_eq!(validate_command_name(name).is_ok(), parses_whole, "{name}");
+        }
+    }
 }
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use crate::TerminalState;
+
+/// Event sent with the plain text of each line added to the terminal scrollback.
+///
+/// The terminal is painted by egui, which screen readers can't read.
+/// Route these lines to text-to-speech or another accessible output instead.
+/// Echoed commands lose their prompt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TerminalAccessibleLine(pub String);
+
+pub(crate) fn send_accessible_lines(
+    state: Res<TerminalState>,
+    mut accessible_lines: EventWriter<TerminalAccessibleLine>,
+    mut seen: Local<usize>,
+) {
+    // Lines are counted since the last clear, including those trimmed from the front,
+    // so the count only goes down when the scrollback is cleared
+    let printed = state.trimmed_lines + state.scrollback.len();
+    if printed < *seen {
+        *seen = 0;
+    }
+    let start = seen.saturating_sub(state.trimmed_lines);
+    for line in state.scrollback.range(start..) {
+        accessible_lines.send(TerminalAccessibleLine(line.command_text().to_string()));
+    }
+    *seen = printed;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+    use bevy::prelude::*;
+
+    use super::TerminalAccessibleLine;
+    use crate::harness::test_app;
+    use crate::{PrintTerminalLine, TerminalState};
+
+    fn drain(app: &mut App) -> Vec<String> {
+        app.world
+            .resource_mut::<Events<TerminalAccessibleLine>>()
+            .drain()
+            .map(|TerminalAccessibleLine(line)| line)
+            .collect()
+    }
+
+    #[test]
+    fn it_sends_each_new_line_once() {
+        let mut app = test_app();
+
+        app.world
+            .resource_mut::<TerminalState>()
+            .scrollback
+            .push_back("$ spawn_wave 3".into());
+        app.world
+            .resource_mut::<Events<PrintTerminalLine>>()
+            .send(PrintTerminalLine::new("spawned 3 enemies".to_string()));
+        app.update();
+        assert_eq!(drain(&mut app), ["spawn_wave 3", "spawned 3 enemies"]);
+
+        app.update();
+        assert!(drain(&mut app).is_empty());
+
+        app.world.resource_mut::<TerminalState>().scrollback.clear();
+        app.update();
+        app.world
+            .resource_mut::<Events<PrintTerminalLine>>()
+            .send(PrintTerminalLine::new("[ok]".to_string()));
+        app.update();
+        assert_eq!(drain(&mut app), ["[ok]"]);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::terminal::LineId;
+use crate::{TerminalLine, TerminalState};
+
+/// Metadata of the divider line added by `mark`, given the anchor's label.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct AnchorDivider(pub(crate) String);
+
+/// A scrollback position recorded by `mark`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ScrollAnchor {
+    pub(crate) label: String,
+    /// The anchor's divider line
+    pub(crate) line: LineId,
+}
+
+impl TerminalState {
+    /// Anchors recorded with `mark`, as their label and scrollback line, oldest first.
+    pub fn anchors(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.anchors
+            .iter()
+            .map(|anchor| (anchor.label.as_str(), self.clamp_line(anchor.line)))
+    }
+
+    /// The first number not already used as an anchor label.
+    pub(crate) fn next_anchor_label(&self) -> String {
+        (1..)
+            .map(|n: usize| n.to_string())
+            .find(|label| self.anchors.iter().all(|anchor| anchor.label != *label))
+            .unwrap()
+    }
+
+    /// Record an anchor at the end of the scrollback, adding `divider` as its line.
+    ///
+    /// An anchor with the same label is moved here.
+    pub(crate) fn mark(&mut self, label: String, divider: String) {
+        self.anchors.retain(|anchor| anchor.label != label);
+        self.anchors.push(ScrollAnchor {
+            label: label.clone(),
+            line: self.line_id(self.scrollback.len()),
+        });
+        self.scrollback
+            .push_back(TerminalLine::from(divider).with_meta(AnchorDivider(label)));
+    }
+
+    /// Scroll to the anchor with `label`, returning whether there is one.
+    ///
+    /// Anchors point at their divider, or the last line if the scrollback no longer reaches it.
+    pub(crate) fn jump_to_anchor(&mut self, label: &str) -> bool {
+        let line = self
+            .anchors
+            .iter()
+            .find(|anchor| anchor.label == label)
+            .map(|anchor| anchor.line);
+        self.scroll_to = line.map(|line| self.line_id(self.clamp_line(line)));
+        self.scroll_to.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnchorDivider;
+    use crate::terminal::LineId;
+    use crate::TerminalState;
+
+    #[test]
+    fn it_records_anchors() {
+        let mut state = TerminalState::default();
+        state.scrollback.push_back("spawned 3 enemies".into());
+        state.mark(state.next_anchor_label(), "-- 1 --".to_string());
+        state.scrollback.push_back("[ok]".into());
+        state.mark("flag".to_string(), "-- flag --".to_string());
+        assert_eq!(state.next_anchor_label(), "2");
+
+        assert_eq!(state.anchors().collect::<Vec<_>>(), [("1", 1), ("flag", 3)]);
+        assert_eq!(
+            state.scrollback[3].get_meta::<AnchorDivider>(),
+            Some(&AnchorDivider("flag".to_string()))
+        );
+
+        // Marking a label again moves it
+        state.mark("1".to_string(), "-- 1 --".to_string());
+        assert_eq!(state.anchors().collect::<Vec<_>>(), [("flag", 3), ("1", 4)]);
+
+        assert!(state.jump_to_anchor("flag"));
+        assert_eq!(state.scroll_to, Some(LineId(3)));
+        assert!(!state.jump_to_anchor("missing"));
+        assert_eq!(state.scroll_to, None);
+    }
+
+    #[test]
+    fn it_clamps_anchors_to_the_scrollback() {
+        let mut state = TerminalState::default();
+        state.scrollback.push_back("old".into());
+        state.scrollback.push_back("older".into());
+        state.mark("end".to_string(), "-- end --".to_string());
+        state.scrollback.truncate(1);
+
+        assert!(state.jump_to_anchor("end"));
+        assert_eq!(state.scroll_to, Some(LineId(0)));
+    }
+}
@@ -0,0 +1,210 @@
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use bevy::asset::{Asset, AssetServer};
+use bevy::prelude::*;
+use leafwing_terminal_parser::ValueRawOwned;
+
+use crate::keys::edit_distance;
+use crate::{FromValue, FromValueError, IntoValue};
+
+/// How many similar paths a missing asset error lists at most.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// A path in the assets folder, such as `"sounds/jump.ogg"` or `"models/crate.gltf#Scene1"`.
+///
+/// Parsing doesn't look at the assets folder, check that the asset exists with [`ValidatedAssetPath`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetPathArg(pub String);
+
+impl FromValue for AssetPathArg {
+    fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        String::from_value(value, arg_num).map(AssetPathArg)
+    }
+
+    fn from_value_owned(value: ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        String::from_value_owned(value, arg_num).map(AssetPathArg)
+    }
+}
+
+impl IntoValue for AssetPathArg {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        self.0.push_values(values);
+    }
+}
+
+/// An [`AssetPathArg`] whose file exists in the assets folder, to be loaded as a `T`.
+///
+/// Checking when the command runs catches typos which would otherwise only fail once the asset loads.
+///
+/// # Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use leafwing_terminal::{AssetPathArg, TerminalCommand, ValidatedAssetPath};
+/// #
+/// /// Plays a sound
+/// #[derive(TerminalCommand)]
+/// #[terminal_command(name = "play_sound")]
+/// struct PlaySoundCommand {
+///     /// Path in the assets folder
+///     path: AssetPathArg,
+/// }
+///
+/// fn play_sound_command(
+///     mut play_sound: TerminalCommand<PlaySoundCommand>,
+///     asset_server: Res<AssetServer>,
+///     audio: Res<Audio>,
+/// ) {
+///     if let Some(PlaySoundCommand { path }) = play_sound.take() {
+///         match ValidatedAssetPath::<AudioSource>::resolve(&asset_server, path) {
+///             Ok(path) => {
+///                 audio.play(path.load(&asset_server));
+///                 play_sound.ok();
+///             }
+///             Err(msg) => {
+///                 play_sound.reply_error(msg);
+///                 play_sound.failed();
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+pub struct ValidatedAssetPath<T: Asset> {
+    path: String,
+    marker: PhantomData<T>,
+}
+
+impl<T: Asset> ValidatedAssetPath<T> {
+    /// Check that the file of `path` exists, ignoring any `#label` after it.
+    ///
+    /// The error names files with a similar name in the same folder.
+    pub fn resolve(asset_server: &AssetServer, path: AssetPathArg) -> Result<Self, String> {
+        let AssetPathArg(path) = path;
+        let file = Path::new(path.split_once('#').map_or(path.as_str(), |(file, _)| file));
+        let asset_io = asset_server.asset_io();
+        let siblings: Vec<PathBuf> = asset_io
+            .read_directory(file.parent().unwrap_or_else(|| Path::new("")))
+            .map(|entries| {
+                entries
+                    .filter(|entry| !asset_io.is_directory(entry))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if siblings.iter().any(|sibling| sibling == file) {
+            return Ok(ValidatedAssetPath {
+                path,
+                marker: PhantomData,
+            });
+        }
+
+        let suggestions = suggest_paths(file, &siblings);
+        match suggestions.is_empty() {
+            true => Err(format!("no asset at '{path}'")),
+            false => Err(format!(
+                "no asset at '{path}', did you mean {}?",
+                suggestions.join(", ")
+            )),
+        }
+    }
+
+    /// The path, including any label.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Load the asset, see [`AssetServer::load`].
+    pub fn load(&self, asset_server: &AssetServer) -> Handle<T> {
+        asset_server.load(self.path.as_str())
+    }
+}
+
+/// Paths of `siblings` whose file name is within a small edit distance of the name of `file`, closest first.
+fn suggest_paths(file: &Path, siblings: &[PathBuf]) -> Vec<String> {
+    let file_name = |path: &Path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+    };
+    let name = file_name(file).unwrap_or_default();
+    let max_distance = (name.chars().count() / 3).clamp(1, 3);
+    let mut candidates: Vec<(usize, String)> = siblings
+        .iter()
+        .filter_map(|sibling| {
+            let distance = edit_distance(&name, &file_name(sibling)?);
+            let path = sibling.to_string_lossy().replace('\\', "/");
+            (distance <= max_distance).then(|| (distance, format!("'{path}'")))
+        })
+        .collect();
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use bevy::asset::{AssetServer, FileAssetIo};
+    use bevy::tasks::TaskPool;
+    use bevy::text::Font;
+
+    use super::{AssetPathArg, ValidatedAssetPath};
+
+    /// An asset server reading a new folder in the temp dir, with `files` in it
+    fn asset_server(name: &str, files: &[&str]) -> AssetServer {
+        let root: PathBuf = std::env::temp_dir().join(format!("leafwing_terminal_{name}"));
+        let _ = fs::remove_dir_all(&root);
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, []).unwrap();
+        }
+        AssetServer::new(FileAssetIo::new(root, false), TaskPool::new())
+    }
+
+    fn resolve(asset_server: &AssetServer, path: &str) -> Result<String, String> {
+        ValidatedAssetPath::<Font>::resolve(asset_server, AssetPathArg(path.to_string()))
+            .map(|path| path.path().to_string())
+    }
+
+    #[test]
+    fn it_resolves_existing_assets() {
+        let asset_server = asset_server("resolve", &["fonts/mono.ttf", "title.ttf"]);
+        assert_eq!(
+            resolve(&asset_server, "fonts/mono.ttf"),
+            Ok("fonts/mono.ttf".to_string())
+        );
+        assert_eq!(
+            resolve(&asset_server, "title.ttf#Regular"),
+            Ok("title.ttf#Regular".to_string())
+        );
+        assert_eq!(
+            resolve(&asset_server, "fonts"),
+            Err("no asset at 'fonts'".to_string())
+        );
+    }
+
+    #[test]
+    fn it_suggests_similar_assets() {
+        let asset_server = asset_server(
+            "suggest",
+            &["fonts/mono.ttf", "fonts/mona.ttf", "fonts/serif.ttf"],
+        );
+        assert_eq!(
+            resolve(&asset_server, "fonts/mnoo.ttf"),
+            Err(
+                "no asset at 'fonts/mnoo.ttf', did you mean 'fonts/mono.ttf', 'fonts/mona.ttf'?"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            resolve(&asset_server, "fnts/mono.ttf"),
+            Err("no asset at 'fnts/mono.ttf'".to_string())
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use std::mem;
+
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::strings::fill;
+use crate::terminal::TerminalState;
+use crate::{TerminalCommand, TerminalCommandQueue, TerminalLine, TerminalStrings};
+
+/// Prints the size of the scrollback, history and command queue
+#[derive(TerminalCommand)]
+#[terminal_command(name = "bufstats")]
+pub(crate) struct BufferStatsCommand;
+
+pub(crate) fn buffer_stats_command(
+    mut bufstats: TerminalCommand<BufferStatsCommand>,
+    state: Res<TerminalState>,
+    queue: Res<TerminalCommandQueue>,
+    strings: Res<TerminalStrings>,
+) {
+    if bufstats.take().is_some() {
+        let scrollback_bytes = state.scrollback.capacity() * mem::size_of::<TerminalLine>()
+            + state
+                .scrollback
+                .iter()
+                .map(|line| line.text.capacity())
+                .sum::<usize>();
+        let kib = format!("{:.1}", scrollback_bytes as f64 / 1024.0);
+        let history_len = state.history().count();
+        bufstats.reply(fill(
+            &strings.scrollback_stats,
+            &[&state.scrollback.len(), &kib],
+        ));
+        bufstats.reply(fill(&strings.history_stats, &[&history_len]));
+        bufstats.reply(fill(&strings.queue_stats, &[&queue.len()]));
+        bufstats.done();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::harness::test_app;
+    use crate::{TerminalCommandQueue, TerminalState};
+
+    #[test]
+    fn it_prints_buffer_stats() {
+        let mut app = test_app();
+        app.world
+            .resource_mut::<TerminalState>()
+            .push_history("spawn_wave 1".to_string(), 20);
+        app.update();
+
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.submit("bufstats").unwrap();
+        queue.submit("help").unwrap();
+        app.update();
+        app.update();
+
+        let scrollback = app.world.resource::<TerminalState>().scrollback();
+        assert!(scrollback
+            .iter()
+            .any(|line| line.as_str().starts_with("Scrollback: 0 lines")));
+        assert!(scrollback.iter().any(|line| line == "History: 1 commands"));
+        assert!(scrollback.iter().any(|line| line == "Queued: 1 commands"));
+    }
+}
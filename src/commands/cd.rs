@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::strings::fill;
+use crate::{TerminalCommand, TerminalPaths, TerminalStrings};
+
+/// Changes the directory relative paths start from, or goes back to where it started without a path
+#[derive(TerminalCommand)]
+#[terminal_command(name = "cd")]
+pub(crate) struct CdCommand {
+    /// Directory to change to
+    path: Option<String>,
+}
+
+pub(crate) fn cd_command(
+    mut cd: TerminalCommand<CdCommand>,
+    mut paths: ResMut<TerminalPaths>,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(CdCommand { path }) = cd.take() {
+        match path {
+            Some(path) if !paths.resolve(&path).is_dir() => {
+                cd.reply_failed(fill(&strings.not_a_directory, &[&path]));
+                return;
+            }
+            Some(path) => paths.set_current(path),
+            None => paths.reset_current(),
+        }
+        cd.reply_ok(paths.current().display().to_string());
+    }
+}
+
+/// Prints the directory relative paths start from
+#[derive(TerminalCommand)]
+#[terminal_command(name = "pwd")]
+pub(crate) struct PwdCommand;
+
+pub(crate) fn pwd_command(mut pwd: TerminalCommand<PwdCommand>, paths: Res<TerminalPaths>) {
+    if pwd.take().is_some() {
+        pwd.reply_ok(paths.current().display().to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::harness::{submit_and_collect, test_app};
+    use crate::TerminalPaths;
+
+    #[test]
+    fn it_changes_the_current_directory() {
+        let root =
+            std::env::temp_dir().join(format!("leafwing_terminal_cd_{}", std::process::id()));
+        fs::create_dir_all(root.join("saves")).unwrap();
+        fs::write(root.join("notes.txt"), "").unwrap();
+
+        let mut app = test_app();
+        app.insert_resource(TerminalPaths::new(&root).with_jail(true));
+        app.update();
+
+        let saves = root.join("saves").display().to_string();
+        assert_eq!(
+            submit_and_collect(&mut app, "cd saves"),
+            [saves.as_str(), "[ok]"]
+        );
+        assert_eq!(
+            submit_and_collect(&mut app, "pwd"),
+            [saves.as_str(), "[ok]"]
+        );
+        assert_eq!(
+            submit_and_collect(&mut app, r#"cd "../notes.txt""#),
+            ["[error] no directory '../notes.txt'", "[failed]"]
+        );
+
+        // The base is the top of a jail
+        let base = root.display().to_string();
+        assert_eq!(
+            submit_and_collect(&mut app, r#"cd "../../..""#),
+            [base.as_str(), "[ok]"]
+        );
+        submit_and_collect(&mut app, "cd saves");
+        assert_eq!(submit_and_collect(&mut app, "cd"), [base.as_str(), "[ok]"]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
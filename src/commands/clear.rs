@@ -14,6 +14,16 @@ pub(crate) fn clear_command(
     mut state: ResMut<TerminalState>,
 ) {
     if clear.take().is_some() {
+        // Line ids keep counting, so nothing left pointing at the cleared lines finds new ones
+        let cleared = state.scrollback.len();
+        state.first_line.0 += cleared as u64;
         state.scrollback.clear();
+        state.trimmed_lines = 0;
+        state.row_heights.clear();
+        state.expanded.clear();
+        state.clear_selection();
+        state.anchors.clear();
+        state.scroll_to = None;
+        clear.done();
     }
 }
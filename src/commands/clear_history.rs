@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::terminal::TerminalState;
+use crate::TerminalCommand;
+
+/// Clears the command history
+#[derive(TerminalCommand)]
+#[terminal_command(name = "clearhistory", exclude_from_history, destructive)]
+pub(crate) struct ClearHistoryCommand;
+
+pub(crate) fn clear_history_command(
+    mut clear_history: TerminalCommand<ClearHistoryCommand>,
+    mut state: ResMut<TerminalState>,
+) {
+    if clear_history.take().is_some() {
+        state.clear_history();
+        clear_history.ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::harness::test_app;
+    use crate::{TerminalCommandQueue, TerminalState};
+
+    #[test]
+    fn it_clears_the_history() {
+        let mut app = test_app();
+        let mut state = app.world.resource_mut::<TerminalState>();
+        state.push_history("spawn_wave 1".to_string(), 20);
+        state.push_history("login hunter2".to_string(), 20);
+        app.update();
+
+        app.world
+            .resource_mut::<TerminalCommandQueue>()
+            .submit("clearhistory")
+            .unwrap();
+        app.update();
+
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(state.history().count(), 0);
+        assert!(state.history.is_empty());
+    }
+}
@@ -0,0 +1,344 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::strings::fill;
+use crate::{TerminalCommand, TerminalConfiguration, TerminalStrings, ValueRawOwned};
+
+/// Argument of `config` which changes a field
+const SET: &str = "set";
+
+/// Lists the terminal configuration, shows one field, or changes one with `config set <field> <value>`
+#[derive(TerminalCommand)]
+#[terminal_command(name = "config")]
+pub(crate) struct ConfigCommand {
+    /// Field to show, or `set`
+    field: Option<String>,
+    /// Field to change after `set`
+    set_field: Option<String>,
+    /// New value after `set`
+    value: Option<ValueRawOwned>,
+}
+
+/// A field of [`TerminalConfiguration`] which `config` shows and changes.
+struct ConfigField {
+    name: &'static str,
+    show: fn(&TerminalConfiguration) -> String,
+    set: fn(&mut TerminalConfiguration, &ValueRawOwned) -> Result<(), &'static str>,
+}
+
+macro_rules! config_fields {
+    ($($field: ident),* $(,)?) => {
+        &[$(ConfigField {
+            name: stringify!($field),
+            show: |config| config.$field.show(),
+            set: |config, value| {
+                config.$field = ConfigValue::parse(value)?;
+                Ok(())
+            },
+        }),*]
+    };
+}
+
+/// Fields with a plain value, in the order they are listed.
+const FIELDS: &[ConfigField] = config_fields![
+    block_game_input,
+    left_pos,
+    top_pos,
+    height,
+    width,
+    movable,
+    resizable,
+    input_height,
+    font_size,
+    history_size,
+    persist_history,
+    history_file_size,
+    scrollback_size,
+    print_queue_size,
+    print_lines_per_frame,
+    history_wheel,
+    key_repeat_delay,
+    key_repeat_rate,
+    command_batch_size,
+    command_time_budget,
+    close_on_submit,
+    toasts,
+    toast_successes,
+    toast_duration,
+    max_toasts,
+    attention,
+    attention_flash_duration,
+    attention_threshold,
+    interactive_scrollback,
+    line_numbers,
+    show_timestamps,
+    copy_line_buttons,
+    max_line_length,
+    auto_close_quotes,
+    tail_size,
+    motd,
+    notice_duration,
+    mirror_notices,
+    help_suffix,
+    help_search,
+    app_version,
+    split_lines,
+    mirror_to_log,
+    move_to_open_window,
+    always_on_top,
+    echo_prompt,
+    echo_aliases,
+    report_slow_commands,
+    slow_command_threshold,
+    outcome_timeout,
+];
+
+/// A type of configuration field, shown and typed the same way.
+trait ConfigValue: Sized {
+    fn show(&self) -> String;
+
+    /// Parse a typed value, returning what was expected if it's invalid.
+    fn parse(value: &ValueRawOwned) -> Result<Self, &'static str>;
+}
+
+/// Sizes in points and durations in seconds
+impl ConfigValue for f32 {
+    fn show(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse(value: &ValueRawOwned) -> Result<Self, &'static str> {
+        match value {
+            ValueRawOwned::Int(num, _) if *num >= 0 => Ok(*num as f32),
+            ValueRawOwned::Float(num, _) if num.is_finite() && *num >= 0. => Ok(*num as f32),
+            _ => Err("a number of at least 0"),
+        }
+    }
+}
+
+impl ConfigValue for usize {
+    fn show(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse(value: &ValueRawOwned) -> Result<Self, &'static str> {
+        match value {
+            ValueRawOwned::Int(num, _) => {
+                usize::try_from(*num).map_err(|_| "a whole number of at least 0")
+            }
+            _ => Err("a whole number of at least 0"),
+        }
+    }
+}
+
+impl ConfigValue for bool {
+    fn show(&self) -> String {
+        self.to_string()
+    }
+
+    fn parse(value: &ValueRawOwned) -> Result<Self, &'static str> {
+        match value {
+            ValueRawOwned::Bool(b, _) => Ok(*b),
+            _ => Err("true or false"),
+        }
+    }
+}
+
+/// Optional limits, turned off with `off`
+impl ConfigValue for Option<f32> {
+    fn show(&self) -> String {
+        self.map_or("off".to_string(), |value| value.show())
+    }
+
+    fn parse(value: &ValueRawOwned) -> Result<Self, &'static str> {
+        match value {
+            ValueRawOwned::String(off) if off == "off" => Ok(None),
+            value => f32::parse(value)
+                .map(Some)
+                .map_err(|_| "a number of at least 0, or off"),
+        }
+    }
+}
+
+impl ConfigValue for String {
+    fn show(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn parse(value: &ValueRawOwned) -> Result<Self, &'static str> {
+        match value {
+            ValueRawOwned::String(text) => Ok(text.clone()),
+            ValueRawOwned::Int(_, raw)
+            | ValueRawOwned::Float(_, raw)
+            | ValueRawOwned::Bool(_, raw) => Ok(raw.clone()),
+        }
+    }
+}
+
+/// Optional text, cleared with an empty string
+impl ConfigValue for Option<String> {
+    fn show(&self) -> String {
+        format!("{:?}", self.as_deref().unwrap_or_default())
+    }
+
+    fn parse(value: &ValueRawOwned) -> Result<Self, &'static str> {
+        match value {
+            ValueRawOwned::String(text) if text.is_empty() => Ok(None),
+            ValueRawOwned::String(text) => Ok(Some(text.clone())),
+            ValueRawOwned::Int(_, raw)
+            | ValueRawOwned::Float(_, raw)
+            | ValueRawOwned::Bool(_, raw) => Ok(Some(raw.clone())),
+        }
+    }
+}
+
+pub(crate) fn config_command(
+    mut config: TerminalCommand<ConfigCommand>,
+    mut configuration: ResMut<TerminalConfiguration>,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(ConfigCommand {
+        field,
+        set_field,
+        value,
+    }) = config.take()
+    {
+        let (name, value) = match (field.as_deref(), set_field, value) {
+            (None, None, None) => {
+                let fields = FIELDS
+                    .iter()
+                    .map(|field| (field.name, (field.show)(&configuration)));
+                config.reply_kv(fields);
+                config.ok();
+                return;
+            }
+            (Some(SET), Some(name), Some(value)) => (name, Some(value)),
+            (Some(name), None, None) if name != SET => (name.to_string(), None),
+            _ => {
+                config.reply_usage::<ConfigCommand>();
+                config.failed();
+                return;
+            }
+        };
+
+        let field = match FIELDS.iter().find(|field| field.name == name) {
+            Some(field) => field,
+            None => {
+                config.reply_failed(fill(&strings.unknown_config_field, &[&name]));
+                return;
+            }
+        };
+        if let Some(value) = value {
+            if let Err(expected) = (field.set)(&mut configuration, &value) {
+                config.reply_failed(fill(&strings.invalid_config_value, &[&name, &expected]));
+                return;
+            }
+        }
+        config.reply_kv([(field.name, (field.show)(&configuration))]);
+        config.ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::harness::{submit_and_collect, test_app};
+    use crate::TerminalConfiguration;
+
+    fn app() -> App {
+        let mut app = test_app();
+        app.update();
+        app
+    }
+
+    #[test]
+    fn it_changes_fields() {
+        let mut app = app();
+
+        assert_eq!(
+            submit_and_collect(&mut app, "config set height 600"),
+            ["  height: 600", "[ok]"]
+        );
+        assert_eq!(
+            submit_and_collect(&mut app, "config set history_size 100"),
+            ["  history_size: 100", "[ok]"]
+        );
+        assert_eq!(
+            submit_and_collect(&mut app, "config set toasts true"),
+            ["  toasts: true", "[ok]"]
+        );
+        assert_eq!(
+            submit_and_collect(&mut app, "config set command_time_budget off"),
+            ["  command_time_budget: off", "[ok]"]
+        );
+        assert_eq!(
+            submit_and_collect(&mut app, r#"config set motd "Welcome, dev""#),
+            [r#"  motd: "Welcome, dev""#, "[ok]"]
+        );
+
+        let config = app.world.resource::<TerminalConfiguration>();
+        assert_eq!(config.height, 600.);
+        assert_eq!(config.history_size, 100);
+        assert!(config.toasts);
+        assert_eq!(config.command_time_budget, None);
+        assert_eq!(config.motd.as_deref(), Some("Welcome, dev"));
+
+        assert_eq!(
+            submit_and_collect(&mut app, "config set motd ''"),
+            [r#"  motd: """#, "[ok]"]
+        );
+        assert_eq!(app.world.resource::<TerminalConfiguration>().motd, None);
+    }
+
+    #[test]
+    fn it_rejects_invalid_values() {
+        let mut app = app();
+
+        assert_eq!(
+            submit_and_collect(&mut app, "config set history_size -5"),
+            [
+                "[error] invalid value for 'history_size', expected a whole number of at least 0",
+                "[failed]"
+            ]
+        );
+        assert_eq!(
+            submit_and_collect(&mut app, "config set height tall"),
+            [
+                "[error] invalid value for 'height', expected a number of at least 0",
+                "[failed]"
+            ]
+        );
+        assert_eq!(
+            submit_and_collect(&mut app, "config set toasts 2"),
+            [
+                "[error] invalid value for 'toasts', expected true or false",
+                "[failed]"
+            ]
+        );
+        assert_eq!(
+            submit_and_collect(&mut app, "config set prompt x"),
+            ["[error] no config field 'prompt'", "[failed]"]
+        );
+        assert_eq!(
+            app.world.resource::<TerminalConfiguration>().history_size,
+            20
+        );
+    }
+
+    #[test]
+    fn it_lists_fields() {
+        let mut app = app();
+
+        let listed = submit_and_collect(&mut app, "config");
+        assert_eq!(listed.len(), super::FIELDS.len() + 1);
+        assert!(
+            listed.iter().any(|line| line.trim_start() == "height: 400"),
+            "{listed:?}"
+        );
+        assert_eq!(
+            submit_and_collect(&mut app, "config width"),
+            ["  width: 800", "[ok]"]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::{CommandRegistry, TerminalCommand, TerminalConfiguration, TerminalStrings};
+
+/// Lists deprecated commands and what to use instead
+#[derive(TerminalCommand)]
+#[terminal_command(name = "deprecations")]
+pub(crate) struct DeprecationsCommand;
+
+pub(crate) fn deprecations_command(
+    mut deprecations: TerminalCommand<DeprecationsCommand>,
+    config: Res<TerminalConfiguration>,
+    registry: Res<CommandRegistry>,
+    strings: Res<TerminalStrings>,
+) {
+    if deprecations.take().is_none() {
+        return;
+    }
+    let deprecated: Vec<(&str, &str)> = registry
+        .iter()
+        .filter_map(|(name, info)| Some((name, info?.deprecated.as_deref()?)))
+        .collect();
+    if deprecated.is_empty() {
+        deprecations.reply(strings.no_deprecated_commands.clone());
+    } else {
+        deprecations.reply(strings.deprecated_commands.clone());
+        let longest = deprecated.iter().map(|(name, _)| name.len()).max();
+        let indent = " ".repeat(config.help_format.indent);
+        for (name, note) in deprecated {
+            let padding = " ".repeat(longest.unwrap_or(0) - name.len());
+            deprecations.reply(format!("{indent}{name}{padding} - {note}"));
+        }
+    }
+    deprecations.done();
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate as leafwing_terminal;
+    use crate::harness::test_app;
+    use crate::{AddTerminalCommand, CommandRegistry, TerminalCommand, TerminalCommandQueue};
+
+    /// Spawns a wave of enemies
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "spawnwave", deprecated = "use 'spawn wave' instead")]
+    struct SpawnWaveCommand;
+
+    fn spawn_wave_command(mut spawn_wave: TerminalCommand<SpawnWaveCommand>) {
+        if spawn_wave.take().is_some() {
+            spawn_wave.reply_ok("spawned 10 enemies");
+        }
+    }
+
+    #[test]
+    fn it_warns_before_running_deprecated_commands() {
+        let mut app = test_app();
+        app.add_terminal_command::<SpawnWaveCommand, _, _>(spawn_wave_command);
+        app.update();
+        app.world
+            .resource_mut::<CommandRegistry>()
+            .add_alias("sw", "spawnwave");
+
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        let spawn_wave = queue.submit("sw").unwrap();
+        let deprecations = queue.submit("deprecations").unwrap();
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(
+            spawn_wave.collect_output(&app),
+            [
+                "[warning] 'spawnwave' is deprecated: use 'spawn wave' instead",
+                "spawned 10 enemies",
+                "[ok]",
+            ]
+        );
+        assert_eq!(
+            deprecations.collect_output(&app),
+            [
+                "Deprecated commands:",
+                "  spawnwave - use 'spawn wave' instead"
+            ]
+        );
+    }
+}
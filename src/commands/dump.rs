@@ -0,0 +1,84 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::selection::selection_text;
+use crate::strings::fill;
+use crate::{
+    TerminalCommand, TerminalConfiguration, TerminalPaths, TerminalState, TerminalStrings,
+};
+
+/// Writes the scrollback to a file, replacing it if it exists
+#[derive(TerminalCommand)]
+#[terminal_command(name = "dump", destructive)]
+pub(crate) struct DumpCommand {
+    /// File to write to
+    path: String,
+}
+
+pub(crate) fn dump_command(
+    mut dump: TerminalCommand<DumpCommand>,
+    state: Res<TerminalState>,
+    paths: Res<TerminalPaths>,
+    config: Res<TerminalConfiguration>,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(DumpCommand { path }) = dump.take() {
+        let lines: Vec<_> = state.scrollback().iter().collect();
+        let mut text = selection_text(&lines, config.show_timestamps);
+        if !lines.is_empty() {
+            text.push('\n');
+        }
+        let path = paths.resolve(path);
+        match fs::write(&path, text) {
+            Ok(()) => dump.reply_ok(fill(
+                &strings.scrollback_dumped,
+                &[&lines.len(), &path.display()],
+            )),
+            Err(error) => dump.reply_failed(fill(&strings.scrollback_dump_failed, &[&error])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::harness::{submit_and_collect, test_app};
+    use crate::{TerminalPaths, TerminalState};
+
+    #[test]
+    fn it_writes_the_scrollback_to_a_file() {
+        let root =
+            std::env::temp_dir().join(format!("leafwing_terminal_dump_{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join("scrollback.txt");
+        fs::write(&path, "stale\n").unwrap();
+
+        let mut app = test_app();
+        app.insert_resource(TerminalPaths::new(&root));
+        app.update();
+        let mut state = app.world.resource_mut::<TerminalState>();
+        state.scrollback.push_back("spawned 3 enemies".into());
+        state.scrollback.push_back("[ok]".into());
+
+        let output = submit_and_collect(&mut app, r#"dump "scrollback.txt""#);
+        let text = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines.starts_with(&["spawned 3 enemies", "[ok]"]));
+        assert_eq!(
+            output,
+            [
+                format!("Wrote {} lines to '{}'", lines.len(), path.display()),
+                "[ok]".to_string(),
+            ]
+        );
+
+        let output = submit_and_collect(&mut app, r#"dump "missing/scrollback.txt""#);
+        assert!(output[0].starts_with("[error] failed to dump the scrollback: "));
+        assert_eq!(output[1], "[failed]");
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -2,18 +2,21 @@ use bevy::app::AppExit;
 use bevy::prelude::*;
 
 use crate as leafwing_terminal;
-use crate::TerminalCommand;
+use crate::{TerminalCommand, TerminalOpen};
 
 /// Exits the app
 #[derive(TerminalCommand)]
-#[terminal_command(name = "exit")]
+#[terminal_command(name = "exit", destructive)]
 pub(crate) struct ExitCommand;
 
 pub(crate) fn exit_command(
     mut exit: TerminalCommand<ExitCommand>,
+    mut open: ResMut<TerminalOpen>,
     mut exit_writer: EventWriter<AppExit>,
 ) {
     if exit.take().is_some() {
+        // Closed first, so games see `TerminalClosed` as for any other close
+        open.0 = false;
         exit_writer.send(AppExit);
         exit.ok();
     }
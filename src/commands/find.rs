@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::strings::fill;
+use crate::{resolve_named_entity, ByName, TerminalCommand, TerminalStrings};
+
+/// Prints the id of the entity with a name
+#[derive(TerminalCommand)]
+#[terminal_command(name = "find")]
+pub(crate) struct FindCommand {
+    /// Name of the entity
+    name: ByName,
+}
+
+pub(crate) fn find_command(
+    mut find: TerminalCommand<FindCommand>,
+    names: Query<(Entity, &Name)>,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(FindCommand { name }) = find.take() {
+        match resolve_named_entity(names.iter(), &name) {
+            Ok(entity) => {
+                let entity = format!("{entity:?}");
+                find.reply_ok(fill(&strings.found_entity, &[&name.0, &entity]));
+            }
+            Err(err) => find.reply_failed(fill(&strings.custom_error, &[&err])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::harness::{submit_and_collect, test_app};
+
+    #[test]
+    fn it_finds_entities_by_name() {
+        let mut app = test_app();
+        let player = app.world.spawn().insert(Name::new("Player")).id();
+        app.update();
+
+        let expected = format!("'Player' is entity {player:?}");
+        let output = submit_and_collect(&mut app, "find Player");
+        assert!(output.contains(&expected), "{output:?}");
+    }
+}
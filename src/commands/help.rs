@@ -1,7 +1,10 @@
+use std::mem;
+
 use bevy::prelude::*;
 
 use crate as leafwing_terminal;
-use crate::{reply, CommandInfo, TerminalCommand, TerminalConfiguration};
+use crate::strings::fill;
+use crate::{CommandRegistry, TerminalCommand, TerminalConfiguration, TerminalStrings};
 
 /// Prints available arguments and usage
 #[derive(TerminalCommand)]
@@ -14,40 +17,58 @@ pub(crate) struct HelpCommand {
 pub(crate) fn help_command(
     mut help: TerminalCommand<HelpCommand>,
     config: Res<TerminalConfiguration>,
+    registry: Res<CommandRegistry>,
+    strings: Res<TerminalStrings>,
 ) {
     match help.take() {
-        Some(HelpCommand { command: Some(cmd) }) => match config.commands.get(cmd.as_str()) {
+        Some(HelpCommand { command: Some(cmd) }) => match registry.get(&cmd) {
             Some(Some(command_info)) => {
-                help.reply(command_info.help_text());
+                help.reply(command_info.help_text_localized(&config.help_format, &strings));
             }
             Some(None) => {
-                reply!(help, "Help not available for command '{}'", cmd);
+                help.reply(fill(&strings.help_not_available, &[&cmd]));
             }
             None => {
-                reply!(help, "Command '{}' does not exist", cmd);
+                help.reply(fill(&strings.unknown_command, &[&cmd]));
             }
         },
         Some(HelpCommand { command: None }) => {
-            reply!(help, "Available commands:");
-            let longest_command_name = config
-                .commands
-                .keys()
-                .map(|name| name.len())
+            help.reply(strings.available_commands.clone());
+            let longest_command_name = registry
+                .iter()
+                .map(|(name, _)| name.len())
                 .max()
                 .unwrap_or(0);
-            for (name, cmd) in &config.commands {
-                let mut line = format!("  {name}{}", " ".repeat(longest_command_name - name.len()));
-                if let Some(CommandInfo {
-                    description: Some(description),
-                    ..
-                }) = cmd
-                {
-                    line.push_str(&format!(" - {description}"));
+            let format = &config.help_format;
+            let indent = " ".repeat(format.indent);
+            for (name, cmd) in registry.iter() {
+                let mut line = format!(
+                    "{indent}{name}{}",
+                    " ".repeat(longest_command_name - name.len())
+                );
+                let description = cmd.and_then(|cmd| cmd.summary());
+                let description = match cmd.and_then(|cmd| cmd.deprecated.as_ref()) {
+                    Some(_) => Some(match description {
+                        Some(description) => format!("{description} {}", strings.deprecated_tag),
+                        None => strings.deprecated_tag.clone(),
+                    }),
+                    None => description.map(str::to_string),
+                };
+                if let Some(description) = description {
+                    line.push_str(" - ");
+                    let used = line.chars().count();
+                    for (i, wrapped) in format.wrap(&description, used).iter().enumerate() {
+                        if i > 0 {
+                            help.reply(mem::replace(&mut line, " ".repeat(used)));
+                        }
+                        line.push_str(wrapped);
+                    }
                 }
                 help.reply(line);
             }
             help.reply("");
         }
-        None => {}
+        None => return,
     }
+    help.done();
 }
@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::strings::fill;
+use crate::{validate_registry, CommandRegistry, TerminalCommand, TerminalStrings};
+
+/// Checks the metadata of registered commands and aliases for mistakes
+#[derive(TerminalCommand)]
+#[terminal_command(name = "lint")]
+pub(crate) struct LintCommand;
+
+pub(crate) fn lint_command(
+    mut lint: TerminalCommand<LintCommand>,
+    registry: Res<CommandRegistry>,
+    strings: Res<TerminalStrings>,
+) {
+    if lint.take().is_some() {
+        let warnings = validate_registry(&registry);
+        for warning in &warnings {
+            lint.reply_warn(warning.message(&strings));
+        }
+        lint.reply_ok(fill(&strings.lint_summary, &[&warnings.len()]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::harness::{submit_and_collect, test_app};
+    use crate::CommandRegistry;
+
+    #[test]
+    fn it_lists_mistakes() {
+        let mut app = test_app();
+        app.update();
+        app.world
+            .resource_mut::<CommandRegistry>()
+            .add_alias("cls", "cleer");
+
+        let output = submit_and_collect(&mut app, "lint");
+        assert_eq!(
+            output,
+            [
+                "[warning] alias `cls` runs `cleer`, which isn't registered",
+                "mistakes found in command metadata: 1",
+                "[ok]",
+            ]
+        );
+    }
+}
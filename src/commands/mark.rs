@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::strings::fill;
+use crate::terminal::TerminalState;
+use crate::{TerminalCommand, TerminalStrings};
+
+/// Marks the end of the scrollback with a divider, to come back to it with `jump`
+#[derive(TerminalCommand)]
+#[terminal_command(name = "mark")]
+pub(crate) struct MarkCommand {
+    /// Name of the mark, numbered if not given
+    label: Option<String>,
+}
+
+pub(crate) fn mark_command(
+    mut mark: TerminalCommand<MarkCommand>,
+    mut state: ResMut<TerminalState>,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(MarkCommand { label }) = mark.take() {
+        let label = label.unwrap_or_else(|| state.next_anchor_label());
+        let divider = fill(&strings.mark_divider, &[&label]);
+        state.mark(label, divider);
+        mark.done();
+    }
+}
+
+/// Scrolls back to a divider added by `mark`
+#[derive(TerminalCommand)]
+#[terminal_command(name = "jump")]
+pub(crate) struct JumpCommand {
+    /// Name of the mark
+    label: String,
+}
+
+pub(crate) fn jump_command(
+    mut jump: TerminalCommand<JumpCommand>,
+    mut state: ResMut<TerminalState>,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(JumpCommand { label }) = jump.take() {
+        match state.jump_to_anchor(&label) {
+            true => jump.done(),
+            false => jump.reply_failed(fill(&strings.anchor_not_found, &[&label])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::harness::{submit_and_collect, test_app};
+    use crate::TerminalState;
+
+    #[test]
+    fn it_jumps_to_marks_until_cleared() {
+        let mut app = test_app();
+        app.update();
+
+        submit_and_collect(&mut app, "mark flag");
+        submit_and_collect(&mut app, "mark");
+        submit_and_collect(&mut app, "jump flag");
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(
+            *state.scrollback(),
+            ["──────── flag ────────", "──────── 1 ────────"]
+        );
+        assert_eq!(state.scroll_to.map(|line| state.clamp_line(line)), Some(0));
+
+        submit_and_collect(&mut app, "clear");
+        submit_and_collect(&mut app, "jump flag");
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(state.anchors().count(), 0);
+        assert_eq!(*state.scrollback(), ["[error] no mark 'flag'", "[failed]"]);
+    }
+}
@@ -1,3 +1,22 @@
+pub(crate) mod buffer_stats;
+pub(crate) mod cd;
+pub(crate) mod clear;
+pub(crate) mod clear_history;
+pub(crate) mod config;
+pub(crate) mod deprecations;
+pub(crate) mod dump;
 pub(crate) mod exit;
+#[cfg(feature = "entity-commands")]
+pub(crate) mod find;
 pub(crate) mod help;
-pub(crate) mod clear;
+pub(crate) mod lint;
+pub(crate) mod mark;
+pub(crate) mod repeat;
+pub(crate) mod schedule;
+pub(crate) mod script;
+pub(crate) mod selftest;
+pub(crate) mod sticky;
+pub(crate) mod tail;
+pub(crate) mod timing;
+#[cfg(feature = "world-commands")]
+pub(crate) mod world;
@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::{
+    FromValue, TerminalCommand, TerminalCommandEntered, TerminalCommandQueue, TerminalStrings,
+    ValueRawOwned,
+};
+
+/// Flag which keeps repeating after a run fails
+const KEEP_GOING: &str = "--keep-going";
+
+/// Runs a command a number of times, stopping at the first error unless `--keep-going` comes before the command
+#[derive(TerminalCommand)]
+#[terminal_command(name = "repeat")]
+pub(crate) struct RepeatCommand {
+    /// Number of runs
+    count: u64,
+    /// Command to run, or `--keep-going`
+    command: String,
+    /// Arguments of the command
+    args: Vec<ValueRawOwned>,
+}
+
+pub(crate) fn repeat_command(
+    mut repeat: TerminalCommand<RepeatCommand>,
+    mut queue: ResMut<TerminalCommandQueue>,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(RepeatCommand {
+        count,
+        command,
+        mut args,
+    }) = repeat.take()
+    {
+        let keep_going = command == KEEP_GOING;
+        let command = match keep_going {
+            false => Ok(command),
+            true if args.is_empty() => {
+                repeat.reply_usage::<RepeatCommand>();
+                repeat.failed();
+                return;
+            }
+            true => String::from_value_owned(args.remove(0), 2),
+        };
+        let command = match command {
+            Ok(command) => TerminalCommandEntered { command, args },
+            Err(err) => {
+                repeat.reply_failed(strings.from_value_error(&err));
+                return;
+            }
+        };
+
+        if queue.repeat(command, count, keep_going) {
+            repeat.ok();
+        } else {
+            repeat.reply_failed(strings.already_repeating.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate as leafwing_terminal;
+    use crate::harness::test_app;
+    use crate::{
+        AddTerminalCommand, TerminalCommand, TerminalCommandQueue, TerminalConfiguration,
+        TerminalState,
+    };
+
+    /// Spawns an enemy, failing once there are too many
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "spawn_enemy")]
+    struct SpawnEnemyCommand {
+        name: String,
+    }
+
+    #[derive(Default)]
+    struct Enemies(Vec<String>);
+
+    fn spawn_enemy_command(
+        mut spawn: TerminalCommand<SpawnEnemyCommand>,
+        mut enemies: ResMut<Enemies>,
+    ) {
+        if let Some(SpawnEnemyCommand { name }) = spawn.take() {
+            if enemies.0.len() < 3 {
+                enemies.0.push(name);
+                spawn.ok();
+            } else {
+                spawn.reply_failed("too many enemies");
+            }
+        }
+    }
+
+    fn app_with_batch_size(command_batch_size: usize) -> App {
+        let mut app = test_app();
+        app.insert_resource(TerminalConfiguration {
+            command_batch_size,
+            ..Default::default()
+        })
+        .init_resource::<Enemies>()
+        .add_terminal_command::<SpawnEnemyCommand, _, _>(spawn_enemy_command);
+        app.update();
+        app
+    }
+
+    fn run(app: &mut App, line: &str, frames: usize) {
+        app.world
+            .resource_mut::<TerminalCommandQueue>()
+            .submit(line)
+            .unwrap();
+        for _ in 0..frames {
+            app.update();
+        }
+    }
+
+    #[test]
+    fn it_repeats_commands_in_batches() {
+        let mut app = app_with_batch_size(2);
+        run(&mut app, "repeat 3 spawn_enemy 'goblin king'", 2);
+        // The whole first batch is released, but the system takes one invocation a frame
+        assert_eq!(app.world.resource::<Enemies>().0.len(), 1);
+        assert!(app.world.resource::<TerminalCommandQueue>().is_repeating());
+        app.update();
+        app.update();
+        assert_eq!(app.world.resource::<Enemies>().0, ["goblin king"; 3]);
+        assert!(!app.world.resource::<TerminalCommandQueue>().is_repeating());
+    }
+
+    #[test]
+    fn it_stops_repeating_after_a_failure() {
+        let mut app = app_with_batch_size(1);
+        run(&mut app, "repeat 10 spawn_enemy orc", 12);
+        assert_eq!(app.world.resource::<Enemies>().0.len(), 3);
+        let state = app.world.resource::<TerminalState>();
+        assert!(state
+            .scrollback()
+            .iter()
+            .any(|line| line == "Stopped repeating after 4 of 10 runs"));
+        assert_eq!(
+            state
+                .scrollback()
+                .iter()
+                .filter(|line| *line == "too many enemies")
+                .count(),
+            1
+        );
+
+        let mut app = app_with_batch_size(1);
+        run(&mut app, "repeat 10 --keep-going spawn_enemy orc", 14);
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(
+            state
+                .scrollback()
+                .iter()
+                .filter(|line| *line == "too many enemies")
+                .count(),
+            7
+        );
+    }
+
+    #[test]
+    fn it_keeps_repeating_when_other_commands_fail() {
+        let mut app = app_with_batch_size(1);
+        run(&mut app, "repeat 3 spawn_enemy orc", 2);
+        // Fails as a repeat is already running
+        run(&mut app, "repeat 2 spawn_enemy goblin", 6);
+        assert_eq!(app.world.resource::<Enemies>().0, ["orc"; 3]);
+        let state = app.world.resource::<TerminalState>();
+        let scrollback = state.scrollback();
+        assert!(scrollback
+            .iter()
+            .any(|line| line.text.contains("already repeating")));
+        assert!(!scrollback
+            .iter()
+            .any(|line| line.text.starts_with("Stopped repeating")));
+    }
+}
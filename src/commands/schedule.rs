@@ -0,0 +1,239 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::parse::format_command;
+use crate::strings::fill;
+use crate::{
+    reply, ScheduleTrigger, TerminalCommand, TerminalCommandEntered, TerminalCommandQueue,
+    TerminalStrings, ValueRawOwned,
+};
+
+/// Runs a command a number of frames from now
+#[derive(TerminalCommand)]
+#[terminal_command(name = "after")]
+pub(crate) struct AfterCommand {
+    /// Frames to wait
+    frames: u64,
+    /// Command to run
+    command: String,
+    /// Arguments of the command
+    args: Vec<ValueRawOwned>,
+}
+
+/// Runs a command at a time since startup, such as 90, 1:30 or 1:02:30.5
+#[derive(TerminalCommand)]
+#[terminal_command(name = "at")]
+pub(crate) struct AtCommand {
+    /// Seconds, minutes:seconds or hours:minutes:seconds, quoted if it has colons
+    time: String,
+    /// Command to run
+    command: String,
+    /// Arguments of the command
+    args: Vec<ValueRawOwned>,
+}
+
+/// Lists scheduled commands, or `scheduled cancel <id>` to cancel one
+#[derive(TerminalCommand)]
+#[terminal_command(name = "scheduled")]
+pub(crate) struct ListScheduledCommand {
+    /// `cancel` to cancel a command
+    action: Option<String>,
+    /// Id of the command to cancel
+    id: Option<u64>,
+}
+
+pub(crate) fn after_command(
+    mut after: TerminalCommand<AfterCommand>,
+    mut queue: ResMut<TerminalCommandQueue>,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(AfterCommand {
+        frames,
+        command,
+        args,
+    }) = after.take()
+    {
+        let trigger = ScheduleTrigger::Frame(queue.frame() + frames);
+        let id = queue.schedule(TerminalCommandEntered { command, args }, trigger);
+        after.reply_ok(fill(&strings.scheduled, &[&id]));
+    }
+}
+
+pub(crate) fn at_command(
+    mut at: TerminalCommand<AtCommand>,
+    mut queue: ResMut<TerminalCommandQueue>,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(AtCommand {
+        time,
+        command,
+        args,
+    }) = at.take()
+    {
+        match parse_game_time(&time) {
+            Some(secs) => {
+                let trigger = ScheduleTrigger::Time(secs);
+                let id = queue.schedule(TerminalCommandEntered { command, args }, trigger);
+                at.reply_ok(fill(&strings.scheduled, &[&id]));
+            }
+            None => at.reply_failed(strings.invalid_time.clone()),
+        }
+    }
+}
+
+pub(crate) fn list_scheduled_command(
+    mut list: TerminalCommand<ListScheduledCommand>,
+    mut queue: ResMut<TerminalCommandQueue>,
+    strings: Res<TerminalStrings>,
+) {
+    match list.take() {
+        Some(ListScheduledCommand {
+            action: Some(action),
+            id: Some(id),
+        }) if action == "cancel" => {
+            if queue.cancel_scheduled(id) {
+                list.ok();
+            } else {
+                list.reply_failed(fill(&strings.scheduled_not_found, &[&id]));
+            }
+        }
+        Some(ListScheduledCommand { action: None, .. }) => {
+            if queue.scheduled().is_empty() {
+                list.reply(strings.no_scheduled.clone());
+            }
+            let frame = queue.frame();
+            for scheduled in queue.scheduled() {
+                let when = match scheduled.trigger {
+                    ScheduleTrigger::Frame(at) => {
+                        format!("frame {at} (in {})", at.saturating_sub(frame))
+                    }
+                    ScheduleTrigger::Time(at) => format!("at {at:.1}s"),
+                };
+                reply!(
+                    list,
+                    "  #{} {}: {}",
+                    scheduled.id,
+                    when,
+                    format_command(&scheduled.command)
+                );
+            }
+            list.done();
+        }
+        Some(_) => {
+            list.reply_failed(fill(
+                &strings.custom_error,
+                &[&"usage: scheduled [cancel <id>]"],
+            ));
+        }
+        None => {}
+    }
+}
+
+/// Parses seconds, `m:ss` or `h:mm:ss`, where the seconds may have a fraction.
+fn parse_game_time(time: &str) -> Option<f64> {
+    let mut parts = time.rsplit(':');
+    let secs = parts.next()?.parse::<f64>().ok()?;
+    let mut total = secs;
+    let mut scale = 60.0;
+    for part in parts {
+        total += part.parse::<u64>().ok()? as f64 * scale;
+        scale *= 60.0;
+        if scale > 3600.0 * 60.0 {
+            return None;
+        }
+    }
+    (total.is_finite() && total >= 0.0).then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::parse_game_time;
+    use crate as leafwing_terminal;
+    use crate::harness::test_app;
+    use crate::{AddTerminalCommand, TerminalCommand, TerminalCommandQueue, TerminalState};
+
+    #[test]
+    fn it_parses_game_times() {
+        assert_eq!(parse_game_time("90"), Some(90.0));
+        assert_eq!(parse_game_time("18:32.5"), Some(18.0 * 60.0 + 32.5));
+        assert_eq!(parse_game_time("1:02:30"), Some(3750.0));
+        assert_eq!(parse_game_time("1:2:3:4"), None);
+        assert_eq!(parse_game_time("soon"), None);
+        assert_eq!(parse_game_time("-5"), None);
+    }
+
+    /// Records the frame it ran on
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "mark")]
+    struct MarkCommand {
+        label: String,
+    }
+
+    #[derive(Default)]
+    struct Marks(Vec<(u64, String)>);
+
+    fn mark_command(
+        mut mark: TerminalCommand<MarkCommand>,
+        queue: Res<TerminalCommandQueue>,
+        mut marks: ResMut<Marks>,
+    ) {
+        if let Some(MarkCommand { label }) = mark.take() {
+            marks.0.push((queue.frame(), label));
+        }
+    }
+
+    fn app() -> App {
+        let mut app = test_app();
+        app.init_resource::<Marks>()
+            .add_terminal_command::<MarkCommand, _, _>(mark_command);
+        app.update();
+        app
+    }
+
+    fn submit(app: &mut App, line: &str) {
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.submit(line).unwrap();
+        app.update();
+    }
+
+    #[test]
+    fn it_runs_commands_after_frames() {
+        let mut app = app();
+        let submitted = app.world.resource::<TerminalCommandQueue>().frame() + 1;
+        submit(&mut app, "after 5 mark 'five frames'");
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world.resource::<Marks>().0,
+            [(submitted + 5, "five frames".to_string())]
+        );
+        assert!(app
+            .world
+            .resource::<TerminalState>()
+            .scrollback()
+            .iter()
+            .any(|line| line == "⏲ $ mark \"five frames\""));
+    }
+
+    #[test]
+    fn it_cancels_scheduled_commands() {
+        let mut app = app();
+        submit(&mut app, "after 5 mark cancelled");
+        let id = app.world.resource::<TerminalCommandQueue>().scheduled()[0].id;
+        submit(&mut app, &format!("scheduled cancel {id}"));
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert!(app.world.resource::<Marks>().0.is_empty());
+        assert!(app
+            .world
+            .resource::<TerminalCommandQueue>()
+            .scheduled()
+            .is_empty());
+    }
+}
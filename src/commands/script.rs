@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::strings::fill;
+use crate::{TerminalCommand, TerminalStrings, ValueRawOwned};
+
+/// Runs a command, stopping the rest of the script if it fails. Only works in scripts
+#[derive(TerminalCommand)]
+#[terminal_command(name = "require")]
+pub(crate) struct RequireCommand {
+    /// Command to run
+    command: String,
+    /// Arguments of the command
+    args: Vec<ValueRawOwned>,
+}
+
+pub(crate) fn require_command(
+    mut require: TerminalCommand<RequireCommand>,
+    strings: Res<TerminalStrings>,
+) {
+    if require.take().is_some() {
+        require.reply_failed(fill(&strings.script_only, &[&"require"]));
+    }
+}
+
+/// Runs a command once if a later line of the script fails. Only works in scripts
+#[derive(TerminalCommand)]
+#[terminal_command(name = "onfail")]
+pub(crate) struct OnfailCommand {
+    /// Command to run
+    command: String,
+    /// Arguments of the command
+    args: Vec<ValueRawOwned>,
+}
+
+pub(crate) fn onfail_command(
+    mut onfail: TerminalCommand<OnfailCommand>,
+    strings: Res<TerminalStrings>,
+) {
+    if onfail.take().is_some() {
+        onfail.reply_failed(fill(&strings.script_only, &[&"onfail"]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate as leafwing_terminal;
+    use crate::harness::test_app;
+    use crate::{
+        AddTerminalCommand, PrintTerminalLine, TerminalCommand, TerminalCommandQueue,
+        TerminalInvocation, TerminalState,
+    };
+
+    /// Records a step, failing at `fail` and a few frames after `late_fail`
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "step")]
+    struct StepCommand {
+        name: String,
+    }
+
+    #[derive(Default)]
+    struct Steps(Vec<String>);
+
+    /// The `late_fail` step in progress, with the frames since it started
+    #[derive(Default)]
+    struct LateFailure(Option<(TerminalInvocation, usize)>);
+
+    fn step_command(
+        mut step: TerminalCommand<StepCommand>,
+        mut steps: ResMut<Steps>,
+        mut late_failure: ResMut<LateFailure>,
+    ) {
+        if let Some(StepCommand { name }) = step.take() {
+            steps.0.push(name.clone());
+            match name.as_str() {
+                "fail" => step.failed(),
+                "late_fail" => late_failure.0 = step.invocation().map(|invocation| (invocation, 0)),
+                _ => step.ok(),
+            }
+        }
+    }
+
+    fn finish_late_failure(
+        mut late_failure: ResMut<LateFailure>,
+        mut terminal_line: EventWriter<PrintTerminalLine>,
+    ) {
+        if let Some((invocation, frames)) = late_failure.0.as_mut() {
+            *frames += 1;
+            if *frames == 3 {
+                terminal_line
+                    .send(PrintTerminalLine::new("[failed]".to_string()).with_meta(*invocation));
+                late_failure.0 = None;
+            }
+        }
+    }
+
+    fn app() -> App {
+        let mut app = test_app();
+        app.init_resource::<Steps>()
+            .init_resource::<LateFailure>()
+            .add_terminal_command::<StepCommand, _, _>(step_command)
+            .add_system(finish_late_failure.after(step_command));
+        app.update();
+        app
+    }
+
+    fn count(app: &App, line: &str) -> usize {
+        let state = app.world.resource::<TerminalState>();
+        state.scrollback().iter().filter(|l| *l == line).count()
+    }
+
+    #[test]
+    fn it_stops_scripts_at_required_failures() {
+        let mut app = app();
+        let script = "
+            # Set up
+            step first
+            onfail step cleanup
+            step fail
+            require step fail
+            step never
+        ";
+        app.world
+            .resource_mut::<TerminalCommandQueue>()
+            .push_script(script.lines())
+            .unwrap();
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world.resource::<Steps>().0,
+            ["first", "fail", "cleanup", "fail"]
+        );
+        assert_eq!(
+            count(&app, "Stopped the script after a required command failed"),
+            1
+        );
+        assert!(!app
+            .world
+            .resource::<TerminalCommandQueue>()
+            .is_running_script());
+    }
+
+    #[test]
+    fn it_waits_for_outcomes_printed_later() {
+        let mut app = app();
+        let script = "
+            onfail step cleanup
+            require step late_fail
+            step never
+        ";
+        app.world
+            .resource_mut::<TerminalCommandQueue>()
+            .push_script(script.lines())
+            .unwrap();
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert_eq!(app.world.resource::<Steps>().0, ["late_fail", "cleanup"]);
+        assert_eq!(
+            count(&app, "Stopped the script after a required command failed"),
+            1
+        );
+    }
+
+    #[test]
+    fn it_only_works_in_scripts() {
+        let mut app = app();
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        let require = queue.submit("require step first").unwrap();
+        let onfail = queue.submit("onfail step cleanup").unwrap();
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert!(app.world.resource::<Steps>().0.is_empty());
+        assert_eq!(
+            require.collect_output(&app),
+            ["[error] `require` only works in scripts", "[failed]"]
+        );
+        assert_eq!(
+            onfail.collect_output(&app),
+            ["[error] `onfail` only works in scripts", "[failed]"]
+        );
+    }
+}
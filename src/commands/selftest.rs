@@ -0,0 +1,161 @@
+use std::mem;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::utils::Instant;
+
+use crate as leafwing_terminal;
+use crate::print_queue::PrintQueue;
+use crate::selection::PrintedAt;
+use crate::strings::fill;
+use crate::terminal::TerminalState;
+use crate::{
+    parse_command, CommandRegistry, PrintTerminalLine, TerminalCommand, TerminalConfiguration,
+    TerminalStrings,
+};
+
+/// Lines printed by `selftest`
+const PRINTED_LINES: usize = 10_000;
+/// Commands parsed by `selftest`
+const PARSED_COMMANDS: usize = 1_000;
+/// Commands added to the history by `selftest`
+const HISTORY_PUSHES: usize = 10_000;
+
+/// Times printing, parsing, formatting help and history, leaving the scrollback and history as they were
+#[derive(TerminalCommand)]
+#[terminal_command(name = "selftest")]
+pub(crate) struct SelftestCommand;
+
+pub(crate) fn selftest_command(
+    mut selftest: TerminalCommand<SelftestCommand>,
+    mut state: ResMut<TerminalState>,
+    registry: Res<CommandRegistry>,
+    config: Res<TerminalConfiguration>,
+    strings: Res<TerminalStrings>,
+) {
+    if selftest.take().is_none() {
+        return;
+    }
+
+    // Run on an empty history, put back afterwards
+    let history = mem::take(&mut state.history);
+    let history_pushed = state.history_pushed;
+    let history_index = state.history_index;
+    let history_prefix = state.history_prefix.take();
+
+    // Printed the way `receive_terminal_line` does, into an empty scrollback
+    let mut printed = TerminalState::default();
+    let print = time(|| {
+        let mut queue = PrintQueue::default();
+        for i in 0..PRINTED_LINES {
+            // Ending in a newline like `println!` output, so they go through splitting
+            let line = format!("[selftest] line {i} of {PRINTED_LINES}\n");
+            queue.push(
+                PrintTerminalLine::new(line),
+                config.print_queue_size,
+                &strings,
+            );
+        }
+        loop {
+            let lines = queue.take(config.print_lines_per_frame, &strings);
+            if lines.is_empty() {
+                break;
+            }
+            for line in lines {
+                printed.push_printed(line, PrintedAt(0.), &config);
+            }
+            printed.trim_scrollback(config.scrollback_size);
+        }
+    });
+    let mut parsed = 0;
+    let parse = time(|| {
+        for i in 0..PARSED_COMMANDS {
+            let line = format!(r#"spawn_wave {i} "forest edge" 1.5 true"#);
+            parsed += usize::from(parse_command(&line).is_ok());
+        }
+    });
+    let mut helped = 0;
+    let help = time(|| {
+        for info in registry.iter().filter_map(|(_, info)| info) {
+            info.help_text_localized(&config.help_format, &strings);
+            helped += 1;
+        }
+    });
+    let history_time = time(|| {
+        for i in 0..HISTORY_PUSHES {
+            state.push_history(format!("spawn_wave {i}"), config.history_size);
+        }
+    });
+
+    state.history = history;
+    state.history_pushed = history_pushed;
+    state.history_index = history_index;
+    state.history_prefix = history_prefix;
+
+    let phase =
+        |took: Duration, count: usize| fill(&strings.selftest_phase, &[&millis(took), &count]);
+    selftest.reply_kv([
+        ("print", phase(print, PRINTED_LINES)),
+        ("parse", phase(parse, parsed)),
+        ("help", phase(help, helped)),
+        ("history", phase(history_time, HISTORY_PUSHES)),
+    ]);
+    selftest.reply_kv([
+        ("scrollback", state.scrollback.len().to_string()),
+        ("scrollback_size", config.scrollback_size.to_string()),
+        // Only the scrollback lines in view are laid out
+        ("virtualized", "on".to_string()),
+        ("history_size", config.history_size.to_string()),
+        ("max_line_length", config.max_line_length.to_string()),
+        ("commands", registry.iter().count().to_string()),
+    ]);
+    let total = print + parse + help + history_time;
+    selftest.reply_ok(fill(&strings.selftest_total, &[&millis(total)]));
+}
+
+/// How long `run` took.
+fn time(run: impl FnOnce()) -> Duration {
+    let started = Instant::now();
+    run();
+    started.elapsed()
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.2}", duration.as_secs_f64() * 1000.)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::harness::{submit_and_collect, test_app};
+    use crate::{TerminalConfiguration, TerminalState};
+
+    #[test]
+    fn it_leaves_the_scrollback_and_history_as_they_were() {
+        let mut app = test_app();
+        // Debug builds may take long enough to be reported as slow
+        app.world
+            .resource_mut::<TerminalConfiguration>()
+            .report_slow_commands = false;
+        app.update();
+        let mut state = app.world.resource_mut::<TerminalState>();
+        state.scrollback.push_back("loaded autoexec".into());
+        state.push_history("spawn_wave 1".to_string(), 20);
+
+        let output = submit_and_collect(&mut app, "selftest");
+
+        let output: Vec<_> = output.iter().map(|line| line.trim_start()).collect();
+        assert_eq!(output.len(), 12);
+        assert!(output[0].starts_with("print: ") && output[0].ends_with(" ms for 10000"));
+        assert!(output[1].starts_with("parse: ") && output[1].ends_with(" ms for 1000"));
+        assert!(output[3].ends_with(" ms for 10000"));
+        assert_eq!(output[4], "scrollback: 1");
+        assert_eq!(output[6], "virtualized: on");
+        assert!(output[10].starts_with("self-test took "));
+        assert_eq!(output[11], "[ok]");
+
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(state.scrollback()[0], "loaded autoexec");
+        assert_eq!(state.history().collect::<Vec<_>>(), ["spawn_wave 1"]);
+    }
+}
@@ -0,0 +1,97 @@
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::parse::format_command;
+use crate::strings::fill;
+use crate::{
+    TerminalCommand, TerminalCommandEntered, TerminalState, TerminalStrings, ValueRawOwned,
+};
+
+/// Argument of `sticky` which leaves sticky mode
+const OFF: &str = "off";
+
+/// Appends each line typed from now on to a command, such as `sticky set bloom`, until `sticky off` or Escape
+#[derive(TerminalCommand)]
+#[terminal_command(name = "sticky")]
+pub(crate) struct StickyCommand {
+    /// Command to append lines to, or `off`
+    command: Option<String>,
+    /// Arguments typed lines come after
+    args: Vec<ValueRawOwned>,
+}
+
+pub(crate) fn sticky_command(
+    mut sticky: TerminalCommand<StickyCommand>,
+    mut state: ResMut<TerminalState>,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(StickyCommand { command, args }) = sticky.take() {
+        match command {
+            Some(command) if command == OFF && args.is_empty() => state.sticky = None,
+            Some(command) => {
+                state.sticky = Some(format_command(&TerminalCommandEntered { command, args }))
+            }
+            None => {}
+        }
+
+        match &state.sticky {
+            Some(prefix) => sticky.reply_ok(fill(&strings.sticky_on, &[prefix])),
+            None => sticky.reply_ok(strings.sticky_off.clone()),
+        }
+    }
+}
+
+impl TerminalState {
+    /// The command typed lines are appended to, set with `sticky`.
+    pub fn sticky_prefix(&self) -> Option<&str> {
+        self.sticky.as_deref()
+    }
+
+    /// The command line `line` stands for, with the sticky prefix unless it runs `sticky` itself.
+    pub(crate) fn with_sticky_prefix<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        match &self.sticky {
+            Some(prefix) if line.split_whitespace().next() != Some("sticky") => {
+                Cow::Owned(format!("{prefix} {}", line.trim_start()))
+            }
+            _ => Cow::Borrowed(line),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::harness::{submit_and_collect, test_app};
+    use crate::TerminalState;
+
+    #[test]
+    fn it_prefixes_lines_until_turned_off() {
+        let mut app = test_app();
+        app.update();
+
+        submit_and_collect(&mut app, r#"sticky set "bloom strength""#);
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(state.sticky_prefix(), Some(r#"set "bloom strength""#));
+        assert_eq!(
+            state.with_sticky_prefix(" 0.45"),
+            r#"set "bloom strength" 0.45"#
+        );
+        assert_eq!(state.with_sticky_prefix("sticky off"), "sticky off");
+
+        submit_and_collect(&mut app, "sticky off");
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(state.sticky_prefix(), None);
+        assert_eq!(state.with_sticky_prefix("0.45"), "0.45");
+        assert_eq!(
+            *state.scrollback(),
+            [
+                r#"Appending typed lines to 'set "bloom strength"' until `sticky off` or Escape"#,
+                "[ok]",
+                "Not appending typed lines to a command",
+                "[ok]",
+            ]
+        );
+    }
+}
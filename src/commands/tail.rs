@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::strings::fill;
+use crate::terminal::{Tail, TerminalState};
+use crate::{TerminalCommand, TerminalNotice, TerminalStrings};
+
+/// Maximum number of tails open at once
+pub(crate) const MAX_TAILS: usize = 4;
+
+/// Shows new lines containing a pattern in their own pane, or `tail stop` to close them all
+#[derive(TerminalCommand)]
+#[terminal_command(name = "tail")]
+pub(crate) struct TailCommand {
+    /// Text the lines must contain
+    pattern: String,
+}
+
+pub(crate) fn tail_command(
+    mut tail: TerminalCommand<TailCommand>,
+    mut state: ResMut<TerminalState>,
+    mut notices: EventWriter<TerminalNotice>,
+    strings: Res<TerminalStrings>,
+) {
+    match tail.take() {
+        Some(TailCommand { pattern }) if pattern == "stop" => {
+            state.tails.clear();
+            notices.send(TerminalNotice::new(strings.stopped_tails.clone()));
+            tail.ok();
+        }
+        Some(TailCommand { pattern }) => {
+            if state.tails.iter().any(|tail| tail.pattern == pattern) {
+                tail.reply(fill(&strings.already_tailing, &[&pattern]));
+                tail.done();
+            } else if state.tails.len() >= MAX_TAILS {
+                tail.reply_failed(fill(&strings.too_many_tails, &[&MAX_TAILS]));
+            } else {
+                notices.send(TerminalNotice::new(fill(&strings.tailing, &[&pattern])));
+                state.tails.push(Tail::new(pattern));
+                tail.ok();
+            }
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+    use bevy::prelude::*;
+
+    use crate::harness::test_app;
+    use crate::{
+        PrintTerminalLine, TerminalCommandEntered, TerminalConfiguration, TerminalState,
+        ValueRawOwned,
+    };
+
+    fn enter_tail(app: &mut App, pattern: &str) {
+        app.world
+            .resource_mut::<Events<TerminalCommandEntered>>()
+            .send(TerminalCommandEntered {
+                command: "tail".to_string(),
+                args: vec![ValueRawOwned::String(pattern.to_string())],
+            });
+        app.update();
+    }
+
+    fn print(app: &mut App, line: &str) {
+        app.world
+            .resource_mut::<Events<PrintTerminalLine>>()
+            .send(PrintTerminalLine::new(line.to_string()));
+    }
+
+    fn tail_lines(app: &App, index: usize) -> Vec<&str> {
+        app.world.resource::<TerminalState>().tails[index]
+            .lines
+            .iter()
+            .map(String::as_str)
+            .collect()
+    }
+
+    #[test]
+    fn it_tails_new_matching_lines() {
+        let mut app = test_app();
+        app.world.resource_mut::<TerminalConfiguration>().tail_size = 2;
+
+        print(&mut app, "[net] before the tail");
+        app.update();
+        enter_tail(&mut app, "[net]");
+        enter_tail(&mut app, "[ai]");
+
+        print(&mut app, "[net] connected");
+        print(&mut app, "[ai] thinking");
+        print(&mut app, "[net] sent 3 packets");
+        print(&mut app, "[net] sent 5 packets");
+        app.update();
+
+        assert_eq!(
+            tail_lines(&app, 0),
+            vec!["[net] sent 3 packets", "[net] sent 5 packets"]
+        );
+        assert_eq!(tail_lines(&app, 1), vec!["[ai] thinking"]);
+        // The scrollback keeps everything
+        let scrollback = app.world.resource::<TerminalState>().scrollback();
+        assert!(scrollback.iter().any(|line| line == "[net] connected"));
+
+        enter_tail(&mut app, "stop");
+        assert!(app.world.resource::<TerminalState>().tails.is_empty());
+    }
+}
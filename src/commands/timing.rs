@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate as leafwing_terminal;
+use crate::strings::fill;
+use crate::{TerminalCommand, TerminalConfiguration, TerminalStrings};
+
+/// Turns reporting how long slow commands took `on` or `off`, or shows whether it is on
+#[derive(TerminalCommand)]
+#[terminal_command(name = "timing")]
+pub(crate) struct TimingCommand {
+    /// `on` or `off`
+    state: Option<String>,
+}
+
+pub(crate) fn timing_command(
+    mut timing: TerminalCommand<TimingCommand>,
+    mut config: ResMut<TerminalConfiguration>,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(TimingCommand { state }) = timing.take() {
+        match state.as_deref() {
+            Some("on") => config.report_slow_commands = true,
+            Some("off") => config.report_slow_commands = false,
+            Some(_) => {
+                timing.reply_usage::<TimingCommand>();
+                timing.failed();
+                return;
+            }
+            None => {}
+        }
+
+        let threshold = (config.slow_command_threshold * 1000.).round();
+        match config.report_slow_commands {
+            true => timing.reply_ok(fill(&strings.timing_on, &[&threshold])),
+            false => timing.reply_ok(strings.timing_off.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use crate as leafwing_terminal;
+    use crate::harness::{submit_and_collect, test_app};
+    use crate::{AddTerminalCommand, TerminalCommand, TerminalConfiguration};
+
+    /// Takes a while
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "rebuild_navmesh")]
+    struct RebuildNavmeshCommand;
+
+    fn rebuild_navmesh_command(mut rebuild: TerminalCommand<RebuildNavmeshCommand>) {
+        if rebuild.take().is_some() {
+            thread::sleep(Duration::from_millis(20));
+            rebuild.ok();
+        }
+    }
+
+    #[test]
+    fn it_reports_slow_commands() {
+        let mut app = test_app();
+        app.insert_resource(TerminalConfiguration {
+            slow_command_threshold: 0.01,
+            ..Default::default()
+        })
+        .add_terminal_command::<RebuildNavmeshCommand, _, _>(rebuild_navmesh_command);
+        app.update();
+
+        let outcome = submit_and_collect(&mut app, "rebuild_navmesh")
+            .pop()
+            .unwrap();
+        assert!(outcome.starts_with("[ok] (took "), "{outcome}");
+        assert!(outcome.ends_with(" ms)"), "{outcome}");
+
+        assert_eq!(
+            submit_and_collect(&mut app, "timing off"),
+            ["Not reporting slow commands", "[ok]"]
+        );
+        assert!(
+            !app.world
+                .resource::<TerminalConfiguration>()
+                .report_slow_commands
+        );
+        assert_eq!(submit_and_collect(&mut app, "rebuild_navmesh"), ["[ok]"]);
+    }
+}
@@ -0,0 +1,252 @@
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy::scene::{Scene, SceneSpawner};
+
+use crate as leafwing_terminal;
+use crate::strings::fill;
+use crate::{
+    AssetPathArg, PrintTerminalLine, TerminalCommand, TerminalInvocation, TerminalStrings,
+    ValidatedAssetPath,
+};
+
+/// Flag which despawns the children of an entity too
+const RECURSIVE: &str = "--recursive";
+
+/// Despawns an entity, with its children if `--recursive` comes after it
+#[derive(TerminalCommand)]
+#[terminal_command(name = "despawn", destructive)]
+pub(crate) struct DespawnCommand {
+    /// Entity id, such as 4 or "4v1"
+    entity: Entity,
+    /// `--recursive` to despawn its children too
+    recursive: Option<String>,
+}
+
+pub(crate) fn despawn_command(
+    mut despawn: TerminalCommand<DespawnCommand>,
+    entities: Query<Entity>,
+    mut commands: Commands,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(DespawnCommand { entity, recursive }) = despawn.take() {
+        let recursive = match recursive.as_deref() {
+            None => false,
+            Some(RECURSIVE) => true,
+            Some(_) => {
+                despawn.reply_usage::<DespawnCommand>();
+                despawn.failed();
+                return;
+            }
+        };
+        if entities.get(entity).is_err() {
+            let entity = format!("{entity:?}");
+            despawn.reply_failed(fill(&strings.entity_not_found, &[&entity]));
+            return;
+        }
+
+        if recursive {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            commands.entity(entity).despawn();
+        }
+        despawn.ok();
+    }
+}
+
+/// Spawns a glTF scene, such as "models/crate.gltf", printing its root entity once loaded
+#[derive(TerminalCommand)]
+#[terminal_command(name = "spawn")]
+pub(crate) struct SpawnCommand {
+    /// Path in the assets folder, with an optional label such as #Scene1
+    scene_path: AssetPathArg,
+    /// Position of the root entity
+    x: Option<f64>,
+    y: Option<f64>,
+    z: Option<f64>,
+}
+
+/// A scene spawned by `spawn`, waiting for its asset to load.
+struct PendingSceneSpawn {
+    path: String,
+    scene: Handle<Scene>,
+    root: Entity,
+    invocation: Option<TerminalInvocation>,
+}
+
+/// Scenes spawned by `spawn` which are still loading.
+///
+/// The command only creates the root entity, the outcome is printed by [`spawn_loaded_scenes`].
+#[derive(Default)]
+pub(crate) struct PendingSceneSpawns(Vec<PendingSceneSpawn>);
+
+/// The asset path of a glTF scene, defaulting to its first scene.
+fn scene_asset_path(path: &str) -> Option<String> {
+    let (file, label) = path.split_once('#').unwrap_or((path, "Scene0"));
+    let extension = file
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase());
+    matches!(extension.as_deref(), Some("gltf" | "glb")).then(|| format!("{file}#{label}"))
+}
+
+pub(crate) fn spawn_command(
+    mut spawn: TerminalCommand<SpawnCommand>,
+    asset_server: Option<Res<AssetServer>>,
+    scene_spawner: Option<Res<SceneSpawner>>,
+    mut pending: ResMut<PendingSceneSpawns>,
+    mut commands: Commands,
+    strings: Res<TerminalStrings>,
+) {
+    if let Some(SpawnCommand {
+        scene_path: AssetPathArg(scene_path),
+        x,
+        y,
+        z,
+    }) = spawn.take()
+    {
+        let translation = match (x, y, z) {
+            (None, None, None) => Vec3::ZERO,
+            (Some(x), Some(y), Some(z)) => Vec3::new(x as f32, y as f32, z as f32),
+            _ => {
+                spawn.reply_usage::<SpawnCommand>();
+                spawn.failed();
+                return;
+            }
+        };
+        let asset_server = match (asset_server, scene_spawner) {
+            (Some(asset_server), Some(_)) => asset_server,
+            _ => {
+                let msg = "scenes can't be spawned without the AssetPlugin and ScenePlugin";
+                spawn.reply_failed(fill(&strings.custom_error, &[&msg]));
+                return;
+            }
+        };
+        let asset_path = match scene_asset_path(&scene_path) {
+            Some(asset_path) => AssetPathArg(asset_path),
+            None => {
+                spawn.reply_failed(fill(&strings.not_a_scene, &[&scene_path]));
+                return;
+            }
+        };
+        let scene = match ValidatedAssetPath::<Scene>::resolve(&asset_server, asset_path) {
+            Ok(scene) => scene,
+            Err(msg) => {
+                spawn.reply_failed(fill(&strings.custom_error, &[&msg]));
+                return;
+            }
+        };
+
+        let root = commands
+            .spawn()
+            .insert(Transform::from_translation(translation))
+            .insert(GlobalTransform::default())
+            .id();
+        pending.0.push(PendingSceneSpawn {
+            path: scene_path.clone(),
+            scene: scene.load(&asset_server),
+            root,
+            invocation: spawn.invocation(),
+        });
+        spawn.reply(fill(&strings.loading_scene, &[&scene_path]));
+    }
+}
+
+/// Spawn scenes whose assets have loaded under their root entity, printing the outcome of `spawn`.
+pub(crate) fn spawn_loaded_scenes(
+    mut pending: ResMut<PendingSceneSpawns>,
+    asset_server: Option<Res<AssetServer>>,
+    scene_spawner: Option<ResMut<SceneSpawner>>,
+    mut commands: Commands,
+    strings: Res<TerminalStrings>,
+    mut terminal_line: EventWriter<PrintTerminalLine>,
+) {
+    let (asset_server, mut scene_spawner) = match (asset_server, scene_spawner) {
+        (Some(asset_server), Some(scene_spawner)) => (asset_server, scene_spawner),
+        _ => return,
+    };
+
+    pending.0.retain(|spawn| {
+        // Tag the outcome with the invocation of `spawn`, as if the command printed it
+        let mut send = |line: String| {
+            let line = PrintTerminalLine::new(line);
+            terminal_line.send(match spawn.invocation {
+                Some(invocation) => line.with_meta(invocation),
+                None => line,
+            });
+        };
+        match asset_server.get_load_state(&spawn.scene) {
+            LoadState::Loaded => {
+                scene_spawner.spawn_as_child(spawn.scene.clone(), spawn.root);
+                let root = format!("{:?}", spawn.root);
+                send(fill(&strings.scene_spawned, &[&spawn.path, &root]));
+                send(strings.ok.clone());
+                false
+            }
+            LoadState::Failed => {
+                commands.entity(spawn.root).despawn();
+                send(fill(&strings.scene_load_failed, &[&spawn.path]));
+                send(strings.failed.clone());
+                false
+            }
+            _ => true,
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::scene_asset_path;
+    use crate::harness::{submit_and_collect, test_app};
+
+    #[test]
+    fn it_despawns_entities() {
+        let mut app = test_app();
+        let child = app.world.spawn().id();
+        let parent = app.world.spawn().push_children(&[child]).id();
+        let other = app.world.spawn().id();
+        app.update();
+
+        assert_eq!(
+            submit_and_collect(&mut app, &format!("despawn {}", other.id())),
+            ["[ok]"]
+        );
+        assert!(app.world.get_entity(other).is_none());
+
+        let recursive = format!(r#"despawn "{parent:?}" --recursive"#);
+        assert_eq!(submit_and_collect(&mut app, &recursive), ["[ok]"]);
+        assert!(app.world.get_entity(child).is_none());
+
+        assert_eq!(
+            submit_and_collect(&mut app, &recursive),
+            [
+                format!("[error] entity {parent:?} does not exist"),
+                "[failed]".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn it_rejects_scenes_it_cannot_load() {
+        let mut app = test_app();
+        app.update();
+
+        assert_eq!(
+            submit_and_collect(&mut app, r#"spawn "crate.gltf""#),
+            [
+                "[error] scenes can't be spawned without the AssetPlugin and ScenePlugin",
+                "[failed]"
+            ]
+        );
+
+        assert_eq!(
+            scene_asset_path("models/Crate.glb"),
+            Some("models/Crate.glb#Scene0".to_string())
+        );
+        assert_eq!(
+            scene_asset_path("crate.gltf#Scene2"),
+            Some("crate.gltf#Scene2".to_string())
+        );
+        assert_eq!(scene_asset_path("crate.scn.ron"), None);
+    }
+}
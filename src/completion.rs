@@ -0,0 +1,554 @@
+use std::collections::BTreeMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::input::TerminalKeys;
+use crate::parse::push_quoted;
+use crate::{CommandRegistry, TerminalOpen, TerminalPaths, TerminalState};
+
+/// Completes the arguments of a command when Tab is pressed in the terminal input.
+///
+/// See [`CommandCompleters::set_completer`].
+pub trait ArgCompleter: Send + Sync + 'static {
+    /// Values starting with `partial`, the unquoted text typed so far for argument `arg_index`.
+    fn complete(&mut self, arg_index: usize, partial: &str) -> Vec<String>;
+
+    /// Called with the [`TerminalPaths`] before each completion, for completers of files to resolve paths with.
+    fn set_paths(&mut self, _paths: &TerminalPaths) {}
+
+    /// Called when Tab is pressed, with the arguments typed before the one being completed.
+    ///
+    /// Completers of game data, such as entity or map names, look it up in the `world` here.
+    fn refresh(&mut self, _world: &mut World, _args: &[String]) {}
+}
+
+/// Complete the arguments of commands with values looked up in the world.
+pub trait AddTerminalCompletions {
+    /// Complete the arguments of `command` with the values `candidates` returns,
+    /// given the world and the arguments typed before the one being completed.
+    ///
+    /// The values are looked up each time Tab is pressed, and those starting with the typed text are offered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use leafwing_terminal::AddTerminalCompletions;
+    /// #
+    /// App::new().add_terminal_completions("teleport", |world, _args| {
+    ///     world
+    ///         .query::<&Name>()
+    ///         .iter(world)
+    ///         .map(|name| name.to_string())
+    ///         .collect()
+    /// });
+    /// ```
+    fn add_terminal_completions(
+        &mut self,
+        command: impl Into<String>,
+        candidates: impl Fn(&mut World, &[String]) -> Vec<String> + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl AddTerminalCompletions for App {
+    fn add_terminal_completions(
+        &mut self,
+        command: impl Into<String>,
+        candidates: impl Fn(&mut World, &[String]) -> Vec<String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(CommandCompleters::default)
+            .set_completer(
+                command,
+                WorldCompleter {
+                    candidates,
+                    values: Vec::new(),
+                },
+            );
+        self
+    }
+}
+
+/// Completes arguments with the values looked up by a closure, see [`AddTerminalCompletions`].
+struct WorldCompleter<F> {
+    candidates: F,
+    /// Values looked up when Tab was last pressed
+    values: Vec<String>,
+}
+
+impl<F> ArgCompleter for WorldCompleter<F>
+where
+    F: Fn(&mut World, &[String]) -> Vec<String> + Send + Sync + 'static,
+{
+    fn complete(&mut self, _arg_index: usize, partial: &str) -> Vec<String> {
+        self.values
+            .iter()
+            .filter(|value| value.starts_with(partial))
+            .cloned()
+            .collect()
+    }
+
+    fn refresh(&mut self, world: &mut World, args: &[String]) {
+        self.values = (self.candidates)(world, args);
+    }
+}
+
+/// Argument completers of terminal commands, by command name.
+///
+/// Tab completes the argument being typed with the completer of the command,
+/// or inserts what all completions have in common and lists them above the input.
+///
+/// # Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use leafwing_terminal::{CommandCompleters, PathCompleter};
+/// #
+/// fn add_completers(mut completers: ResMut<CommandCompleters>) {
+///     completers.set_completer("loadlevel", PathCompleter::new("assets/levels"));
+/// }
+/// ```
+#[derive(Default)]
+pub struct CommandCompleters {
+    completers: BTreeMap<String, Box<dyn ArgCompleter>>,
+}
+
+impl CommandCompleters {
+    /// Complete the arguments of `command` with `completer`, replacing any completer it had.
+    pub fn set_completer(&mut self, command: impl Into<String>, completer: impl ArgCompleter) {
+        self.completers.insert(command.into(), Box::new(completer));
+    }
+
+    /// Remove the completer of a command, returning true if it had one.
+    pub fn remove_completer(&mut self, command: &str) -> bool {
+        self.completers.remove(command).is_some()
+    }
+
+    /// Refresh the completer of the command or alias of `line`, see [`ArgCompleter::refresh`].
+    pub(crate) fn refresh(&mut self, line: &str, world: &mut World) {
+        let words = split_words(line);
+        let Some((&(_, command), args)) = words.split_first() else {
+            return;
+        };
+        let Some((_, previous)) = args.split_last() else {
+            return;
+        };
+        let registry = world.resource::<CommandRegistry>();
+        let Some(completer) = registry
+            .resolve(command)
+            .and_then(|command| self.completers.get_mut(command))
+        else {
+            return;
+        };
+        let previous: Vec<String> = previous.iter().map(|&(_, word)| unquote(word)).collect();
+        completer.refresh(world, &previous);
+    }
+
+    /// Complete the last argument of `line` with the completer of its command or alias,
+    /// or the command itself while it is the only word, see [`CommandRegistry::completions`].
+    ///
+    /// Returns `None` if the command has no completer or nothing matches.
+    pub(crate) fn complete(
+        &mut self,
+        line: &str,
+        registry: &CommandRegistry,
+        paths: &TerminalPaths,
+    ) -> Option<Completion> {
+        let words = split_words(line);
+        let (&(command_start, command), args) = words.split_first()?;
+        let (start, partial, mut candidates) = match args.last() {
+            None if command.is_empty() => return None,
+            None => {
+                let names = registry.completions(command);
+                let names = names.into_iter().map(str::to_string).collect();
+                (command_start, command.to_string(), names)
+            }
+            Some(&(start, typed)) => {
+                let completer = self.completers.get_mut(registry.resolve(command)?)?;
+                let partial = unquote(typed);
+                completer.set_paths(paths);
+                let candidates = completer.complete(args.len() - 1, &partial);
+                (start, partial, candidates)
+            }
+        };
+        candidates.sort();
+        candidates.dedup();
+        let common = common_prefix(&candidates);
+        if candidates.is_empty() || (common.len() <= partial.len() && candidates.len() == 1) {
+            return None;
+        }
+        if common.len() <= partial.len() {
+            return Some(Completion {
+                line: line.to_string(),
+                candidates,
+            });
+        }
+
+        let mut completed = line[..start].to_string();
+        push_quoted(&mut completed, common);
+        // Leave the quote open until the argument is complete, so typing continues inside it
+        let finished = candidates.len() == 1 && !common.ends_with('/');
+        if finished {
+            completed.push(' ');
+        } else if completed[start..].starts_with('"') {
+            completed.pop();
+        }
+        if candidates.len() == 1 {
+            candidates.clear();
+        }
+        Some(Completion {
+            line: completed,
+            candidates,
+        })
+    }
+}
+
+/// Refreshes the completer of the command being typed when Tab is pressed, before the terminal UI completes it.
+pub(crate) fn refresh_completers(world: &mut World) {
+    let tab = world
+        .get_resource::<TerminalKeys>()
+        .is_some_and(|keys| keys.0.just_pressed(KeyCode::Tab));
+    if !tab || !world.resource::<TerminalOpen>().0 {
+        return;
+    }
+    let state = world.resource::<TerminalState>();
+    let line = state.with_sticky_prefix(&state.buf).into_owned();
+    world.resource_scope(|world, mut completers: Mut<CommandCompleters>| {
+        completers.refresh(&line, world);
+    });
+}
+
+/// The line completed with Tab, see [`CommandCompleters`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Completion {
+    /// The line with the last argument completed as far as all candidates agree
+    pub(crate) line: String,
+    /// The candidates to pick from, or none if there was only one
+    pub(crate) candidates: Vec<String>,
+}
+
+/// The words of a partly typed line and the positions they start at,
+/// with an empty last word if the line ends with whitespace.
+///
+/// Quoted words are kept quoted, and an unclosed quote extends to the end of the line.
+fn split_words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quote.is_some() => escaped = true,
+            c if quote == Some(c) => quote = None,
+            '"' | '\'' if quote.is_none() => {
+                quote = Some(c);
+                start.get_or_insert(index);
+            }
+            c if c.is_whitespace() && quote.is_none() => {
+                if let Some(start) = start.take() {
+                    words.push((start, &line[start..index]));
+                }
+            }
+            _ => {
+                start.get_or_insert(index);
+            }
+        }
+    }
+    match start {
+        Some(start) => words.push((start, &line[start..])),
+        None => words.push((line.len(), "")),
+    }
+    words
+}
+
+/// A word without its quotes and escapes.
+fn unquote(word: &str) -> String {
+    let inner = match word.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let word = &word[1..];
+            word.strip_suffix(quote).unwrap_or(word)
+        }
+        _ => return word.to_string(),
+    };
+    let mut unquoted = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.extend(chars.next()),
+            c => unquoted.push(c),
+        }
+    }
+    unquoted
+}
+
+/// The longest prefix shared by all `words`.
+fn common_prefix(words: &[String]) -> &str {
+    let (first, rest) = match words.split_first() {
+        Some(split) => split,
+        None => return "",
+    };
+    let mut len = first.len();
+    for word in rest {
+        len = first
+            .char_indices()
+            .zip(word.chars())
+            .take_while(|((index, a), b)| *index < len && a == b)
+            .map(|((index, a), _)| index + a.len_utf8())
+            .last()
+            .unwrap_or(0);
+    }
+    &first[..len]
+}
+
+/// Completes paths of files and directories relative to a root directory,
+/// appending `/` to directories so their contents can be completed next.
+///
+/// A relative root starts from the current directory of the [`TerminalPaths`].
+///
+/// Files starting with `.` are only listed once a `.` is typed.
+/// Each directory is listed once, until a path in another directory is completed.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct PathCompleter {
+    root: PathBuf,
+    paths: TerminalPaths,
+    /// The directory listed last and the names in it
+    listing: Option<(PathBuf, Vec<String>)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PathCompleter {
+    /// Complete paths relative to `root`, such as `"assets/levels"`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        PathCompleter {
+            root: root.into(),
+            paths: TerminalPaths::default(),
+            listing: None,
+        }
+    }
+
+    /// Names in `dir`, with `/` appended to directories, listing it only if it wasn't listed last.
+    fn list(&mut self, dir: PathBuf) -> &[String] {
+        if !matches!(&self.listing, Some((listed, _)) if *listed == dir) {
+            let mut names: Vec<String> = std::fs::read_dir(&dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter_map(|entry| {
+                    let name = entry.file_name().into_string().ok()?;
+                    match entry.file_type().ok()?.is_dir() {
+                        true => Some(name + "/"),
+                        false => Some(name),
+                    }
+                })
+                .collect();
+            names.sort();
+            self.listing = Some((dir, names));
+        }
+        self.listing
+            .as_ref()
+            .map_or(&[], |(_, names)| names.as_slice())
+    }
+}
+
+/// Completes paths relative to the current directory of the [`TerminalPaths`].
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for PathCompleter {
+    fn default() -> Self {
+        PathCompleter::new(".")
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ArgCompleter for PathCompleter {
+    fn complete(&mut self, _arg_index: usize, partial: &str) -> Vec<String> {
+        let (dir, name) = match partial.rfind('/') {
+            Some(slash) => partial.split_at(slash + 1),
+            None => ("", partial),
+        };
+        let path = self.paths.resolve(self.root.join(dir));
+        self.list(path)
+            .iter()
+            .filter(|entry| {
+                entry.starts_with(name) && (name.starts_with('.') || !entry.starts_with('.'))
+            })
+            .map(|entry| format!("{dir}{entry}"))
+            .collect()
+    }
+
+    fn set_paths(&mut self, paths: &TerminalPaths) {
+        self.paths.clone_from(paths);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::harness::test_app;
+
+    #[test]
+    fn it_splits_partly_typed_lines() {
+        assert_eq!(split_words("exec"), [(0, "exec")]);
+        assert_eq!(split_words("exec "), [(0, "exec"), (5, "")]);
+        assert_eq!(
+            split_words(r#"exec "my scr"#),
+            [(0, "exec"), (5, r#""my scr"#)]
+        );
+        assert_eq!(
+            split_words(r#"exec "a \" b" c"#),
+            [(0, "exec"), (5, r#""a \" b""#), (14, "c")]
+        );
+        assert_eq!(unquote(r#""a \" b""#), r#"a " b"#);
+        assert_eq!(unquote("'levels/"), "levels/");
+        assert_eq!(unquote("plain"), "plain");
+    }
+
+    #[test]
+    fn it_finds_common_prefixes() {
+        let words = |words: &[&str]| {
+            words
+                .iter()
+                .map(|word| word.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(common_prefix(&words(&["forest", "fort", "forum"])), "for");
+        assert_eq!(common_prefix(&words(&["café", "cafés"])), "café");
+        assert_eq!(common_prefix(&words(&["a", "b"])), "");
+        assert_eq!(common_prefix(&[]), "");
+    }
+
+    #[test]
+    fn it_completes_paths() {
+        let root =
+            std::env::temp_dir().join(format!("leafwing_terminal_paths_{}", std::process::id()));
+        fs::create_dir_all(root.join("levels/caves")).unwrap();
+        for file in [
+            "levels/forest.ron",
+            "levels/fort night.ron",
+            ".hidden",
+            "notes.txt",
+        ] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let mut registry = CommandRegistry::default();
+        registry.insert("loadlevel", None);
+        registry.add_alias("ll", "loadlevel");
+        let mut completers = CommandCompleters::default();
+        completers.set_completer("loadlevel", PathCompleter::new(&root));
+        let paths = TerminalPaths::default();
+        let mut complete = |line: &str| completers.complete(line, &registry, &paths);
+
+        // Directories end with a slash to drill down, leaving the quote open
+        assert_eq!(
+            complete("loadlevel le"),
+            Some(Completion {
+                line: r#"loadlevel "levels/"#.to_string(),
+                candidates: Vec::new(),
+            })
+        );
+        // Several matches insert their common prefix and are listed
+        assert_eq!(
+            complete(r#"ll "levels/f"#),
+            Some(Completion {
+                line: r#"ll "levels/for"#.to_string(),
+                candidates: vec![
+                    "levels/forest.ron".to_string(),
+                    "levels/fort night.ron".to_string()
+                ],
+            })
+        );
+        // Files end the argument
+        assert_eq!(
+            complete(r#"loadlevel "levels/fort"#).unwrap().line,
+            r#"loadlevel "levels/fort night.ron" "#
+        );
+
+        // Listings are kept until another directory is completed
+        fs::write(root.join("levels/fog.ron"), "").unwrap();
+        assert_eq!(complete(r#"ll "levels/fo"#).unwrap().candidates.len(), 2);
+        assert_eq!(complete("ll no").unwrap().line, r#"ll "notes.txt" "#);
+        assert_eq!(complete(r#"ll "levels/fo"#).unwrap().candidates.len(), 3);
+
+        // Hidden files are only listed once a dot is typed
+        assert_eq!(
+            complete("loadlevel "),
+            Some(Completion {
+                line: "loadlevel ".to_string(),
+                candidates: vec!["levels/".to_string(), "notes.txt".to_string()],
+            })
+        );
+        assert_eq!(
+            complete("loadlevel .").unwrap().line,
+            r#"loadlevel ".hidden" "#
+        );
+        assert_eq!(complete("loadlevel x"), None);
+        assert_eq!(complete("loadlevel notes.txt"), None);
+        assert_eq!(complete("clear "), None);
+
+        // Commands are completed while they are the only word
+        registry.insert("loadsave", None);
+        let mut complete = |line: &str| completers.complete(line, &registry, &paths);
+        assert_eq!(complete("lo").unwrap().line, "load");
+        assert_eq!(complete("loadl").unwrap().line, "loadlevel ");
+        assert_eq!(complete(""), None);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn it_completes_values_from_the_world() {
+        let mut app = test_app();
+        app.init_resource::<Input<KeyCode>>()
+            .add_system(refresh_completers.exclusive_system().at_start())
+            .add_terminal_completions("teleport", |world, args| match args {
+                [] => world
+                    .query::<&Name>()
+                    .iter(world)
+                    .map(|name| name.to_string())
+                    .collect(),
+                _ => vec![format!("{}_spawn", args[0])],
+            });
+        app.world
+            .resource_mut::<CommandRegistry>()
+            .insert("teleport", None);
+        app.world.spawn().insert(Name::new("forest_guard"));
+        app.world.spawn().insert(Name::new("fort_keeper"));
+        app.world.spawn().insert(Name::new("merchant"));
+
+        let complete = |app: &mut App, line: &str| {
+            app.world.resource_mut::<TerminalOpen>().0 = true;
+            app.world.resource_mut::<TerminalState>().buf = line.to_string();
+            let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+            keys.clear();
+            keys.press(KeyCode::Tab);
+            app.update();
+            app.world
+                .resource_mut::<Input<KeyCode>>()
+                .release(KeyCode::Tab);
+            app.world
+                .resource_scope(|world, mut completers: Mut<CommandCompleters>| {
+                    completers.complete(line, world.resource(), &TerminalPaths::default())
+                })
+        };
+
+        assert_eq!(
+            complete(&mut app, "teleport f"),
+            Some(Completion {
+                line: "teleport for".to_string(),
+                candidates: vec!["forest_guard".to_string(), "fort_keeper".to_string()],
+            })
+        );
+        assert_eq!(
+            complete(&mut app, "teleport merchant "),
+            Some(Completion {
+                line: "teleport merchant merchant_spawn ".to_string(),
+                candidates: Vec::new(),
+            })
+        );
+    }
+}
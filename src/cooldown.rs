@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Cooldowns for terminal commands, keyed by command name.
+///
+/// Commands declare a cooldown with `#[terminal_command(cooldown = "2s")]`,
+/// or one can be set at any time with [`TerminalCooldowns::set_cooldown`].
+/// Invoking a command again within its cooldown replies with the remaining time
+/// instead of running the command.
+///
+/// Times are measured in seconds since startup, as given by [`Time`](bevy::core::Time).
+#[derive(Clone, Debug, Default)]
+pub struct TerminalCooldowns {
+    cooldowns: HashMap<String, Duration>,
+    last_invoked: HashMap<String, f64>,
+}
+
+impl TerminalCooldowns {
+    /// Set the cooldown for a command, replacing any existing one.
+    pub fn set_cooldown(&mut self, command: impl Into<String>, cooldown: Duration) {
+        self.cooldowns.insert(command.into(), cooldown);
+    }
+
+    /// Remove the cooldown for a command.
+    pub fn remove_cooldown(&mut self, command: &str) {
+        self.cooldowns.remove(command);
+        self.last_invoked.remove(command);
+    }
+
+    /// Returns the cooldown of a command, if it has one.
+    pub fn cooldown(&self, command: &str) -> Option<Duration> {
+        self.cooldowns.get(command).copied()
+    }
+
+    /// Returns how long until a command can be invoked again,
+    /// or `None` if it can be invoked now.
+    pub fn remaining(&self, command: &str, now: f64) -> Option<Duration> {
+        let cooldown = self.cooldowns.get(command)?.as_secs_f64();
+        let last_invoked = self.last_invoked.get(command)?;
+        let remaining = last_invoked + cooldown - now;
+
+        (remaining > 0.0).then(|| Duration::from_secs_f64(remaining))
+    }
+
+    /// Record that a command was invoked, starting its cooldown.
+    pub(crate) fn trigger(&mut self, command: &str, now: f64) {
+        if self.cooldowns.contains_key(command) {
+            self.last_invoked.insert(command.to_string(), now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    use super::TerminalCooldowns;
+    use crate as leafwing_terminal;
+    use crate::harness::submit_and_collect;
+    use crate::{AddTerminalCommand, TerminalCommand, TerminalCorePlugin};
+
+    /// Rebuilds the navigation mesh
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "rebuild", cooldown = "50ms")]
+    struct RebuildCommand;
+
+    fn rebuild_command(mut rebuild: TerminalCommand<RebuildCommand>) {
+        if rebuild.take().is_some() {
+            rebuild.ok();
+        }
+    }
+
+    fn rebuild(app: &mut App) -> Vec<String> {
+        submit_and_collect(app, "rebuild")
+    }
+
+    #[test]
+    fn it_tracks_cooldown_window() {
+        let mut cooldowns = TerminalCooldowns::default();
+        cooldowns.set_cooldown("rebuild", Duration::from_secs(2));
+        assert_eq!(cooldowns.remaining("rebuild", 10.0), None);
+
+        cooldowns.trigger("rebuild", 10.0);
+        let remaining = cooldowns.remaining("rebuild", 10.7).unwrap();
+        assert!((remaining.as_secs_f64() - 1.3).abs() < 1e-9);
+
+        assert_eq!(cooldowns.remaining("rebuild", 12.0), None);
+        assert_eq!(cooldowns.remaining("rebuild", 15.0), None);
+    }
+
+    #[test]
+    fn it_ignores_commands_without_cooldown() {
+        let mut cooldowns = TerminalCooldowns::default();
+        cooldowns.trigger("log", 1.0);
+        assert_eq!(cooldowns.remaining("log", 1.0), None);
+
+        cooldowns.set_cooldown("log", Duration::from_secs(1));
+        cooldowns.trigger("log", 1.0);
+        assert!(cooldowns.remaining("log", 1.5).is_some());
+        cooldowns.remove_cooldown("log");
+        assert_eq!(cooldowns.remaining("log", 1.5), None);
+    }
+
+    #[test]
+    fn it_holds_commands_back_until_the_cooldown_ends() {
+        // Without `MinimalPlugins`, `Time` only moves when the test updates it
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_plugin(TerminalCorePlugin)
+            .add_terminal_command::<RebuildCommand, _, _>(rebuild_command);
+        app.update();
+
+        assert_eq!(rebuild(&mut app), ["[ok]"]);
+        assert_eq!(rebuild(&mut app), ["command on cooldown, 0.1s remaining"]);
+
+        // `Time` can't be moved to a given instant outside of Bevy,
+        // so move the last run a cooldown into the past instead of waiting for it
+        let now = app.world.resource::<Time>().seconds_since_startup();
+        app.world
+            .resource_mut::<TerminalCooldowns>()
+            .trigger("rebuild", now - 0.05);
+        assert_eq!(rebuild(&mut app), ["[ok]"]);
+    }
+}
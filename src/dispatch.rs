@@ -0,0 +1,208 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::echo::{format_echo, EchoSource, SubmitInfo};
+use crate::parse::format_command;
+use crate::{
+    CommandName, CommandRegistry, IntoValues, PrintTerminalLine, TerminalCommandEntered,
+    TerminalCommandQueue, TerminalConfiguration, TerminalInvocation, TerminalState,
+};
+
+/// Runs commands from buttons and other systems as if they were typed in the terminal.
+///
+/// Commands are echoed and added to the history as the line which parses back to them,
+/// then queued in the [`TerminalCommandQueue`], so their replies are printed as usual.
+///
+/// # Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use leafwing_terminal::{TerminalCommand, TerminalDispatch};
+/// #
+/// /// Spawns a wave of enemies
+/// #[derive(TerminalCommand)]
+/// #[terminal_command(name = "spawn_wave")]
+/// struct SpawnWaveCommand {
+///     /// Number of enemies
+///     count: u32,
+///     /// Kind of enemies, or all kinds
+///     kind: Option<String>,
+/// }
+///
+/// #[derive(Component)]
+/// struct SpawnWaveButton;
+///
+/// fn spawn_wave_button(
+///     buttons: Query<&Interaction, (Changed<Interaction>, With<SpawnWaveButton>)>,
+///     mut dispatch: TerminalDispatch,
+/// ) {
+///     for interaction in buttons.iter() {
+///         if *interaction == Interaction::Clicked {
+///             dispatch.dispatch_typed(SpawnWaveCommand {
+///                 count: 10,
+///                 kind: Some("Big Slime".to_string()),
+///             });
+///         }
+///     }
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct TerminalDispatch<'w, 's> {
+    config: Res<'w, TerminalConfiguration>,
+    registry: Res<'w, CommandRegistry>,
+    state: ResMut<'w, TerminalState>,
+    queue: ResMut<'w, TerminalCommandQueue>,
+    terminal_line: EventWriter<'w, 's, PrintTerminalLine>,
+    time: Res<'w, Time>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl<'w, 's> TerminalDispatch<'w, 's> {
+    /// Echo, record and queue a command, see [`TerminalCommandQueue::push`] for the returned invocation.
+    ///
+    /// The command is echoed as `$ spawn_wave 10 "Big Slime"`, with the configured [`echo_prompt`](TerminalConfiguration::echo_prompt).
+    pub fn dispatch_typed<T: CommandName + IntoValues>(
+        &mut self,
+        command: T,
+    ) -> TerminalInvocation {
+        let command = TerminalCommandEntered {
+            command: T::command_name().to_string(),
+            args: command.into_values(),
+        };
+        let line = format_command(&command);
+        let invocation = self.queue.push(command);
+        let info = SubmitInfo {
+            line: &line,
+            source: EchoSource::Dispatched,
+            invocation: Some(invocation),
+            printed_at: self.time.seconds_since_startup(),
+        };
+        let echo = format_echo(&self.config, &self.registry, &info);
+        self.terminal_line.send(echo.into());
+        self.state.record_submission(line, &self.config);
+        invocation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+    use crate as leafwing_terminal;
+    use crate::harness::test_app;
+    use crate::{
+        parse_command, AddTerminalCommand, CommandArgGroup, CommandArgs, FromValue, IntoValue,
+        TerminalCommand,
+    };
+
+    #[derive(CommandArgGroup, Clone, Debug, PartialEq)]
+    struct Pos {
+        x: f64,
+        y: f64,
+    }
+
+    /// Has an argument of each kind
+    #[derive(TerminalCommand, Clone, Debug, PartialEq)]
+    #[terminal_command(name = "every")]
+    struct EveryCommand {
+        text: String,
+        int: i32,
+        big: u64,
+        flag: bool,
+        #[terminal_command(flatten)]
+        pos: Pos,
+        name: Option<String>,
+        rest: Vec<i64>,
+    }
+
+    fn every() -> EveryCommand {
+        EveryCommand {
+            text: "it's \"quoted\"\nover\ttwo lines \\".to_string(),
+            int: -5,
+            big: u64::MAX,
+            flag: false,
+            pos: Pos { x: -0.5, y: 1e20 },
+            name: Some(String::new()),
+            rest: vec![-1, 0, 12_000],
+        }
+    }
+
+    const EVERY_LINE: &str = r#"every "it\'s \"quoted\"\nover\ttwo lines \\" -5 "18446744073709551615" false "-0.5" 1e20 "" -1 0 12000"#;
+
+    fn every_command(mut every: TerminalCommand<EveryCommand>) {
+        if let Some(EveryCommand { text, .. }) = every.take() {
+            every.reply_ok(text);
+        }
+    }
+
+    #[test]
+    fn typed_commands_round_trip() {
+        let values = every().into_values();
+        assert_eq!(EveryCommand::from_values(values.clone()), Ok(every()));
+
+        let command = TerminalCommandEntered {
+            command: "every".to_string(),
+            args: values,
+        };
+        assert_eq!(format_command(&command), EVERY_LINE);
+        assert_eq!(parse_command(EVERY_LINE), Ok(command));
+
+        let mut values = Vec::new();
+        f64::NAN.push_values(&mut values);
+        f64::NEG_INFINITY.push_values(&mut values);
+        assert!(f64::from_value(&values[0], 0).unwrap().is_nan());
+        assert_eq!(f64::from_value(&values[1], 1), Ok(f64::NEG_INFINITY));
+    }
+
+    #[test]
+    fn it_echoes_and_records_dispatched_commands() {
+        let mut app = test_app();
+        app.add_terminal_command::<EveryCommand, _, _>(every_command);
+        app.update();
+
+        let mut dispatch = SystemState::<TerminalDispatch>::new(&mut app.world);
+        let invocation = dispatch.get_mut(&mut app.world).dispatch_typed(every());
+        app.update();
+        app.update();
+
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(state.scrollback()[0].as_str(), format!("$ {EVERY_LINE}"));
+        assert_eq!(state.history().next(), Some(EVERY_LINE));
+        // The echoed text is printed a line at a time
+        let text = every().text;
+        let mut expected: Vec<_> = text.lines().collect();
+        expected.push("[ok]");
+        assert_eq!(invocation.collect_output(&app), expected);
+    }
+
+    #[test]
+    fn it_echoes_after_output_waiting_to_be_printed() {
+        let mut app = test_app();
+        app.add_terminal_command::<EveryCommand, _, _>(every_command);
+        let mut config = app.world.resource_mut::<TerminalConfiguration>();
+        config.print_lines_per_frame = 1000;
+        config.scrollback_size = 0;
+        app.update();
+
+        let mut events = app.world.resource_mut::<Events<PrintTerminalLine>>();
+        for i in 0..2000 {
+            events.send(PrintTerminalLine::new(format!("bulk {i}")));
+        }
+        app.update();
+        let mut dispatch = SystemState::<TerminalDispatch>::new(&mut app.world);
+        dispatch.get_mut(&mut app.world).dispatch_typed(every());
+        app.update();
+        app.update();
+
+        let state = app.world.resource::<TerminalState>();
+        let echo = format!("$ {EVERY_LINE}");
+        let echo_index = state.scrollback().iter().position(|line| *line == echo);
+        assert_eq!(echo_index, Some(2000));
+        assert_eq!(state.scrollback()[1999], "bulk 1999");
+    }
+}
@@ -0,0 +1,183 @@
+use crate::selection::PrintedAt;
+use crate::ui::strip_decorations;
+use crate::{CommandRegistry, TerminalConfiguration, TerminalInvocation, TerminalLine};
+
+/// How an echoed command was submitted, see [`Echo`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EchoSource {
+    /// Typed or pasted into the input
+    Typed,
+    /// Dispatched by a system with [`TerminalDispatch`](crate::TerminalDispatch)
+    Dispatched,
+    /// Scheduled with `after` or `at`, and run once its time came
+    Scheduled,
+}
+
+/// Attached to the scrollback line echoing a submitted command, see [`TerminalLine::get_meta`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Echo {
+    /// The command line, without the prompt
+    pub command: String,
+    /// The command line with its alias replaced by the command it runs, if shown,
+    /// see [`TerminalConfiguration::echo_aliases`]
+    pub expansion: Option<String>,
+    /// How the command was submitted
+    pub source: EchoSource,
+    /// The invocation running the command, or `None` if it didn't run, such as when it didn't parse
+    pub invocation: Option<TerminalInvocation>,
+}
+
+/// A submitted command line to echo, see [`format_echo`].
+pub(crate) struct SubmitInfo<'a> {
+    pub(crate) line: &'a str,
+    pub(crate) source: EchoSource,
+    pub(crate) invocation: Option<TerminalInvocation>,
+    /// Seconds since startup it was submitted at
+    pub(crate) printed_at: f64,
+}
+
+/// The scrollback line echoing a submitted command, built the same way however it was submitted.
+///
+/// The line is [`TerminalConfiguration::echo_prompt`] followed by the command, after a `⏲` for scheduled commands.
+/// It carries an [`Echo`], which the terminal draws the alias expansion from, and the time it was submitted at.
+pub(crate) fn format_echo(
+    config: &TerminalConfiguration,
+    registry: &CommandRegistry,
+    info: &SubmitInfo,
+) -> TerminalLine {
+    let marker = match info.source {
+        EchoSource::Scheduled => "⏲ ",
+        EchoSource::Typed | EchoSource::Dispatched => "",
+    };
+    let text = format!("{marker}{}{}", config.echo_prompt, info.line);
+    let expansion = config
+        .echo_aliases
+        .then(|| alias_expansion(info.line, registry))
+        .flatten();
+    TerminalLine::from(text)
+        .with_meta(Echo {
+            command: info.line.to_string(),
+            expansion,
+            source: info.source,
+            invocation: info.invocation,
+        })
+        .with_meta(PrintedAt(info.printed_at))
+}
+
+/// `line` with the alias it starts with replaced by the command the alias runs, or `None` if it doesn't start with one.
+fn alias_expansion(line: &str, registry: &CommandRegistry) -> Option<String> {
+    let trimmed = line.trim_start();
+    let name = trimmed.split_whitespace().next()?;
+    let command = registry.resolve(name)?;
+    (command != name).then(|| format!("{command}{}", &trimmed[name.len()..]))
+}
+
+impl TerminalLine {
+    /// The command of an echoed line, or the line without the decorations of help, see [`strip_decorations`].
+    pub(crate) fn command_text(&self) -> &str {
+        match self.get_meta::<Echo>() {
+            Some(echo) => &echo.command,
+            None => strip_decorations(self.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+    use crate as leafwing_terminal;
+    use crate::harness::test_app;
+    use crate::{
+        AddTerminalCommand, TerminalCommand, TerminalCommandQueue, TerminalDispatch, TerminalState,
+    };
+
+    /// Gives gold
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "give_gold")]
+    struct GiveGoldCommand {
+        amount: u32,
+    }
+
+    fn give_gold_command(mut give_gold: TerminalCommand<GiveGoldCommand>) {
+        if give_gold.take().is_some() {
+            give_gold.ok();
+        }
+    }
+
+    #[test]
+    fn it_expands_aliases() {
+        let mut registry = CommandRegistry::default();
+        registry.insert("give_gold", None);
+        registry.add_alias("gg", "give_gold");
+        assert_eq!(
+            alias_expansion("gg 1000", &registry).as_deref(),
+            Some("give_gold 1000")
+        );
+        assert_eq!(alias_expansion("give_gold 1000", &registry), None);
+        assert_eq!(alias_expansion("unknown", &registry), None);
+        assert_eq!(alias_expansion("", &registry), None);
+    }
+
+    #[test]
+    fn it_echoes_the_same_from_every_source() {
+        let mut app = test_app();
+        app.add_terminal_command::<GiveGoldCommand, _, _>(give_gold_command);
+        app.update();
+        {
+            let mut config = app.world.resource_mut::<TerminalConfiguration>();
+            config.echo_prompt = "> ".to_string();
+            config.echo_aliases = true;
+        }
+        app.world
+            .resource_mut::<CommandRegistry>()
+            .add_alias("gg", "give_gold");
+
+        // Dispatched by a system
+        let mut dispatch: SystemState<TerminalDispatch> = SystemState::new(&mut app.world);
+        dispatch
+            .get_mut(&mut app.world)
+            .dispatch_typed(GiveGoldCommand { amount: 1000 });
+        // Scheduled
+        app.world
+            .resource_mut::<TerminalCommandQueue>()
+            .submit("after 1 give_gold 1000")
+            .unwrap();
+        for _ in 0..6 {
+            app.update();
+        }
+
+        let state = app.world.resource::<TerminalState>();
+        let echoes: Vec<(&str, &Echo)> = state
+            .scrollback()
+            .iter()
+            .filter_map(|line| Some((line.as_str(), line.get_meta::<Echo>()?)))
+            .collect();
+        assert_eq!(echoes.len(), 2);
+        assert_eq!(echoes[0].0, "> give_gold 1000");
+        assert_eq!(echoes[0].1.source, EchoSource::Dispatched);
+        assert!(echoes[0].1.invocation.is_some());
+        assert_eq!(echoes[1].0, "⏲ > give_gold 1000");
+        assert_eq!(echoes[1].1.source, EchoSource::Scheduled);
+        assert_eq!(echoes[0].1.command, echoes[1].1.command);
+
+        // Typed, with an alias
+        let config = app.world.resource::<TerminalConfiguration>();
+        let registry = app.world.resource::<CommandRegistry>();
+        let typed = format_echo(
+            config,
+            registry,
+            &SubmitInfo {
+                line: "gg 1000",
+                source: EchoSource::Typed,
+                invocation: None,
+                printed_at: 0.,
+            },
+        );
+        assert_eq!(typed, "> gg 1000");
+        let echo = typed.get_meta::<Echo>().unwrap();
+        assert_eq!(echo.expansion.as_deref(), Some("give_gold 1000"));
+        assert_eq!(typed.command_text(), "gg 1000");
+    }
+}
@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+use leafwing_terminal_parser::ValueRawOwned;
+
+use crate::value::{FromValue, FromValueError, IntoValue, ValueType};
+
+/// How many candidates an unresolved name error lists at most.
+const MAX_CANDIDATES: usize = 5;
+
+/// Argument naming an entity by its [`Name`] component.
+///
+/// Resolve it with [`resolve_named_entity`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ByName(pub String);
+
+impl FromValue for ByName {
+    fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        String::from_value(value, arg_num).map(ByName)
+    }
+
+    fn from_value_owned(value: ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        String::from_value_owned(value, arg_num).map(ByName)
+    }
+}
+
+impl IntoValue for ByName {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        self.0.push_values(values);
+    }
+}
+
+/// Entities are given by their id, such as `4`, or as printed by `{:?}` with their generation, such as `"4v1"`.
+impl FromValue for Entity {
+    fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        match value {
+            ValueRawOwned::Int(id, _) => u32::try_from(*id).map(Entity::from_raw).map_err(|_| {
+                FromValueError::ValueTooLarge {
+                    arg_num,
+                    max: u32::MAX.into(),
+                }
+            }),
+            ValueRawOwned::String(s) => s
+                .split_once('v')
+                .and_then(|(id, generation)| {
+                    let id: u32 = id.parse().ok()?;
+                    let generation: u32 = generation.parse().ok()?;
+                    Some(Entity::from_bits(
+                        u64::from(generation) << 32 | u64::from(id),
+                    ))
+                })
+                .ok_or_else(|| {
+                    FromValueError::Custom(format!("expected an entity such as 4v1, got '{s}'"))
+                }),
+            ValueRawOwned::Float(_, _) => Err(FromValueError::UnexpectedArgType {
+                arg_num,
+                expected: ValueType::Int,
+                received: ValueType::Float,
+            }),
+            ValueRawOwned::Bool(_, _) => Err(FromValueError::UnexpectedArgType {
+                arg_num,
+                expected: ValueType::Int,
+                received: ValueType::Bool,
+            }),
+        }
+    }
+}
+
+/// Entities of the first generation are given by their id, others with their generation.
+impl IntoValue for Entity {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        match self.generation() {
+            0 => self.id().push_values(values),
+            generation => values.push(ValueRawOwned::String(format!("{}v{generation}", self.id()))),
+        }
+    }
+}
+
+/// Finds the one entity whose [`Name`] is exactly `name`.
+///
+/// When no entity or several entities match, the error lists the candidates:
+/// the ambiguous entities, or names containing `name` ignoring case.
+///
+/// # Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use leafwing_terminal::{resolve_named_entity, ByName, TerminalCommand};
+/// #
+/// /// Kills an entity
+/// #[derive(TerminalCommand)]
+/// #[terminal_command(name = "kill")]
+/// struct KillCommand {
+///     /// Name of the entity
+///     name: ByName,
+/// }
+///
+/// fn kill_command(
+///     mut kill: TerminalCommand<KillCommand>,
+///     names: Query<(Entity, &Name)>,
+///     mut commands: Commands,
+/// ) {
+///     if let Some(KillCommand { name }) = kill.take() {
+///         match resolve_named_entity(names.iter(), &name) {
+///             Ok(entity) => {
+///                 commands.entity(entity).despawn();
+///                 kill.ok();
+///             }
+///             Err(err) => kill.reply_failed(err),
+///         }
+///     }
+/// }
+/// ```
+pub fn resolve_named_entity<'a>(
+    entities: impl IntoIterator<Item = (Entity, &'a Name)>,
+    name: &ByName,
+) -> Result<Entity, String> {
+    let mut matches = Vec::new();
+    let mut similar = Vec::new();
+    let lowercase = name.0.to_lowercase();
+    for (entity, entity_name) in entities {
+        if entity_name.as_str() == name.0 {
+            matches.push(entity);
+        } else if entity_name.as_str().to_lowercase().contains(&lowercase) {
+            similar.push(entity_name.as_str().to_string());
+        }
+    }
+
+    match matches.as_slice() {
+        [entity] => Ok(*entity),
+        [] if similar.is_empty() => Err(format!("no entity named '{}'", name.0)),
+        [] => {
+            similar.sort();
+            similar.dedup();
+            similar.truncate(MAX_CANDIDATES);
+            Err(format!(
+                "no entity named '{}', did you mean {}?",
+                name.0,
+                similar.join(", ")
+            ))
+        }
+        entities => {
+            let entities: Vec<String> = entities
+                .iter()
+                .take(MAX_CANDIDATES)
+                .map(|entity| format!("{entity:?}"))
+                .collect();
+            Err(format!(
+                "{} entities named '{}': {}",
+                matches.len(),
+                name.0,
+                entities.join(", ")
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world() -> World {
+        let mut world = World::new();
+        for name in ["Player", "Goblin", "Goblin", "Goblin King"] {
+            world.spawn().insert(Name::new(name));
+        }
+        world
+    }
+
+    fn resolve(world: &mut World, name: &str) -> Result<Entity, String> {
+        let mut names = world.query::<(Entity, &Name)>();
+        resolve_named_entity(names.iter(world), &ByName(name.to_string()))
+    }
+
+    #[test]
+    fn it_resolves_unique_names() {
+        let mut world = world();
+        let player = resolve(&mut world, "Player").unwrap();
+        assert_eq!(world.get::<Name>(player).unwrap().as_str(), "Player");
+        assert!(resolve(&mut world, "Goblin King").is_ok());
+    }
+
+    #[test]
+    fn it_parses_entities() {
+        let parse = |value| Entity::from_value(&value, 0);
+        let entity = Entity::from_raw(4);
+        assert_eq!(parse(ValueRawOwned::Int(4, "4".to_string())), Ok(entity));
+        assert_eq!(
+            parse(ValueRawOwned::String(format!("{entity:?}"))),
+            Ok(entity)
+        );
+        assert_eq!(
+            parse(ValueRawOwned::String("4v1".to_string())).map(Entity::generation),
+            Ok(1)
+        );
+        assert_eq!(
+            parse(ValueRawOwned::String("player".to_string())),
+            Err(FromValueError::Custom(
+                "expected an entity such as 4v1, got 'player'".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn it_lists_candidates() {
+        let mut world = world();
+        let err = resolve(&mut world, "Goblin").unwrap_err();
+        assert!(err.starts_with("2 entities named 'Goblin': "), "{err}");
+
+        assert_eq!(
+            resolve(&mut world, "king"),
+            Err("no entity named 'king', did you mean Goblin King?".to_string())
+        );
+        assert_eq!(
+            resolve(&mut world, "Dragon"),
+            Err("no entity named 'Dragon'".to_string())
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::parse::push_quoted;
+
+/// Expands `%last%` to the last line of output and `%sel%` to the selected scrollback line.
+///
+/// Expansions containing whitespace or quotes are quoted so they stay a single argument,
+/// and `%%` is a literal `%`. Other text between percent signs is left as is.
+/// Fails with the placeholder when there is nothing to expand it to.
+pub(crate) fn expand_placeholders(
+    line: &str,
+    last: Option<&str>,
+    selection: Option<&str>,
+) -> Result<String, &'static str> {
+    let mut expanded = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find('%') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let (placeholder, source) = if rest.starts_with("%%") {
+            expanded.push('%');
+            rest = &rest[2..];
+            continue;
+        } else if rest.starts_with("%last%") {
+            ("%last%", last.ok_or("%last%")?)
+        } else if rest.starts_with("%sel%") {
+            ("%sel%", selection.ok_or("%sel%")?)
+        } else {
+            expanded.push('%');
+            rest = &rest[1..];
+            continue;
+        };
+
+        push_quoted(&mut expanded, source);
+        rest = &rest[placeholder.len()..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// Expands a leading `!!` to the previously submitted line, keeping anything typed after it.
+///
+/// Fails with `!!` when nothing has been submitted yet.
+pub(crate) fn expand_history(line: &str, previous: Option<&str>) -> Result<String, &'static str> {
+    match line.trim_start().strip_prefix("!!") {
+        Some(rest) => Ok(format!("{}{rest}", previous.ok_or("!!")?)),
+        None => Ok(line.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_expands_bang_bang() {
+        let previous = Some("spawn_wave 'goblin king'");
+        assert_eq!(
+            expand_history("!!", previous).unwrap(),
+            "spawn_wave 'goblin king'"
+        );
+        assert_eq!(
+            expand_history("!! 3", previous).unwrap(),
+            "spawn_wave 'goblin king' 3"
+        );
+        assert_eq!(expand_history("log !!", previous).unwrap(), "log !!");
+        assert_eq!(expand_history("!!", None), Err("!!"));
+    }
+
+    #[test]
+    fn it_expands_placeholders() {
+        let expanded = expand_placeholders("inspect %last% %sel%", Some("42v0"), Some("player"));
+        assert_eq!(expanded.unwrap(), r#"inspect "42v0" player"#);
+
+        let expanded = expand_placeholders("log %last%", Some(r#"say "hi""#), None);
+        assert_eq!(expanded.unwrap(), r#"log "say \"hi\"""#);
+    }
+
+    #[test]
+    fn it_escapes_percent_signs() {
+        let expanded = expand_placeholders("log 100%% %%last%% 5%", None, None);
+        assert_eq!(expanded.unwrap(), "log 100% %last% 5%");
+    }
+
+    #[test]
+    fn it_fails_on_missing_sources() {
+        assert_eq!(
+            expand_placeholders("inspect %last%", None, None),
+            Err("%last%")
+        );
+        assert_eq!(
+            expand_placeholders("inspect %sel%", Some("42v0"), None),
+            Err("%sel%")
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bevy::prelude::*;
+
+use crate::TerminalState;
+
+static NEXT_INVOCATION: AtomicU64 = AtomicU64::new(0);
+
+/// Identifies one run of a command, for tests and tools which need the output of each command they submit.
+///
+/// Returned by [`TerminalCommandQueue::push`](crate::TerminalCommandQueue::push) and
+/// [`TerminalCommandQueue::submit`](crate::TerminalCommandQueue::submit).
+/// Lines printed through [`TerminalCommand`](struct@crate::TerminalCommand) carry the invocation they belong to
+/// as metadata, so commands run in the same frame don't mix up their output.
+///
+/// # Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use leafwing_terminal::{TerminalCommandQueue, TerminalCorePlugin};
+/// #
+/// let mut app = App::new();
+/// app.add_plugins(MinimalPlugins).add_plugin(TerminalCorePlugin);
+/// app.update();
+///
+/// let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+/// let help = queue.submit("help clear").unwrap();
+/// let missing = queue.submit("help nope").unwrap();
+/// for _ in 0..3 {
+///     app.update();
+/// }
+///
+/// assert!(help.collect_output(&app)[0].starts_with("Usage:"));
+/// assert_eq!(
+///     missing.collect_output(&app),
+///     ["Command 'nope' does not exist"]
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TerminalInvocation(u64);
+
+impl TerminalInvocation {
+    pub(crate) fn next() -> Self {
+        TerminalInvocation(NEXT_INVOCATION.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Number of the invocation, unique while the app runs, as sent in [`TerminalStructuredOutput`](crate::TerminalStructuredOutput).
+    pub fn id(&self) -> u64 {
+        self.0
+    }
+
+    /// Lines of the scrollback printed by this invocation, oldest first.
+    ///
+    /// Output only reaches the scrollback at the end of the frame the command ran in,
+    /// so update the app until the command has printed its outcome.
+    pub fn collect_output(&self, app: &App) -> Vec<String> {
+        app.world
+            .resource::<TerminalState>()
+            .scrollback()
+            .iter()
+            .filter(|line| line.get_meta::<TerminalInvocation>() == Some(self))
+            .map(|line| line.text.clone())
+            .collect()
+    }
+}
+
+/// An app running the terminal without a window, which tests add their commands and resources to.
+#[cfg(test)]
+pub(crate) fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(crate::TerminalCorePlugin);
+    app
+}
+
+/// Submit `line` and give it two frames to run, returning what it printed.
+#[cfg(test)]
+pub(crate) fn submit_and_collect(app: &mut App, line: &str) -> Vec<String> {
+    let invocation = app
+        .world
+        .resource_mut::<crate::TerminalCommandQueue>()
+        .submit(line)
+        .unwrap();
+    app.update();
+    app.update();
+    invocation.collect_output(app)
+}
+
+/// Validates the level of a `loadlevel` command, which only knows the forest.
+#[cfg(test)]
+pub(crate) fn validate_level(args: &[crate::ValueRawOwned]) -> Result<(), String> {
+    match args.first() {
+        Some(crate::ValueRawOwned::String(level)) if level != "forest" => {
+            Err(format!("unknown level '{level}'"))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::test_app;
+    use crate::{TerminalCommandQueue, TerminalConfiguration};
+
+    #[test]
+    fn it_separates_output_of_commands_run_in_the_same_frame() {
+        let mut app = test_app();
+        app.insert_resource(TerminalConfiguration {
+            command_batch_size: 0,
+            ..Default::default()
+        });
+        app.update();
+
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        let missing = queue.submit("help nope").unwrap();
+        let invalid = queue.submit("jump").unwrap();
+        let jump = queue.submit("jump nowhere").unwrap();
+        // The jump system takes one invocation a frame, so the second jump runs a frame later
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_eq!(
+            missing.collect_output(&app),
+            ["Command 'nope' does not exist"]
+        );
+        let invalid = invalid.collect_output(&app);
+        assert!(invalid[0].starts_with("[error]"));
+        assert!(invalid[1..].iter().all(|line| !line.contains("nowhere")));
+        assert_eq!(
+            jump.collect_output(&app),
+            ["[error] no mark 'nowhere'", "[failed]"]
+        );
+    }
+}
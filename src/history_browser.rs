@@ -0,0 +1,88 @@
+use crate::TerminalState;
+
+/// The history browser opened with [`TerminalConfiguration::history_browser_key`](crate::TerminalConfiguration::history_browser_key).
+#[derive(Debug, Default)]
+pub(crate) struct HistoryBrowser {
+    /// Typed filter, matched fuzzily against history entries
+    pub(crate) filter: String,
+    /// Highlighted match, counted back from the newest
+    pub(crate) selected: usize,
+}
+
+impl TerminalState {
+    /// Open the history browser, or close it if it's open.
+    pub(crate) fn toggle_history_browser(&mut self) {
+        self.history_browser = match self.history_browser {
+            Some(_) => None,
+            None => Some(HistoryBrowser::default()),
+        };
+    }
+
+    /// History entries matching the browser's filter, newest first and without repeats.
+    pub(crate) fn history_matches(&self) -> Vec<&str> {
+        let filter = self
+            .history_browser
+            .as_ref()
+            .map_or("", |browser| browser.filter.as_str());
+        let mut matches: Vec<&str> = Vec::new();
+        for entry in self.history() {
+            if fuzzy_match(filter, entry) && !matches.contains(&entry) {
+                matches.push(entry);
+            }
+        }
+        matches
+    }
+
+    /// Remove every copy of `entry` from the history.
+    pub(crate) fn remove_history_entry(&mut self, entry: &str) {
+        self.history.retain(|command| command != entry);
+        self.history_index = self.history_index.min(self.history.len());
+    }
+}
+
+/// Whether the characters of `filter` appear in `text` in order, ignoring case.
+pub(crate) fn fuzzy_match(filter: &str, text: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    filter
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| text.any(|t| t == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TerminalMode;
+
+    #[test]
+    fn it_matches_fuzzily() {
+        assert!(fuzzy_match("", "spawn_wave 3"));
+        assert!(fuzzy_match("spw", "spawn_wave 3"));
+        assert!(fuzzy_match("SW3", "spawn_wave 3"));
+        assert!(!fuzzy_match("ws", "spawn_wave 3"));
+        assert!(!fuzzy_match("spawn_waves", "spawn_wave 3"));
+    }
+
+    #[test]
+    fn it_lists_matches_newest_first() {
+        let mut state = TerminalState::default();
+        for cmd in ["spawn_wave 1", "log hi", "spawn_wave 2", "spawn_wave 1"] {
+            state.push_history(cmd.to_string(), 20);
+        }
+
+        state.toggle_history_browser();
+        assert_eq!(state.mode(), TerminalMode::HistoryBrowser);
+        assert_eq!(
+            state.history_matches(),
+            ["spawn_wave 1", "spawn_wave 2", "log hi"]
+        );
+        state.history_browser.as_mut().unwrap().filter = "sw2".to_string();
+        assert_eq!(state.history_matches(), ["spawn_wave 2"]);
+
+        state.remove_history_entry("spawn_wave 1");
+        assert!(state.history().eq(["spawn_wave 2", "log hi"]));
+
+        state.toggle_history_browser();
+        assert_eq!(state.mode(), TerminalMode::Normal);
+    }
+}
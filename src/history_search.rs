@@ -0,0 +1,150 @@
+use std::mem;
+
+use crate::TerminalState;
+
+/// The reverse history search started with [`TerminalConfiguration::reverse_search_key`](crate::TerminalConfiguration::reverse_search_key).
+#[derive(Debug, Default)]
+pub(crate) struct HistorySearch {
+    /// Typed text searched for in history entries
+    pub(crate) query: String,
+    /// Matches skipped by pressing the key again, counted back from the newest
+    pub(crate) skipped: usize,
+    /// Input typed before the search, put back when it is cancelled
+    pub(crate) typed: String,
+}
+
+impl TerminalState {
+    /// Start searching the history backward, or go to the next older match if already searching.
+    ///
+    /// The input shows the match, or what was typed before the search if nothing matches.
+    pub(crate) fn search_history_backward(&mut self) {
+        let older = self.history_search_matches().len();
+        if let Some(search) = &mut self.history_search {
+            if search.skipped + 1 < older {
+                search.skipped += 1;
+            }
+        } else {
+            self.history_search = Some(HistorySearch {
+                typed: self.buf.clone(),
+                ..Default::default()
+            });
+        }
+        self.show_history_search_match();
+    }
+
+    /// Search for `query` instead, going back to its newest match.
+    pub(crate) fn set_history_search_query(&mut self, query: String) {
+        if let Some(search) = &mut self.history_search {
+            search.query = query;
+            search.skipped = 0;
+        }
+        self.show_history_search_match();
+    }
+
+    /// History entries containing the query, newest first and without repeats.
+    ///
+    /// Nothing matches an empty query, so the input keeps what was typed until the search has something to look for.
+    fn history_search_matches(&self) -> Vec<&str> {
+        let query = match &self.history_search {
+            Some(search) if !search.query.is_empty() => search.query.as_str(),
+            _ => return Vec::new(),
+        };
+        let mut matches: Vec<&str> = Vec::new();
+        for entry in self.history() {
+            if entry.contains(query) && !matches.contains(&entry) {
+                matches.push(entry);
+            }
+        }
+        matches
+    }
+
+    /// The history entry the search is at, if the query matches any.
+    pub(crate) fn history_search_match(&self) -> Option<&str> {
+        let skipped = self.history_search.as_ref()?.skipped;
+        self.history_search_matches().get(skipped).copied()
+    }
+
+    fn show_history_search_match(&mut self) {
+        let Some(search) = &self.history_search else {
+            return;
+        };
+        let shown = self
+            .history_search_match()
+            .unwrap_or(&search.typed)
+            .to_string();
+        self.buf = shown;
+    }
+
+    /// End the search, returning the line shown in the input to submit.
+    pub(crate) fn accept_history_search(&mut self) -> String {
+        self.show_history_search_match();
+        self.history_search = None;
+        mem::take(&mut self.buf)
+    }
+
+    /// End the search, putting back what was typed before it.
+    pub(crate) fn cancel_history_search(&mut self) {
+        if let Some(search) = self.history_search.take() {
+            self.buf = search.typed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{TerminalMode, TerminalState};
+
+    fn state() -> TerminalState {
+        let mut state = TerminalState::default();
+        for cmd in [
+            "spawn_wave 1",
+            "log hi",
+            "spawn_wave 2 brute",
+            "spawn_wave 1",
+            "clear",
+        ] {
+            state.push_history(cmd.to_string(), 20);
+        }
+        state.buf = "tele".to_string();
+        state
+    }
+
+    #[test]
+    fn it_searches_history_backward() {
+        let mut state = state();
+        state.search_history_backward();
+        assert_eq!(state.mode(), TerminalMode::HistorySearch);
+        assert_eq!(state.buf, "tele");
+
+        state.set_history_search_query("wave".to_string());
+        assert_eq!(state.buf, "spawn_wave 1");
+        // Pressing the key again goes to older matches, skipping repeats, and stops at the oldest
+        state.search_history_backward();
+        assert_eq!(state.buf, "spawn_wave 2 brute");
+        state.search_history_backward();
+        assert_eq!(state.buf, "spawn_wave 2 brute");
+
+        // Typing starts over from the newest match
+        state.set_history_search_query("wave 2".to_string());
+        assert_eq!(state.history_search_match(), Some("spawn_wave 2 brute"));
+        state.set_history_search_query("teleport".to_string());
+        assert_eq!(state.history_search_match(), None);
+        assert_eq!(state.buf, "tele");
+
+        state.set_history_search_query("log".to_string());
+        assert_eq!(state.accept_history_search(), "log hi");
+        assert_eq!(state.mode(), TerminalMode::Normal);
+        assert!(state.buf.is_empty());
+    }
+
+    #[test]
+    fn it_puts_back_the_input_when_cancelled() {
+        let mut state = state();
+        state.search_history_backward();
+        state.set_history_search_query("clear".to_string());
+        assert_eq!(state.buf, "clear");
+        state.cancel_history_search();
+        assert_eq!(state.mode(), TerminalMode::Normal);
+        assert_eq!(state.buf, "tele");
+    }
+}
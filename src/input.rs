@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use bevy::ecs::event::Events;
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+
+use crate::{TerminalConfiguration, TerminalOpen, TerminalState};
+
+/// The keyboard as it was before [`block_game_input`] hid it from the game, which the terminal reads its own keys from.
+#[derive(Default)]
+pub(crate) struct TerminalKeys(pub(crate) Input<KeyCode>);
+
+/// Copies the keyboard for the terminal, before it may be hidden from the game.
+pub(crate) fn stash_terminal_keys(
+    keyboard_input: Option<Res<Input<KeyCode>>>,
+    mut keys: ResMut<TerminalKeys>,
+) {
+    keys.0 = keyboard_input.map_or_else(Input::default, |input| input.clone());
+}
+
+/// Times the keys the terminal repeats while held, see [`TerminalConfiguration::key_repeat_rate`].
+#[derive(Default)]
+pub(crate) struct KeyRepeat {
+    /// When each held key was pressed, and the steps it has made since
+    held: HashMap<KeyCode, (f64, u32)>,
+}
+
+impl KeyRepeat {
+    /// Steps `key` makes this frame: one when pressed, then more once it is held long enough.
+    pub(crate) fn steps(
+        &mut self,
+        key: KeyCode,
+        keys: &Input<KeyCode>,
+        now: f64,
+        config: &TerminalConfiguration,
+    ) -> u32 {
+        if keys.just_pressed(key) {
+            self.held.insert(key, (now, 1));
+            return 1;
+        }
+        if !keys.pressed(key) {
+            self.held.remove(&key);
+            return 0;
+        }
+        let Some((pressed_at, taken)) = self.held.get_mut(&key) else {
+            return 0;
+        };
+        let held = (now - *pressed_at) as f32;
+        let total = repeat_steps(held, config.key_repeat_delay, config.key_repeat_rate);
+        let steps = total.saturating_sub(*taken);
+        *taken = total;
+        steps
+    }
+}
+
+/// Steps made in total by a key held for `held` seconds:
+/// one when pressed, one after `delay` seconds, then `rate` steps per second.
+pub(crate) fn repeat_steps(held: f32, delay: f32, rate: f32) -> u32 {
+    if rate <= 0. || held < delay {
+        return 1;
+    }
+    2 + ((held - delay) * rate) as u32
+}
+
+/// Hides the keyboard from the game while the terminal is open, and the mouse while it is over the terminal,
+/// see [`TerminalConfiguration::block_game_input`].
+///
+/// Runs after egui has read the input events, so the terminal itself still sees them.
+pub(crate) fn block_game_input(
+    config: Res<TerminalConfiguration>,
+    open: Res<TerminalOpen>,
+    state: Res<TerminalState>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut characters: ResMut<Events<ReceivedCharacter>>,
+    mut mouse_input: ResMut<Input<MouseButton>>,
+    mut wheel: ResMut<Events<MouseWheel>>,
+) {
+    if !config.block_game_input || !open.0 {
+        return;
+    }
+
+    // The toggle keys and Escape still reach the game, so it can close the terminal or pause
+    let blocked: Vec<KeyCode> = keyboard_input
+        .get_pressed()
+        .chain(keyboard_input.get_just_released())
+        .filter(|key| **key != KeyCode::Escape && !config.keys.contains(key))
+        .copied()
+        .collect();
+    for key in blocked {
+        keyboard_input.reset(key);
+    }
+    characters.clear();
+
+    if state.pointer_over {
+        let buttons: Vec<MouseButton> = mouse_input
+            .get_pressed()
+            .chain(mouse_input.get_just_released())
+            .copied()
+            .collect();
+        for button in buttons {
+            mouse_input.reset(button);
+        }
+        wheel.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+    use crate::harness::test_app;
+
+    #[test]
+    fn it_repeats_held_keys_after_a_delay() {
+        assert_eq!(repeat_steps(0., 0.4, 10.), 1);
+        assert_eq!(repeat_steps(0.39, 0.4, 10.), 1);
+        assert_eq!(repeat_steps(0.4, 0.4, 10.), 2);
+        assert_eq!(repeat_steps(0.45, 0.4, 10.), 2);
+        assert_eq!(repeat_steps(0.55, 0.4, 10.), 3);
+        assert_eq!(repeat_steps(1.45, 0.4, 10.), 12);
+        // Not repeating
+        assert_eq!(repeat_steps(5., 0.4, 0.), 1);
+
+        let config = TerminalConfiguration {
+            key_repeat_delay: 0.4,
+            key_repeat_rate: 10.,
+            ..Default::default()
+        };
+        let mut repeat = KeyRepeat::default();
+        let mut keys = Input::default();
+        keys.press(KeyCode::Up);
+        assert_eq!(repeat.steps(KeyCode::Up, &keys, 1.0, &config), 1);
+        keys.clear();
+        assert_eq!(repeat.steps(KeyCode::Up, &keys, 1.2, &config), 0);
+        assert_eq!(repeat.steps(KeyCode::Up, &keys, 1.45, &config), 1);
+        // A slow frame makes up for the steps it missed
+        assert_eq!(repeat.steps(KeyCode::Up, &keys, 1.85, &config), 4);
+        keys.release(KeyCode::Up);
+        keys.clear();
+        assert_eq!(repeat.steps(KeyCode::Up, &keys, 1.9, &config), 0);
+    }
+
+    #[test]
+    fn it_hides_typed_keys_from_the_game() {
+        let mut app = test_app();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<MouseButton>>()
+            .add_event::<ReceivedCharacter>()
+            .add_event::<MouseWheel>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                block_game_input.after(stash_terminal_keys),
+            );
+        let press = |app: &mut App, keys: &[KeyCode]| {
+            // Only `keys` are held
+            let mut input = app.world.resource_mut::<Input<KeyCode>>();
+            *input = Input::default();
+            for key in keys {
+                input.press(*key);
+            }
+            app.update();
+        };
+        let pressed = |app: &App| {
+            let mut keys: Vec<_> = app
+                .world
+                .resource::<Input<KeyCode>>()
+                .get_pressed()
+                .copied()
+                .collect();
+            keys.sort_by_key(|key| *key as u32);
+            keys
+        };
+
+        // Closed
+        press(&mut app, &[KeyCode::W]);
+        assert_eq!(pressed(&app), [KeyCode::W]);
+
+        app.world.resource_mut::<TerminalOpen>().0 = true;
+        press(&mut app, &[KeyCode::W, KeyCode::Escape, KeyCode::Grave]);
+        assert_eq!(pressed(&app), [KeyCode::Escape, KeyCode::Grave]);
+        // The terminal still sees every key
+        assert!(app.world.resource::<TerminalKeys>().0.pressed(KeyCode::W));
+
+        // Mouse buttons are only hidden over the terminal
+        app.world
+            .resource_mut::<Input<MouseButton>>()
+            .press(MouseButton::Left);
+        app.update();
+        assert!(app
+            .world
+            .resource::<Input<MouseButton>>()
+            .pressed(MouseButton::Left));
+        app.world.resource_mut::<TerminalState>().pointer_over = true;
+        app.update();
+        assert!(!app
+            .world
+            .resource::<Input<MouseButton>>()
+            .pressed(MouseButton::Left));
+
+        app.world
+            .resource_mut::<TerminalConfiguration>()
+            .block_game_input = false;
+        press(&mut app, &[KeyCode::W]);
+        assert_eq!(pressed(&app), [KeyCode::W]);
+    }
+}
@@ -0,0 +1,226 @@
+use bevy::input::keyboard::KeyCode;
+use leafwing_terminal_parser::ValueRawOwned;
+
+use crate::value::{FromValue, FromValueError, IntoValue, ValueType};
+
+macro_rules! key_codes {
+    ($($key: ident),* $(,)?) => {
+        /// Every [`KeyCode`] with its variant name, which is matched case-insensitively.
+        const KEY_CODES: &[(KeyCode, &str)] = &[$((KeyCode::$key, stringify!($key))),*];
+    };
+}
+
+key_codes! {
+    Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0, A, B, C, D, E, F, G, H, I, J, K, L,
+    M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z, Escape, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11,
+    F12, F13, F14, F15, F16, F17, F18, F19, F20, F21, F22, F23, F24, Snapshot, Scroll, Pause,
+    Insert, Home, Delete, End, PageDown, PageUp, Left, Up, Right, Down, Back, Return, Space,
+    Compose, Caret, Numlock, Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7,
+    Numpad8, Numpad9, AbntC1, AbntC2, NumpadAdd, Apostrophe, Apps, Asterisk, Plus, At, Ax,
+    Backslash, Calculator, Capital, Colon, Comma, Convert, NumpadDecimal, NumpadDivide, Equals,
+    Grave, Kana, Kanji, LAlt, LBracket, LControl, LShift, LWin, Mail, MediaSelect, MediaStop, Minus,
+    NumpadMultiply, Mute, MyComputer, NavigateForward, NavigateBackward, NextTrack, NoConvert,
+    NumpadComma, NumpadEnter, NumpadEquals, Oem102, Period, PlayPause, Power, PrevTrack, RAlt,
+    RBracket, RControl, RShift, RWin, Semicolon, Slash, Sleep, Stop, NumpadSubtract, Sysrq, Tab,
+    Underline, Unlabeled, VolumeDown, VolumeUp, Wake, WebBack, WebFavorites, WebForward, WebHome,
+    WebRefresh, WebSearch, WebStop, Yen, Copy, Paste, Cut,
+}
+
+/// Common names that differ from the variant name.
+const KEY_ALIASES: &[(&str, KeyCode)] = &[
+    ("enter", KeyCode::Return),
+    ("backspace", KeyCode::Back),
+    ("esc", KeyCode::Escape),
+    ("del", KeyCode::Delete),
+    ("capslock", KeyCode::Capital),
+    ("printscreen", KeyCode::Snapshot),
+    ("ctrl", KeyCode::LControl),
+    ("lctrl", KeyCode::LControl),
+    ("rctrl", KeyCode::RControl),
+    ("shift", KeyCode::LShift),
+    ("alt", KeyCode::LAlt),
+    ("tilde", KeyCode::Grave),
+];
+
+/// How many suggestions an unknown key name error lists at most.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns the name of a key as accepted by its [`FromValue`] implementation.
+///
+/// Names are the lowercase [`KeyCode`] variant names, eg. `f5`, `space` or `lcontrol`.
+pub fn key_name(key: KeyCode) -> String {
+    KEY_CODES
+        .iter()
+        .find(|(code, _)| *code == key)
+        .map(|(_, name)| name.to_ascii_lowercase())
+        .unwrap_or_else(|| format!("{key:?}").to_ascii_lowercase())
+}
+
+fn find_key(name: &str) -> Option<KeyCode> {
+    KEY_CODES
+        .iter()
+        .find(|(_, key_name)| key_name.eq_ignore_ascii_case(name))
+        .map(|(key, _)| *key)
+        .or_else(|| {
+            KEY_ALIASES
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+                .map(|(_, key)| *key)
+        })
+}
+
+/// Key names within a small edit distance of `name`, closest first.
+fn suggest_keys(name: &str) -> Vec<String> {
+    let name = name.to_ascii_lowercase();
+    let max_distance = (name.len() / 3).clamp(1, 2);
+    let mut candidates: Vec<(usize, String)> = KEY_CODES
+        .iter()
+        .map(|(_, key_name)| key_name.to_ascii_lowercase())
+        .chain(KEY_ALIASES.iter().map(|(alias, _)| alias.to_string()))
+        .map(|candidate| (edit_distance(&name, &candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Optimal string alignment distance, so swapping two adjacent letters counts as one edit.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// Parses case-insensitive key names such as `f5`, `space`, `numpad3` or `lcontrol`.
+///
+/// Digits `0` to `9` are accepted for the number row keys.
+impl FromValue for KeyCode {
+    fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        let name = match value {
+            ValueRawOwned::String(name) => name.as_str(),
+            ValueRawOwned::Int(_, raw) => raw.as_str(),
+            ValueRawOwned::Float(_, _) => {
+                return Err(FromValueError::UnexpectedArgType {
+                    arg_num,
+                    expected: ValueType::String,
+                    received: ValueType::Float,
+                })
+            }
+            ValueRawOwned::Bool(_, _) => {
+                return Err(FromValueError::UnexpectedArgType {
+                    arg_num,
+                    expected: ValueType::String,
+                    received: ValueType::Bool,
+                })
+            }
+        };
+
+        let digit_key = match name.as_bytes() {
+            [digit @ b'0'..=b'9'] => Some(format!("key{}", *digit as char)),
+            _ => None,
+        };
+        if let Some(key) = find_key(digit_key.as_deref().unwrap_or(name)) {
+            return Ok(key);
+        }
+
+        let suggestions = suggest_keys(name);
+        Err(FromValueError::Custom(if suggestions.is_empty() {
+            format!("unknown key '{name}' for arg #{}", arg_num + 1)
+        } else {
+            format!(
+                "unknown key '{name}' for arg #{}, did you mean {}?",
+                arg_num + 1,
+                suggestions.join(", ")
+            )
+        }))
+    }
+}
+
+/// Keys are given by their [`key_name`].
+impl IntoValue for KeyCode {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        values.push(ValueRawOwned::String(key_name(self)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(name: &str) -> Result<KeyCode, FromValueError> {
+        let value = leafwing_terminal_parser::parse_terminal_command(&format!("bind {name}"))
+            .unwrap()
+            .args
+            .remove(0)
+            .into();
+        KeyCode::from_value(&value, 0)
+    }
+
+    #[test]
+    fn it_parses_key_names() {
+        assert_eq!(parse("f5"), Ok(KeyCode::F5));
+        assert_eq!(parse("F5"), Ok(KeyCode::F5));
+        assert_eq!(parse("space"), Ok(KeyCode::Space));
+        assert_eq!(parse("Space"), Ok(KeyCode::Space));
+        assert_eq!(parse("numpad3"), Ok(KeyCode::Numpad3));
+        assert_eq!(parse("LControl"), Ok(KeyCode::LControl));
+        assert_eq!(parse("enter"), Ok(KeyCode::Return));
+        assert_eq!(parse("7"), Ok(KeyCode::Key7));
+        assert_eq!(parse("w"), Ok(KeyCode::W));
+        assert_eq!(parse("W"), Ok(KeyCode::W));
+    }
+
+    #[test]
+    fn key_names_round_trip() {
+        for (key, _) in KEY_CODES {
+            assert_eq!(parse(&key_name(*key)), Ok(*key));
+        }
+        assert_eq!(key_name(KeyCode::PageDown), "pagedown");
+    }
+
+    #[test]
+    fn it_suggests_near_misses() {
+        let err = parse("spcae").unwrap_err();
+        assert_eq!(
+            err,
+            FromValueError::Custom(
+                "unknown key 'spcae' for arg #1, did you mean space?".to_string()
+            )
+        );
+
+        let FromValueError::Custom(msg) = parse("numpad33").unwrap_err() else {
+            panic!("expected a custom error");
+        };
+        assert!(msg.contains("numpad3"), "{msg}");
+
+        assert_eq!(
+            parse("notakeyatall"),
+            Err(FromValueError::Custom(
+                "unknown key 'notakeyatall' for arg #1".to_string()
+            ))
+        );
+    }
+}
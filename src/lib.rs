@@ -1,42 +1,275 @@
 #![doc = include_str!("../README.md")]
 #![deny(missing_docs)]
 
+use bevy::input::InputSystem;
 use bevy::prelude::*;
-use bevy_egui::EguiPlugin;
-pub use leafwing_terminal_derive::TerminalCommand;
-pub use leafwing_terminal_parser::{Value, ValueRawOwned};
+use bevy_egui::{EguiPlugin, EguiSystem};
+pub use leafwing_terminal_derive::{CommandArgGroup, TerminalCommand};
+pub use leafwing_terminal_parser::{validate_command_name, ParseError, Value, ValueRawOwned};
 
+use crate::accessibility::send_accessible_lines;
+pub use crate::accessibility::TerminalAccessibleLine;
+#[cfg(feature = "assets")]
+pub use crate::asset_path::{AssetPathArg, ValidatedAssetPath};
+use crate::commands::buffer_stats::{buffer_stats_command, BufferStatsCommand};
+use crate::commands::cd::{cd_command, pwd_command, CdCommand, PwdCommand};
 use crate::commands::clear::{clear_command, ClearCommand};
+use crate::commands::clear_history::{clear_history_command, ClearHistoryCommand};
+use crate::commands::config::{config_command, ConfigCommand};
+use crate::commands::deprecations::{deprecations_command, DeprecationsCommand};
+use crate::commands::dump::{dump_command, DumpCommand};
 use crate::commands::exit::{exit_command, ExitCommand};
+#[cfg(feature = "entity-commands")]
+use crate::commands::find::{find_command, FindCommand};
 use crate::commands::help::{help_command, HelpCommand};
-use crate::terminal::{receive_terminal_line, TerminalState};
+use crate::commands::lint::{lint_command, LintCommand};
+use crate::commands::mark::{jump_command, mark_command, JumpCommand, MarkCommand};
+use crate::commands::repeat::{repeat_command, RepeatCommand};
+use crate::commands::schedule::{
+    after_command, at_command, list_scheduled_command, AfterCommand, AtCommand,
+    ListScheduledCommand,
+};
+use crate::commands::script::{onfail_command, require_command, OnfailCommand, RequireCommand};
+use crate::commands::selftest::{selftest_command, SelftestCommand};
+use crate::commands::sticky::{sticky_command, StickyCommand};
+use crate::commands::tail::{tail_command, TailCommand};
+use crate::commands::timing::{timing_command, TimingCommand};
+#[cfg(feature = "world-commands")]
+use crate::commands::world::{
+    despawn_command, spawn_command, spawn_loaded_scenes, DespawnCommand, PendingSceneSpawns,
+    SpawnCommand,
+};
+use crate::completion::refresh_completers;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::completion::PathCompleter;
+pub use crate::completion::{AddTerminalCompletions, ArgCompleter, CommandCompleters};
+pub use crate::cooldown::TerminalCooldowns;
+pub use crate::dispatch::TerminalDispatch;
+pub use crate::echo::{Echo, EchoSource};
+pub use crate::entity::{resolve_named_entity, ByName};
+pub use crate::harness::TerminalInvocation;
+use crate::input::{block_game_input, stash_terminal_keys, TerminalKeys};
+pub use crate::keys::key_name;
+pub use crate::line::{Severity, TerminalLine};
+#[cfg(debug_assertions)]
+use crate::lint::lint_registry;
+pub use crate::lint::{validate_registry, LintWarning};
+pub use crate::parse::parse_command;
+pub use crate::paths::TerminalPaths;
+use crate::print_queue::PrintQueue;
+use crate::prompt::{receive_prompt_requests, TerminalPromptRequested};
+pub use crate::prompt::{PromptError, PromptId, TerminalPromptAnswered};
+use crate::queue::{release_queued_commands, track_window_focus};
+pub use crate::queue::{ScheduleTrigger, ScheduledCommand, TerminalCommandQueue, UnfocusedPolicy};
+pub use crate::registry::{CommandRegistry, CommandValidator};
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::storage::FileStorage;
+use crate::storage::{load_history, report_storage_errors, save_history, SavedHistory};
+pub use crate::storage::{MemoryStorage, TerminalStorage, TerminalStore};
+pub use crate::strings::TerminalStrings;
+use crate::structured::send_structured_output;
+pub use crate::structured::TerminalStructuredOutput;
+use crate::terminal::{
+    buffer_terminal_commands, print_motd, receive_notices, receive_terminal_line,
+    report_trimmed_references, send_open_events, TerminalCommandBuffer,
+};
 pub use crate::terminal::{
-    AddTerminalCommand, CommandArgInfo, CommandArgs, CommandHelp, CommandInfo, CommandName,
-    PrintTerminalLine, TerminalCommand, TerminalCommandEntered, TerminalConfiguration,
+    AddTerminalCommand, CommandArgGroup, CommandArgInfo, CommandArgs, CommandExample, CommandHelp,
+    CommandInfo, CommandName, HelpFormat, IntoValues, PasteBehavior, PrintTerminalLine,
+    SeparatorStyle, SeverityColors, TerminalAttentionRequested, TerminalBootBuffer, TerminalClosed,
+    TerminalCommand, TerminalCommandEntered, TerminalConfiguration, TerminalLayout, TerminalMode,
+    TerminalNotice, TerminalOpen, TerminalOpened, TerminalParseFailed, TerminalState,
+    TerminalStyle,
 };
-use crate::ui::terminal_ui;
-pub use crate::value::{FromValue, FromValueError, ValueType};
+use crate::toolbar::TerminalToolbarItems;
+pub use crate::toolbar::{AddTerminalToolbar, TerminalToolbar};
+use crate::ui::{terminal_tails, terminal_toasts, terminal_ui};
+pub use crate::value::{FromValue, FromValueError, IntoValue, ValueType};
+use crate::watchdog::{watch_outcomes, PendingOutcomes};
+pub use crate::watchdog::{CommandOutcome, TerminalCommandCompleted};
 
+mod accessibility;
+mod anchor;
+#[cfg(feature = "assets")]
+mod asset_path;
 mod commands;
+mod completion;
+mod cooldown;
+mod dispatch;
+mod echo;
+mod entity;
+mod expand;
+mod harness;
+mod history_browser;
+mod history_search;
+mod input;
+mod keys;
+mod line;
+mod lint;
+mod log;
 mod macros;
+mod net;
+mod palette;
+mod parse;
+mod paths;
+pub mod prelude;
+mod print_queue;
+mod prompt;
+mod queue;
+mod registry;
+mod rows;
+mod selection;
+mod storage;
+mod strings;
+mod structured;
 mod terminal;
+mod toolbar;
 mod ui;
 mod value;
+mod watchdog;
+
+/// The items code generated by the derive macros refers to.
+///
+/// Generated code only names items through this module, so they can move inside the crate
+/// without breaking crates deriving [`TerminalCommand`](derive@TerminalCommand) or [`CommandArgGroup`].
+/// Removing or changing an item here is a breaking change.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::{
+        CommandArgGroup, CommandArgInfo, CommandArgs, CommandExample, CommandHelp, CommandInfo,
+        CommandName, FromValue, FromValueError, IntoValue, IntoValues, ValueRawOwned,
+    };
+}
 
 /// Terminal plugin.
 pub struct TerminalPlugin;
 
 impl Plugin for TerminalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(TerminalCorePlugin)
+            .add_plugin(EguiPlugin)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                block_game_input
+                    .after(stash_terminal_keys)
+                    .after(EguiSystem::ProcessInput),
+            )
+            .add_system(refresh_completers.exclusive_system().at_start())
+            .add_system(terminal_ui)
+            .add_system(terminal_toasts.after(terminal_ui))
+            .add_system(terminal_tails.after(terminal_ui));
+    }
+}
+
+/// Terminal resources, events and built-in commands, without the UI.
+///
+/// Use this instead of [`TerminalPlugin`] in headless apps and tests.
+pub struct TerminalCorePlugin;
+
+impl Plugin for TerminalCorePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<TerminalConfiguration>()
+            .init_resource::<CommandRegistry>()
+            .init_resource::<CommandCompleters>()
+            .init_resource::<TerminalStrings>()
             .init_resource::<TerminalState>()
+            .init_resource::<TerminalOpen>()
+            .init_resource::<TerminalKeys>()
+            .init_resource::<TerminalCooldowns>()
+            .init_resource::<TerminalCommandQueue>()
+            .init_resource::<TerminalCommandBuffer>()
+            .init_resource::<TerminalToolbarItems>()
+            .init_resource::<TerminalPaths>()
+            .init_resource::<TerminalBootBuffer>()
+            .init_resource::<PrintQueue>()
+            .init_resource::<TerminalStore>()
+            .init_resource::<SavedHistory>()
+            .init_resource::<PendingOutcomes>()
             .add_event::<TerminalCommandEntered>()
+            .add_event::<TerminalParseFailed>()
             .add_event::<PrintTerminalLine>()
-            .add_plugin(EguiPlugin)
+            .add_event::<TerminalNotice>()
+            .add_event::<TerminalAttentionRequested>()
+            .add_event::<TerminalAccessibleLine>()
+            .add_event::<TerminalPromptRequested>()
+            .add_event::<TerminalPromptAnswered>()
+            .add_event::<TerminalOpened>()
+            .add_event::<TerminalClosed>()
+            .add_event::<TerminalCommandCompleted>()
+            .add_event::<TerminalStructuredOutput>()
             .add_terminal_command::<ClearCommand, _, _>(clear_command)
+            .add_terminal_command::<ClearHistoryCommand, _, _>(clear_history_command)
+            .add_terminal_command::<BufferStatsCommand, _, _>(buffer_stats_command)
+            .add_terminal_command::<CdCommand, _, _>(cd_command)
+            .add_terminal_command::<PwdCommand, _, _>(pwd_command)
+            .add_terminal_command::<ConfigCommand, _, _>(config_command)
             .add_terminal_command::<ExitCommand, _, _>(exit_command)
             .add_terminal_command::<HelpCommand, _, _>(help_command)
-            .add_system(terminal_ui)
-            .add_system(receive_terminal_line);
+            .add_terminal_command::<LintCommand, _, _>(lint_command)
+            .add_terminal_command::<DeprecationsCommand, _, _>(deprecations_command)
+            .add_terminal_command::<DumpCommand, _, _>(dump_command)
+            .add_terminal_command::<SelftestCommand, _, _>(selftest_command)
+            .add_terminal_command::<TailCommand, _, _>(tail_command)
+            .add_terminal_command::<TimingCommand, _, _>(timing_command)
+            .add_terminal_command::<AfterCommand, _, _>(after_command)
+            .add_terminal_command::<AtCommand, _, _>(at_command)
+            .add_terminal_command::<ListScheduledCommand, _, _>(list_scheduled_command)
+            .add_terminal_command::<RepeatCommand, _, _>(repeat_command)
+            .add_terminal_command::<RequireCommand, _, _>(require_command)
+            .add_terminal_command::<OnfailCommand, _, _>(onfail_command)
+            .add_terminal_command::<MarkCommand, _, _>(mark_command)
+            .add_terminal_command::<JumpCommand, _, _>(jump_command)
+            .add_terminal_command::<StickyCommand, _, _>(sticky_command)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                track_window_focus.before(release_queued_commands),
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, release_queued_commands)
+            .add_system_to_stage(CoreStage::PreUpdate, stash_terminal_keys.after(InputSystem))
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                buffer_terminal_commands.after(release_queued_commands),
+            )
+            .add_system(receive_terminal_line)
+            .add_system(print_motd.after(receive_terminal_line))
+            .add_system(receive_notices.after(receive_terminal_line))
+            .add_system(
+                report_trimmed_references
+                    .after(receive_terminal_line)
+                    .before(receive_notices),
+            )
+            .add_system(report_storage_errors.before(receive_notices))
+            .add_system(
+                send_accessible_lines
+                    .after(receive_notices)
+                    .after(print_motd),
+            )
+            .add_system(receive_prompt_requests)
+            .add_system_to_stage(CoreStage::PostUpdate, send_open_events)
+            .add_system_to_stage(CoreStage::PostUpdate, watch_outcomes)
+            .add_system_to_stage(CoreStage::PostUpdate, save_history)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                send_structured_output.after(watch_outcomes),
+            );
+
+        app.add_startup_system_to_stage(StartupStage::PostStartup, load_history);
+        #[cfg(debug_assertions)]
+        app.add_startup_system_to_stage(StartupStage::PostStartup, lint_registry);
+        #[cfg(not(target_arch = "wasm32"))]
+        app.world
+            .resource_mut::<CommandCompleters>()
+            .set_completer("cd", PathCompleter::default());
+        #[cfg(not(target_arch = "wasm32"))]
+        app.world
+            .resource_mut::<CommandCompleters>()
+            .set_completer("dump", PathCompleter::default());
+        #[cfg(feature = "entity-commands")]
+        app.add_terminal_command::<FindCommand, _, _>(find_command);
+        #[cfg(feature = "world-commands")]
+        app.init_resource::<PendingSceneSpawns>()
+            .add_terminal_command::<DespawnCommand, _, _>(despawn_command)
+            .add_terminal_command::<SpawnCommand, _, _>(spawn_command)
+            .add_system(spawn_loaded_scenes);
     }
 }
@@ -0,0 +1,230 @@
+use std::any::{Any, TypeId};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::selection::PrintedAt;
+
+/// Typed values attached to a line, at most one per type.
+///
+/// Values are shared between clones of a line, so splitting or copying lines is cheap.
+/// Every printed line gets a [`PrintedAt`], which is kept inline so printing doesn't allocate for it.
+#[derive(Clone, Default)]
+pub(crate) struct LineMeta {
+    values: BTreeMap<TypeId, Arc<dyn Any + Send + Sync>>,
+    printed_at: Option<PrintedAt>,
+}
+
+impl LineMeta {
+    pub(crate) const fn new() -> Self {
+        Self {
+            values: BTreeMap::new(),
+            printed_at: None,
+        }
+    }
+
+    pub(crate) fn insert<T: Any + Send + Sync>(&mut self, value: T) {
+        match (&value as &dyn Any).downcast_ref::<PrintedAt>() {
+            Some(printed_at) => self.printed_at = Some(*printed_at),
+            None => {
+                self.values.insert(TypeId::of::<T>(), Arc::new(value));
+            }
+        }
+    }
+
+    pub(crate) fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        if let Some(printed_at) = (&self.printed_at as &dyn Any).downcast_ref::<Option<T>>() {
+            return printed_at.as_ref();
+        }
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    pub(crate) fn remove<T: Any + Send + Sync>(&mut self) -> bool {
+        if let Some(printed_at) = (&mut self.printed_at as &mut dyn Any).downcast_mut::<Option<T>>()
+        {
+            return printed_at.take().is_some();
+        }
+        self.values.remove(&TypeId::of::<T>()).is_some()
+    }
+}
+
+impl fmt::Debug for LineMeta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.values.len() + usize::from(self.printed_at.is_some());
+        write!(f, "LineMeta({count} values)")
+    }
+}
+
+/// How a printed line is colored in the scrollback, see [`TerminalStyle::severity`](crate::TerminalStyle::severity).
+///
+/// Attach it with [`PrintTerminalLine::with_meta`](crate::PrintTerminalLine::with_meta) or the constructors for each severity,
+/// such as [`PrintTerminalLine::warn`](crate::PrintTerminalLine::warn).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// Information worth noticing
+    Info,
+    /// Something that may need attention
+    Warn,
+    /// Something went wrong
+    Error,
+    /// A command succeeded
+    Ok,
+    /// A command failed
+    Failed,
+}
+
+/// A line in the terminal scrollback.
+///
+/// Besides its text, a line can carry one value of each type as metadata,
+/// so games can attach their own data to lines with [`TerminalLine::insert_meta`].
+/// Metadata attached to a [`PrintTerminalLine`](crate::PrintTerminalLine) is copied to each line it prints.
+///
+/// Lines compare equal to lines and strings with the same text, ignoring metadata,
+/// as the attached values can't be compared.
+///
+/// # Example
+///
+/// ```
+/// # use leafwing_terminal::TerminalLine;
+/// #
+/// struct Speaker(String);
+///
+/// let line = TerminalLine::from("hello there").with_meta(Speaker("Kenobi".to_string()));
+/// assert_eq!(line, "hello there");
+/// assert_eq!(line.get_meta::<Speaker>().unwrap().0, "Kenobi");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TerminalLine {
+    /// Text of the line
+    pub text: String,
+    meta: LineMeta,
+}
+
+impl TerminalLine {
+    pub(crate) fn with_line_meta(text: String, meta: LineMeta) -> Self {
+        Self { text, meta }
+    }
+
+    pub(crate) fn into_parts(self) -> (String, LineMeta) {
+        (self.text, self.meta)
+    }
+
+    /// Text of the line.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Attach `value`, replacing any value of the same type.
+    pub fn insert_meta<T: Any + Send + Sync>(&mut self, value: T) {
+        self.meta.insert(value);
+    }
+
+    /// Attach `value`, replacing any value of the same type.
+    pub fn with_meta<T: Any + Send + Sync>(mut self, value: T) -> Self {
+        self.insert_meta(value);
+        self
+    }
+
+    /// The attached value of type `T`, if there is one.
+    pub fn get_meta<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.meta.get()
+    }
+
+    /// Detach the value of type `T`, returning whether there was one.
+    pub fn remove_meta<T: Any + Send + Sync>(&mut self) -> bool {
+        self.meta.remove::<T>()
+    }
+
+    /// The attached [`Severity`], if there is one.
+    pub fn severity(&self) -> Option<Severity> {
+        self.get_meta().copied()
+    }
+}
+
+impl From<String> for TerminalLine {
+    fn from(text: String) -> Self {
+        Self {
+            text,
+            meta: LineMeta::default(),
+        }
+    }
+}
+
+impl From<&str> for TerminalLine {
+    fn from(text: &str) -> Self {
+        text.to_string().into()
+    }
+}
+
+impl fmt::Display for TerminalLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl AsRef<str> for TerminalLine {
+    fn as_ref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl PartialEq for TerminalLine {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text
+    }
+}
+
+impl Eq for TerminalLine {}
+
+impl PartialEq<str> for TerminalLine {
+    fn eq(&self, other: &str) -> bool {
+        self.text == other
+    }
+}
+
+impl PartialEq<&str> for TerminalLine {
+    fn eq(&self, other: &&str) -> bool {
+        self.text == *other
+    }
+}
+
+impl PartialEq<String> for TerminalLine {
+    fn eq(&self, other: &String) -> bool {
+        self.text == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Level(u8);
+
+    #[test]
+    fn it_stores_one_value_per_type() {
+        let mut line = TerminalLine::from("[error] boom").with_meta(Level(1));
+        line.insert_meta(Level(2));
+        line.insert_meta("source");
+        assert_eq!(line.get_meta::<Level>(), Some(&Level(2)));
+        assert_eq!(line.get_meta::<&str>(), Some(&"source"));
+        assert_eq!(line.get_meta::<u32>(), None);
+
+        assert!(line.remove_meta::<Level>());
+        assert!(!line.remove_meta::<Level>());
+        assert_eq!(line, "[error] boom");
+    }
+
+    #[test]
+    fn it_shares_meta_between_clones() {
+        let line = TerminalLine::from("spawned").with_meta(Level(3));
+        let clone = line.clone();
+        assert_eq!(clone, line);
+        assert_eq!(clone.get_meta::<Level>(), Some(&Level(3)));
+        // Only the text is compared
+        assert_eq!(clone, TerminalLine::from("spawned").with_meta(Level(4)));
+        assert_ne!(clone, TerminalLine::from("despawned").with_meta(Level(3)));
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::strings::fill;
+use crate::{CommandRegistry, PrintTerminalLine, TerminalStrings};
+
+/// A mistake in the metadata of registered commands, found by [`validate_registry`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LintWarning {
+    /// An alias with the name of a command, which never runs because commands take precedence
+    ShadowedAlias {
+        /// Name of the alias and the command
+        alias: String,
+        /// Command the alias was meant to run
+        command: String,
+    },
+    /// An alias of a command which isn't registered
+    DanglingAlias {
+        /// Name of the alias
+        alias: String,
+        /// Command the alias runs
+        command: String,
+    },
+    /// A validator of a command which isn't registered
+    OrphanValidator {
+        /// Name the validator was set for
+        command: String,
+    },
+    /// Help registered for a command under another name, such as from a hand-written `CommandHelp`
+    MismatchedName {
+        /// Registered name
+        command: String,
+        /// Name in the help
+        help_name: String,
+    },
+    /// A required argument listed after an optional one, so the optional one can't be left out
+    RequiredAfterOptional {
+        /// Name of the command
+        command: String,
+        /// Name of the required argument
+        arg: String,
+    },
+    /// An argument listed more than once
+    DuplicateArg {
+        /// Name of the command
+        command: String,
+        /// Name of the argument
+        arg: String,
+    },
+    /// Limits on the number of arguments which the listed arguments don't fit
+    ArgLimits {
+        /// Name of the command
+        command: String,
+        /// Number of required arguments listed
+        required: usize,
+    },
+}
+
+impl LintWarning {
+    /// Describe the mistake with `strings`.
+    pub fn message(&self, strings: &TerminalStrings) -> String {
+        match self {
+            LintWarning::ShadowedAlias { alias, command } => {
+                fill(&strings.lint_shadowed_alias, &[alias, command])
+            }
+            LintWarning::DanglingAlias { alias, command } => {
+                fill(&strings.lint_dangling_alias, &[alias, command])
+            }
+            LintWarning::OrphanValidator { command } => {
+                fill(&strings.lint_orphan_validator, &[command])
+            }
+            LintWarning::MismatchedName { command, help_name } => {
+                fill(&strings.lint_mismatched_name, &[command, help_name])
+            }
+            LintWarning::RequiredAfterOptional { command, arg } => {
+                fill(&strings.lint_required_after_optional, &[command, arg])
+            }
+            LintWarning::DuplicateArg { command, arg } => {
+                fill(&strings.lint_duplicate_arg, &[command, arg])
+            }
+            LintWarning::ArgLimits { command, required } => {
+                fill(&strings.lint_arg_limits, &[command, required])
+            }
+        }
+    }
+}
+
+/// Check the commands, aliases and validators of `registry` for mistakes in their metadata.
+///
+/// This runs at startup in debug builds, printing each warning in the terminal, and with the `lint` command.
+pub fn validate_registry(registry: &CommandRegistry) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+
+    for (alias, command) in registry.all_aliases() {
+        if registry.iter().any(|(name, _)| name == alias) {
+            warnings.push(LintWarning::ShadowedAlias {
+                alias: alias.to_string(),
+                command: command.to_string(),
+            });
+        } else if !registry.contains(command) {
+            warnings.push(LintWarning::DanglingAlias {
+                alias: alias.to_string(),
+                command: command.to_string(),
+            });
+        }
+    }
+    for command in registry.validated() {
+        if registry.resolve(command) != Some(command) {
+            warnings.push(LintWarning::OrphanValidator {
+                command: command.to_string(),
+            });
+        }
+    }
+
+    for (name, info) in registry.iter() {
+        let Some(info) = info else {
+            continue;
+        };
+        let command = || name.to_string();
+        if info.name != name {
+            warnings.push(LintWarning::MismatchedName {
+                command: command(),
+                help_name: info.name.clone(),
+            });
+        }
+
+        let mut seen = HashSet::new();
+        let mut optional = false;
+        for arg in &info.args {
+            if !seen.insert(arg.name.as_str()) {
+                warnings.push(LintWarning::DuplicateArg {
+                    command: command(),
+                    arg: arg.name.clone(),
+                });
+            }
+            if optional && !arg.optional {
+                warnings.push(LintWarning::RequiredAfterOptional {
+                    command: command(),
+                    arg: arg.name.clone(),
+                });
+            }
+            optional |= arg.optional;
+        }
+
+        let required = info.args.iter().filter(|arg| !arg.optional).count();
+        let too_few = info.max_args.is_some_and(|max| max < required);
+        let crossed = matches!((info.min_args, info.max_args), (Some(min), Some(max)) if min > max);
+        if too_few || crossed {
+            warnings.push(LintWarning::ArgLimits {
+                command: command(),
+                required,
+            });
+        }
+    }
+    warnings
+}
+
+/// Prints the mistakes in command metadata once every command is registered.
+pub(crate) fn lint_registry(
+    registry: Res<CommandRegistry>,
+    strings: Res<TerminalStrings>,
+    mut terminal_line: EventWriter<PrintTerminalLine>,
+) {
+    for warning in validate_registry(&registry) {
+        let line = fill(&strings.warning, &[&warning.message(&strings)]);
+        terminal_line.send(PrintTerminalLine::warn(line));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommandArgInfo, CommandInfo};
+
+    fn arg(name: &str, optional: bool) -> CommandArgInfo {
+        CommandArgInfo {
+            name: name.to_string(),
+            ty: "String".to_string(),
+            description: None,
+            optional,
+            default: None,
+        }
+    }
+
+    fn info(name: &str, args: Vec<CommandArgInfo>) -> CommandInfo {
+        CommandInfo {
+            args,
+            ..CommandInfo::new(name, None)
+        }
+    }
+
+    #[test]
+    fn it_passes_valid_registries() {
+        let mut registry = CommandRegistry::default();
+        registry.insert("spawn", Some(info("spawn", vec![arg("kind", false)])));
+        registry.insert("clear", None);
+        registry.add_alias("cls", "clear");
+        registry.set_validator("spawn", |_| Ok(()));
+        assert_eq!(validate_registry(&registry), []);
+    }
+
+    #[test]
+    fn it_finds_alias_and_validator_mistakes() {
+        let mut registry = CommandRegistry::default();
+        registry.insert("clear", None);
+        registry.insert("quit", None);
+        registry.add_alias("quit", "clear");
+        registry.add_alias("gone", "not_registered");
+        registry.set_validator("typo", |_| Ok(()));
+        assert_eq!(
+            validate_registry(&registry),
+            [
+                LintWarning::DanglingAlias {
+                    alias: "gone".to_string(),
+                    command: "not_registered".to_string(),
+                },
+                LintWarning::ShadowedAlias {
+                    alias: "quit".to_string(),
+                    command: "clear".to_string(),
+                },
+                LintWarning::OrphanValidator {
+                    command: "typo".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_finds_argument_mistakes() {
+        let mut registry = CommandRegistry::default();
+        registry.insert("teleport", Some(info("tp", Vec::new())));
+        registry.insert(
+            "give",
+            Some(info(
+                "give",
+                vec![arg("count", true), arg("item", false), arg("count", true)],
+            )),
+        );
+        let mut limited = info("spawn", vec![arg("kind", false), arg("count", false)]);
+        limited.max_args = Some(1);
+        registry.insert("spawn", Some(limited));
+        let mut crossed = info("wait", Vec::new());
+        crossed.min_args = Some(2);
+        crossed.max_args = Some(1);
+        registry.insert("wait", Some(crossed));
+
+        assert_eq!(
+            validate_registry(&registry),
+            [
+                LintWarning::RequiredAfterOptional {
+                    command: "give".to_string(),
+                    arg: "item".to_string(),
+                },
+                LintWarning::DuplicateArg {
+                    command: "give".to_string(),
+                    arg: "count".to_string(),
+                },
+                LintWarning::ArgLimits {
+                    command: "spawn".to_string(),
+                    required: 2,
+                },
+                LintWarning::MismatchedName {
+                    command: "teleport".to_string(),
+                    help_name: "tp".to_string(),
+                },
+                LintWarning::ArgLimits {
+                    command: "wait".to_string(),
+                    required: 0,
+                },
+            ]
+        );
+    }
+}
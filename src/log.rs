@@ -0,0 +1,144 @@
+use bevy::log::{info, warn};
+
+use crate::parse::format_command;
+use crate::{PrintTerminalLine, Severity, TerminalCommandEntered, TerminalStrings};
+
+/// Logs an entered command at info level, as it would be echoed.
+pub(crate) fn log_command(command: &TerminalCommandEntered) {
+    info!(target: "leafwing_terminal", "$ {}", format_command(command));
+}
+
+/// Whether a printed line is an error or a failed outcome.
+///
+/// Lines with a [`Severity`] are judged by it, and others by the configured prefixes of failures and errors,
+/// so lines printed by hand count too.
+pub(crate) fn is_error_line(line: &PrintTerminalLine, strings: &TerminalStrings) -> bool {
+    if let Some(severity) = line.severity() {
+        return matches!(severity, Severity::Error | Severity::Failed);
+    }
+    // The text of `custom_error` before the message, such as `[error] `
+    let error_prefix = strings.custom_error.split("{}").next().unwrap_or_default();
+    line.line.starts_with(&strings.failed)
+        || (!error_prefix.is_empty() && line.line.starts_with(error_prefix))
+}
+
+/// Logs a printed line, at warn level for errors and failures and info level otherwise.
+pub(crate) fn log_line(line: &PrintTerminalLine, strings: &TerminalStrings) {
+    let text = &line.line;
+    if is_error_line(line, strings) {
+        warn!(target: "leafwing_terminal", "{text}");
+    } else {
+        info!(target: "leafwing_terminal", "{text}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    use bevy::utils::tracing::field::{Field, Visit};
+    use bevy::utils::tracing::{span, subscriber, Event, Level, Metadata, Subscriber};
+
+    use super::*;
+    use crate::parse_command;
+
+    type Captured = Arc<Mutex<Vec<(Level, String, String)>>>;
+
+    /// Records the level, target and message of every event.
+    struct Capture(Captured);
+
+    struct MessageVisitor(String);
+
+    impl Visit for MessageVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "message" {
+                self.0 = format!("{value:?}");
+            }
+        }
+    }
+
+    impl Subscriber for Capture {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut visitor = MessageVisitor(String::new());
+            event.record(&mut visitor);
+            let metadata = event.metadata();
+            self.0.lock().unwrap().push((
+                *metadata.level(),
+                metadata.target().to_string(),
+                visitor.0,
+            ));
+        }
+
+        fn enter(&self, _span: &span::Id) {}
+
+        fn exit(&self, _span: &span::Id) {}
+    }
+
+    #[test]
+    fn it_mirrors_activity_to_the_log() {
+        let captured = Captured::default();
+        let strings = TerminalStrings::default();
+        subscriber::with_default(Capture(captured.clone()), || {
+            log_command(&parse_command("spawn_wave 3 'big one'").unwrap());
+            log_line(
+                &PrintTerminalLine::new("Spawned 3 enemies".into()),
+                &strings,
+            );
+            log_line(&PrintTerminalLine::ok("[ok]"), &strings);
+            log_line(
+                &PrintTerminalLine::new("[error] not enough arguments provided".into()),
+                &strings,
+            );
+            log_line(&PrintTerminalLine::new("[failed]".into()), &strings);
+        });
+
+        let captured = captured.lock().unwrap();
+        let expected = [
+            (Level::INFO, "$ spawn_wave 3 \"big one\""),
+            (Level::INFO, "Spawned 3 enemies"),
+            (Level::INFO, "[ok]"),
+            (Level::WARN, "[error] not enough arguments provided"),
+            (Level::WARN, "[failed]"),
+        ];
+        assert_eq!(captured.len(), expected.len());
+        for ((level, target, message), (expected_level, expected_message)) in
+            captured.iter().zip(expected)
+        {
+            assert_eq!(*level, expected_level);
+            assert_eq!(target, "leafwing_terminal");
+            assert_eq!(message, expected_message);
+        }
+    }
+
+    #[test]
+    fn it_finds_errors_by_severity_and_configured_prefix() {
+        let strings = TerminalStrings {
+            custom_error: "[erreur] {}".to_string(),
+            ..Default::default()
+        };
+        let is_error = |line: PrintTerminalLine| is_error_line(&line, &strings);
+        assert!(is_error(PrintTerminalLine::error("no such level")));
+        assert!(is_error(PrintTerminalLine::failed("[échec]")));
+        assert!(is_error(PrintTerminalLine::new(
+            "[erreur] introuvable".into()
+        )));
+        assert!(is_error(PrintTerminalLine::new("[failed]".into())));
+        assert!(!is_error(PrintTerminalLine::warn("[erreur] deprecated")));
+        assert!(!is_error(PrintTerminalLine::new(
+            "[error] untranslated".into()
+        )));
+    }
+}
@@ -48,3 +48,126 @@ macro_rules! reply_failed {
         }
     };
 }
+
+/// Reply with the [`format!`] syntax as a warning.
+///
+/// # Example
+///
+/// ```
+/// # use leafwing_terminal::{reply_warn, TerminalCommand};
+/// #
+/// # /// Sets the volume
+/// # #[derive(TerminalCommand)]
+/// # #[terminal_command(name = "volume")]
+/// # struct VolumeCommand {
+/// #     level: u8,
+/// # }
+/// #
+/// fn volume_command(mut volume: TerminalCommand<VolumeCommand>) {
+///     if let Some(VolumeCommand { level }) = volume.take() {
+///         if level > 100 {
+///             reply_warn!(volume, "volume {} is above 100 and will be clipped", level);
+///         }
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! reply_warn {
+    ($cmd: ident, $fmt: literal$(, $($arg:expr),* $(,)?)?) => {
+        {
+            let msg = format!($fmt$(, $($arg),*)?);
+            $cmd.reply_warn(msg);
+        }
+    };
+}
+
+/// Reply with the [`format!`] syntax as an error.
+///
+/// # Example
+///
+/// ```
+/// # use leafwing_terminal::{reply_error, TerminalCommand};
+/// #
+/// # /// Loads a save
+/// # #[derive(TerminalCommand)]
+/// # #[terminal_command(name = "load")]
+/// # struct LoadCommand {
+/// #     slot: u8,
+/// # }
+/// #
+/// fn load_command(mut load: TerminalCommand<LoadCommand>) {
+///     if let Some(LoadCommand { slot }) = load.take() {
+///         reply_error!(load, "save slot {} is empty", slot);
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! reply_error {
+    ($cmd: ident, $fmt: literal$(, $($arg:expr),* $(,)?)?) => {
+        {
+            let msg = format!($fmt$(, $($arg),*)?);
+            $cmd.reply_error(msg);
+        }
+    };
+}
+
+/// Reply with the [`format!`] syntax, printing each line of the result as its own terminal line.
+///
+/// # Example
+///
+/// ```
+/// # use leafwing_terminal::{reply_lines, TerminalCommand};
+/// #
+/// # /// Prints the player's stats
+/// # #[derive(TerminalCommand)]
+/// # #[terminal_command(name = "stats")]
+/// # struct StatsCommand;
+/// #
+/// fn stats_command(mut stats: TerminalCommand<StatsCommand>) {
+///     if stats.take().is_some() {
+///         reply_lines!(stats, "health: {}\nmana: {}", 80, 35);
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! reply_lines {
+    ($cmd: ident, $fmt: literal$(, $($arg:expr),* $(,)?)?) => {
+        {
+            let msg = format!($fmt$(, $($arg),*)?);
+            $cmd.reply_lines(msg);
+        }
+    };
+}
+
+/// Reply with the usage of a command, such as after finding its arguments don't make sense together.
+///
+/// # Example
+///
+/// ```
+/// # use leafwing_terminal::{reply_usage, TerminalCommand};
+/// #
+/// /// Teleports the player to a zone, or to coordinates
+/// #[derive(TerminalCommand)]
+/// #[terminal_command(name = "teleport")]
+/// struct TeleportCommand {
+///     /// Zone name
+///     zone: Option<String>,
+///     /// Coordinates
+///     coords: Vec<f64>,
+/// }
+///
+/// fn teleport_command(mut teleport: TerminalCommand<TeleportCommand>) {
+///     if let Some(TeleportCommand { zone, coords }) = teleport.take() {
+///         if zone.is_some() == !coords.is_empty() {
+///             reply_usage!(teleport, TeleportCommand);
+///             teleport.failed();
+///         }
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! reply_usage {
+    ($cmd: ident, $ty: ty) => {
+        $cmd.reply_usage::<$ty>()
+    };
+}
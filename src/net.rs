@@ -0,0 +1,145 @@
+use std::net::{IpAddr, SocketAddr};
+
+use leafwing_terminal_parser::ValueRawOwned;
+
+use crate::value::{FromValue, FromValueError, IntoValue, ValueType};
+
+/// Address used in the hint for a bare port.
+const LOCALHOST: &str = "127.0.0.1";
+
+fn received_type(value: &ValueRawOwned) -> ValueType {
+    match value {
+        ValueRawOwned::String(_) => ValueType::String,
+        ValueRawOwned::Int(_, _) => ValueType::Int,
+        ValueRawOwned::Float(_, _) => ValueType::Float,
+        ValueRawOwned::Bool(_, _) => ValueType::Bool,
+    }
+}
+
+/// Parses addresses such as `"192.168.1.10:7777"` or `"[::1]:26000"`.
+///
+/// Addresses contain `.` or `:`, so they must be quoted.
+/// A bare port is rejected with a hint to add a host.
+impl FromValue for SocketAddr {
+    fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        let addr = match value {
+            ValueRawOwned::String(addr) => addr.as_str(),
+            ValueRawOwned::Int(_, raw) => raw.as_str(),
+            _ => "",
+        };
+        if let Ok(addr) = addr.parse() {
+            return Ok(addr);
+        }
+
+        if addr.parse::<u16>().is_ok() {
+            return Err(FromValueError::Custom(format!(
+                "expected an address with a host for arg #{}, did you mean {LOCALHOST}:{addr}?",
+                arg_num + 1
+            )));
+        }
+        Err(FromValueError::UnexpectedArgType {
+            arg_num,
+            expected: ValueType::Addr,
+            received: received_type(value),
+        })
+    }
+}
+
+impl IntoValue for SocketAddr {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        values.push(ValueRawOwned::String(self.to_string()));
+    }
+}
+
+/// Parses IPv4 and IPv6 addresses such as `"192.168.1.10"` or `"::1"`, which must be quoted.
+impl FromValue for IpAddr {
+    fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        let ip = match value {
+            ValueRawOwned::String(ip) => ip.parse().ok(),
+            _ => None,
+        };
+        ip.ok_or(FromValueError::UnexpectedArgType {
+            arg_num,
+            expected: ValueType::Ip,
+            received: received_type(value),
+        })
+    }
+}
+
+impl IntoValue for IpAddr {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        values.push(ValueRawOwned::String(self.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::*;
+    use crate::parse_command;
+
+    fn parse<T: FromValue>(arg: &str) -> Result<T, FromValueError> {
+        let command = parse_command(&format!("connect {arg}")).unwrap();
+        T::from_value(&command.args[0], 0)
+    }
+
+    #[test]
+    fn it_parses_socket_addrs() {
+        assert_eq!(
+            parse::<SocketAddr>("'192.168.1.10:7777'"),
+            Ok(SocketAddr::from(([192, 168, 1, 10], 7777)))
+        );
+        assert_eq!(
+            parse::<SocketAddr>("'[::1]:26000'"),
+            Ok(SocketAddr::from((Ipv6Addr::LOCALHOST, 26000)))
+        );
+    }
+
+    #[test]
+    fn it_parses_ip_addrs() {
+        assert_eq!(
+            parse::<IpAddr>("'0.0.0.0'"),
+            Ok(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+        );
+        assert_eq!(
+            parse::<IpAddr>("'fe80::1'"),
+            Ok(IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1)))
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_addrs() {
+        assert_eq!(
+            parse::<SocketAddr>("7777"),
+            Err(FromValueError::Custom(
+                "expected an address with a host for arg #1, did you mean 127.0.0.1:7777?"
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            parse::<SocketAddr>("'::1:7777'"),
+            Err(FromValueError::UnexpectedArgType {
+                arg_num: 0,
+                expected: ValueType::Addr,
+                received: ValueType::String,
+            })
+        );
+        assert_eq!(
+            parse::<SocketAddr>("true"),
+            Err(FromValueError::UnexpectedArgType {
+                arg_num: 0,
+                expected: ValueType::Addr,
+                received: ValueType::Bool,
+            })
+        );
+        assert_eq!(
+            parse::<IpAddr>("'192.168.1'"),
+            Err(FromValueError::UnexpectedArgType {
+                arg_num: 0,
+                expected: ValueType::Ip,
+                received: ValueType::String,
+            })
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::history_browser::fuzzy_match;
+use crate::{CommandInfo, CommandRegistry, TerminalState};
+
+/// The command palette opened with [`TerminalConfiguration::palette_key`](crate::TerminalConfiguration::palette_key).
+#[derive(Debug, Default)]
+pub(crate) struct CommandPalette {
+    /// Typed filter, matched fuzzily against command names and descriptions
+    pub(crate) filter: String,
+    /// Highlighted match, counted from the best
+    pub(crate) selected: usize,
+}
+
+impl TerminalState {
+    /// Open the command palette, or close it if it's open.
+    pub(crate) fn toggle_palette(&mut self) {
+        self.palette = match self.palette {
+            Some(_) => None,
+            None => Some(CommandPalette::default()),
+        };
+    }
+}
+
+/// How well `filter` matches a command, higher is better, or `None` if it doesn't match.
+///
+/// Matches in the name rank above matches in the description:
+/// a prefix of the name, then anywhere in the name, then the characters of `filter` in order in the name,
+/// then anywhere in the description, then in order in the description. Case is ignored.
+pub(crate) fn palette_score(filter: &str, name: &str, description: Option<&str>) -> Option<u8> {
+    let filter = filter.to_lowercase();
+    let name = name.to_lowercase();
+    let description = description.map(str::to_lowercase).unwrap_or_default();
+    if name.starts_with(&filter) {
+        Some(5)
+    } else if name.contains(&filter) {
+        Some(4)
+    } else if fuzzy_match(&filter, &name) {
+        Some(3)
+    } else if description.contains(&filter) {
+        Some(2)
+    } else if fuzzy_match(&filter, &description) {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+/// Registered commands matching `filter` and their help, best first, then sorted by name.
+pub(crate) fn palette_matches<'a>(
+    filter: &str,
+    registry: &'a CommandRegistry,
+) -> Vec<(&'a str, Option<&'a CommandInfo>)> {
+    let mut matches: Vec<_> = registry
+        .iter()
+        .filter_map(|(name, info)| {
+            let description = info.and_then(|info| info.description.as_deref());
+            Some((palette_score(filter, name, description)?, name, info))
+        })
+        .collect();
+    // Stable, so equal scores stay sorted by name
+    matches.sort_by(|(a, ..), (b, ..)| b.cmp(a));
+    matches
+        .into_iter()
+        .map(|(_, name, info)| (name, info))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TerminalMode;
+
+    #[test]
+    fn it_scores_name_matches_above_description_matches() {
+        let description = Some("Spawns a wave of enemies");
+        assert_eq!(palette_score("", "spawn_wave", description), Some(5));
+        assert_eq!(palette_score("Spawn", "spawn_wave", description), Some(5));
+        assert_eq!(palette_score("wave", "spawn_wave", description), Some(4));
+        assert_eq!(palette_score("swv", "spawn_wave", description), Some(3));
+        assert_eq!(palette_score("enemies", "spawn_wave", description), Some(2));
+        assert_eq!(palette_score("enms", "spawn_wave", description), Some(1));
+        assert_eq!(palette_score("boss", "spawn_wave", description), None);
+        assert_eq!(palette_score("enemies", "spawn_wave", None), None);
+    }
+
+    #[test]
+    fn it_ranks_matches() {
+        let info = |name: &str, description: &str| Some(CommandInfo::new(name, Some(description)));
+        let mut registry = CommandRegistry::default();
+        registry.insert("clear", info("clear", "Clears the scrollback"));
+        registry.insert("respawn", info("respawn", "Respawns the player"));
+        registry.insert("spawn_wave", info("spawn_wave", "Spawns a wave of enemies"));
+        registry.insert("wave_info", info("wave_info", "Prints the current wave"));
+        registry.insert("help", None);
+
+        let names = |filter| {
+            palette_matches(filter, &registry)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("wave"), ["wave_info", "spawn_wave"]);
+        assert_eq!(names("spawn"), ["spawn_wave", "respawn"]);
+        assert_eq!(names("player"), ["respawn"]);
+        assert_eq!(names("").len(), 5);
+
+        let mut state = TerminalState::default();
+        state.toggle_palette();
+        assert_eq!(state.mode(), TerminalMode::CommandPalette);
+        state.toggle_palette();
+        assert_eq!(state.mode(), TerminalMode::Normal);
+    }
+}
@@ -0,0 +1,206 @@
+use leafwing_terminal_parser::{parse_terminal_command, ParseError, ValueRaw, ValueRawOwned};
+
+use crate::TerminalCommandEntered;
+
+/// Parse a command line with the same grammar as the terminal input.
+///
+/// This doesn't need the terminal UI, so it can be used to accept commands from a chat box or a config file.
+/// Queue the result with [`TerminalCommandQueue::push`](crate::TerminalCommandQueue::push)
+/// to run it exactly as if it was typed in the terminal,
+/// or use [`TerminalCommandQueue::submit`](crate::TerminalCommandQueue::submit) to do both.
+///
+/// # Stability
+///
+/// Lines which parse successfully will keep parsing to the same command and arguments in
+/// non-breaking releases. New syntax may be added, so lines which fail to parse today may parse in future.
+/// A leading `-` makes a word rather than a number, so `-5` parses as the string `"-5"`,
+/// which numeric arguments accept through [`FromValue`](crate::FromValue).
+/// Negative numbers with a fraction, such as `"-0.5"`, must be quoted.
+///
+/// # Example
+///
+/// ```
+/// # use leafwing_terminal::{parse_command, ParseError, TerminalCommandEntered, ValueRawOwned};
+/// #
+/// let command = parse_command(r#"give sword 2 "Sword of Truth" true"#).unwrap();
+/// assert_eq!(
+///     command,
+///     TerminalCommandEntered {
+///         command: "give".to_string(),
+///         args: vec![
+///             ValueRawOwned::String("sword".to_string()),
+///             ValueRawOwned::Int(2, "2".to_string()),
+///             ValueRawOwned::String("Sword of Truth".to_string()),
+///             ValueRawOwned::Bool(true, "true".to_string()),
+///         ],
+///     }
+/// );
+///
+/// let command = parse_command("  say 'single quotes' 1.5 ").unwrap();
+/// assert_eq!(command.command, "say");
+/// assert_eq!(command.args[1], ValueRawOwned::Float(1.5, "1.5".to_string()));
+///
+/// assert_eq!(
+///     parse_command(r#"say "unterminated"#),
+///     Err(ParseError::UnterminatedString { start: 4 })
+/// );
+/// assert_eq!(parse_command(""), Err(ParseError::EmptyInput));
+/// ```
+pub fn parse_command(line: &str) -> Result<TerminalCommandEntered, ParseError> {
+    let cmd = parse_terminal_command(line)?;
+    Ok(TerminalCommandEntered {
+        command: cmd.command.to_string(),
+        args: cmd.args.into_iter().map(ValueRawOwned::from).collect(),
+    })
+}
+
+/// Finds the opening quote of a quoted string left open at the end of `line`.
+pub(crate) fn unterminated_quote(line: &str) -> Option<usize> {
+    match parse_terminal_command(line) {
+        Err(ParseError::UnterminatedString { start }) => Some(start),
+        _ => None,
+    }
+}
+
+/// Strips what pasting from other programs leaves at the end of a submitted line,
+/// such as trailing tabs and the `\r` of Windows line endings.
+///
+/// Whitespace inside a quote left open is kept, as the quoted string continues on the next line.
+pub(crate) fn normalize_input(line: &str) -> &str {
+    let line = line.trim_end_matches('\r');
+    match unterminated_quote(line) {
+        Some(_) => line,
+        None => line.trim_end(),
+    }
+}
+
+/// Whether a line has control characters, such as a stray escape code, and nothing else but whitespace.
+pub(crate) fn only_control_characters(line: &str) -> bool {
+    line.chars().any(|c| c.is_control() && !c.is_whitespace())
+        && line.chars().all(|c| c.is_control() || c.is_whitespace())
+}
+
+/// Formats a command as a line which parses back to it.
+pub(crate) fn format_command(command: &TerminalCommandEntered) -> String {
+    let mut line = command.command.clone();
+    for arg in &command.args {
+        line.push(' ');
+        match arg {
+            ValueRawOwned::String(s) => push_quoted(&mut line, s),
+            ValueRawOwned::Int(_, raw)
+            | ValueRawOwned::Float(_, raw)
+            | ValueRawOwned::Bool(_, raw) => line.push_str(raw),
+        }
+    }
+    line
+}
+
+/// Pushes `text`, quoted if it would otherwise parse as more or less than one string.
+///
+/// Quotes and control characters are escaped, so the quoted text stays on one line.
+pub(crate) fn push_quoted(expanded: &mut String, text: &str) {
+    let plain = text
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && matches!(
+            parse_terminal_command(&format!("_ {text}")),
+            Ok(command) if command.args.len() == 1 && matches!(&command.args[0], ValueRaw::String(s) if s == text)
+        );
+    if plain {
+        expanded.push_str(text);
+        return;
+    }
+
+    expanded.push('"');
+    for c in text.chars() {
+        match c {
+            '"' | '\'' | '\\' => {
+                expanded.push('\\');
+                expanded.push(c);
+            }
+            '\n' => expanded.push_str(r"\n"),
+            '\r' => expanded.push_str(r"\r"),
+            '\t' => expanded.push_str(r"\t"),
+            '\u{08}' => expanded.push_str(r"\b"),
+            '\u{0C}' => expanded.push_str(r"\f"),
+            c => expanded.push(c),
+        }
+    }
+    expanded.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FromValue;
+
+    #[test]
+    fn finds_unterminated_quotes() {
+        assert_eq!(unterminated_quote(r#"log "hello"#), Some(4));
+        assert_eq!(unterminated_quote(r#"log "hello" 'wor"#), Some(12));
+        assert_eq!(unterminated_quote("log \"hello\nworld"), Some(4));
+        assert_eq!(unterminated_quote(r#"log "escaped \""#), Some(4));
+        assert_eq!(unterminated_quote(r#"log "hello""#), None);
+        assert_eq!(unterminated_quote("log \"hello\nworld\""), None);
+        assert_eq!(unterminated_quote("log $"), None);
+        assert_eq!(unterminated_quote(""), None);
+    }
+
+    #[test]
+    fn normalizes_submitted_lines() {
+        assert_eq!(normalize_input("set_difficulty 3\r"), "set_difficulty 3");
+        assert_eq!(normalize_input("give sword 2 \t \r\r"), "give sword 2");
+        assert_eq!(normalize_input("  say hi"), "  say hi");
+
+        // Spaces inside quotes are part of the argument
+        let line = normalize_input("say \"padded  \"  \r");
+        assert_eq!(line, "say \"padded  \"");
+        let command = parse_command(line).unwrap();
+        assert_eq!(String::from_value(&command.args[0], 0).unwrap(), "padded  ");
+        assert_eq!(normalize_input("say \"open  \r"), "say \"open  ");
+    }
+
+    #[test]
+    fn tabs_separate_args_like_spaces() {
+        assert_eq!(
+            parse_command("give\tsword \t2\t"),
+            parse_command("give sword 2")
+        );
+        let command = parse_command("say \"a\tb\"").unwrap();
+        assert_eq!(String::from_value(&command.args[0], 0).unwrap(), "a\tb");
+    }
+
+    #[test]
+    fn rejects_only_control_characters() {
+        assert!(only_control_characters("\u{1b}\u{7}"));
+        assert!(only_control_characters(" \u{1b} \r"));
+        assert!(!only_control_characters(""));
+        assert!(!only_control_characters(" \t\r"));
+        assert!(!only_control_characters("say \u{7}"));
+    }
+
+    #[test]
+    fn composed_text_round_trips_into_string_args() {
+        // Precomposed and decomposed forms stay distinct, nothing is normalized
+        for text in ["café", "cafe\u{301}", "日本語", "ｶﾞ", "\u{30ab}\u{3099}"] {
+            for line in [format!("say {text}"), format!("say \"{text}\"")] {
+                let command = parse_command(&line).unwrap();
+                assert_eq!(String::from_value(&command.args[0], 0).unwrap(), text);
+                assert_eq!(parse_command(&format_command(&command)).unwrap(), command);
+            }
+        }
+    }
+
+    #[test]
+    fn formatted_commands_parse_back() {
+        for line in [
+            "spawn_enemy 3 1.5 true",
+            r#"say "hello world" 'quoted \" mark' plain"#,
+            "give ''",
+            r#"say "it\'s \"quoted\"\nover\ttwo lines""#,
+        ] {
+            let command = parse_command(line).unwrap();
+            assert_eq!(parse_command(&format_command(&command)).unwrap(), command);
+        }
+    }
+}
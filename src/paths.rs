@@ -0,0 +1,165 @@
+use std::path::{Component, Path, PathBuf};
+
+/// The directory relative paths typed in the terminal start from, changed with `cd` and shown with `pwd`.
+///
+/// The working directory of the process differs between `cargo run` and a shipped build,
+/// so built-in commands and [`PathCompleter`](crate::PathCompleter) resolve paths with [`TerminalPaths::resolve`] instead.
+/// The base defaults to the working directory of the process. Set it to the assets directory
+/// or a per-user data directory at startup with [`TerminalPaths::new`].
+///
+/// Without a filesystem, such as on wasm, the base is empty and paths are left as typed.
+///
+/// # Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use leafwing_terminal::{TerminalCorePlugin, TerminalPaths};
+/// #
+/// App::new()
+///     .add_plugin(TerminalCorePlugin)
+///     .insert_resource(TerminalPaths::new("assets").with_jail(true));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TerminalPaths {
+    base: PathBuf,
+    /// Directory set by `cd`, starting at `base`
+    current: PathBuf,
+    jail: bool,
+}
+
+impl TerminalPaths {
+    /// Resolve relative paths against `base`.
+    pub fn new(base: impl Into<PathBuf>) -> Self {
+        let base = base.into();
+        TerminalPaths {
+            current: base.clone(),
+            base,
+            jail: false,
+        }
+    }
+
+    /// Keep resolved paths inside the base, so `..` stops at it and absolute paths start from it.
+    ///
+    /// Paths are resolved without touching the filesystem, so symbolic links can still lead outside.
+    pub fn with_jail(mut self, jail: bool) -> Self {
+        self.jail = jail;
+        self
+    }
+
+    /// The directory relative paths start from before any `cd`.
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// The directory relative paths start from, as changed by `cd`.
+    pub fn current(&self) -> &Path {
+        &self.current
+    }
+
+    /// Change the current directory to `path`, resolved against the current directory.
+    ///
+    /// This doesn't check that the directory exists, see the `cd` command.
+    pub fn set_current(&mut self, path: impl AsRef<Path>) {
+        self.current = self.resolve(path);
+    }
+
+    /// Change the current directory back to the base.
+    pub fn reset_current(&mut self) {
+        self.current = self.base.clone();
+    }
+
+    /// `path` resolved against the current directory, with `.` and `..` removed.
+    pub fn resolve(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        if self.base.as_os_str().is_empty() {
+            return path.to_path_buf();
+        }
+
+        let mut resolved = self.current.clone();
+        for component in path.components() {
+            match component {
+                Component::Prefix(_) | Component::RootDir if self.jail => {
+                    resolved = self.base.clone();
+                }
+                Component::Prefix(_) | Component::RootDir => resolved.push(component),
+                Component::CurDir => {}
+                Component::ParentDir if self.jail && resolved == self.base => {}
+                Component::ParentDir => match resolved.components().next_back() {
+                    Some(Component::Normal(_)) => {
+                        resolved.pop();
+                    }
+                    Some(Component::Prefix(_) | Component::RootDir) => {}
+                    _ => resolved.push(component),
+                },
+                Component::Normal(name) => resolved.push(name),
+            }
+        }
+        resolved
+    }
+}
+
+/// Resolves paths against the working directory of the process.
+impl Default for TerminalPaths {
+    fn default() -> Self {
+        TerminalPaths::new(std::env::current_dir().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn it_resolves_absolute_paths() {
+        let paths = TerminalPaths::new("/games/rpg");
+        assert_eq!(paths.resolve("/etc/hosts"), Path::new("/etc/hosts"));
+        assert_eq!(
+            paths.resolve("saves/1.ron"),
+            Path::new("/games/rpg/saves/1.ron")
+        );
+
+        // Absolute paths start from the base of a jail
+        let paths = paths.with_jail(true);
+        assert_eq!(
+            paths.resolve("/etc/hosts"),
+            Path::new("/games/rpg/etc/hosts")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_resolves_parent_directories() {
+        let mut paths = TerminalPaths::new("/games/rpg");
+        paths.set_current("saves/./slot_1");
+        assert_eq!(paths.current(), Path::new("/games/rpg/saves/slot_1"));
+        assert_eq!(
+            paths.resolve("../2.ron"),
+            Path::new("/games/rpg/saves/2.ron")
+        );
+        assert_eq!(paths.resolve("../../../../../"), Path::new("/"));
+
+        // `..` stops at the base of a jail
+        let mut paths = paths.with_jail(true);
+        assert_eq!(paths.resolve("../../../.."), Path::new("/games/rpg"));
+        assert_eq!(
+            paths.resolve("../../../../mods/x"),
+            Path::new("/games/rpg/mods/x")
+        );
+        paths.reset_current();
+        assert_eq!(paths.resolve(".."), Path::new("/games/rpg"));
+
+        // Relative bases keep the `..` they can't remove
+        let paths = TerminalPaths::new("assets");
+        assert_eq!(paths.resolve("../../x"), Path::new("../x"));
+    }
+
+    #[test]
+    fn it_leaves_paths_without_a_base() {
+        // Such as on wasm, which has no working directory
+        let mut paths = TerminalPaths::new("").with_jail(true);
+        paths.set_current("levels");
+        assert_eq!(paths.resolve("../x/./y"), Path::new("../x/./y"));
+        assert_eq!(paths.current(), Path::new("levels"));
+    }
+}
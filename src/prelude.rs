@@ -0,0 +1,32 @@
+//! The items most command authors need.
+//!
+//! ```
+//! use bevy::prelude::*;
+//! use leafwing_terminal::prelude::*;
+//!
+//! /// Greets someone
+//! #[derive(TerminalCommand)]
+//! #[terminal_command(name = "greet")]
+//! struct GreetCommand {
+//!     /// Who to greet
+//!     name: String,
+//! }
+//!
+//! fn greet_command(mut greet: TerminalCommand<GreetCommand>) {
+//!     if let Some(GreetCommand { name }) = greet.take() {
+//!         reply_ok!(greet, "Hello, {name}!");
+//!     }
+//! }
+//!
+//! App::new()
+//!     .add_plugin(TerminalCorePlugin)
+//!     .add_terminal_command::<GreetCommand, _, _>(greet_command);
+//! ```
+
+pub use crate::{reply, reply_error, reply_failed, reply_lines, reply_ok, reply_usage, reply_warn};
+pub use crate::{
+    AddTerminalCommand, AddTerminalCompletions, AddTerminalToolbar, CommandArgGroup,
+    CommandRegistry, FromValue, FromValueError, IntoValue, PrintTerminalLine, TerminalCommand,
+    TerminalCommandEntered, TerminalCommandQueue, TerminalConfiguration, TerminalCorePlugin,
+    TerminalDispatch, TerminalOpen, TerminalParseFailed, TerminalPlugin, ValueRawOwned, ValueType,
+};
@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+
+use crate::echo::Echo;
+use crate::line::Severity;
+use crate::log::is_error_line;
+use crate::strings::fill;
+use crate::ui::group_thousands;
+use crate::{PrintTerminalLine, TerminalInvocation, TerminalStrings};
+
+/// Lines printed and not yet added to the scrollback, see [`TerminalConfiguration::print_queue_size`](crate::TerminalConfiguration::print_queue_size).
+///
+/// Every [`PrintTerminalLine`] and line of the [`TerminalBootBuffer`](crate::TerminalBootBuffer) goes through it,
+/// so a burst of output is spread over the next frames instead of added all at once,
+/// and bulk output beyond the limit is dropped instead of growing without bound.
+#[derive(Debug, Default)]
+pub(crate) struct PrintQueue {
+    lines: VecDeque<PrintTerminalLine>,
+    /// Low priority lines dropped since the last summary
+    dropped: usize,
+}
+
+/// Whether `line` must never be dropped: echoes of and replies to commands, and warnings and errors.
+fn is_high_priority(line: &PrintTerminalLine, strings: &TerminalStrings) -> bool {
+    line.get_meta::<TerminalInvocation>().is_some()
+        || line.get_meta::<Echo>().is_some()
+        || matches!(
+            line.severity(),
+            Some(Severity::Warn | Severity::Error | Severity::Failed)
+        )
+        || is_error_line(line, strings)
+}
+
+impl PrintQueue {
+    /// Queue `line`, dropping the oldest low priority line if that makes more than `limit` lines, or never if it is 0.
+    ///
+    /// When every queued line is high priority, the queue grows past `limit`.
+    pub(crate) fn push(
+        &mut self,
+        line: PrintTerminalLine,
+        limit: usize,
+        strings: &TerminalStrings,
+    ) {
+        self.lines.push_back(line);
+        if limit == 0 || self.lines.len() <= limit {
+            return;
+        }
+        let oldest = self
+            .lines
+            .iter()
+            .position(|line| !is_high_priority(line, strings));
+        if let Some(index) = oldest {
+            self.lines.remove(index);
+            self.dropped += 1;
+        }
+    }
+
+    /// Take up to `count` lines to print, or every line if it is 0,
+    /// after a line telling how many were dropped since the last time.
+    pub(crate) fn take(
+        &mut self,
+        count: usize,
+        strings: &TerminalStrings,
+    ) -> Vec<PrintTerminalLine> {
+        let count = match count {
+            0 => self.lines.len(),
+            count => count.min(self.lines.len()),
+        };
+        let mut lines = Vec::with_capacity(count + 1);
+        if self.dropped > 0 {
+            let summary = fill(&strings.dropped_lines, &[&group_thousands(self.dropped)]);
+            lines.push(PrintTerminalLine::warn(summary));
+            self.dropped = 0;
+        }
+        lines.extend(self.lines.drain(..count));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+
+    use super::*;
+    use crate::harness::test_app;
+    use crate::{TerminalConfiguration, TerminalState};
+
+    #[test]
+    fn it_drops_the_oldest_bulk_lines_beyond_the_limit() {
+        let strings = TerminalStrings::default();
+        let mut queue = PrintQueue::default();
+        let invocation = TerminalInvocation::next();
+        for i in 0..10 {
+            queue.push(PrintTerminalLine::new(format!("bulk {i}")), 4, &strings);
+            if i % 3 == 0 {
+                let reply = PrintTerminalLine::new(format!("reply {i}")).with_meta(invocation);
+                queue.push(reply, 4, &strings);
+            }
+        }
+        queue.push(
+            PrintTerminalLine::error("no spawn point".to_string()),
+            4,
+            &strings,
+        );
+
+        let lines: Vec<_> = queue.take(0, &strings);
+        let texts: Vec<_> = lines.iter().map(|line| line.line.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "[dropped 10 low-priority lines]",
+                "reply 0",
+                "reply 3",
+                "reply 6",
+                "reply 9",
+                "no spawn point",
+            ]
+        );
+        assert!(queue.take(0, &strings).is_empty());
+    }
+
+    #[test]
+    fn it_spreads_bursts_over_frames() {
+        let mut app = test_app();
+        let mut config = app.world.resource_mut::<TerminalConfiguration>();
+        config.print_queue_size = 2000;
+        config.print_lines_per_frame = 1000;
+        config.scrollback_size = 0;
+
+        let invocation = TerminalInvocation::next();
+        let mut events = app.world.resource_mut::<Events<PrintTerminalLine>>();
+        for i in 0..5000 {
+            events.send(PrintTerminalLine::new(format!("bulk {i}")));
+        }
+        events.send(PrintTerminalLine::new("spawned 3 enemies".to_string()).with_meta(invocation));
+        events.send(PrintTerminalLine::new("[ok]".to_string()).with_meta(invocation));
+        app.update();
+        // The summary comes on top of the lines of the frame
+        assert_eq!(
+            app.world.resource::<TerminalState>().scrollback().len(),
+            1001
+        );
+        app.update();
+        app.update();
+
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(state.scrollback().len(), 2001);
+        assert_eq!(state.scrollback()[0], "[dropped 3,002 low-priority lines]");
+        assert_eq!(state.scrollback()[1], "bulk 3002");
+        assert_eq!(
+            invocation.collect_output(&app),
+            ["spawned 3 enemies", "[ok]"]
+        );
+    }
+}
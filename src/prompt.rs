@@ -0,0 +1,212 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bevy::prelude::*;
+use leafwing_terminal_parser::{parse_arg_str, Value, ValueRawOwned};
+
+use crate::terminal::TerminalState;
+use crate::{FromValue, FromValueError, PrintTerminalLine};
+
+static NEXT_PROMPT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Identifies an input request made with
+/// [`TerminalCommand::request_input`](crate::TerminalCommand::request_input).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PromptId(u64);
+
+impl PromptId {
+    pub(crate) fn next() -> Self {
+        PromptId(NEXT_PROMPT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Error answering a prompt.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PromptError {
+    /// The prompt was cancelled with Escape
+    Cancelled,
+    /// The answer could not be parsed as the requested type
+    InvalidValue(FromValueError),
+}
+
+impl fmt::Display for PromptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromptError::Cancelled => write!(f, "[cancelled]"),
+            PromptError::InvalidValue(err) => err.fmt(f),
+        }
+    }
+}
+
+/// Event sent when the user answers a prompt.
+///
+/// # Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use leafwing_terminal::{PromptId, TerminalPromptAnswered, Value};
+/// #
+/// struct RenamePrompt(Option<PromptId>);
+///
+/// fn rename_answered(
+///     mut answers: EventReader<TerminalPromptAnswered>,
+///     prompt: Res<RenamePrompt>,
+/// ) {
+///     for answer in answers.iter() {
+///         if Some(answer.id) == prompt.0 {
+///             if let Ok(Value::String(name)) = &answer.value {
+///                 // rename the player
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerminalPromptAnswered {
+    /// Prompt which was answered
+    pub id: PromptId,
+    /// Parsed answer
+    pub value: Result<Value, PromptError>,
+}
+
+pub(crate) struct TerminalPromptRequested {
+    pub(crate) id: PromptId,
+    pub(crate) message: String,
+    pub(crate) parse: fn(&str) -> Result<Value, FromValueError>,
+}
+
+pub(crate) struct PendingPrompt {
+    pub(crate) id: PromptId,
+    pub(crate) message: String,
+    pub(crate) parse: fn(&str) -> Result<Value, FromValueError>,
+}
+
+impl PendingPrompt {
+    pub(crate) fn answer(&self, line: &str) -> TerminalPromptAnswered {
+        TerminalPromptAnswered {
+            id: self.id,
+            value: (self.parse)(line).map_err(PromptError::InvalidValue),
+        }
+    }
+
+    pub(crate) fn cancel(&self) -> TerminalPromptAnswered {
+        TerminalPromptAnswered {
+            id: self.id,
+            value: Err(PromptError::Cancelled),
+        }
+    }
+}
+
+impl TerminalState {
+    /// Answer the current prompt with `line`, returning the answer and its echo to print.
+    pub(crate) fn answer_prompt(
+        &mut self,
+        line: &str,
+    ) -> Option<(TerminalPromptAnswered, PrintTerminalLine)> {
+        let prompt = self.prompts.pop_front()?;
+        let echo = PrintTerminalLine::new(format!("{} {}", prompt.message, line));
+        Some((prompt.answer(line), echo))
+    }
+
+    /// Cancel the current prompt, returning the cancellation and its echo to print.
+    pub(crate) fn cancel_prompt(&mut self) -> Option<(TerminalPromptAnswered, PrintTerminalLine)> {
+        let prompt = self.prompts.pop_front()?;
+        let echo = PrintTerminalLine::new(format!("{} {}", prompt.message, PromptError::Cancelled));
+        Some((prompt.cancel(), echo))
+    }
+}
+
+/// Parse a prompt answer as `T`.
+///
+/// The whole line is used as a string if `T` accepts any text,
+/// otherwise it must be a single value accepted by `T`.
+pub(crate) fn parse_answer<T>(line: &str) -> Result<Value, FromValueError>
+where
+    T: FromValue,
+{
+    let line = line.trim();
+    let parsed = parse_arg_str(line)
+        .ok()
+        .filter(|values| values.len() == 1)
+        .and_then(|values| values.into_iter().next())
+        .map(ValueRawOwned::from);
+
+    // Numbers accept strings holding a negative number too, so probe with text which is not one
+    let takes_text = T::from_value(&ValueRawOwned::String(String::new()), 0).is_ok();
+    let value = match parsed {
+        Some(value) if !takes_text => value,
+        Some(ValueRawOwned::String(s)) => ValueRawOwned::String(s),
+        _ => ValueRawOwned::String(line.to_string()),
+    };
+    T::from_value(&value, 0)?;
+    Ok(match value {
+        ValueRawOwned::String(s) if !takes_text => number_value(&s).unwrap_or(Value::String(s)),
+        value => value.into(),
+    })
+}
+
+/// The number held by a string which a number type accepted, such as `-5`.
+///
+/// Integers too large for a [`Value::Int`] stay strings.
+fn number_value(s: &str) -> Option<Value> {
+    if let Ok(num) = s.parse::<i64>() {
+        return Some(Value::Int(num));
+    }
+    if s.parse::<i128>().is_ok() {
+        return None;
+    }
+    s.parse().ok().map(Value::Float)
+}
+
+pub(crate) fn receive_prompt_requests(
+    mut terminal_state: ResMut<TerminalState>,
+    mut events: EventReader<TerminalPromptRequested>,
+) {
+    for TerminalPromptRequested { id, message, parse } in events.iter() {
+        terminal_state.prompts.push_back(PendingPrompt {
+            id: *id,
+            message: message.clone(),
+            parse: *parse,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use leafwing_terminal_parser::Value;
+
+    use super::parse_answer;
+    use crate::{FromValueError, ValueType};
+
+    #[test]
+    fn it_parses_string_answers() {
+        assert_eq!(
+            parse_answer::<String>("  bob smith "),
+            Ok(Value::String("bob smith".to_string()))
+        );
+        assert_eq!(
+            parse_answer::<String>(r#""bob smith""#),
+            Ok(Value::String("bob smith".to_string()))
+        );
+        assert_eq!(
+            parse_answer::<String>("42"),
+            Ok(Value::String("42".to_string()))
+        );
+    }
+
+    #[test]
+    fn it_parses_typed_answers() {
+        assert_eq!(parse_answer::<i64>("42"), Ok(Value::Int(42)));
+        assert_eq!(parse_answer::<i64>("-5"), Ok(Value::Int(-5)));
+        assert_eq!(parse_answer::<f64>("-0.5"), Ok(Value::Float(-0.5)));
+        assert_eq!(parse_answer::<bool>("true"), Ok(Value::Bool(true)));
+        assert_eq!(
+            parse_answer::<i64>("forty two"),
+            Err(FromValueError::UnexpectedArgType {
+                arg_num: 0,
+                expected: ValueType::Int,
+                received: ValueType::String,
+            })
+        );
+    }
+}
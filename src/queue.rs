@@ -0,0 +1,930 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem;
+use std::time::Duration;
+
+use bevy::ecs::event::{Events, ManualEventReader};
+use bevy::prelude::*;
+use bevy::window::{WindowFocused, WindowId};
+
+use crate::echo::{format_echo, EchoSource, SubmitInfo};
+use crate::log::is_error_line;
+use crate::parse::{format_command, normalize_input};
+use crate::strings::fill;
+use crate::structured::strip_json_flag;
+use crate::terminal::TerminalCommandBuffer;
+use crate::ui::group_thousands;
+use crate::watchdog::PendingOutcomes;
+use crate::{
+    parse_command, CommandOutcome, CommandRegistry, ParseError, PrintTerminalLine,
+    TerminalCommandCompleted, TerminalCommandEntered, TerminalConfiguration, TerminalInvocation,
+    TerminalMode, TerminalNotice, TerminalState, TerminalStrings, ValueRawOwned,
+};
+
+/// Seconds between notices showing the progress of a long backlog of queued commands.
+const PROGRESS_INTERVAL: f64 = 0.5;
+
+/// Commands waiting to be run, in submission order.
+///
+/// Commands submitted in the terminal are queued here and released as [`TerminalCommandEntered`] events,
+/// [`TerminalConfiguration::command_batch_size`] per frame, or as many as fit in
+/// [`TerminalConfiguration::command_time_budget`].
+/// The order in which command systems run within a frame is decided by the schedule,
+/// so releasing one command per frame (the default) is what guarantees each command observes
+/// the effects of the commands submitted before it. Larger batches run queued commands sooner,
+/// but commands in the same batch may run in any order.
+///
+/// While the terminal is waiting for a prompt answer (see [`TerminalState::mode`]),
+/// queued and triggered scheduled commands are held, then released in order once the prompt is resolved.
+/// Key binds, scripts and other non-interactive sources should submit here so they can't
+/// interrupt a prompt.
+///
+/// While the primary window is unfocused, queued commands are held or denied according to
+/// [`TerminalConfiguration::unfocused_policy`].
+///
+/// Sending [`TerminalCommandEntered`] events directly bypasses the queue.
+/// When the queue takes more than half a second to empty, its progress is shown as a [`TerminalNotice`].
+///
+/// # Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use leafwing_terminal::{TerminalCommandEntered, TerminalCommandQueue};
+/// #
+/// fn run_setup_commands(mut queue: ResMut<TerminalCommandQueue>) {
+///     queue.push(TerminalCommandEntered {
+///         command: "spawn_wave".to_string(),
+///         args: Vec::new(),
+///     });
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TerminalCommandQueue {
+    commands: VecDeque<(TerminalInvocation, TerminalCommandEntered)>,
+    /// Invocations of the commands released this frame, by the id of their [`TerminalCommandEntered`] event
+    pub(crate) released: Vec<(usize, TerminalInvocation)>,
+    scheduled: Vec<ScheduledCommand>,
+    next_scheduled_id: u64,
+    frame: u64,
+    repeat: Option<Repeat>,
+    /// Scripts being run, innermost last
+    scripts: Vec<Script>,
+    /// Time taken by the last run of each command, by registered name
+    costs: HashMap<String, Duration>,
+    backlog: Option<Backlog>,
+    /// Whether the primary window has lost focus, see [`track_window_focus`]
+    unfocused: bool,
+    /// Whether commands were held by [`UnfocusedPolicy::QueueUntilFocused`]
+    held_while_unfocused: bool,
+    /// Invocations submitted with `--json` which haven't completed, see [`TerminalStructuredOutput`](crate::TerminalStructuredOutput)
+    pub(crate) structured: HashSet<TerminalInvocation>,
+}
+
+/// What the queue does with commands while the primary window is unfocused or minimized.
+///
+/// Commands are typed in the terminal with the window focused,
+/// so this is about key binds, scripts and other sources submitting to the queue while nobody is watching.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnfocusedPolicy {
+    /// Run commands as usual
+    AllowAll,
+    /// Hold commands until the window is focused again, then run them with a notice
+    QueueUntilFocused,
+    /// Run commands as usual, except those marked [`destructive`](crate::CommandInfo::destructive)
+    /// which fail with an explanation
+    DenyDestructive,
+}
+
+/// Commands released since the queue was last empty, for showing progress.
+#[derive(Clone, Debug)]
+struct Backlog {
+    released: usize,
+    /// Seconds since startup when the queue stopped being empty
+    started: f64,
+    /// Seconds since startup when progress was last shown
+    notified: Option<f64>,
+}
+
+/// A command being run a number of times, see [`TerminalCommandQueue::repeat`].
+#[derive(Clone, Debug)]
+struct Repeat {
+    command: TerminalCommandEntered,
+    count: u64,
+    queued: u64,
+    keep_going: bool,
+    /// Invocations of the runs queued so far, whose failures stop the repeat
+    runs: HashSet<TerminalInvocation>,
+}
+
+/// A script being run a line at a time, see [`TerminalCommandQueue::push_script`].
+#[derive(Clone, Debug)]
+struct Script {
+    lines: VecDeque<ScriptLine>,
+    /// Commands registered with `onfail`, run once if a later line fails
+    cleanup: Vec<TerminalCommandEntered>,
+    /// The line waiting for its outcome, and whether it was run with `require`
+    running: Option<(TerminalInvocation, bool)>,
+}
+
+#[derive(Clone, Debug)]
+enum ScriptLine {
+    Run {
+        command: TerminalCommandEntered,
+        required: bool,
+    },
+    OnFail(TerminalCommandEntered),
+}
+
+impl ScriptLine {
+    /// Splits `require` or `onfail` from the command they come before.
+    fn new(line: TerminalCommandEntered) -> Self {
+        let inner = match (line.command.as_str(), line.args.first()) {
+            ("require" | "onfail", Some(ValueRawOwned::String(inner))) => inner.clone(),
+            _ => {
+                return ScriptLine::Run {
+                    command: line,
+                    required: false,
+                }
+            }
+        };
+        let command = TerminalCommandEntered {
+            command: inner,
+            args: line.args[1..].to_vec(),
+        };
+        match line.command.as_str() {
+            "onfail" => ScriptLine::OnFail(command),
+            _ => ScriptLine::Run {
+                command,
+                required: true,
+            },
+        }
+    }
+}
+
+/// When a [`ScheduledCommand`] is queued.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScheduleTrigger {
+    /// At the start of this frame, see [`TerminalCommandQueue::frame`]
+    Frame(u64),
+    /// At the first frame at least this many seconds after startup
+    Time(f64),
+}
+
+/// A command waiting for its trigger before it is queued.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduledCommand {
+    /// Id for cancelling the command
+    pub id: u64,
+    /// When the command is queued
+    pub trigger: ScheduleTrigger,
+    /// The command to run
+    pub command: TerminalCommandEntered,
+}
+
+impl TerminalCommandQueue {
+    /// Queue a command to run after all previously queued commands.
+    ///
+    /// The returned invocation identifies the lines the command prints, see [`TerminalInvocation::collect_output`].
+    pub fn push(&mut self, command: TerminalCommandEntered) -> TerminalInvocation {
+        let invocation = TerminalInvocation::next();
+        self.commands.push_back((invocation, command));
+        invocation
+    }
+
+    /// Parse a command line and queue it, exactly as if it was typed in the terminal.
+    ///
+    /// Trailing whitespace and `\r` are stripped from the line first, as they are from typed lines,
+    /// and a trailing `--json` asks for the output as [`TerminalStructuredOutput`](crate::TerminalStructuredOutput) too.
+    /// See [`parse_command`] for the grammar, and [`TerminalCommandQueue::push`] for the returned invocation.
+    pub fn submit(&mut self, line: &str) -> Result<TerminalInvocation, ParseError> {
+        let (line, json) = strip_json_flag(normalize_input(line));
+        let invocation = self.push(parse_command(line)?);
+        if json {
+            self.structured.insert(invocation);
+        }
+        Ok(invocation)
+    }
+
+    /// Number of queued commands.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns true if there are no queued commands.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Remove all queued commands.
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Queue a command once `trigger` is reached, returning an id for cancelling it.
+    ///
+    /// Scheduled commands run ahead of other queued commands on the frame they are triggered,
+    /// and are echoed in the terminal with a `⏲` marker.
+    pub fn schedule(&mut self, command: TerminalCommandEntered, trigger: ScheduleTrigger) -> u64 {
+        let id = self.next_scheduled_id;
+        self.next_scheduled_id += 1;
+        self.scheduled.push(ScheduledCommand {
+            id,
+            trigger,
+            command,
+        });
+        id
+    }
+
+    /// Cancel a scheduled command, returning false if there was no command with that id waiting.
+    pub fn cancel_scheduled(&mut self, id: u64) -> bool {
+        let len = self.scheduled.len();
+        self.scheduled.retain(|scheduled| scheduled.id != id);
+        self.scheduled.len() < len
+    }
+
+    /// Commands waiting for their triggers, in the order they were scheduled.
+    pub fn scheduled(&self) -> &[ScheduledCommand] {
+        &self.scheduled
+    }
+
+    /// The number of frames since startup, counting the current one, as used by [`ScheduleTrigger::Frame`].
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Run a command `count` times, returning false without doing anything if a command is already repeating.
+    ///
+    /// Runs are queued once the queue is empty, [`TerminalConfiguration::command_batch_size`] at a time,
+    /// so other queued commands go first and long repeats don't stall a frame.
+    /// Unless `keep_going` is set, the remaining runs are cancelled once a run prints an error or failure.
+    pub fn repeat(
+        &mut self,
+        command: TerminalCommandEntered,
+        count: u64,
+        keep_going: bool,
+    ) -> bool {
+        if self.repeat.is_some() {
+            return false;
+        }
+        self.repeat = Some(Repeat {
+            command,
+            count,
+            queued: 0,
+            keep_going,
+            runs: HashSet::new(),
+        });
+        true
+    }
+
+    /// Cancel the remaining runs of the repeating command,
+    /// returning how many runs were queued and how many were requested.
+    pub fn cancel_repeat(&mut self) -> Option<(u64, u64)> {
+        self.repeat
+            .take()
+            .map(|repeat| (repeat.queued, repeat.count))
+    }
+
+    /// Returns true if a command is being repeated.
+    pub fn is_repeating(&self) -> bool {
+        self.repeat.is_some()
+    }
+
+    /// Run the lines of a script in order, each once everything queued before it has run.
+    ///
+    /// Blank lines and lines starting with `#` are skipped. Two commands only work in scripts:
+    /// - `require <command...>` runs a command and stops the script if it fails
+    /// - `onfail <command...>` runs a command once if any later line of the script fails
+    ///
+    /// Each line waits for its [`TerminalCommandCompleted`](crate::TerminalCommandCompleted), and fails if it completes with
+    /// [`CommandOutcome::Failed`](crate::CommandOutcome::Failed), so commands printing their outcome in a later frame are waited on.
+    /// Lines no command system accepts, such as those with invalid arguments, fail if they print an error.
+    /// Scripts pushed while another is running, such as by one of its commands, run before the rest of it
+    /// and have their own `onfail` commands.
+    ///
+    /// Nothing is queued if a line can't be parsed.
+    pub fn push_script<'a>(
+        &mut self,
+        lines: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), ParseError> {
+        let lines = lines
+            .into_iter()
+            .map(normalize_input)
+            .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .map(|line| parse_command(line).map(ScriptLine::new))
+            .collect::<Result<_, _>>()?;
+        self.scripts.push(Script {
+            lines,
+            cleanup: Vec::new(),
+            running: None,
+        });
+        Ok(())
+    }
+
+    /// Returns true if a script is being run.
+    pub fn is_running_script(&self) -> bool {
+        !self.scripts.is_empty()
+    }
+
+    /// Check the outcome of the line each script is waiting for,
+    /// then queue the next line of the innermost script once the queue is empty.
+    ///
+    /// Lines for which `in_progress` is true haven't completed yet, and those which complete without
+    /// being accepted fail if they are in `rejected`.
+    /// Returns the number of scripts stopped by a required line failing.
+    fn advance_scripts(
+        &mut self,
+        completed: &HashMap<TerminalInvocation, CommandOutcome>,
+        rejected: &HashSet<TerminalInvocation>,
+        in_progress: impl Fn(TerminalInvocation) -> bool,
+    ) -> usize {
+        let commands = &mut self.commands;
+        let scripts = self.scripts.len();
+        self.scripts.retain_mut(|script| {
+            let Some((invocation, required)) = script.running else {
+                return true;
+            };
+            let failed = match completed.get(&invocation) {
+                Some(outcome) => *outcome == CommandOutcome::Failed,
+                None if commands.iter().any(|(queued, _)| *queued == invocation)
+                    || in_progress(invocation) =>
+                {
+                    return true
+                }
+                None => rejected.contains(&invocation),
+            };
+            script.running = None;
+            if failed {
+                let cleanup = script.cleanup.drain(..);
+                commands.extend(cleanup.map(|command| (TerminalInvocation::next(), command)));
+                return !required;
+            }
+            true
+        });
+        let stopped = scripts - self.scripts.len();
+
+        while self.commands.is_empty() {
+            let Some(script) = self
+                .scripts
+                .last_mut()
+                .filter(|script| script.running.is_none())
+            else {
+                break;
+            };
+            match script.lines.pop_front() {
+                Some(ScriptLine::OnFail(command)) => script.cleanup.push(command),
+                Some(ScriptLine::Run { command, required }) => {
+                    let invocation = TerminalInvocation::next();
+                    script.running = Some((invocation, required));
+                    self.commands.push_back((invocation, command));
+                }
+                None => {
+                    self.scripts.pop();
+                }
+            }
+        }
+        stopped
+    }
+
+    /// Number of queued commands whose last runs fit in `budget`, and at least one.
+    ///
+    /// Stops at a second invocation of a command, which its systems only take on their next run anyway.
+    fn budgeted_count(&self, budget: Duration, registry: &CommandRegistry) -> usize {
+        let mut spent = Duration::ZERO;
+        let mut released = HashSet::new();
+        let mut count = 0;
+        for (_, command) in &self.commands {
+            let name = registry
+                .resolve(&command.command)
+                .unwrap_or(command.command.as_str());
+            let cost = self.costs.get(name).copied().unwrap_or(budget);
+            if count > 0 && (spent + cost > budget || released.contains(name)) {
+                break;
+            }
+            released.insert(name);
+            spent += cost;
+            count += 1;
+        }
+        count
+    }
+}
+
+/// Tracks whether the primary window is focused, for [`TerminalConfiguration::unfocused_policy`].
+///
+/// Does nothing in apps without windows.
+pub(crate) fn track_window_focus(
+    focus_changes: Option<Res<Events<WindowFocused>>>,
+    mut reader: Local<ManualEventReader<WindowFocused>>,
+    mut queue: ResMut<TerminalCommandQueue>,
+) {
+    let Some(focus_changes) = focus_changes else {
+        return;
+    };
+    for WindowFocused { id, focused } in reader.iter(&focus_changes) {
+        if *id == WindowId::primary() {
+            queue.unfocused = !focused;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn release_queued_commands(
+    config: Res<TerminalConfiguration>,
+    strings: Res<TerminalStrings>,
+    state: Res<TerminalState>,
+    time: Res<Time>,
+    registry: Res<CommandRegistry>,
+    mut queue: ResMut<TerminalCommandQueue>,
+    mut buffer: ResMut<TerminalCommandBuffer>,
+    mut command_entered: ResMut<Events<TerminalCommandEntered>>,
+    mut sent: Local<ManualEventReader<TerminalCommandEntered>>,
+    mut terminal_line: ResMut<Events<PrintTerminalLine>>,
+    mut printed: Local<ManualEventReader<PrintTerminalLine>>,
+    completed_events: Res<Events<TerminalCommandCompleted>>,
+    mut completed_reader: Local<ManualEventReader<TerminalCommandCompleted>>,
+    outcomes: Res<PendingOutcomes>,
+    mut notices: EventWriter<TerminalNotice>,
+) {
+    let queue = &mut *queue;
+    queue.frame += 1;
+    queue.released.clear();
+    for (name, cost) in buffer.take_costs() {
+        queue.costs.insert(name.to_string(), cost);
+    }
+
+    // Invocations which printed an error or failure, for scripts and repeats
+    let mut failed_invocations = HashSet::new();
+    for line in printed.iter(&terminal_line) {
+        if is_error_line(line, &strings) {
+            failed_invocations.extend(line.get_meta::<TerminalInvocation>().copied());
+        }
+    }
+
+    let completed: HashMap<_, _> = completed_reader
+        .iter(&completed_events)
+        .map(|completed| (completed.invocation, completed.outcome))
+        .collect();
+
+    // Stop repeating once one of its runs fails, ignoring errors from other commands
+    if let Some(repeat) = &queue.repeat {
+        let failed = !repeat.runs.is_disjoint(&failed_invocations);
+        if failed && !repeat.keep_going {
+            let line = fill(&strings.repeat_stopped, &[&repeat.queued, &repeat.count]);
+            terminal_line.send(PrintTerminalLine::new(line));
+            queue.repeat = None;
+        }
+    }
+
+    let (frame, now) = (queue.frame, time.seconds_since_startup());
+
+    let due = |scheduled: &ScheduledCommand| match scheduled.trigger {
+        ScheduleTrigger::Frame(at) => at <= frame,
+        ScheduleTrigger::Time(at) => at <= now,
+    };
+    let mut triggered = Vec::new();
+    queue.scheduled.retain(|scheduled| {
+        if due(scheduled) {
+            triggered.push(scheduled.command.clone());
+        }
+        !due(scheduled)
+    });
+    for command in triggered.into_iter().rev() {
+        let invocation = TerminalInvocation::next();
+        let line = format_command(&command);
+        let info = SubmitInfo {
+            line: &line,
+            source: EchoSource::Scheduled,
+            invocation: Some(invocation),
+            printed_at: now,
+        };
+        terminal_line.send(format_echo(&config, &registry, &info).into());
+        queue.commands.push_front((invocation, command));
+    }
+
+    // Hold commands until the prompt is resolved
+    if state.mode() != TerminalMode::Normal {
+        return;
+    }
+    // Queue the next runs of a repeating command once everything before them has run
+    if let Some(repeat) = queue.repeat.as_mut() {
+        if queue.commands.is_empty() {
+            let remaining = repeat.count - repeat.queued;
+            let runs = match config.command_batch_size {
+                0 => remaining,
+                batch_size => remaining.min(batch_size as u64),
+            };
+            for _ in 0..runs {
+                let invocation = TerminalInvocation::next();
+                repeat.runs.insert(invocation);
+                queue
+                    .commands
+                    .push_back((invocation, repeat.command.clone()));
+            }
+            repeat.queued += runs;
+            if repeat.queued == repeat.count {
+                queue.repeat = None;
+            }
+        }
+    }
+    // Run scripts a line at a time, stopping those whose required line failed
+    let in_progress =
+        |invocation| buffer.is_in_progress(invocation) || outcomes.is_pending(invocation);
+    for _ in 0..queue.advance_scripts(&completed, &failed_invocations, in_progress) {
+        terminal_line.send(PrintTerminalLine::new(strings.script_stopped.clone()));
+    }
+
+    // Hold or deny commands while the window is unfocused
+    if queue.unfocused && !queue.commands.is_empty() {
+        match config.unfocused_policy {
+            UnfocusedPolicy::AllowAll => {}
+            UnfocusedPolicy::QueueUntilFocused => {
+                queue.held_while_unfocused = true;
+                return;
+            }
+            UnfocusedPolicy::DenyDestructive => {
+                let (denied, allowed): (Vec<_>, Vec<_>) =
+                    queue.commands.drain(..).partition(|(_, command)| {
+                        matches!(registry.get(&command.command), Some(Some(info)) if info.destructive)
+                    });
+                queue.commands = allowed.into();
+                for (invocation, command) in denied {
+                    let line = fill(&strings.denied_while_unfocused, &[&command.command]);
+                    terminal_line.send(PrintTerminalLine::new(line).with_meta(invocation));
+                    terminal_line
+                        .send(PrintTerminalLine::new(strings.failed.clone()).with_meta(invocation));
+                }
+            }
+        }
+    }
+    if !queue.unfocused && mem::take(&mut queue.held_while_unfocused) {
+        let held = fill(&strings.running_held_commands, &[&queue.commands.len()]);
+        notices.send(TerminalNotice::new(held));
+    }
+
+    let count = match (config.command_time_budget, config.command_batch_size) {
+        (Some(budget), _) => {
+            queue.budgeted_count(Duration::from_secs_f32(budget.max(0.)), &registry)
+        }
+        (None, 0) => queue.commands.len(),
+        (None, batch_size) => batch_size.min(queue.commands.len()),
+    };
+    // Skip events sent by others, so the events read after sending are the released commands
+    sent.iter(&command_entered).for_each(drop);
+    let mut invocations = Vec::with_capacity(count);
+    for (invocation, command) in queue.commands.drain(..count) {
+        command_entered.send(command);
+        invocations.push(invocation);
+    }
+    queue.released = sent
+        .iter_with_id(&command_entered)
+        .zip(invocations)
+        .map(|((_, event_id), invocation)| (event_id.id, invocation))
+        .collect();
+
+    // Show the progress of backlogs which take a while, and their end if progress was shown
+    if count > 0 {
+        queue
+            .backlog
+            .get_or_insert(Backlog {
+                released: 0,
+                started: now,
+                notified: None,
+            })
+            .released += count;
+    }
+    if let Some(backlog) = &mut queue.backlog {
+        let remaining = queue.commands.len();
+        let due = now - backlog.notified.unwrap_or(backlog.started) >= PROGRESS_INTERVAL;
+        if (remaining > 0 && due) || (remaining == 0 && backlog.notified.is_some()) {
+            let total = backlog.released + remaining;
+            let progress = fill(
+                &strings.queue_progress,
+                &[&group_thousands(backlog.released), &group_thousands(total)],
+            );
+            notices.send(TerminalNotice::new(progress));
+            backlog.notified = Some(now);
+        }
+        if remaining == 0 {
+            queue.backlog = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use bevy::ecs::event::Events;
+    use bevy::prelude::*;
+    use bevy::window::{WindowFocused, WindowId};
+
+    use crate as leafwing_terminal;
+    use crate::harness::test_app;
+    use crate::{
+        AddTerminalCommand, TerminalCommand, TerminalCommandEntered, TerminalCommandQueue,
+        TerminalConfiguration, TerminalMode, TerminalState, UnfocusedPolicy, ValueRawOwned,
+    };
+
+    /// Sets the difficulty
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "set_difficulty")]
+    struct SetDifficultyCommand {
+        difficulty: String,
+    }
+
+    /// Spawns a wave at the current difficulty
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "spawn_wave")]
+    struct SpawnWaveCommand;
+
+    /// Asks for the player's name
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "rename")]
+    struct RenameCommand;
+
+    /// Takes a while
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "load_level")]
+    struct LoadLevelCommand;
+
+    /// Takes a while too
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "bake_lighting")]
+    struct BakeLightingCommand;
+
+    #[derive(Default)]
+    struct Difficulty(String);
+
+    /// Frames in which an expensive command ran
+    #[derive(Default)]
+    struct ExpensiveFrames(Vec<u64>);
+
+    #[derive(Default)]
+    struct SpawnedWaves(Vec<String>);
+
+    fn set_difficulty_command(
+        mut set_difficulty: TerminalCommand<SetDifficultyCommand>,
+        mut difficulty: ResMut<Difficulty>,
+    ) {
+        if let Some(SetDifficultyCommand { difficulty: new }) = set_difficulty.take() {
+            difficulty.0 = new;
+        }
+    }
+
+    fn spawn_wave_command(
+        mut spawn_wave: TerminalCommand<SpawnWaveCommand>,
+        difficulty: Res<Difficulty>,
+        mut waves: ResMut<SpawnedWaves>,
+    ) {
+        if spawn_wave.take().is_some() {
+            waves.0.push(difficulty.0.clone());
+        }
+    }
+
+    fn rename_command(mut rename: TerminalCommand<RenameCommand>) {
+        if rename.take().is_some() {
+            rename.request_input::<String>("name?");
+        }
+    }
+
+    const EXPENSIVE: Duration = Duration::from_millis(20);
+
+    fn load_level_command(
+        mut load_level: TerminalCommand<LoadLevelCommand>,
+        queue: Res<TerminalCommandQueue>,
+        mut frames: ResMut<ExpensiveFrames>,
+    ) {
+        if load_level.take().is_some() {
+            thread::sleep(EXPENSIVE);
+            frames.0.push(queue.frame());
+        }
+    }
+
+    fn bake_lighting_command(
+        mut bake_lighting: TerminalCommand<BakeLightingCommand>,
+        queue: Res<TerminalCommandQueue>,
+        mut frames: ResMut<ExpensiveFrames>,
+    ) {
+        if bake_lighting.take().is_some() {
+            thread::sleep(EXPENSIVE);
+            frames.0.push(queue.frame());
+        }
+    }
+
+    fn app_with_batch_size(command_batch_size: usize) -> App {
+        let mut app = test_app();
+        app.insert_resource(TerminalConfiguration {
+            command_batch_size,
+            ..Default::default()
+        })
+        .insert_resource(Difficulty("easy".to_string()))
+        .init_resource::<SpawnedWaves>()
+        // Spawning runs first within a frame
+        .add_terminal_command::<SpawnWaveCommand, _, _>(spawn_wave_command.label("spawn"))
+        .add_terminal_command::<SetDifficultyCommand, _, _>(set_difficulty_command.after("spawn"));
+        app.update();
+
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.push(TerminalCommandEntered {
+            command: "set_difficulty".to_string(),
+            args: vec![ValueRawOwned::String("hard".to_string())],
+        });
+        queue.push(TerminalCommandEntered {
+            command: "spawn_wave".to_string(),
+            args: Vec::new(),
+        });
+        app
+    }
+
+    #[test]
+    fn it_runs_queued_commands_in_submission_order() {
+        let mut app = app_with_batch_size(1);
+        app.update();
+        assert_eq!(app.world.resource::<TerminalCommandQueue>().len(), 1);
+        app.update();
+        app.update();
+
+        assert!(app.world.resource::<TerminalCommandQueue>().is_empty());
+        assert_eq!(app.world.resource::<SpawnedWaves>().0, ["hard"]);
+    }
+
+    #[test]
+    fn it_releases_whole_queue_with_unlimited_batch_size() {
+        let mut app = app_with_batch_size(0);
+        app.update();
+        assert!(app.world.resource::<TerminalCommandQueue>().is_empty());
+        app.update();
+
+        // Both commands ran in the same frame, in schedule order
+        assert_eq!(app.world.resource::<SpawnedWaves>().0, ["easy"]);
+    }
+
+    fn app_with_time_budget(budget: f32) -> App {
+        let mut app = test_app();
+        app.insert_resource(TerminalConfiguration {
+            command_time_budget: Some(budget),
+            ..Default::default()
+        })
+        .insert_resource(Difficulty("easy".to_string()))
+        .init_resource::<SpawnedWaves>()
+        .init_resource::<ExpensiveFrames>()
+        .add_terminal_command::<SpawnWaveCommand, _, _>(spawn_wave_command)
+        .add_terminal_command::<SetDifficultyCommand, _, _>(set_difficulty_command)
+        .add_terminal_command::<LoadLevelCommand, _, _>(load_level_command)
+        .add_terminal_command::<BakeLightingCommand, _, _>(bake_lighting_command);
+        app.update();
+        app
+    }
+
+    /// Updates the app until the queue is empty, returning the number of frames it took.
+    fn run_queue(app: &mut App) -> usize {
+        let mut frames = 0;
+        while !app.world.resource::<TerminalCommandQueue>().is_empty() {
+            app.update();
+            frames += 1;
+        }
+        app.update();
+        frames
+    }
+
+    #[test]
+    fn it_runs_cheap_scripts_in_few_frames() {
+        let mut app = app_with_time_budget(0.01);
+        let script = [
+            "set_difficulty hard",
+            "spawn_wave",
+            "help clear",
+            "bufstats",
+            "scheduled",
+            "sticky",
+        ];
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        for _ in 0..50 {
+            for line in script {
+                queue.submit(line).unwrap();
+            }
+        }
+
+        let frames = run_queue(&mut app);
+        assert!(frames < 300 / 3, "took {frames} frames");
+        assert_eq!(app.world.resource::<SpawnedWaves>().0.len(), 50);
+    }
+
+    #[test]
+    fn it_gives_expensive_commands_their_own_frame() {
+        let mut app = app_with_time_budget(0.005);
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        for _ in 0..5 {
+            queue.submit("load_level").unwrap();
+            queue.submit("spawn_wave").unwrap();
+            queue.submit("bake_lighting").unwrap();
+        }
+
+        run_queue(&mut app);
+        let mut frames = app.world.resource::<ExpensiveFrames>().0.clone();
+        assert_eq!(frames.len(), 10);
+        frames.dedup();
+        assert_eq!(frames.len(), 10, "expensive commands shared a frame");
+        assert_eq!(app.world.resource::<SpawnedWaves>().0.len(), 5);
+    }
+
+    fn app_with_unfocused_policy(unfocused_policy: UnfocusedPolicy) -> App {
+        let mut app = test_app();
+        app.add_event::<WindowFocused>()
+            .insert_resource(TerminalConfiguration {
+                unfocused_policy,
+                ..Default::default()
+            })
+            .insert_resource(Difficulty("easy".to_string()))
+            .init_resource::<SpawnedWaves>()
+            .add_terminal_command::<SpawnWaveCommand, _, _>(spawn_wave_command);
+        app.update();
+        app
+    }
+
+    fn set_focus(app: &mut App, focused: bool) {
+        app.world
+            .resource_mut::<Events<WindowFocused>>()
+            .send(WindowFocused {
+                id: WindowId::primary(),
+                focused,
+            });
+        app.update();
+    }
+
+    #[test]
+    fn it_holds_commands_until_focused() {
+        let mut app = app_with_unfocused_policy(UnfocusedPolicy::QueueUntilFocused);
+        set_focus(&mut app, false);
+
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.submit("spawn_wave").unwrap();
+        app.update();
+        app.update();
+        assert!(app.world.resource::<SpawnedWaves>().0.is_empty());
+
+        set_focus(&mut app, true);
+        app.update();
+        assert_eq!(app.world.resource::<SpawnedWaves>().0, ["easy"]);
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(
+            state.notice.as_ref().unwrap().0,
+            "Running 1 commands queued while the window was unfocused"
+        );
+    }
+
+    #[test]
+    fn it_denies_destructive_commands_while_unfocused() {
+        let mut app = app_with_unfocused_policy(UnfocusedPolicy::DenyDestructive);
+        set_focus(&mut app, false);
+
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        let clear_history = queue.submit("clearhistory").unwrap();
+        queue.submit("spawn_wave").unwrap();
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_eq!(app.world.resource::<SpawnedWaves>().0, ["easy"]);
+        assert_eq!(
+            clear_history.collect_output(&app),
+            [
+                "[error] 'clearhistory' is destructive and isn't run while the window is unfocused",
+                "[failed]"
+            ]
+        );
+    }
+
+    #[test]
+    fn it_holds_queued_commands_while_prompting() {
+        let mut app = test_app();
+        app.insert_resource(Difficulty("easy".to_string()))
+            .init_resource::<SpawnedWaves>()
+            .add_terminal_command::<SpawnWaveCommand, _, _>(spawn_wave_command)
+            .add_terminal_command::<RenameCommand, _, _>(rename_command);
+        app.update();
+
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.submit("rename").unwrap();
+        app.update();
+        app.update();
+        assert!(matches!(
+            app.world.resource::<TerminalState>().mode(),
+            TerminalMode::Prompt(_)
+        ));
+
+        // A key bind fires while the prompt is waiting for an answer
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.submit("spawn_wave").unwrap();
+        app.update();
+        app.update();
+        assert!(app.world.resource::<SpawnedWaves>().0.is_empty());
+        assert_eq!(app.world.resource::<TerminalCommandQueue>().len(), 1);
+
+        let mut state = app.world.resource_mut::<TerminalState>();
+        assert!(state.answer_prompt("bob").is_some());
+        assert_eq!(state.mode(), TerminalMode::Normal);
+        app.update();
+
+        assert_eq!(app.world.resource::<SpawnedWaves>().0, ["easy"]);
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::BTreeMap;
+
+use leafwing_terminal_parser::ValueRawOwned;
+
+use crate::CommandInfo;
+
+/// Checks a command's raw arguments before it runs, returning why they are invalid.
+///
+/// See [`CommandRegistry::set_validator`].
+pub type CommandValidator = fn(&[ValueRawOwned]) -> Result<(), String>;
+
+/// Registered terminal commands and their aliases.
+///
+/// Commands are added by [`AddTerminalCommand`](crate::AddTerminalCommand) when the app starts.
+/// Iteration is sorted by name, so help listings and completions don't change order between runs.
+///
+/// # Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use leafwing_terminal::CommandRegistry;
+/// #
+/// fn add_aliases(mut registry: ResMut<CommandRegistry>) {
+///     registry.add_alias("quit", "exit");
+///     registry.add_alias("cls", "clear");
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CommandRegistry {
+    commands: BTreeMap<String, Option<CommandInfo>>,
+    aliases: BTreeMap<String, String>,
+    validators: BTreeMap<String, CommandValidator>,
+}
+
+impl CommandRegistry {
+    /// Register a command, returning true if it replaced a command with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, info: Option<CommandInfo>) -> bool {
+        self.commands.insert(name.into(), info).is_some()
+    }
+
+    /// Unregister a command, returning true if it was registered.
+    ///
+    /// Aliases of the command are kept, but no longer resolve.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.commands.remove(name).is_some()
+    }
+
+    /// Make `alias` run `command`, replacing any alias with the same name.
+    ///
+    /// Aliases resolve only while `command` is registered, so they can be added before it.
+    pub fn add_alias(&mut self, alias: impl Into<String>, command: impl Into<String>) {
+        self.aliases.insert(alias.into(), command.into());
+    }
+
+    /// Remove an alias, returning true if it existed.
+    pub fn remove_alias(&mut self, alias: &str) -> bool {
+        self.aliases.remove(alias).is_some()
+    }
+
+    /// The registered name of a command or alias.
+    ///
+    /// Commands take precedence over aliases with the same name.
+    pub fn resolve(&self, name: &str) -> Option<&str> {
+        if let Some((name, _)) = self.commands.get_key_value(name) {
+            return Some(name.as_str());
+        }
+        let command = self.aliases.get(name)?;
+        self.commands
+            .get_key_value(command)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns true if `name` is a registered command or an alias of one.
+    pub fn contains(&self, name: &str) -> bool {
+        self.resolve(name).is_some()
+    }
+
+    /// Help for a command or alias.
+    ///
+    /// Returns `None` if there is no such command, or `Some(None)` if the command has no help.
+    pub fn get(&self, name: &str) -> Option<Option<&CommandInfo>> {
+        let name = self.resolve(name)?;
+        self.commands.get(name).map(Option::as_ref)
+    }
+
+    /// Registered commands and their help, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&CommandInfo>)> {
+        self.commands
+            .iter()
+            .map(|(name, info)| (name.as_str(), info.as_ref()))
+    }
+
+    /// Aliases and the commands they run, sorted by alias, skipping aliases which don't resolve.
+    pub fn aliases(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases
+            .iter()
+            .filter(|(_, command)| self.commands.contains_key(command.as_str()))
+            .map(|(alias, command)| (alias.as_str(), command.as_str()))
+    }
+
+    /// Every alias and the command it runs, sorted by alias, including aliases which don't resolve.
+    pub(crate) fn all_aliases(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases
+            .iter()
+            .map(|(alias, command)| (alias.as_str(), command.as_str()))
+    }
+
+    /// Aliases of a command, sorted.
+    pub fn aliases_of<'a>(&'a self, command: &'a str) -> impl Iterator<Item = &'a str> {
+        self.aliases()
+            .filter(move |(_, aliased)| *aliased == command)
+            .map(|(alias, _)| alias)
+    }
+
+    /// Names of commands and aliases, sorted, for completion.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .commands
+            .keys()
+            .map(String::as_str)
+            .chain(self.aliases().map(|(alias, _)| alias))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Names of commands and aliases starting with `prefix`, sorted, for completion.
+    ///
+    /// Deprecated commands and their aliases are left out, unless nothing else matches.
+    pub fn completions(&self, prefix: &str) -> Vec<&str> {
+        let (deprecated, current): (Vec<&str>, Vec<&str>) = self
+            .names()
+            .into_iter()
+            .filter(|name| name.starts_with(prefix))
+            .partition(|name| self.deprecation(name).is_some());
+        match current.is_empty() {
+            true => deprecated,
+            false => current,
+        }
+    }
+
+    /// Mark a command as deprecated, with a note on what to use instead, see [`CommandInfo::deprecated`].
+    ///
+    /// The command still runs, after a warning with the note.
+    /// Returns false if the command isn't registered or has no help to record it in.
+    pub fn deprecate(&mut self, command: &str, note: impl Into<String>) -> bool {
+        match self.commands.get_mut(command) {
+            Some(Some(info)) => {
+                info.deprecated = Some(note.into());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The note of a deprecated command or alias of one, or `None` if it isn't deprecated.
+    pub fn deprecation(&self, name: &str) -> Option<&str> {
+        self.get(name)??.deprecated.as_deref()
+    }
+
+    /// Validate the arguments of `command` with `validator`, replacing any validator it had.
+    ///
+    /// Arguments are validated while they are typed, marking the input when they are invalid,
+    /// and again when the command is run, where invalid arguments print an error instead of running it.
+    /// Validators should be cheap, as they run every frame while the command is typed.
+    ///
+    /// See [`AddTerminalCommand::add_terminal_command_validator`](crate::AddTerminalCommand::add_terminal_command_validator) for an example.
+    pub fn set_validator(&mut self, command: impl Into<String>, validator: CommandValidator) {
+        self.validators.insert(command.into(), validator);
+    }
+
+    /// Remove the validator of a command, returning true if it had one.
+    pub fn remove_validator(&mut self, command: &str) -> bool {
+        self.validators.remove(command).is_some()
+    }
+
+    /// Names validators were set for, sorted, including names which aren't registered.
+    pub(crate) fn validated(&self) -> impl Iterator<Item = &str> {
+        self.validators.keys().map(String::as_str)
+    }
+
+    /// Run the validator of a command or alias on its arguments.
+    ///
+    /// Commands without a validator, and names which aren't registered, are always valid.
+    pub fn validate(&self, name: &str, args: &[ValueRawOwned]) -> Result<(), String> {
+        let validator = self
+            .resolve(name)
+            .and_then(|name| self.validators.get(name));
+        match validator {
+            Some(validator) => validator(args),
+            None => Ok(()),
+        }
+    }
+
+    /// Number of registered commands, not counting aliases.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns true if no commands are registered.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::validate_level;
+
+    fn registry() -> CommandRegistry {
+        let mut registry = CommandRegistry::default();
+        registry.insert("spawn_wave", None);
+        registry.insert("net.connect", None);
+        registry.insert("clear", None);
+        registry.add_alias("cls", "clear");
+        registry.add_alias("sw", "spawn_wave");
+        registry.add_alias("gone", "not_registered");
+        registry
+    }
+
+    #[test]
+    fn it_resolves_aliases() {
+        let registry = registry();
+        assert_eq!(registry.resolve("cls"), Some("clear"));
+        assert_eq!(registry.resolve("net.connect"), Some("net.connect"));
+        assert_eq!(registry.resolve("gone"), None);
+        assert_eq!(registry.get("sw"), Some(None));
+        assert!(!registry.contains("nope"));
+        assert_eq!(registry.aliases_of("clear").collect::<Vec<_>>(), ["cls"]);
+    }
+
+    #[test]
+    fn it_validates_through_aliases() {
+        let mut registry = registry();
+        registry.set_validator("spawn_wave", validate_level);
+        let forest = [ValueRawOwned::String("forest".to_string())];
+        let moon = [ValueRawOwned::String("moon".to_string())];
+
+        assert_eq!(registry.validate("sw", &forest), Ok(()));
+        assert_eq!(
+            registry.validate("sw", &moon),
+            Err("unknown level 'moon'".to_string())
+        );
+        assert_eq!(registry.validate("clear", &moon), Ok(()));
+        assert!(registry.remove_validator("spawn_wave"));
+        assert_eq!(registry.validate("spawn_wave", &moon), Ok(()));
+    }
+
+    #[test]
+    fn it_demotes_deprecated_commands_in_completion() {
+        let mut registry = registry();
+        registry.insert("spawn", Some(CommandInfo::new("spawn", None)));
+        assert!(registry.deprecate("spawn", "use 'spawn_wave' instead"));
+        assert!(!registry.deprecate("clear", "no help to record it in"));
+        registry.add_alias("sp", "spawn");
+
+        assert_eq!(registry.deprecation("sp"), Some("use 'spawn_wave' instead"));
+        assert_eq!(registry.deprecation("spawn_wave"), None);
+        assert_eq!(registry.completions("sp"), ["spawn_wave"]);
+        assert_eq!(registry.completions("spawn"), ["spawn_wave"]);
+        // Deprecated commands are still completed when nothing else matches
+        registry.remove("spawn_wave");
+        assert_eq!(registry.completions("sp"), ["sp", "spawn"]);
+        assert_eq!(registry.completions("x"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn it_iterates_in_sorted_order() {
+        let registry = registry();
+        assert_eq!(
+            registry.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            ["clear", "net.connect", "spawn_wave"]
+        );
+        assert_eq!(
+            registry.names(),
+            ["clear", "cls", "net.connect", "spawn_wave", "sw"]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// Heights of the scrollback lines as they were last laid out, so only the lines in view are laid out each frame.
+///
+/// Lines which were never laid out are assumed to be one row high, which holds for most lines,
+/// and are measured once they scroll into view.
+#[derive(Debug, Default)]
+pub(crate) struct RowHeights {
+    /// Height of each line and the spacing below it, or `None` if it wasn't laid out yet
+    heights: VecDeque<Option<f32>>,
+    /// Width and row height the lines were laid out at, as wrapping depends on both
+    layout: (f32, f32),
+}
+
+/// The lines in view of a scrollback, see [`RowHeights::visible`].
+#[derive(Debug, PartialEq)]
+pub(crate) struct VisibleRows {
+    /// Lines to lay out
+    pub(crate) rows: Range<usize>,
+    /// Height of the lines above them
+    pub(crate) above: f32,
+    /// Height of the lines below them
+    pub(crate) below: f32,
+}
+
+impl RowHeights {
+    /// Match `lines` lines laid out at `width` with rows `row` high, forgetting every height if either changed.
+    pub(crate) fn sync(&mut self, lines: usize, width: f32, row: f32) {
+        if self.layout != (width, row) {
+            self.layout = (width, row);
+            self.heights.clear();
+        }
+        self.heights.resize(lines, None);
+    }
+
+    /// Forget the heights of the `count` oldest lines, as they were dropped from the scrollback.
+    pub(crate) fn trim(&mut self, count: usize) {
+        self.heights.drain(..count.min(self.heights.len()));
+    }
+
+    /// Forget every height, as the scrollback was cleared.
+    pub(crate) fn clear(&mut self) {
+        self.heights.clear();
+    }
+
+    /// Record the height of line `index` as it was just laid out.
+    pub(crate) fn measure(&mut self, index: usize, height: f32) {
+        if let Some(slot) = self.heights.get_mut(index) {
+            *slot = Some(height);
+        }
+    }
+
+    /// Height of line `index`, or a row and `spacing` if it wasn't laid out yet.
+    fn height(&self, index: usize, spacing: f32) -> f32 {
+        self.heights[index].unwrap_or(self.layout.1 + spacing)
+    }
+
+    /// Distance from the top of the scrollback to line `index`.
+    pub(crate) fn offset(&self, index: usize, spacing: f32) -> f32 {
+        (0..index.min(self.heights.len()))
+            .map(|index| self.height(index, spacing))
+            .sum()
+    }
+
+    /// Lines at least partly between `top` and `bottom`, measured from the top of the scrollback.
+    pub(crate) fn visible(&self, top: f32, bottom: f32, spacing: f32) -> VisibleRows {
+        let mut y = 0.;
+        let mut start = None;
+        let mut above = 0.;
+        let mut end = self.heights.len();
+        for index in 0..self.heights.len() {
+            let height = self.height(index, spacing);
+            if start.is_none() && y + height > top {
+                start = Some(index);
+                above = y;
+            }
+            if y >= bottom {
+                end = index;
+                break;
+            }
+            y += height;
+        }
+        let start = start.unwrap_or(end);
+        let below = (end..self.heights.len())
+            .map(|index| self.height(index, spacing))
+            .sum();
+        VisibleRows {
+            rows: start..end,
+            above: match start == end {
+                true => y,
+                false => above,
+            },
+            below,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_the_lines_in_view() {
+        let mut heights = RowHeights::default();
+        heights.sync(100, 400., 10.);
+        assert_eq!(
+            heights.visible(95., 125., 0.),
+            VisibleRows {
+                rows: 9..13,
+                above: 90.,
+                below: 870.,
+            }
+        );
+
+        // A wrapped line pushes the lines after it down
+        heights.measure(2, 30.);
+        assert_eq!(heights.visible(95., 125., 0.).rows, 7..11);
+        assert_eq!(heights.offset(7, 0.), 90.);
+        assert_eq!(heights.offset(100, 0.), 1020.);
+
+        // Dropped lines take their heights with them
+        heights.trim(5);
+        assert_eq!(heights.offset(95, 0.), 950.);
+
+        // Heights are measured again at another width
+        heights.measure(0, 30.);
+        heights.sync(95, 300., 10.);
+        assert_eq!(heights.offset(95, 0.), 950.);
+
+        // Scrolled past the end
+        assert_eq!(
+            heights.visible(2000., 2030., 0.),
+            VisibleRows {
+                rows: 95..95,
+                above: 950.,
+                below: 0.,
+            }
+        );
+    }
+}
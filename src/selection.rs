@@ -0,0 +1,194 @@
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, io};
+
+use crate::{TerminalLine, TerminalPaths, TerminalState};
+
+/// Metadata of lines printed through [`PrintTerminalLine`](crate::PrintTerminalLine),
+/// given the seconds since startup they were printed at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct PrintedAt(pub(crate) f64);
+
+/// The time `line` was printed at as `[hh:mm:ss.mmm] `, or as many spaces if it wasn't recorded,
+/// see [`TerminalConfiguration::show_timestamps`](crate::TerminalConfiguration::show_timestamps).
+pub(crate) fn timestamp(line: &TerminalLine) -> String {
+    match line.get_meta::<PrintedAt>() {
+        Some(PrintedAt(seconds)) => {
+            let millis = (seconds * 1000.).round() as u64;
+            format!(
+                "[{:02}:{:02}:{:02}.{:03}] ",
+                millis / 3_600_000,
+                millis / 60_000 % 60,
+                millis / 1000 % 60,
+                millis % 1000
+            )
+        }
+        None => " ".repeat(15),
+    }
+}
+
+impl TerminalState {
+    /// The scrollback lines selected by clicking a line and Shift+clicking another, first to last.
+    ///
+    /// The selection stays on the same lines as new lines arrive, and as the oldest lines are trimmed,
+    /// see [`TerminalConfiguration::scrollback_size`](crate::TerminalConfiguration::scrollback_size).
+    /// Trimming selected lines cuts the selection short, or clears it once every selected line is gone.
+    pub fn selection(&self) -> Option<RangeInclusive<usize>> {
+        let anchor = self.selected?;
+        let end = self.selection_end.unwrap_or(anchor);
+        self.scrollback.back()?;
+        Some(self.clamp_line(anchor.min(end))..=self.clamp_line(anchor.max(end)))
+    }
+
+    /// The selected scrollback lines, see [`TerminalState::selection`].
+    pub fn selected_lines(&self) -> Vec<&TerminalLine> {
+        match self.selection() {
+            Some(selection) => self.scrollback.range(selection).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Select a line, or with `extend` stretch the selection from the line clicked first.
+    ///
+    /// Clicking the only selected line again deselects it.
+    pub(crate) fn select_line(&mut self, index: usize, extend: bool) {
+        let index = self.line_id(index);
+        match self.selected {
+            Some(_) if extend => self.selection_end = Some(index),
+            Some(selected) if selected == index && self.selection_end.is_none() => {
+                self.selected = None;
+            }
+            _ => {
+                self.selected = Some(index);
+                self.selection_end = None;
+            }
+        }
+    }
+
+    pub(crate) fn clear_selection(&mut self) {
+        self.selected = None;
+        self.selection_end = None;
+    }
+}
+
+/// The text of `lines`, one per line, each after its [`timestamp`] if `timestamps` is set.
+pub(crate) fn selection_text(lines: &[&TerminalLine], timestamps: bool) -> String {
+    let lines: Vec<String> = lines
+        .iter()
+        .map(|line| match timestamps {
+            true => timestamp(line) + line.as_str(),
+            false => line.as_str().to_string(),
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// `lines` for pasting in a bug report: below `header`, each line prefixed with when it was printed.
+///
+/// Lines which weren't printed through [`PrintTerminalLine`](crate::PrintTerminalLine),
+/// such as prompts, have a blank time.
+pub(crate) fn bug_report_snippet(header: &str, lines: &[&TerminalLine]) -> String {
+    let mut snippet = format!("{header}\n```\n");
+    for line in lines {
+        match line.get_meta::<PrintedAt>() {
+            Some(PrintedAt(seconds)) => write!(snippet, "[{seconds:>9.3}s] "),
+            None => write!(snippet, "{:13}", ""),
+        }
+        .unwrap();
+        snippet.push_str(line.as_str());
+        snippet.push('\n');
+    }
+    snippet.push_str("```\n");
+    snippet
+}
+
+/// Writes `lines` to a new file in the current directory of `paths`, returning its path.
+///
+/// Lines are written as they are copied, see [`selection_text`].
+pub(crate) fn dump_lines(
+    lines: &[&TerminalLine],
+    paths: &TerminalPaths,
+    timestamps: bool,
+) -> io::Result<PathBuf> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let path = paths.resolve(format!("terminal_{}.txt", now.as_millis()));
+    fs::write(&path, selection_text(lines, timestamps) + "\n")?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> TerminalState {
+        let mut state = TerminalState::default();
+        for line in [
+            "$ spawn_wave",
+            "[error] no spawn point",
+            "[failed]",
+            "$ help",
+        ] {
+            state.scrollback.push_back(line.into());
+        }
+        state
+    }
+
+    #[test]
+    fn it_selects_ranges() {
+        let mut state = state();
+        state.select_line(2, false);
+        assert_eq!(state.selection(), Some(2..=2));
+        state.select_line(0, true);
+        assert_eq!(state.selection(), Some(0..=2));
+        assert_eq!(
+            selection_text(&state.selected_lines(), false),
+            "$ spawn_wave\n[error] no spawn point\n[failed]"
+        );
+
+        // New lines don't move the selection
+        state.scrollback.push_back("Usage:".into());
+        assert_eq!(state.selection(), Some(0..=2));
+
+        // Clicking a line starts a new selection, and clicking it again deselects it
+        state.select_line(1, false);
+        assert_eq!(state.selection(), Some(1..=1));
+        state.select_line(1, false);
+        assert_eq!(state.selection(), None);
+        assert!(state.selected_lines().is_empty());
+    }
+
+    #[test]
+    fn it_prefixes_timestamps() {
+        let spawned = TerminalLine::from("enemy spawned").with_meta(PrintedAt(43424.21));
+        let prompt = TerminalLine::from("Quit? [y/n]");
+        assert_eq!(timestamp(&spawned), "[12:03:44.210] ");
+        assert_eq!(
+            selection_text(&[&spawned, &prompt], true),
+            "[12:03:44.210] enemy spawned\n               Quit? [y/n]"
+        );
+        assert_eq!(
+            selection_text(&[&spawned, &prompt], false),
+            "enemy spawned\nQuit? [y/n]"
+        );
+    }
+
+    #[test]
+    fn it_formats_bug_report_snippets() {
+        let echo = TerminalLine::from("$ spawn_wave");
+        let error = TerminalLine::from("[error] no spawn point").with_meta(PrintedAt(12.5));
+        assert_eq!(
+            bug_report_snippet("my_game 0.3.1", &[&echo, &error]),
+            "\
+my_game 0.3.1
+```
+             $ spawn_wave
+[   12.500s] [error] no spawn point
+```
+"
+        );
+    }
+}
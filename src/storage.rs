@@ -0,0 +1,329 @@
+use std::collections::{HashMap, VecDeque};
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::strings::fill;
+use crate::{TerminalConfiguration, TerminalNotice, TerminalState, TerminalStrings};
+
+/// Where the terminal keeps data between runs, by key.
+///
+/// Keys are short names such as `"history"`, made of ASCII letters, digits, `_` and `-`.
+/// Install a backend with [`TerminalStore::new`], such as one syncing to a settings service.
+pub trait TerminalStorage: Send + Sync + 'static {
+    /// The bytes saved under `key`, or `None` if nothing was saved.
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// Save `bytes` under `key`, replacing what was saved before.
+    fn save(&mut self, key: &str, bytes: &[u8]) -> io::Result<()>;
+
+    /// Remove what was saved under `key`, if anything.
+    fn remove(&mut self, key: &str) -> io::Result<()>;
+}
+
+/// Keeps each key in a file of the same name in a directory, created when first saved to.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    /// Keep files in `dir`, such as a per-user data directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FileStorage { dir: dir.into() }
+    }
+}
+
+/// Keeps files in `.leafwing_terminal` in the working directory of the process.
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for FileStorage {
+    fn default() -> Self {
+        FileStorage::new(".leafwing_terminal")
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TerminalStorage for FileStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.dir.join(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn save(&mut self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.dir.join(key), bytes)
+    }
+
+    fn remove(&mut self, key: &str) -> io::Result<()> {
+        match std::fs::remove_file(self.dir.join(key)) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Keeps data in memory until the app exits, for tests and platforms without storage.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStorage {
+    entries: HashMap<String, Vec<u8>>,
+}
+
+impl TerminalStorage for MemoryStorage {
+    fn load(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.entries.get(key).cloned())
+    }
+
+    fn save(&mut self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.entries.insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &str) -> io::Result<()> {
+        self.entries.remove(key);
+        Ok(())
+    }
+}
+
+/// The [`TerminalStorage`] the terminal keeps data in between runs.
+///
+/// Failures don't interrupt anything: they are shown as a [`TerminalNotice`] on the next update.
+/// The default keeps files with [`FileStorage::default`], or keeps data in memory on wasm.
+///
+/// # Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use leafwing_terminal::{FileStorage, TerminalCorePlugin, TerminalStore};
+/// #
+/// App::new()
+///     .add_plugin(TerminalCorePlugin)
+///     .insert_resource(TerminalStore::new(FileStorage::new("saves/terminal")));
+/// ```
+pub struct TerminalStore {
+    backend: Box<dyn TerminalStorage>,
+    /// Failures not reported yet, with the key they were for
+    errors: Vec<(String, io::Error)>,
+}
+
+impl TerminalStore {
+    /// Keep data in `backend`.
+    pub fn new(backend: impl TerminalStorage) -> Self {
+        TerminalStore {
+            backend: Box::new(backend),
+            errors: Vec::new(),
+        }
+    }
+
+    /// The bytes saved under `key`, or `None` if nothing was saved or they couldn't be loaded.
+    pub fn load(&mut self, key: &str) -> Option<Vec<u8>> {
+        let loaded = self.backend.load(key);
+        self.report(key, loaded).flatten()
+    }
+
+    /// Save `bytes` under `key`, returning false if they couldn't be saved.
+    pub fn save(&mut self, key: &str, bytes: &[u8]) -> bool {
+        let saved = self.backend.save(key, bytes);
+        self.report(key, saved).is_some()
+    }
+
+    /// Remove what was saved under `key`, returning false if it couldn't be removed.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let removed = self.backend.remove(key);
+        self.report(key, removed).is_some()
+    }
+
+    fn report<T>(&mut self, key: &str, result: io::Result<T>) -> Option<T> {
+        result
+            .map_err(|error| self.errors.push((key.to_string(), error)))
+            .ok()
+    }
+}
+
+impl Default for TerminalStore {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default() -> Self {
+        TerminalStore::new(FileStorage::default())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn default() -> Self {
+        TerminalStore::new(MemoryStorage::default())
+    }
+}
+
+/// Key the history is kept under, see [`TerminalConfiguration::persist_history`].
+const HISTORY_KEY: &str = "history";
+
+/// The saved history, which keeps commands dropped from [`TerminalState`]'s until `history_file_size` is reached.
+#[derive(Default)]
+pub(crate) struct SavedHistory {
+    /// Saved commands, oldest first
+    commands: VecDeque<String>,
+    /// [`TerminalState::history_pushed`] when last saved
+    pushed: usize,
+}
+
+/// Loads the history saved by the last run, see [`TerminalConfiguration::persist_history`].
+pub(crate) fn load_history(
+    config: Res<TerminalConfiguration>,
+    mut store: ResMut<TerminalStore>,
+    mut state: ResMut<TerminalState>,
+    mut saved: ResMut<SavedHistory>,
+) {
+    if !config.persist_history {
+        return;
+    }
+    let Some(bytes) = store.load(HISTORY_KEY) else {
+        return;
+    };
+    for line in String::from_utf8_lossy(&bytes).lines() {
+        if !line.trim().is_empty() {
+            saved.commands.push_back(line.to_string());
+            state.push_history(line.to_string(), config.history_size);
+        }
+    }
+    // The loaded history doesn't need saving again
+    saved.pushed = state.history_pushed;
+}
+
+/// Saves the history whenever it changes, see [`TerminalConfiguration::persist_history`].
+pub(crate) fn save_history(
+    config: Res<TerminalConfiguration>,
+    mut store: ResMut<TerminalStore>,
+    state: Res<TerminalState>,
+    mut saved: ResMut<SavedHistory>,
+) {
+    if !config.persist_history || !state.is_changed() || state.history_pushed == saved.pushed {
+        return;
+    }
+    let new = if state.history_pushed < saved.pushed {
+        // Cleared since last saved
+        saved.commands.clear();
+        state.history_pushed
+    } else {
+        state.history_pushed - saved.pushed
+    };
+    saved.pushed = state.history_pushed;
+    let new = new.min(state.history.len());
+    let commands = &mut saved.commands;
+    commands.extend(state.history.range(state.history.len() - new..).cloned());
+    let dropped = commands.len().saturating_sub(config.history_file_size);
+    commands.drain(..dropped);
+
+    let mut bytes = Vec::new();
+    for command in commands.iter() {
+        // Commands are typed on one line, but may have been dispatched with a newline in a string
+        bytes.extend(command.replace('\n', " ").bytes());
+        bytes.push(b'\n');
+    }
+    store.save(HISTORY_KEY, &bytes);
+}
+
+pub(crate) fn report_storage_errors(
+    mut store: ResMut<TerminalStore>,
+    strings: Res<TerminalStrings>,
+    mut notices: EventWriter<TerminalNotice>,
+) {
+    for (key, error) in store.errors.drain(..) {
+        notices.send(TerminalNotice::new(fill(
+            &strings.storage_failed,
+            &[&key, &error],
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+
+    use super::*;
+    use crate::harness::test_app;
+
+    #[test]
+    fn it_keeps_data_by_key() {
+        let mut store = TerminalStore::new(MemoryStorage::default());
+        assert_eq!(store.load("history"), None);
+        assert!(store.save("history", b"help\nclear"));
+        assert!(store.save("config", b"toasts true"));
+        assert_eq!(store.load("history").as_deref(), Some(&b"help\nclear"[..]));
+        assert!(store.remove("history"));
+        assert!(store.remove("history"));
+        assert_eq!(store.load("history"), None);
+        assert_eq!(store.load("config").as_deref(), Some(&b"toasts true"[..]));
+    }
+
+    #[test]
+    fn it_keeps_the_history_between_runs() {
+        let mut store = TerminalStore::new(MemoryStorage::default());
+        store.save("history", b"spawn goblin\n\nteleport 10 0 5\r\nhelp\n");
+        let mut app = test_app();
+        app.insert_resource(store)
+            .insert_resource(TerminalConfiguration {
+                persist_history: true,
+                history_size: 2,
+                history_file_size: 3,
+                ..Default::default()
+            });
+        app.update();
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(
+            state.history().collect::<Vec<_>>(),
+            ["help", "teleport 10 0 5"]
+        );
+
+        app.world
+            .resource_mut::<TerminalState>()
+            .push_history("clear".to_string(), 2);
+        app.update();
+        assert_eq!(
+            app.world
+                .resource_mut::<TerminalStore>()
+                .load("history")
+                .as_deref(),
+            Some(&b"teleport 10 0 5\nhelp\nclear\n"[..])
+        );
+
+        app.world.resource_mut::<TerminalState>().clear_history();
+        app.update();
+        assert_eq!(
+            app.world
+                .resource_mut::<TerminalStore>()
+                .load("history")
+                .as_deref(),
+            Some(&b""[..])
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn it_reports_failures_as_notices() {
+        // A file where the storage directory should be
+        let blocked =
+            std::env::temp_dir().join(format!("leafwing_terminal_store_{}", std::process::id()));
+        std::fs::write(&blocked, "").unwrap();
+
+        let mut app = test_app();
+        app.insert_resource(TerminalStore::new(FileStorage::new(&blocked)));
+        assert!(!app
+            .world
+            .resource_mut::<TerminalStore>()
+            .save("history", b"help"));
+        app.update();
+
+        let events = app.world.resource::<Events<TerminalNotice>>();
+        let mut reader = events.get_reader();
+        let notices: Vec<_> = reader.iter(events).collect();
+        assert_eq!(notices.len(), 1);
+        assert!(notices[0]
+            .text
+            .starts_with("[error] couldn't store 'history': "));
+
+        std::fs::remove_file(blocked).unwrap();
+    }
+}
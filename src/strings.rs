@@ -0,0 +1,416 @@
+use crate::FromValueError;
+
+/// Text printed by built-in commands and the terminal itself.
+///
+/// The defaults are English. Overwrite the resource at startup, or whenever the locale changes, to translate them.
+/// Each `{}` is replaced by a value in order, as described on each field.
+/// Lines starting with `[error]` are shown as toasts, see [`TerminalConfiguration::toasts`](crate::TerminalConfiguration::toasts).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TerminalStrings {
+    /// Printed when a command succeeds
+    pub ok: String,
+    /// Printed when a command fails
+    pub failed: String,
+    /// Heading of command help
+    pub usage: String,
+    /// Heading of the examples in command help
+    pub examples: String,
+    /// Heading of the `help` command list
+    pub available_commands: String,
+    /// `help` for a command without help, given the command name
+    pub help_not_available: String,
+    /// A command which isn't registered, given the command name
+    pub unknown_command: String,
+    /// A command entered during its cooldown, given the seconds remaining
+    pub on_cooldown: String,
+    /// A line which couldn't be parsed, given the [`ParseError`](crate::ParseError)
+    pub invalid_arguments: String,
+    /// Too few arguments for a command
+    pub not_enough_args: String,
+    /// Too many arguments for a command, given the maximum
+    pub too_many_args: String,
+    /// An argument of the wrong type, given the expected and received types and the argument number
+    pub unexpected_arg_type: String,
+    /// A number too large for its argument, given the argument number and maximum
+    pub value_too_large: String,
+    /// An error from a custom argument type or `reply_error`, given its message
+    pub custom_error: String,
+    /// A warning from `reply_warn`, given its message
+    pub warning: String,
+    /// A placeholder such as `%last%` with nothing to expand to, given the placeholder
+    pub nothing_to_expand: String,
+    /// Multi-line paste with only the first line inserted, given the number of lines
+    pub paste_first_line_only: String,
+    /// Hover text of the unterminated quote warning
+    pub unterminated_quote: String,
+    /// `tail` with a pattern already being tailed, given the pattern
+    pub already_tailing: String,
+    /// `tail` with the maximum number of tails open, given the maximum
+    pub too_many_tails: String,
+    /// Notice when a tail starts, given the pattern
+    pub tailing: String,
+    /// Notice when `tail stop` closes all tails
+    pub stopped_tails: String,
+    /// A command scheduled by `after` or `at`, given its id
+    pub scheduled: String,
+    /// `at` with a time it couldn't parse
+    pub invalid_time: String,
+    /// `scheduled` with no commands waiting
+    pub no_scheduled: String,
+    /// `scheduled cancel` with an id which isn't waiting, given the id
+    pub scheduled_not_found: String,
+    /// `find` with a name it resolved, given the name and entity
+    pub found_entity: String,
+    /// `bufstats` scrollback line, given the number of lines and approximate KiB used
+    pub scrollback_stats: String,
+    /// `bufstats` history line, given the number of commands
+    pub history_stats: String,
+    /// `bufstats` queue line, given the number of queued commands
+    pub queue_stats: String,
+    /// `selftest` result of a phase, given the milliseconds it took and how many items it handled
+    pub selftest_phase: String,
+    /// `selftest` outcome, given the milliseconds all phases took
+    pub selftest_total: String,
+    /// Appended to the outcome of a slow command, given the milliseconds it took
+    pub took: String,
+    /// Hint printed in debug builds for a command which didn't print its outcome in time, given the command
+    pub missing_outcome: String,
+    /// `timing` with slow commands reported, given the threshold in milliseconds
+    pub timing_on: String,
+    /// `timing` with slow commands not reported
+    pub timing_off: String,
+    /// Notice of a long backlog of queued commands, given the number released and queued in total
+    pub queue_progress: String,
+    /// A destructive command denied while the window is unfocused, given the command
+    pub denied_while_unfocused: String,
+    /// Notice when the window is focused again, given the number of commands held while it wasn't
+    pub running_held_commands: String,
+    /// Last line of a list of Tab completions which was cut short, given the number of completions left out
+    pub more_completions: String,
+    /// Hint in the empty history browser filter
+    pub history_filter_hint: String,
+    /// History browser with no entries matching the filter
+    pub no_history_matches: String,
+    /// Label before the query of the reverse history search
+    pub reverse_search_label: String,
+    /// Reverse history search whose query matches no history entry
+    pub no_reverse_search_match: String,
+    /// Hint in the empty command palette filter
+    pub palette_filter_hint: String,
+    /// Command palette with no commands matching the filter
+    pub no_palette_matches: String,
+    /// Hover text of the button copying a scrollback line
+    pub copy_line: String,
+    /// Context menu entry copying the selected scrollback lines
+    pub copy_selection: String,
+    /// Context menu entry writing the selected scrollback lines to a file
+    pub dump_selection: String,
+    /// Context menu entry copying the selected scrollback lines formatted for a bug report
+    pub bug_report_selection: String,
+    /// First line of a bug report snippet, given the app version
+    pub bug_report_header: String,
+    /// Selected lines written to a file, given the number of lines and the path
+    pub selection_dumped: String,
+    /// Selected lines which couldn't be written to a file, given the error
+    pub dump_failed: String,
+    /// `dump` wrote the scrollback to a file, given the number of lines and the path
+    pub scrollback_dumped: String,
+    /// `dump` couldn't write the scrollback to a file, given the error
+    pub scrollback_dump_failed: String,
+    /// `repeat` while another command is repeating
+    pub already_repeating: String,
+    /// A repeat cancelled by an error or Ctrl+C, given the runs queued and requested
+    pub repeat_stopped: String,
+    /// A toolbar widget which panicked and was removed, given its index
+    pub toolbar_panicked: String,
+    /// Divider line added by `mark`, given the label
+    pub mark_divider: String,
+    /// `jump` with a label which isn't marked, given the label
+    pub anchor_not_found: String,
+    /// `despawn` with an entity which doesn't exist, given the entity
+    pub entity_not_found: String,
+    /// `spawn` with a path which isn't a glTF file, given the path
+    pub not_a_scene: String,
+    /// `spawn` starting to load a scene, given its path
+    pub loading_scene: String,
+    /// `spawn` with a scene which has loaded, given its path and root entity
+    pub scene_spawned: String,
+    /// `spawn` with a scene which couldn't be loaded, given its path
+    pub scene_load_failed: String,
+    /// `sticky` with lines being appended to a command, given the command
+    pub sticky_on: String,
+    /// `sticky` with lines run as typed
+    pub sticky_off: String,
+    /// Heading of the commands found by `?prefix`, given the prefix
+    pub commands_starting_with: String,
+    /// `?prefix` with no command starting with it, given the prefix
+    pub no_commands_starting_with: String,
+    /// A position or size of [`TerminalConfiguration`](crate::TerminalConfiguration) which can't be drawn, given the field and its value
+    pub invalid_geometry: String,
+    /// `config` with a field which isn't configurable, given the field
+    pub unknown_config_field: String,
+    /// `config set` with a value of the wrong type, given the field and the expected value
+    pub invalid_config_value: String,
+    /// Indicator at the bottom of the scrollback while it is scrolled up, given the number of unseen errors
+    pub unseen_errors: String,
+    /// A submitted line of only control characters, such as a stray escape code
+    pub only_control_characters: String,
+    /// `cd` with a path which isn't a directory, given the path
+    pub not_a_directory: String,
+    /// `require` or `onfail` typed outside a script, given the command
+    pub script_only: String,
+    /// A script stopped by a line run with `require` failing
+    pub script_stopped: String,
+    /// Terminal data which couldn't be loaded or saved, given the key and the error
+    pub storage_failed: String,
+    /// An alias with the name of a command, given the alias and the command it runs
+    pub lint_shadowed_alias: String,
+    /// An alias of a command which isn't registered, given the alias and the command
+    pub lint_dangling_alias: String,
+    /// A validator of a command which isn't registered, given the command
+    pub lint_orphan_validator: String,
+    /// Help registered under another name, given the command and the name in the help
+    pub lint_mismatched_name: String,
+    /// A required argument after an optional one, given the command and the argument
+    pub lint_required_after_optional: String,
+    /// An argument listed twice, given the command and the argument
+    pub lint_duplicate_arg: String,
+    /// Argument limits the listed arguments don't fit, given the command and the number of required arguments
+    pub lint_arg_limits: String,
+    /// The result of `lint`, given the number of mistakes found
+    pub lint_summary: String,
+    /// Printed before running a deprecated command, given the command and the note on what to use instead
+    pub deprecated: String,
+    /// After the description of deprecated commands in the `help` list
+    pub deprecated_tag: String,
+    /// Heading of the `deprecations` list
+    pub deprecated_commands: String,
+    /// `deprecations` without any deprecated command
+    pub no_deprecated_commands: String,
+    /// Printed in place of lines dropped from a full print queue, given how many
+    pub dropped_lines: String,
+    /// Notice when the oldest lines were trimmed with anchors on them, given their labels
+    pub trimmed_anchors: String,
+    /// Notice when the oldest lines were trimmed with selected lines among them
+    pub trimmed_selection: String,
+}
+
+impl Default for TerminalStrings {
+    fn default() -> Self {
+        Self {
+            ok: "[ok]".to_string(),
+            failed: "[failed]".to_string(),
+            usage: "Usage:".to_string(),
+            examples: "Examples:".to_string(),
+            available_commands: "Available commands:".to_string(),
+            help_not_available: "Help not available for command '{}'".to_string(),
+            unknown_command: "Command '{}' does not exist".to_string(),
+            on_cooldown: "command on cooldown, {}s remaining".to_string(),
+            invalid_arguments: "[error] {}".to_string(),
+            not_enough_args: "[error] not enough arguments provided".to_string(),
+            too_many_args: "[error] too many arguments provided (max {})".to_string(),
+            unexpected_arg_type: "[error] expected '{}' but got '{}' for arg #{}".to_string(),
+            value_too_large: "[error] number is too large for arg #{} (max {})".to_string(),
+            custom_error: "[error] {}".to_string(),
+            warning: "[warning] {}".to_string(),
+            nothing_to_expand: "[error] {} has nothing to expand to".to_string(),
+            paste_first_line_only: "[warning] pasted {} lines, only the first was inserted"
+                .to_string(),
+            unterminated_quote: "Unterminated quote, Enter continues on a new line".to_string(),
+            already_tailing: "Already tailing '{}'".to_string(),
+            too_many_tails: "At most {} tails can be open".to_string(),
+            tailing: "Tailing '{}'".to_string(),
+            stopped_tails: "Stopped all tails".to_string(),
+            scheduled: "Scheduled #{}".to_string(),
+            invalid_time: "[error] expected a time such as 90, 1:30 or 1:02:30.5".to_string(),
+            no_scheduled: "No scheduled commands".to_string(),
+            scheduled_not_found: "No scheduled command #{}".to_string(),
+            found_entity: "'{}' is entity {}".to_string(),
+            scrollback_stats: "Scrollback: {} lines (~{} KiB)".to_string(),
+            history_stats: "History: {} commands".to_string(),
+            queue_stats: "Queued: {} commands".to_string(),
+            selftest_phase: "{} ms for {}".to_string(),
+            selftest_total: "self-test took {} ms".to_string(),
+            took: "(took {} ms)".to_string(),
+            missing_outcome: "hint: '{}' printed no outcome, call ok() or failed() once it is done"
+                .to_string(),
+            timing_on: "Reporting commands slower than {} ms".to_string(),
+            timing_off: "Not reporting slow commands".to_string(),
+            queue_progress: "Running queued commands: {}/{}".to_string(),
+            denied_while_unfocused:
+                "[error] '{}' is destructive and isn't run while the window is unfocused"
+                    .to_string(),
+            running_held_commands: "Running {} commands queued while the window was unfocused"
+                .to_string(),
+            more_completions: "… and {} more".to_string(),
+            history_filter_hint: "Filter history…".to_string(),
+            no_history_matches: "No matching commands".to_string(),
+            reverse_search_label: "(reverse-i-search)".to_string(),
+            no_reverse_search_match: "no match".to_string(),
+            palette_filter_hint: "Search commands…".to_string(),
+            no_palette_matches: "No matching commands".to_string(),
+            copy_line: "Copy line".to_string(),
+            copy_selection: "Copy".to_string(),
+            dump_selection: "Dump to file…".to_string(),
+            bug_report_selection: "Create bug-report snippet".to_string(),
+            bug_report_header: "Version: {}".to_string(),
+            selection_dumped: "Wrote {} lines to '{}'".to_string(),
+            dump_failed: "[error] failed to dump the selected lines: {}".to_string(),
+            scrollback_dumped: "Wrote {} lines to '{}'".to_string(),
+            scrollback_dump_failed: "[error] failed to dump the scrollback: {}".to_string(),
+            already_repeating: "A command is already repeating, Ctrl+C cancels it".to_string(),
+            repeat_stopped: "Stopped repeating after {} of {} runs".to_string(),
+            toolbar_panicked: "[error] toolbar widget #{} panicked and was removed".to_string(),
+            mark_divider: "──────── {} ────────".to_string(),
+            anchor_not_found: "[error] no mark '{}'".to_string(),
+            entity_not_found: "[error] entity {} does not exist".to_string(),
+            not_a_scene: "[error] expected a .gltf or .glb scene, got '{}'".to_string(),
+            loading_scene: "Loading '{}'".to_string(),
+            scene_spawned: "Spawned '{}' as entity {}".to_string(),
+            scene_load_failed: "[error] failed to load scene '{}'".to_string(),
+            sticky_on: "Appending typed lines to '{}' until `sticky off` or Escape".to_string(),
+            sticky_off: "Not appending typed lines to a command".to_string(),
+            commands_starting_with: "Commands starting with '{}':".to_string(),
+            no_commands_starting_with: "No command starts with '{}'".to_string(),
+            invalid_geometry: "[warning] ignoring TerminalConfiguration::{} of {}".to_string(),
+            unknown_config_field: "[error] no config field '{}'".to_string(),
+            invalid_config_value: "[error] invalid value for '{}', expected {}".to_string(),
+            unseen_errors: "▼ {} new errors".to_string(),
+            only_control_characters: "[error] ignoring a line of only control characters"
+                .to_string(),
+            not_a_directory: "[error] no directory '{}'".to_string(),
+            script_only: "[error] `{}` only works in scripts".to_string(),
+            script_stopped: "Stopped the script after a required command failed".to_string(),
+            storage_failed: "[error] couldn't store '{}': {}".to_string(),
+            lint_shadowed_alias: "alias `{}` has the name of a command, so it never runs `{}`"
+                .to_string(),
+            lint_dangling_alias: "alias `{}` runs `{}`, which isn't registered".to_string(),
+            lint_orphan_validator: "`{}` has a validator but isn't registered".to_string(),
+            lint_mismatched_name: "`{}` is registered with the help of `{}`".to_string(),
+            lint_required_after_optional: "`{}` lists required argument `{}` after an optional one"
+                .to_string(),
+            lint_duplicate_arg: "`{}` lists argument `{}` more than once".to_string(),
+            lint_arg_limits: "argument limits of `{}` don't fit its {} required arguments"
+                .to_string(),
+            lint_summary: "mistakes found in command metadata: {}".to_string(),
+            deprecated: "[warning] '{}' is deprecated: {}".to_string(),
+            deprecated_tag: "(deprecated)".to_string(),
+            deprecated_commands: "Deprecated commands:".to_string(),
+            no_deprecated_commands: "No deprecated commands".to_string(),
+            dropped_lines: "[dropped {} low-priority lines]".to_string(),
+            trimmed_anchors: "anchors trimmed from the scrollback: {}".to_string(),
+            trimmed_selection: "the selection was cut short as the oldest lines were trimmed"
+                .to_string(),
+        }
+    }
+}
+
+impl TerminalStrings {
+    /// Describes an argument error.
+    pub fn from_value_error(&self, err: &FromValueError) -> String {
+        match err {
+            FromValueError::NotEnoughArgs => self.not_enough_args.clone(),
+            FromValueError::TooManyArgs { max } => fill(&self.too_many_args, &[max]),
+            FromValueError::UnexpectedArgType {
+                arg_num,
+                expected,
+                received,
+            } => fill(
+                &self.unexpected_arg_type,
+                &[&expected, &received, &(arg_num + 1)],
+            ),
+            FromValueError::ValueTooLarge { arg_num, max } => {
+                fill(&self.value_too_large, &[&(arg_num + 1), max])
+            }
+            FromValueError::Custom(msg) => fill(&self.custom_error, &[msg]),
+        }
+    }
+}
+
+/// Replaces each `{}` in `template` with the next of `args`.
+pub(crate) fn fill(template: &str, args: &[&dyn std::fmt::Display]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() {
+        filled.push_str(first);
+    }
+    for part in parts {
+        match args.next() {
+            Some(arg) => filled.push_str(&arg.to_string()),
+            None => filled.push_str("{}"),
+        }
+        filled.push_str(part);
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+    use bevy::prelude::*;
+
+    use super::*;
+    use crate::harness::test_app;
+    use crate::{TerminalCommandEntered, TerminalState, ValueRawOwned, ValueType};
+
+    #[test]
+    fn it_fills_templates() {
+        assert_eq!(fill("{} of {}", &[&1, &"two"]), "1 of two");
+        assert_eq!(fill("no args", &[]), "no args");
+        assert_eq!(fill("missing {}", &[]), "missing {}");
+    }
+
+    #[test]
+    fn it_matches_the_default_error_messages() {
+        let strings = TerminalStrings::default();
+        let errors = [
+            FromValueError::NotEnoughArgs,
+            FromValueError::TooManyArgs { max: 2 },
+            FromValueError::UnexpectedArgType {
+                arg_num: 0,
+                expected: ValueType::Int,
+                received: ValueType::String,
+            },
+            FromValueError::ValueTooLarge {
+                arg_num: 2,
+                max: 255,
+            },
+            FromValueError::Custom("bad".to_string()),
+        ];
+        for err in errors {
+            assert_eq!(strings.from_value_error(&err), err.to_string());
+        }
+    }
+
+    fn enter(app: &mut App, command: &str, arg: &str) {
+        app.world
+            .resource_mut::<Events<TerminalCommandEntered>>()
+            .send(TerminalCommandEntered {
+                command: command.to_string(),
+                args: vec![ValueRawOwned::String(arg.to_string())],
+            });
+        app.update();
+    }
+
+    #[test]
+    fn it_prints_replaced_strings() {
+        let mut app = test_app();
+        app.insert_resource(TerminalStrings {
+            ok: "[bien]".to_string(),
+            unknown_command: "La commande '{}' n'existe pas".to_string(),
+            ..Default::default()
+        });
+        app.update();
+
+        enter(&mut app, "help", "nope");
+        enter(&mut app, "tail", "[net]");
+        app.update();
+
+        let scrollback = app.world.resource::<TerminalState>().scrollback();
+        assert!(scrollback
+            .iter()
+            .any(|line| line == "La commande 'nope' n'existe pas"));
+        assert!(scrollback.iter().any(|line| line == "[bien]"));
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt::Write;
+
+use bevy::ecs::event::{Events, ManualEventReader};
+use bevy::prelude::*;
+
+use crate::line::Severity;
+use crate::{
+    PrintTerminalLine, TerminalCommandCompleted, TerminalCommandQueue, TerminalInvocation,
+    TerminalStrings,
+};
+
+/// Flag ending a submitted line to also get the output of the command as JSON,
+/// see [`TerminalStructuredOutput`].
+const JSON_FLAG: &str = "--json";
+
+/// Event sent with the output of an invocation submitted with `--json`, for tools reading the terminal.
+///
+/// A line such as `stats --json` runs `stats` as usual, printing the same lines in the scrollback,
+/// and also sends one of these for each of its key-value replies, outcomes and errors.
+/// Each `json` is an object with the `invocation` it belongs to, see [`TerminalInvocation::id`],
+/// and a `type` telling which of these it is:
+///
+/// - `{"invocation":3,"type":"kv","pairs":{"health":"80","mana":"35"}}`,
+///   for [`TerminalCommand::reply_kv`](crate::TerminalCommand::reply_kv)
+/// - `{"invocation":3,"type":"outcome","outcome":"ok"}`, or `"failed"`
+/// - `{"invocation":3,"type":"error","message":"[error] no spawn point"}`
+/// - `{"invocation":3,"type":"value","value":{"x":1.0}}`,
+///   for `TerminalCommand::reply_json` with the `serde` feature
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TerminalStructuredOutput {
+    /// The invocation which printed the output
+    pub invocation: TerminalInvocation,
+    /// The output as a JSON object
+    pub json: String,
+}
+
+/// Metadata of the first line printed by [`TerminalCommand::reply_kv`](crate::TerminalCommand::reply_kv),
+/// given the pairs it printed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct KeyValues(pub(crate) Vec<(String, String)>);
+
+/// Metadata of a line printed by `TerminalCommand::reply_json`, given the value serialized as JSON.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct JsonValue(pub(crate) String);
+
+/// `line` without a trailing `--json`, and whether it had one.
+pub(crate) fn strip_json_flag(line: &str) -> (&str, bool) {
+    let trimmed = line.trim_end();
+    match trimmed.strip_suffix(JSON_FLAG) {
+        Some(rest) if rest.is_empty() || rest.ends_with(char::is_whitespace) => (rest, true),
+        _ => (line, false),
+    }
+}
+
+/// `text` as a quoted JSON string.
+pub(crate) fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// The fields after `invocation` of the structured output of `line`, or `None` if it has none.
+fn structured_fields(line: &PrintTerminalLine, strings: &TerminalStrings) -> Option<String> {
+    if let Some(KeyValues(pairs)) = line.get_meta() {
+        let pairs: Vec<String> = pairs
+            .iter()
+            .map(|(key, value)| format!("{}:{}", json_string(key), json_string(value)))
+            .collect();
+        return Some(format!(r#""type":"kv","pairs":{{{}}}"#, pairs.join(",")));
+    }
+    #[cfg(feature = "serde")]
+    if let Some(JsonValue(value)) = line.get_meta() {
+        return Some(format!(r#""type":"value","value":{value}"#));
+    }
+    // Matched by text, like the outcome watchdog, so outcomes printed by hand count too
+    let text = line.line.as_str();
+    if text.starts_with(&strings.ok) {
+        Some(r#""type":"outcome","outcome":"ok""#.to_string())
+    } else if text.starts_with(&strings.failed) {
+        Some(r#""type":"outcome","outcome":"failed""#.to_string())
+    } else if line.get_meta() == Some(&Severity::Error) || text.starts_with("[error]") {
+        Some(format!(r#""type":"error","message":{}"#, json_string(text)))
+    } else {
+        None
+    }
+}
+
+/// Sends [`TerminalStructuredOutput`] for the lines printed by invocations submitted with `--json`,
+/// until they complete.
+pub(crate) fn send_structured_output(
+    strings: Res<TerminalStrings>,
+    mut queue: ResMut<TerminalCommandQueue>,
+    terminal_line: Res<Events<PrintTerminalLine>>,
+    mut printed: Local<ManualEventReader<PrintTerminalLine>>,
+    mut completed: EventReader<TerminalCommandCompleted>,
+    mut structured: EventWriter<TerminalStructuredOutput>,
+) {
+    for line in printed.iter(&terminal_line) {
+        let Some(invocation) = line.get_meta::<TerminalInvocation>() else {
+            continue;
+        };
+        if !queue.structured.contains(invocation) {
+            continue;
+        }
+        if let Some(fields) = structured_fields(line, &strings) {
+            structured.send(TerminalStructuredOutput {
+                invocation: *invocation,
+                json: format!(r#"{{"invocation":{},{fields}}}"#, invocation.id()),
+            });
+        }
+    }
+    for completed in completed.iter() {
+        queue.structured.remove(&completed.invocation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as leafwing_terminal;
+    use crate::harness::test_app;
+    use crate::{AddTerminalCommand, TerminalCommand};
+
+    /// Prints the player's stats
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "stats")]
+    struct StatsCommand {
+        player: String,
+    }
+
+    fn stats_command(mut stats: TerminalCommand<StatsCommand>) {
+        if let Some(StatsCommand { player }) = stats.take() {
+            match player.as_str() {
+                "alice" => {
+                    stats.reply_kv([("health", 80), ("mana", 35)]);
+                    stats.ok();
+                }
+                _ => {
+                    stats.reply_error(format!("no player \"{player}\""));
+                    stats.failed();
+                }
+            }
+        }
+    }
+
+    /// Every structured output so far, as events only last two frames
+    #[derive(Default)]
+    struct Structured(Vec<String>);
+
+    fn record_structured(
+        mut events: EventReader<TerminalStructuredOutput>,
+        mut structured: ResMut<Structured>,
+    ) {
+        structured
+            .0
+            .extend(events.iter().map(|output| output.json.clone()));
+    }
+
+    #[test]
+    fn it_strips_the_json_flag() {
+        assert_eq!(
+            strip_json_flag("stats alice --json"),
+            ("stats alice ", true)
+        );
+        assert_eq!(strip_json_flag("stats --json  "), ("stats ", true));
+        assert_eq!(strip_json_flag("stats alice"), ("stats alice", false));
+        assert_eq!(
+            strip_json_flag("stats alice--json"),
+            ("stats alice--json", false)
+        );
+        assert_eq!(json_string("a \"b\"\n\\"), r#""a \"b\"\n\\""#);
+        assert_eq!(json_string("\u{1b}"), r#""\u001b""#);
+    }
+
+    #[test]
+    fn it_sends_json_for_invocations_which_ask_for_it() {
+        let mut app = test_app();
+        app.add_terminal_command::<StatsCommand, _, _>(stats_command)
+            .init_resource::<Structured>()
+            .add_system_to_stage(CoreStage::Last, record_structured);
+        app.update();
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        let alice = queue.submit("stats alice --json").unwrap();
+        let bob = queue.submit("stats bob --json").unwrap();
+        let plain = queue.submit("stats alice").unwrap();
+        for _ in 0..5 {
+            app.update();
+        }
+
+        let (alice, bob) = (alice.id(), bob.id());
+        assert_eq!(
+            app.world.resource::<Structured>().0,
+            [
+                format!(
+                    r#"{{"invocation":{alice},"type":"kv","pairs":{{"health":"80","mana":"35"}}}}"#
+                ),
+                format!(r#"{{"invocation":{alice},"type":"outcome","outcome":"ok"}}"#),
+                format!(
+                    r#"{{"invocation":{bob},"type":"error","message":"[error] no player \"bob\""}}"#
+                ),
+                format!(r#"{{"invocation":{bob},"type":"outcome","outcome":"failed"}}"#),
+            ]
+        );
+        // The scrollback is the same either way
+        assert_eq!(
+            plain.collect_output(&app),
+            ["  health: 80", "    mana: 35", "[ok]"]
+        );
+        assert!(app
+            .world
+            .resource::<TerminalCommandQueue>()
+            .structured
+            .is_empty());
+    }
+}
@@ -1,19 +1,45 @@
-use std::collections::{BTreeMap, VecDeque};
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::marker::PhantomData;
-use std::{fmt::Write, mem};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{fmt, fmt::Write, mem};
 
-use bevy::ecs::schedule::IntoSystemDescriptor;
+use bevy::ecs::schedule::{IntoSystemDescriptor, StageLabel};
 use bevy::{
-    ecs::event::{EventReaderState, EventWriterState, Events},
+    ecs::event::{EventWriterState, Events, ManualEventReader},
     ecs::system::{
-        LocalState, ResMutState, ResState, Resource, SystemMeta, SystemParam, SystemParamFetch,
+        ResMutState, ResState, Resource, SystemMeta, SystemParam, SystemParamFetch,
         SystemParamState,
     },
     prelude::*,
+    utils::Instant,
 };
-use leafwing_terminal_parser::ValueRawOwned;
+use bevy_egui::egui::{style::Margin, Color32, FontFamily, Modifiers};
+use leafwing_terminal_parser::{validate_command_name, ParseError, ValueRawOwned};
 
-use crate::FromValueError;
+use crate::anchor::ScrollAnchor;
+use crate::echo::Echo;
+use crate::history_browser::HistoryBrowser;
+use crate::history_search::HistorySearch;
+use crate::line::{LineMeta, Severity};
+use crate::log::{is_error_line, log_command, log_line};
+use crate::palette::CommandPalette;
+use crate::print_queue::PrintQueue;
+use crate::prompt::{parse_answer, PendingPrompt, TerminalPromptRequested};
+use crate::rows::RowHeights;
+use crate::selection::PrintedAt;
+use crate::strings::fill;
+#[cfg(feature = "serde")]
+use crate::structured::JsonValue;
+use crate::structured::KeyValues;
+use crate::{
+    ArgCompleter, CommandCompleters, CommandRegistry, CommandValidator, FromValue, FromValueError,
+    PromptId, TerminalCommandQueue, TerminalCooldowns, TerminalInvocation, TerminalLine,
+    TerminalStrings, UnfocusedPolicy,
+};
 
 /// Terminal command name.
 ///
@@ -49,8 +75,8 @@ pub trait CommandName {
 /// }
 ///
 /// impl CommandArgs for LogCommand {
-///     fn from_values(values: &[ValueRawOwned]) -> Result<Self, FromValueError> {
-///         let mut values = values.iter();
+///     fn from_values(values: Vec<ValueRawOwned>) -> Result<Self, FromValueError> {
+///         let mut values = values.into_iter();
 ///         let msg = String::from_value_iter(&mut values, 0)?;
 ///
 ///         Ok(LogCommand {
@@ -59,9 +85,120 @@ pub trait CommandName {
 ///     }
 /// }
 /// ```
+///
+/// # Migrating from borrowed values
+///
+/// `from_values` used to borrow its values, so every string argument was copied into the command.
+/// It now owns them, and string arguments are moved into the command when only one system reads it.
+/// Implementations iterate with `values.into_iter()` instead of `values.iter()`,
+/// and callers holding a borrowed slice pass `values.to_vec()`.
+/// [`FromValue`] lost its lifetime parameter along the way: write `impl FromValue for T`,
+/// and implement [`FromValue::from_value_owned`] for types which can take a string argument as is.
 pub trait CommandArgs: Sized {
     /// Parse arguments from values.
-    fn from_values(values: &[ValueRawOwned]) -> Result<Self, FromValueError>;
+    fn from_values(values: Vec<ValueRawOwned>) -> Result<Self, FromValueError>;
+}
+
+/// Convert a command back into its argument values, the reverse of [`CommandArgs`].
+///
+/// `#[derive(TerminalCommand)]` implements it when every field implements [`IntoValue`](crate::IntoValue),
+/// so the command can be run with [`TerminalDispatch::dispatch_typed`](crate::TerminalDispatch::dispatch_typed).
+///
+/// Arguments are positional, so `from_values` only gives back the same command if every `None`
+/// optional field comes after the fields which are set.
+///
+/// # Example
+///
+/// ```
+/// # use leafwing_terminal::{CommandArgs, IntoValues, TerminalCommand};
+/// #
+/// /// Spawns enemies
+/// #[derive(TerminalCommand, Clone, Debug, PartialEq)]
+/// #[terminal_command(name = "spawn")]
+/// struct SpawnCommand {
+///     /// Enemy kind
+///     kind: String,
+///     /// Height above the ground
+///     height: f64,
+///     /// Number of enemies
+///     count: Option<u32>,
+/// }
+///
+/// let spawn = SpawnCommand {
+///     kind: "Big Slime".to_string(),
+///     height: -0.5,
+///     count: Some(3),
+/// };
+/// let values = spawn.clone().into_values();
+/// assert_eq!(SpawnCommand::from_values(values), Ok(spawn));
+/// ```
+pub trait IntoValues {
+    /// The values which [`CommandArgs::from_values`] parses back into `self`.
+    fn into_values(self) -> Vec<ValueRawOwned>;
+}
+
+/// Arguments shared between commands, embedded with `#[terminal_command(flatten)]`.
+///
+/// Derive it with `#[derive(CommandArgGroup)]`, which accepts the same fields as
+/// `#[derive(TerminalCommand)]`. The group's arguments are parsed and listed in help
+/// as if its fields were written in place of the flattened field.
+///
+/// # Example
+///
+/// ```
+/// # use leafwing_terminal::{CommandArgGroup, TerminalCommand};
+/// #
+/// #[derive(CommandArgGroup)]
+/// struct PosArgs {
+///     /// X coordinate
+///     x: f64,
+///     /// Y coordinate
+///     y: f64,
+/// }
+///
+/// /// Spawns an enemy
+/// #[derive(TerminalCommand)]
+/// #[terminal_command(name = "spawn")]
+/// struct SpawnCommand {
+///     /// Enemy kind
+///     kind: String,
+///     #[terminal_command(flatten)]
+///     pos: PosArgs,
+/// }
+/// ```
+///
+/// Flattening a type which doesn't implement `CommandArgGroup` is a compile error:
+///
+/// ```compile_fail
+/// # use leafwing_terminal::TerminalCommand;
+/// #
+/// struct PosArgs {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// #[derive(TerminalCommand)]
+/// #[terminal_command(name = "teleport")]
+/// struct TeleportCommand {
+///     #[terminal_command(flatten)]
+///     pos: PosArgs,
+/// }
+/// ```
+pub trait CommandArgGroup: Sized {
+    /// Number of arguments in the group, including optional ones
+    const ARG_COUNT: u8;
+    /// Fewest arguments the group accepts
+    const MIN_ARGS: usize;
+    /// Most arguments the group accepts, `None` if it takes all remaining arguments
+    const MAX_ARGS: Option<usize>;
+
+    /// Parse the group from an iterator of values, numbering its arguments from `arg_num`.
+    fn from_value_iter<I>(values: &mut I, arg_num: u8) -> Result<Self, FromValueError>
+    where
+        I: Iterator<Item = ValueRawOwned>;
+
+    /// Help for each argument in the group.
+    fn arg_info() -> Vec<CommandArgInfo>;
 }
 
 /// Provides command usage information including description, arguments and their types.
@@ -92,8 +229,12 @@ pub trait CommandArgs: Sized {
 ///                     ty: "string".to_string(),
 ///                     description: Some("message to print".to_string()),
 ///                     optional: false,
+///                     default: None,
 ///                 },
 ///             ],
+///             min_args: Some(1),
+///             max_args: Some(1),
+///             ..Default::default()
 ///         })
 ///     }
 /// }
@@ -105,8 +246,14 @@ pub trait CommandHelp: CommandName {
     }
 }
 
+/// Separates an example in help from its description.
+pub(crate) const EXAMPLE_COMMENT: &str = "  # ";
+
 /// Command information.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Help written by hand should end with `..Default::default()`, see [`CommandHelp`],
+/// so it keeps compiling when fields are added.
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct CommandInfo {
     /// Command name
     pub name: String,
@@ -114,6 +261,33 @@ pub struct CommandInfo {
     pub description: Option<String>,
     /// Command argument information
     pub args: Vec<CommandArgInfo>,
+    /// Minimum duration between invocations
+    pub cooldown: Option<Duration>,
+    /// Leave invocations out of the input history
+    pub exclude_from_history: bool,
+    /// Changes or deletes state in a way that shouldn't happen unattended,
+    /// see [`UnfocusedPolicy::DenyDestructive`](crate::UnfocusedPolicy::DenyDestructive)
+    pub destructive: bool,
+    /// Fewest arguments the command accepts, checked before any argument is parsed
+    pub min_args: Option<usize>,
+    /// Most arguments the command accepts, checked before any argument is parsed
+    pub max_args: Option<usize>,
+    /// Example invocations listed at the end of the help
+    pub examples: Vec<CommandExample>,
+    /// Note on what to use instead if the command is deprecated, see [`CommandRegistry::deprecate`]
+    pub deprecated: Option<String>,
+}
+
+/// An example invocation of a command, listed in its help.
+///
+/// Added with `#[terminal_command(example("teleport 10 0 5", "move to the tower"))]`
+/// or [`CommandInfo::with_example`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandExample {
+    /// Command line, without the echoed `$ `
+    pub command: String,
+    /// What the example does
+    pub description: Option<String>,
 }
 
 /// Command argument information.
@@ -127,43 +301,189 @@ pub struct CommandArgInfo {
     pub description: Option<String>,
     /// Is argument optional
     pub optional: bool,
+    /// Value used when the argument is left out
+    pub default: Option<String>,
 }
 
-impl CommandInfo {
-    /// Compine command help into usage string.
-    #[allow(unused_must_use)]
-    pub fn help_text(&self) -> String {
-        let mut buf = "Usage:\n\n".to_string();
+/// Layout of command help printed by `help` and when a command gets bad arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HelpFormat {
+    /// Wrap descriptions so lines are at most this many chars
+    pub max_width: Option<usize>,
+    /// Spaces before each line, doubled for arguments
+    pub indent: usize,
+    /// Show each argument's type
+    pub show_types: bool,
+    /// Append default values to argument descriptions
+    pub show_defaults: bool,
+}
 
-        write!(buf, "  > {}", self.name);
-        for CommandArgInfo { name, optional, .. } in &self.args {
-            write!(buf, " ");
-            if *optional {
-                write!(buf, "[");
-            } else {
-                write!(buf, "<");
+impl Default for HelpFormat {
+    fn default() -> Self {
+        Self {
+            max_width: None,
+            indent: 2,
+            show_types: true,
+            show_defaults: true,
+        }
+    }
+}
+
+impl HelpFormat {
+    /// Wraps `text` at word boundaries to fit after `used` chars of a line.
+    pub(crate) fn wrap<'a>(&self, text: &'a str, used: usize) -> Vec<Cow<'a, str>> {
+        let width = match self.max_width {
+            Some(max_width) => max_width.saturating_sub(used).max(1),
+            None => return vec![Cow::Borrowed(text)],
+        };
+
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for word in text.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(Cow::Owned(mem::take(&mut line)));
+            }
+            if !line.is_empty() {
+                line.push(' ');
             }
-            write!(buf, "{name}");
-            if *optional {
-                write!(buf, "]");
+            line.push_str(word);
+        }
+        lines.push(Cow::Owned(line));
+        lines
+    }
+
+    /// Lines of a description after `used` chars of a line: paragraphs are wrapped, the blank lines between them kept,
+    /// and indented lines, such as code from doc comments, kept as they are.
+    pub(crate) fn description_lines<'a>(
+        &self,
+        description: &'a str,
+        used: usize,
+    ) -> Vec<Cow<'a, str>> {
+        let mut lines = Vec::new();
+        for line in description.lines() {
+            if line.trim().is_empty() {
+                lines.push(Cow::Borrowed(""));
+            } else if line.starts_with(char::is_whitespace) {
+                lines.push(Cow::Borrowed(line.trim_end()));
             } else {
-                write!(buf, ">");
+                lines.extend(self.wrap(line, used));
+            }
+        }
+        lines
+    }
+
+    /// Lines of `value` pretty-printed with `{:#?}`, nested levels indented by [`indent`](Self::indent).
+    pub(crate) fn debug_lines(&self, value: &dyn fmt::Debug) -> Vec<String> {
+        format!("{value:#?}")
+            .lines()
+            .map(|line| {
+                let text = line.trim_start_matches(' ');
+                let depth = (line.len() - text.len()) / 4;
+                format!("{}{text}", " ".repeat(depth * self.indent))
+            })
+            .collect()
+    }
+
+    /// Lines of `key: value` pairs, indented, with keys right-aligned.
+    ///
+    /// Values spanning several lines continue under the first line of the value.
+    pub(crate) fn key_value_lines(&self, pairs: &[(String, String)]) -> Vec<String> {
+        let width = pairs
+            .iter()
+            .map(|(key, _)| key.chars().count())
+            .max()
+            .unwrap_or(0);
+        let indent = " ".repeat(self.indent);
+        let continued = " ".repeat(self.indent + width + 2);
+        let mut lines = Vec::new();
+        for (key, value) in pairs {
+            let mut value_lines = value.lines();
+            let first = value_lines.next().unwrap_or_default();
+            lines.push(format!("{indent}{key:>width$}: {first}"));
+            lines.extend(value_lines.map(|line| format!("{continued}{line}")));
+        }
+        lines
+    }
+}
+
+impl CommandInfo {
+    /// Help for a command taking no arguments, to fill in further with struct update syntax.
+    pub fn new(name: impl Into<String>, description: Option<&str>) -> Self {
+        Self {
+            name: name.into(),
+            description: description.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    /// Adds an example invocation to the help.
+    pub fn with_example(mut self, command: impl Into<String>, description: Option<&str>) -> Self {
+        self.examples.push(CommandExample {
+            command: command.into(),
+            description: description.map(str::to_string),
+        });
+        self
+    }
+
+    /// The first paragraph of the description, for lists of commands.
+    pub fn summary(&self) -> Option<&str> {
+        let description = self.description.as_deref()?;
+        description.split("\n\n").next()
+    }
+
+    /// Checks a number of arguments against [`min_args`](Self::min_args) and [`max_args`](Self::max_args).
+    pub fn check_arg_count(&self, count: usize) -> Result<(), FromValueError> {
+        if self.min_args.is_some_and(|min| count < min) {
+            return Err(FromValueError::NotEnoughArgs);
+        }
+        match self.max_args {
+            Some(max) if count > max => Err(FromValueError::TooManyArgs { max }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The name followed by the arguments, with `<required>` and `[optional]` ones, as in `spawn <kind> [count]`.
+    pub fn signature(&self) -> String {
+        let mut signature = self.name.clone();
+        for CommandArgInfo { name, optional, .. } in &self.args {
+            match optional {
+                true => write!(signature, " [{name}]"),
+                false => write!(signature, " <{name}>"),
             }
+            .unwrap();
         }
+        signature
+    }
+
+    /// Compine command help into usage string.
+    pub fn help_text(&self) -> String {
+        self.help_text_with(&HelpFormat::default())
+    }
+
+    /// Compine command help into usage string laid out by `format`.
+    pub fn help_text_with(&self, format: &HelpFormat) -> String {
+        self.help_text_localized(format, &TerminalStrings::default())
+    }
+
+    /// Compine command help into usage string laid out by `format`, with headings from `strings`.
+    #[allow(unused_must_use)]
+    pub fn help_text_localized(&self, format: &HelpFormat, strings: &TerminalStrings) -> String {
+        let indent = " ".repeat(format.indent);
+        let arg_indent = indent.repeat(2);
+        let mut buf = format!("{}\n\n", strings.usage);
+
+        write!(buf, "{indent}> {}", self.signature());
         writeln!(buf);
         writeln!(buf);
 
         if let Some(description) = &self.description {
-            let description = description.lines().fold(String::new(), |mut buf, s| {
-                let spaces = s.chars().take_while(|c| *c == ' ').count();
-                for _ in 0..2usize.saturating_sub(spaces) {
-                    buf.push(' ');
-                }
-                buf.push_str(s);
-                buf.push('\n');
-                buf
-            });
-            writeln!(buf, "{description}");
+            for line in format.description_lines(description, format.indent) {
+                match line.is_empty() {
+                    true => writeln!(buf),
+                    false => writeln!(buf, "{indent}{line}"),
+                };
+            }
+            writeln!(buf);
         }
 
         let longest_arg_name = self
@@ -178,32 +498,81 @@ impl CommandInfo {
             ty,
             description,
             optional,
+            default,
         } in &self.args
         {
-            write!(
-                buf,
-                "    {name} {}",
+            let mut line = format!(
+                "{arg_indent}{name} {}",
                 " ".repeat(longest_arg_name - name.len())
             );
-            if *optional {
-                write!(buf, "[");
-            } else {
-                write!(buf, "<");
+            if format.show_types {
+                let (open, close) = if *optional { ('[', ']') } else { ('<', '>') };
+                write!(
+                    line,
+                    "{open}{ty}{close}{}",
+                    " ".repeat(longest_arg_ty - ty.len())
+                );
             }
-            write!(buf, "{ty}");
-            if *optional {
-                write!(buf, "]");
-            } else {
-                write!(buf, ">");
+
+            let description = match (description, default) {
+                (description, Some(default)) if format.show_defaults => Some(match description {
+                    Some(description) => format!("{description} (default: {default})"),
+                    None => format!("(default: {default})"),
+                }),
+                (description, _) => description.clone(),
+            };
+            match description {
+                Some(description) => {
+                    write!(line, "   - ");
+                    let used = line.chars().count();
+                    let lines = format.description_lines(&description, used);
+                    for (i, wrapped) in lines.iter().enumerate() {
+                        if i > 0 {
+                            line = " ".repeat(used);
+                        }
+                        match wrapped.is_empty() {
+                            true => writeln!(buf),
+                            false => writeln!(buf, "{line}{wrapped}"),
+                        };
+                    }
+                }
+                None => {
+                    writeln!(buf, "{line}");
+                }
             }
-            write!(buf, "{}", " ".repeat(longest_arg_ty - ty.len()));
+        }
 
+        if !self.examples.is_empty() {
+            writeln!(buf);
+            writeln!(buf, "{}", strings.examples);
+            writeln!(buf);
+        }
+        let longest_example = self
+            .examples
+            .iter()
+            .map(|example| example.command.chars().count())
+            .max()
+            .unwrap_or(0);
+        for CommandExample {
+            command,
+            description,
+        } in &self.examples
+        {
+            let mut line = format!("{indent}> {command}");
             match description {
                 Some(description) => {
-                    writeln!(buf, "   - {description}");
+                    let padding = longest_example - command.chars().count();
+                    write!(line, "{}{EXAMPLE_COMMENT}", " ".repeat(padding));
+                    let used = line.chars().count();
+                    for (i, wrapped) in format.wrap(description, used).iter().enumerate() {
+                        if i > 0 {
+                            line = " ".repeat(used);
+                        }
+                        writeln!(buf, "{line}{wrapped}");
+                    }
                 }
                 None => {
-                    writeln!(buf);
+                    writeln!(buf, "{line}");
                 }
             }
         }
@@ -238,66 +607,284 @@ impl CommandInfo {
 ///     }
 /// }
 /// ```
+///
+/// # Multiple systems
+///
+/// Any number of systems can read the same command, for example one acting on it and one recording analytics.
+/// Only the first is added with [`AddTerminalCommand::add_terminal_command`], the others are added as plain systems.
+/// Each system sees each invocation once, and the first system to see an invocation decides its outcome:
+///
+/// - If the command is on cooldown, or its arguments are invalid, the first system prints the error and usage.
+///   Every system gets `None`, and nothing more is printed.
+/// - Otherwise the first system triggers the cooldown, and every system gets its own parsed copy of the command.
 pub struct TerminalCommand<'w, 's, T> {
     command: Option<T>,
+    /// Registered name of the command, for measuring its cost
+    name: &'static str,
+    /// When the command was parsed, for reporting slow commands
+    started: Option<Instant>,
+    invocation: Option<TerminalInvocation>,
+    buffer: Res<'w, TerminalCommandBuffer>,
     terminal_line: EventWriter<'w, 's, PrintTerminalLine>,
+    prompt_requested: EventWriter<'w, 's, TerminalPromptRequested>,
 }
 
 impl<'w, 's, T> TerminalCommand<'w, 's, T> {
     /// Returns Some(T) if the command was executed and arguments were valid.
     ///
-    /// This method should only be called once.
+    /// This method should only be called once per system run.
     /// Consecutive calls will return None regardless if the command occured.
+    /// Other systems reading the same command get their own copy, see [Multiple systems](#multiple-systems).
     pub fn take(&mut self) -> Option<T> {
         mem::take(&mut self.command)
     }
 
-    /// Print `[ok]` in the terminal.
+    /// The invocation being run, which the lines printed by this command are tagged with.
+    ///
+    /// Commands which print their outcome in a later system should tag those lines with it too,
+    /// using [`PrintTerminalLine::with_meta`].
+    pub fn invocation(&self) -> Option<TerminalInvocation> {
+        self.invocation
+    }
+
+    /// Print a line tagged with the current invocation.
+    fn send(&mut self, line: String) {
+        self.send_line(PrintTerminalLine::new(line));
+    }
+
+    /// Print a line with `severity`, tagged with the current invocation.
+    fn send_with(&mut self, line: String, severity: Severity) {
+        self.send_line(PrintTerminalLine::new(line).with_meta(severity));
+    }
+
+    /// Print `line` tagged with the current invocation, keeping what is attached to it.
+    fn send_line(&mut self, line: PrintTerminalLine) {
+        self.terminal_line.send(match self.invocation {
+            Some(invocation) => line.with_meta(invocation),
+            None => line,
+        });
+    }
+
+    /// Print `[ok]` in the terminal, or [`TerminalStrings::ok`](crate::TerminalStrings::ok).
+    ///
+    /// See [`TerminalConfiguration::report_slow_commands`] for how long the command took being appended.
     pub fn ok(&mut self) {
-        self.terminal_line
-            .send(PrintTerminalLine::new("[ok]".to_string()));
+        let ok = self.with_timing(&self.buffer.strings.ok);
+        self.send_with(ok, Severity::Ok);
     }
 
-    /// Print `[failed]` in the terminal.
+    /// Print `[failed]` in the terminal, or [`TerminalStrings::failed`](crate::TerminalStrings::failed).
+    ///
+    /// See [`TerminalConfiguration::report_slow_commands`] for how long the command took being appended.
     pub fn failed(&mut self) {
-        self.terminal_line
-            .send(PrintTerminalLine::new("[failed]".to_string()));
+        let failed = self.with_timing(&self.buffer.strings.failed);
+        self.send_with(failed, Severity::Failed);
+    }
+
+    /// Mark the command as done without printing an outcome, for commands whose output is their result, such as `help`.
+    ///
+    /// Commands which call neither this, [`ok`](Self::ok) nor [`failed`](Self::failed) are reported as missing their outcome,
+    /// see [`TerminalConfiguration::outcome_timeout`].
+    pub fn done(&mut self) {
+        if let Some(invocation) = self.invocation {
+            self.buffer.finished.lock().unwrap().push(invocation);
+        }
+    }
+
+    /// Append how long the command has taken to `outcome` if it is over the slow command threshold.
+    fn with_timing(&self, outcome: &str) -> String {
+        let elapsed = self.started.map(|started| started.elapsed());
+        match (elapsed, self.buffer.slow_command_threshold) {
+            (Some(elapsed), Some(threshold)) if elapsed.as_secs_f32() >= threshold => {
+                let took = fill(&self.buffer.strings.took, &[&elapsed.as_millis()]);
+                format!("{outcome} {took}")
+            }
+            _ => outcome.to_string(),
+        }
     }
 
     /// Print a reply in the terminal.
     ///
     /// See [`reply!`](crate::reply) for usage with the [`format!`] syntax.
     pub fn reply(&mut self, msg: impl Into<String>) {
-        self.terminal_line.send(PrintTerminalLine::new(msg.into()));
+        self.send(msg.into());
     }
 
     /// Print a reply in the terminal followed by `[ok]`.
     ///
     /// See [`reply_ok!`](crate::reply_ok) for usage with the [`format!`] syntax.
     pub fn reply_ok(&mut self, msg: impl Into<String>) {
-        self.terminal_line.send(PrintTerminalLine::new(msg.into()));
+        self.send(msg.into());
         self.ok();
     }
 
-    /// Print a reply in the terminal followed by `[failed]`.
+    /// Print a reply in the terminal as an error, followed by `[failed]`.
     ///
     /// See [`reply_failed!`](crate::reply_failed) for usage with the [`format!`] syntax.
     pub fn reply_failed(&mut self, msg: impl Into<String>) {
-        self.terminal_line.send(PrintTerminalLine::new(msg.into()));
+        self.send_with(msg.into(), Severity::Error);
         self.failed();
     }
+
+    /// Print a warning in the terminal, formatted with [`TerminalStrings::warning`](crate::TerminalStrings::warning).
+    ///
+    /// See [`reply_warn!`](crate::reply_warn) for usage with the [`format!`] syntax.
+    pub fn reply_warn(&mut self, msg: impl Into<String>) {
+        let msg = fill(&self.buffer.strings.warning, &[&msg.into()]);
+        self.send_with(msg, Severity::Warn);
+    }
+
+    /// Print an error in the terminal, formatted with [`TerminalStrings::custom_error`](crate::TerminalStrings::custom_error).
+    ///
+    /// Errors are shown as toasts while the terminal is closed.
+    /// See [`reply_error!`](crate::reply_error) for usage with the [`format!`] syntax.
+    pub fn reply_error(&mut self, msg: impl Into<String>) {
+        let msg = fill(&self.buffer.strings.custom_error, &[&msg.into()]);
+        self.send_with(msg, Severity::Error);
+    }
+
+    /// Print each line of a reply as its own terminal line.
+    ///
+    /// See [`reply_lines!`](crate::reply_lines) for usage with the [`format!`] syntax.
+    pub fn reply_lines(&mut self, msg: impl AsRef<str>) {
+        for line in msg.as_ref().split('\n') {
+            self.send(line.to_string());
+        }
+    }
+
+    /// Print `value` pretty-printed with `{:#?}`, one terminal line per line.
+    ///
+    /// Nested fields are indented like arguments in help, see [`HelpFormat::indent`].
+    pub fn reply_debug(&mut self, value: &impl fmt::Debug) {
+        for line in self.buffer.help_format.debug_lines(value) {
+            self.send(line);
+        }
+    }
+
+    /// Print a `key: value` line for each pair, with keys right-aligned so the values line up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use leafwing_terminal::TerminalCommand;
+    /// #
+    /// # /// Prints the player's stats
+    /// # #[derive(TerminalCommand)]
+    /// # #[terminal_command(name = "stats")]
+    /// # struct StatsCommand;
+    /// #
+    /// fn stats_command(mut stats: TerminalCommand<StatsCommand>) {
+    ///     if stats.take().is_some() {
+    ///         // Prints "  health: 80" and "    mana: 35"
+    ///         stats.reply_kv([("health", 80), ("mana", 35)]);
+    ///     }
+    /// }
+    /// ```
+    pub fn reply_kv<K: fmt::Display, V: fmt::Display>(
+        &mut self,
+        pairs: impl IntoIterator<Item = (K, V)>,
+    ) {
+        let pairs: Vec<(String, String)> = pairs
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let lines = self.buffer.help_format.key_value_lines(&pairs);
+        // The pairs go with the first line, for commands submitted with `--json`
+        let mut pairs = Some(KeyValues(pairs));
+        for line in lines {
+            let line = PrintTerminalLine::new(line);
+            self.send_line(match pairs.take() {
+                Some(pairs) => line.with_meta(pairs),
+                None => line,
+            });
+        }
+    }
+
+    /// Print `value` as JSON, which is also sent as a [`TerminalStructuredOutput`](crate::TerminalStructuredOutput)
+    /// when the command was submitted with `--json`.
+    ///
+    /// Values which can't be serialized are reported with [`reply_error`](Self::reply_error).
+    #[cfg(feature = "serde")]
+    pub fn reply_json(&mut self, value: &impl serde::Serialize) {
+        match serde_json::to_string(value) {
+            Ok(json) => {
+                self.send_line(PrintTerminalLine::new(json.clone()).with_meta(JsonValue(json)))
+            }
+            Err(err) => self.reply_error(err.to_string()),
+        }
+    }
+
+    /// Print the usage of command `C`, laid out like the help printed for invalid arguments.
+    ///
+    /// Useful when arguments parse but don't make sense together.
+    /// See [`reply_usage!`](crate::reply_usage).
+    pub fn reply_usage<C: CommandHelp>(&mut self) {
+        if let Some(help) = C::command_help() {
+            let usage = help.help_text_localized(&self.buffer.help_format, &self.buffer.strings);
+            self.send(usage);
+        }
+    }
+
+    /// Ask the user for a value of type `P`.
+    ///
+    /// The next line submitted in the terminal answers the prompt instead of being parsed as a command,
+    /// and is delivered as a [`TerminalPromptAnswered`](crate::TerminalPromptAnswered) event with the returned id.
+    /// Prompts requested while another is outstanding are queued.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use leafwing_terminal::{PromptId, TerminalCommand};
+    /// #
+    /// # #[derive(TerminalCommand)]
+    /// # #[terminal_command(name = "rename_player")]
+    /// # struct RenamePlayerCommand {
+    /// #     name: Option<String>,
+    /// # }
+    /// #
+    /// fn rename_player_command(
+    ///     mut rename: TerminalCommand<RenamePlayerCommand>,
+    ///     mut prompt: Local<Option<PromptId>>,
+    /// ) {
+    ///     if let Some(RenamePlayerCommand { name: None }) = rename.take() {
+    ///         *prompt = Some(rename.request_input::<String>("enter new name:"));
+    ///     }
+    /// }
+    /// ```
+    pub fn request_input<P>(&mut self, message: impl Into<String>) -> PromptId
+    where
+        P: FromValue,
+    {
+        let id = PromptId::next();
+        self.prompt_requested.send(TerminalPromptRequested {
+            id,
+            message: message.into(),
+            parse: parse_answer::<P>,
+        });
+        id
+    }
+}
+
+impl<'w, 's, T> Drop for TerminalCommand<'w, 's, T> {
+    /// Adds the time since the command was parsed to its cost this frame,
+    /// see [`TerminalConfiguration::command_time_budget`].
+    fn drop(&mut self) {
+        if let Some(started) = self.started {
+            let mut costs = self.buffer.costs.lock().unwrap();
+            *costs.entry(self.name).or_default() += started.elapsed();
+        }
+    }
 }
 
 pub struct TerminalCommandState<T> {
-    #[allow(clippy::type_complexity)]
-    event_reader: EventReaderState<
-        (
-            LocalState<(usize, PhantomData<TerminalCommandEntered>)>,
-            ResState<Events<TerminalCommandEntered>>,
-        ),
-        TerminalCommandEntered,
-    >,
+    buffer: ResState<TerminalCommandBuffer>,
+    cursor: Arc<AtomicU64>,
     terminal_line: EventWriterState<(ResMutState<Events<PrintTerminalLine>>,), PrintTerminalLine>,
+    prompt_requested:
+        EventWriterState<(ResMutState<Events<TerminalPromptRequested>>,), TerminalPromptRequested>,
+    cooldowns: ResMutState<TerminalCooldowns>,
+    time: ResState<Time>,
     marker: PhantomData<T>,
 }
 
@@ -307,20 +894,27 @@ impl<'w, 's, T: Resource + CommandName + CommandArgs + CommandHelp> SystemParam
     type Fetch = TerminalCommandState<T>;
 }
 
-unsafe impl<'w, 's, T: Resource> SystemParamState for TerminalCommandState<T> {
-
-
+unsafe impl<T: Resource + CommandName> SystemParamState for TerminalCommandState<T> {
     fn init(world: &mut World, system_meta: &mut SystemMeta) -> Self {
-        let event_reader = EventReaderState::init(world, system_meta);
+        let cursor = world
+            .get_resource_or_insert_with(TerminalCommandBuffer::default)
+            .register_consumer(T::command_name());
+        let buffer = ResState::init(world, system_meta);
         let terminal_line = EventWriterState::init(world, system_meta);
+        let prompt_requested = EventWriterState::init(world, system_meta);
+        let cooldowns = ResMutState::init(world, system_meta);
+        let time = ResState::init(world, system_meta);
 
         TerminalCommandState {
-            event_reader,
+            buffer,
+            cursor,
             terminal_line,
-            marker: PhantomData::default(),
+            prompt_requested,
+            cooldowns,
+            time,
+            marker: PhantomData,
         }
     }
-
 }
 
 impl<'w, 's, T: Resource + CommandName + CommandArgs + CommandHelp> SystemParamFetch<'w, 's>
@@ -335,99 +929,914 @@ impl<'w, 's, T: Resource + CommandName + CommandArgs + CommandHelp> SystemParamF
         world: &'w World,
         change_tick: u32,
     ) -> Self::Item {
-        let mut event_reader =
-            EventReaderState::get_param(&mut state.event_reader, system_meta, world, change_tick);
+        let buffer = ResState::get_param(&mut state.buffer, system_meta, world, change_tick);
         let mut terminal_line =
             EventWriterState::get_param(&mut state.terminal_line, system_meta, world, change_tick);
+        let prompt_requested = EventWriterState::get_param(
+            &mut state.prompt_requested,
+            system_meta,
+            world,
+            change_tick,
+        );
+        let mut cooldowns =
+            ResMutState::get_param(&mut state.cooldowns, system_meta, world, change_tick);
+        let time = ResState::get_param(&mut state.time, system_meta, world, change_tick);
 
-        let command = event_reader
+        // Take the first unseen invocation of this command, leaving any later ones for the next run
+        let cursor = state.cursor.load(Ordering::Acquire);
+        let entered = buffer
+            .commands
             .iter()
-            .find(|cmd| cmd.command == T::command_name())
-            .map(|cmd| T::from_values(&cmd.args))
-            .and_then(|result| match result {
-                Ok(value) => Some(value),
-                Err(err) => {
-                    terminal_line.send(PrintTerminalLine::new(err.to_string()));
-                    match err {
-                        FromValueError::UnexpectedArgType { .. }
-                        | FromValueError::NotEnoughArgs
-                        | FromValueError::Custom(_) => {
-                            if let Some(help_text) = T::command_help() {
-                                terminal_line.send(PrintTerminalLine::new(help_text.help_text()));
-                            }
-                        }
-                        FromValueError::ValueTooLarge { .. } => {}
+            .skip_while(|entered| entered.id < cursor)
+            .find(|entered| entered.command == T::command_name());
+        let cursor = entered.map_or(buffer.next_id, |entered| entered.id + 1);
+        state.cursor.store(cursor, Ordering::Release);
+
+        // The only system reading a command takes its arguments, saving a copy of each string argument
+        let only_reader = buffer.readers(T::command_name()) == 1;
+        let args = |entered: &BufferedCommand| {
+            let mut args = entered.args.lock().unwrap();
+            match only_reader {
+                true => mem::take(&mut *args),
+                false => args.clone(),
+            }
+        };
+
+        let started = entered.map(|_| Instant::now());
+        let command = entered.and_then(|entered| match entered.outcome.load(Ordering::Acquire) {
+            BufferedCommand::UNSEEN => {
+                let now = time.seconds_since_startup();
+                let command = parse_first_invocation::<T>(
+                    entered,
+                    args(entered),
+                    &buffer,
+                    &mut terminal_line,
+                    &mut cooldowns,
+                    now,
+                );
+                let outcome = match command {
+                    Some(_) => {
+                        let started = (entered.invocation, T::command_name());
+                        buffer.started.lock().unwrap().push(started);
+                        BufferedCommand::ACCEPTED
                     }
-                    None
-                }
-            });
+                    None => BufferedCommand::REJECTED,
+                };
+                entered.outcome.store(outcome, Ordering::Release);
+                command
+            }
+            BufferedCommand::ACCEPTED => T::from_values(args(entered)).ok(),
+            _ => None,
+        });
 
         TerminalCommand {
             command,
+            name: T::command_name(),
+            started,
+            invocation: entered.map(|entered| entered.invocation),
+            buffer,
             terminal_line,
+            prompt_requested,
         }
     }
 }
 
-/// Parsed raw terminal command into `command` and `args`.
-#[derive(Clone, Debug, PartialEq)]
-pub struct TerminalCommandEntered {
-    /// Command name
-    pub command: String,
-    /// Raw parsed arguments
-    pub args: Vec<ValueRawOwned>,
+/// Check the cooldown of the first system to see an invocation and parse it, printing any error.
+fn parse_first_invocation<T: CommandName + CommandArgs + CommandHelp>(
+    entered: &BufferedCommand,
+    args: Vec<ValueRawOwned>,
+    buffer: &TerminalCommandBuffer,
+    terminal_line: &mut EventWriter<PrintTerminalLine>,
+    cooldowns: &mut TerminalCooldowns,
+    now: f64,
+) -> Option<T> {
+    let mut send = |line: String| {
+        terminal_line.send(PrintTerminalLine::new(line).with_meta(entered.invocation));
+    };
+    if let Some(remaining) = cooldowns.remaining(T::command_name(), now) {
+        let remaining = format!("{:.1}", remaining.as_secs_f64());
+        send(fill(&buffer.strings.on_cooldown, &[&remaining]));
+        return None;
+    }
+
+    let arg_count = T::command_help().map_or(Ok(()), |help| help.check_arg_count(args.len()));
+    match arg_count.and_then(|()| T::from_values(args)) {
+        Ok(value) => {
+            cooldowns.trigger(T::command_name(), now);
+            Some(value)
+        }
+        Err(err) => {
+            send(buffer.strings.from_value_error(&err));
+            match err {
+                FromValueError::UnexpectedArgType { .. }
+                | FromValueError::NotEnoughArgs
+                | FromValueError::TooManyArgs { .. }
+                | FromValueError::Custom(_) => {
+                    if let Some(help_text) = T::command_help() {
+                        send(help_text.help_text_localized(&buffer.help_format, &buffer.strings));
+                    }
+                }
+                FromValueError::ValueTooLarge { .. } => {}
+            }
+            None
+        }
+    }
 }
 
-/// Events to print to the terminal.
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct PrintTerminalLine {
-    /// Terminal line
-    pub line: String,
+/// An entered command waiting in the [`TerminalCommandBuffer`].
+struct BufferedCommand {
+    id: u64,
+    invocation: TerminalInvocation,
+    /// Registered name of the command
+    command: String,
+    /// Taken by the command system if it is the only one reading the command, see [`TerminalCommandBuffer::readers`]
+    args: Mutex<Vec<ValueRawOwned>>,
+    /// Whether the first command system to see this invocation accepted it
+    outcome: AtomicU8,
 }
 
-impl PrintTerminalLine {
-    /// Creates a new terminal line to print.
-    pub const fn new(line: String) -> Self {
-        Self { line }
-    }
+impl BufferedCommand {
+    const UNSEEN: u8 = 0;
+    const ACCEPTED: u8 = 1;
+    const REJECTED: u8 = 2;
 }
 
-/// Terminal configuration
-#[derive(Clone)]
-pub struct TerminalConfiguration {
-    /// Registered keys for toggling the terminal
-    /// Left position
-    pub left_pos: f32,
-    /// Top position
-    pub top_pos: f32,
-    /// Terminal height
-    pub height: f32,
-    /// Terminal width
-    pub width: f32,
-    /// Registered terminal commands
-    pub commands: BTreeMap<&'static str, Option<CommandInfo>>,
-    /// Number of commands to store in history
+/// Keeps entered commands until every command system has seen them.
+///
+/// Events only live for two frames, so command systems in schedules which don't run every frame,
+/// such as a fixed timestep, would otherwise miss commands.
+/// Each [`TerminalCommand`] tracks the id of the next command it hasn't seen.
+#[derive(Default)]
+pub(crate) struct TerminalCommandBuffer {
+    commands: VecDeque<BufferedCommand>,
+    next_id: u64,
+    /// Cursor of each command system, with the name of the command it reads
+    consumers: Vec<(&'static str, Arc<AtomicU64>)>,
+    reader: ManualEventReader<TerminalCommandEntered>,
+    /// Copy of [`TerminalConfiguration::help_format`], so command systems can also use the configuration
+    help_format: HelpFormat,
+    /// Copy of [`TerminalStrings`], so command systems can also change them
+    strings: TerminalStrings,
+    /// Copy of [`TerminalConfiguration::slow_command_threshold`], or `None` if slow commands aren't reported
+    slow_command_threshold: Option<f32>,
+    /// Time spent running each command by its systems since the costs were last taken
+    costs: Mutex<HashMap<&'static str, Duration>>,
+    /// Invocations accepted by their first command system since last taken, with the command's name,
+    /// which the outcome watchdog waits on
+    started: Mutex<Vec<(TerminalInvocation, &'static str)>>,
+    /// Invocations marked done with [`TerminalCommand::done`] since last taken
+    finished: Mutex<Vec<TerminalInvocation>>,
+}
+
+impl TerminalCommandBuffer {
+    /// Commands are dropped once this many are buffered, even if not seen by every command system.
+    const MAX_BUFFERED: usize = 256;
+
+    fn register_consumer(&mut self, command: &'static str) -> Arc<AtomicU64> {
+        let cursor = Arc::new(AtomicU64::new(self.next_id));
+        self.consumers.push((command, cursor.clone()));
+        cursor
+    }
+
+    /// Number of command systems reading `command`.
+    fn readers(&self, command: &str) -> usize {
+        self.consumers
+            .iter()
+            .filter(|(name, _)| *name == command)
+            .count()
+    }
+
+    /// Time spent running each command since the last call, by registered command name.
+    pub(crate) fn take_costs(&mut self) -> HashMap<&'static str, Duration> {
+        mem::take(self.costs.get_mut().unwrap())
+    }
+
+    /// Invocations accepted since the last call, with the name of their command.
+    pub(crate) fn take_started(&mut self) -> Vec<(TerminalInvocation, &'static str)> {
+        mem::take(self.started.get_mut().unwrap())
+    }
+
+    /// Invocations marked done since the last call.
+    pub(crate) fn take_finished(&mut self) -> Vec<TerminalInvocation> {
+        mem::take(self.finished.get_mut().unwrap())
+    }
+
+    /// Returns true if `invocation` is waiting for a command system, or was accepted by one
+    /// since the outcome watchdog last took the accepted invocations.
+    pub(crate) fn is_in_progress(&self, invocation: TerminalInvocation) -> bool {
+        let buffered = self.commands.iter().any(|entered| {
+            entered.invocation == invocation
+                && entered.outcome.load(Ordering::Acquire) != BufferedCommand::REJECTED
+        });
+        buffered
+            || self
+                .started
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|(started, _)| *started == invocation)
+    }
+}
+
+pub(crate) fn buffer_terminal_commands(
+    mut buffer: ResMut<TerminalCommandBuffer>,
+    events: Res<Events<TerminalCommandEntered>>,
+    queue: Res<TerminalCommandQueue>,
+    config: Res<TerminalConfiguration>,
+    registry: Res<CommandRegistry>,
+    strings: Res<TerminalStrings>,
+    mut terminal_line: EventWriter<PrintTerminalLine>,
+) {
+    let buffer = &mut *buffer;
+    if config.is_changed() {
+        buffer.help_format = config.help_format.clone();
+        buffer.slow_command_threshold = config
+            .report_slow_commands
+            .then(|| config.slow_command_threshold);
+    }
+    if strings.is_changed() {
+        buffer.strings = strings.clone();
+    }
+    for (event, event_id) in buffer.reader.iter_with_id(&events) {
+        // Commands sent as events rather than through the queue get an invocation here
+        let invocation = queue
+            .released
+            .iter()
+            .find(|(id, _)| *id == event_id.id)
+            .map_or_else(TerminalInvocation::next, |(_, invocation)| *invocation);
+        if config.mirror_to_log {
+            log_command(event);
+        }
+        let TerminalCommandEntered { mut command, args } = event.clone();
+        // Command systems only look for their registered name
+        if let Some(name) = registry.resolve(&command) {
+            if name != command {
+                command = name.to_string();
+            }
+        }
+        if let Some(note) = registry.deprecation(&command) {
+            let msg = fill(&buffer.strings.deprecated, &[&command, &note]);
+            terminal_line.send(PrintTerminalLine::warn(msg).with_meta(invocation));
+        }
+        if let Err(msg) = registry.validate(&command, &args) {
+            let msg = fill(&buffer.strings.custom_error, &[&msg]);
+            terminal_line.send(PrintTerminalLine::new(msg).with_meta(invocation));
+            continue;
+        }
+        buffer.commands.push_back(BufferedCommand {
+            id: buffer.next_id,
+            invocation,
+            command,
+            args: Mutex::new(args),
+            outcome: AtomicU8::new(BufferedCommand::UNSEEN),
+        });
+        buffer.next_id += 1;
+    }
+
+    // Forget command systems which have been dropped
+    buffer
+        .consumers
+        .retain(|(_, cursor)| Arc::strong_count(cursor) > 1);
+    let consumed = buffer
+        .consumers
+        .iter()
+        .map(|(_, cursor)| cursor.load(Ordering::Acquire))
+        .min()
+        .unwrap_or(buffer.next_id);
+    while let Some(entered) = buffer.commands.front() {
+        if entered.id >= consumed && buffer.commands.len() <= TerminalCommandBuffer::MAX_BUFFERED {
+            break;
+        }
+        buffer.commands.pop_front();
+    }
+}
+
+/// Parsed raw terminal command into `command` and `args`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerminalCommandEntered {
+    /// Command name
+    pub command: String,
+    /// Raw parsed arguments
+    pub args: Vec<ValueRawOwned>,
+}
+
+/// Event sent when a line submitted in the terminal couldn't be parsed as a command.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerminalParseFailed {
+    /// Submitted line
+    pub input: String,
+    /// Why the line couldn't be parsed
+    pub error: ParseError,
+}
+
+/// Events to print to the terminal.
+///
+/// Each line printed is added to the scrollback as a [`TerminalLine`](crate::TerminalLine),
+/// carrying the metadata attached with [`PrintTerminalLine::with_meta`].
+///
+/// Like [`TerminalLine`](crate::TerminalLine)s, printed lines are equal when their text is, ignoring metadata.
+#[derive(Clone, Debug)]
+pub struct PrintTerminalLine {
+    /// Terminal line
+    pub line: String,
+    meta: LineMeta,
+}
+
+impl PartialEq for PrintTerminalLine {
+    fn eq(&self, other: &Self) -> bool {
+        self.line == other.line
+    }
+}
+
+impl Eq for PrintTerminalLine {}
+
+/// Prints the line with its metadata.
+impl From<TerminalLine> for PrintTerminalLine {
+    fn from(line: TerminalLine) -> Self {
+        let (line, meta) = line.into_parts();
+        PrintTerminalLine { line, meta }
+    }
+}
+
+impl PrintTerminalLine {
+    /// Creates a new terminal line to print.
+    pub const fn new(line: String) -> Self {
+        Self {
+            line,
+            meta: LineMeta::new(),
+        }
+    }
+
+    /// Attach `value` to the printed line, replacing any value of the same type.
+    ///
+    /// See [`TerminalLine::get_meta`](crate::TerminalLine::get_meta).
+    pub fn with_meta<T: Any + Send + Sync>(mut self, value: T) -> Self {
+        self.meta.insert(value);
+        self
+    }
+
+    /// The attached value of type `T`, if there is one.
+    pub fn get_meta<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.meta.get()
+    }
+
+    /// The attached [`Severity`], if there is one.
+    pub fn severity(&self) -> Option<Severity> {
+        self.get_meta().copied()
+    }
+
+    /// Creates a line to print with [`Severity::Info`].
+    pub fn info(line: impl Into<String>) -> Self {
+        Self::new(line.into()).with_meta(Severity::Info)
+    }
+
+    /// Creates a line to print with [`Severity::Warn`].
+    pub fn warn(line: impl Into<String>) -> Self {
+        Self::new(line.into()).with_meta(Severity::Warn)
+    }
+
+    /// Creates a line to print with [`Severity::Error`].
+    pub fn error(line: impl Into<String>) -> Self {
+        Self::new(line.into()).with_meta(Severity::Error)
+    }
+
+    /// Creates a line to print with [`Severity::Ok`].
+    pub fn ok(line: impl Into<String>) -> Self {
+        Self::new(line.into()).with_meta(Severity::Ok)
+    }
+
+    /// Creates a line to print with [`Severity::Failed`].
+    pub fn failed(line: impl Into<String>) -> Self {
+        Self::new(line.into()).with_meta(Severity::Failed)
+    }
+}
+
+/// Lines to print before the terminal systems have run, such as boot diagnostics.
+///
+/// [`PrintTerminalLine`] events sent from startup systems are read on the first update,
+/// but events only live for two frames, and plugins can't send events while the app is being built.
+/// Lines pushed here are printed before any event on the next update, whenever they were pushed.
+///
+/// # Example
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use leafwing_terminal::{PrintTerminalLine, TerminalBootBuffer, TerminalCorePlugin};
+/// #
+/// let mut app = App::new();
+/// app.add_plugin(TerminalCorePlugin);
+/// app.world
+///     .resource_mut::<TerminalBootBuffer>()
+///     .push(PrintTerminalLine::new("renderer: vulkan".to_string()));
+/// ```
+#[derive(Default)]
+pub struct TerminalBootBuffer {
+    lines: Vec<PrintTerminalLine>,
+}
+
+impl TerminalBootBuffer {
+    /// Print `line` on the next update.
+    pub fn push(&mut self, line: PrintTerminalLine) {
+        self.lines.push(line);
+    }
+
+    /// Number of lines waiting to be printed.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Whether every pushed line has been printed.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+/// Events to briefly show in the status line between the scrollback and the input.
+///
+/// Notices are for things happening around the terminal rather than command output,
+/// so they aren't added to the scrollback unless [`TerminalConfiguration::mirror_notices`] is set.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TerminalNotice {
+    /// Notice text
+    pub text: String,
+}
+
+impl TerminalNotice {
+    /// Creates a new notice to show.
+    pub const fn new(text: String) -> Self {
+        Self { text }
+    }
+}
+
+/// Whether the terminal is open, toggled with [`TerminalConfiguration::keys`].
+///
+/// Games can read it to pause the simulation or ignore their own hotkeys while the terminal is open,
+/// and set it to open or close the terminal, whose input takes focus on the next frame.
+/// [`TerminalOpened`] and [`TerminalClosed`] are sent when it changes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TerminalOpen(pub bool);
+
+/// Event sent when the terminal opens, see [`TerminalOpen`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TerminalOpened;
+
+/// Event sent when the terminal closes, see [`TerminalOpen`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TerminalClosed;
+
+/// Event sent when the errors printed while the terminal is closed or scrolled up
+/// reach [`TerminalConfiguration::attention_threshold`].
+///
+/// It is sent once until the errors are seen, so games can play a sound or flash the taskbar.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TerminalAttentionRequested {
+    /// Number of unseen errors
+    pub errors: usize,
+}
+
+/// Terminal configuration
+#[derive(Clone)]
+pub struct TerminalConfiguration {
+    /// Registered keys for toggling the terminal
+    pub keys: Vec<KeyCode>,
+    /// Hide the keyboard from the game while the terminal is open, and the mouse while it is over the terminal
+    ///
+    /// Keys and mouse buttons are released in `Input<KeyCode>` and `Input<MouseButton>`, and typed characters
+    /// and mouse wheel events are dropped, so typing `wasd` doesn't also move the player.
+    /// The toggle [`keys`](TerminalConfiguration::keys) and Escape still reach the game.
+    pub block_game_input: bool,
+    /// Left position
+    ///
+    /// The position and size are in egui points and shrink to keep the terminal inside the window.
+    pub left_pos: f32,
+    /// Top position
+    pub top_pos: f32,
+    /// Terminal height
+    pub height: f32,
+    /// Terminal width
+    pub width: f32,
+    /// Where the terminal is drawn: at the position and size above, or dropped down from the top of the window
+    pub layout: TerminalLayout,
+    /// Let the user drag the terminal around, updating the position above
+    ///
+    /// Only the [`TerminalLayout::Window`] layout can be moved.
+    pub movable: bool,
+    /// Let the user resize the terminal from its bottom right corner, updating the size above
+    ///
+    /// Only the [`TerminalLayout::Window`] layout can be resized.
+    pub resizable: bool,
+    /// Registered terminal commands
+    ///
+    /// Only filled in by [`AddTerminalCommand`], and changing it has no effect.
+    #[deprecated(note = "use the `CommandRegistry` resource instead")]
+    pub commands: BTreeMap<&'static str, Option<CommandInfo>>,
+    /// Number of commands to store in history
     pub history_size: usize,
+    /// Keep the history between runs, one command per line under `history` in the [`TerminalStore`](crate::TerminalStore)
+    ///
+    /// The newest `history_size` saved commands are loaded at startup,
+    /// and the history is saved whenever it changes. Failures to load or save are shown as notices.
+    pub persist_history: bool,
+    /// Number of commands kept in the saved history, dropping the oldest when saving
+    ///
+    /// It can be larger than `history_size`: commands dropped from the history stay saved until this is reached.
+    pub history_file_size: usize,
+    /// Number of lines kept in the scrollback, dropping the oldest, or `0` to keep every line
+    pub scrollback_size: usize,
+    /// Number of printed lines waiting to be added to the scrollback, or `0` for no limit
+    ///
+    /// Beyond it, the oldest lines which aren't replies to a command, warnings or errors are dropped,
+    /// and a line telling how many were dropped is printed in their place.
+    pub print_queue_size: usize,
+    /// Number of printed lines added to the scrollback each frame, or `0` for all of them
+    ///
+    /// The rest wait in a queue bounded by `print_queue_size`, so a burst of output is spread over a few frames.
+    pub print_lines_per_frame: usize,
+    /// Number of queued commands to run each frame, or 0 for all of them
+    ///
+    /// See [`TerminalCommandQueue`](crate::TerminalCommandQueue) for the tradeoff.
+    pub command_batch_size: usize,
+    /// Seconds of command systems to run each frame, replacing `command_batch_size` when set
+    ///
+    /// Queued commands are released until their cost, as measured the last time each command ran,
+    /// would exceed the budget, so long scripts of cheap commands run in few frames
+    /// while an expensive command gets a frame to itself. At least one command is released each frame,
+    /// and commands which haven't run yet are assumed to take the whole budget.
+    pub command_time_budget: Option<f32>,
+    /// What to do with queued commands while the window is unfocused or minimized
+    pub unfocused_policy: UnfocusedPolicy,
+    /// Modifier held with Up/Down to search history by the typed prefix
+    pub history_search_modifier: Modifiers,
+    /// Step through history with the mouse wheel while hovering the input, like Up and Down
+    ///
+    /// The wheel then no longer scrolls anything else while over the input.
+    pub history_wheel: bool,
+    /// Seconds Up, Down, Tab, Page Up or Page Down is held before it repeats
+    pub key_repeat_delay: f32,
+    /// Steps per second Up, Down, Tab, Page Up and Page Down repeat at while held, or `0` to not repeat
+    ///
+    /// The terminal times held keys itself rather than relying on the key repeat of the platform.
+    pub key_repeat_rate: f32,
+    /// Key opening a filterable list of the whole history above the input, or `None` to disable it
+    ///
+    /// Up and Down pick an entry, Enter inserts it into the input, Delete removes it from the history
+    /// and Escape closes the list.
+    pub history_browser_key: Option<KeyCode>,
+    /// Key opening a fuzzy search of every command and its description above the input, or `None` to disable it
+    ///
+    /// Names matching the typed filter come before descriptions matching it. Up and Down pick a command,
+    /// Enter starts typing it in the input and Escape closes the search.
+    pub palette_key: Option<KeyCode>,
+    /// Modifier held with [`TerminalConfiguration::palette_key`]
+    pub palette_modifier: Modifiers,
+    /// Key searching the history backward for entries containing the typed text, or `None` to disable it
+    ///
+    /// The input shows the newest match, pressing the key again goes to older ones.
+    /// Enter submits the match and Escape cancels the search, putting back what was typed before it.
+    pub reverse_search_key: Option<KeyCode>,
+    /// Modifier held with [`TerminalConfiguration::reverse_search_key`]
+    pub reverse_search_modifier: Modifiers,
+    /// Close the terminal once a submitted command is dispatched
+    ///
+    /// Submitting with Shift+Enter does the opposite for that command.
+    pub close_on_submit: bool,
+    /// Show errors and failures printed while the terminal is closed as toasts
+    pub toasts: bool,
+    /// Also show `[ok]` outcomes as toasts
+    pub toast_successes: bool,
+    /// Seconds each toast is shown for
+    pub toast_duration: f32,
+    /// Maximum number of toasts shown at once
+    pub max_toasts: usize,
+    /// Flash the terminal and count the errors printed while it is closed or scrolled up
+    ///
+    /// The count is shown at the bottom of the scrollback until it is scrolled to the bottom or the terminal is opened.
+    pub attention: bool,
+    /// Seconds the terminal border flashes for after an unseen error
+    pub attention_flash_duration: f32,
+    /// Number of unseen errors at which a [`TerminalAttentionRequested`] is sent
+    pub attention_threshold: usize,
+    /// Double-click a scrollback line to copy it, or Ctrl+double-click to insert it at the cursor
+    pub interactive_scrollback: bool,
+    /// Number the scrollback lines in a gutter to their left, see [`TerminalState::line_number`]
+    pub line_numbers: bool,
+    /// Prefix scrollback lines with the time since startup they were printed at, as `[00:12:03.210]`.
+    ///
+    /// Copied and dumped lines are prefixed the same way.
+    /// Lines without a time, such as prompts, are padded instead.
+    pub show_timestamps: bool,
+    /// Show a button copying a scrollback line while it is hovered
+    pub copy_line_buttons: bool,
+    /// Characters of a scrollback line rendered before it is truncated, or 0 for no limit
+    ///
+    /// Clicking the truncation suffix expands the line. The full line is always kept in the scrollback.
+    pub max_line_length: usize,
+    /// What to do with pasted text spanning several lines
+    pub paste_behavior: PasteBehavior,
+    /// Insert the closing quote when typing an opening quote
+    pub auto_close_quotes: bool,
+    /// Number of lines kept by each `tail` pane
+    pub tail_size: usize,
+    /// Message printed when the terminal is first opened, such as a banner or "type help to get started"
+    ///
+    /// It is printed on opening rather than at startup so it comes after anything printed before then.
+    pub motd: Option<String>,
+    /// Names of commands whose invocations are left out of the input history
+    ///
+    /// They are still echoed and run as usual.
+    pub history_exclude: HashSet<String>,
+    /// Seconds a [`TerminalNotice`] is shown in the status line for
+    pub notice_duration: f32,
+    /// Also add notices to the scrollback
+    pub mirror_notices: bool,
+    /// Layout of command help
+    pub help_format: HelpFormat,
+    /// Print a command's help instead of running it when the typed line ends with a bare `?`, like `spawn ?`
+    ///
+    /// A quoted `"?"` is still passed to the command.
+    pub help_suffix: bool,
+    /// List the commands starting with what follows a typed `?`, like `?spawn`
+    pub help_search: bool,
+    /// Version printed at the top of bug report snippets made from selected scrollback lines
+    pub app_version: Option<String>,
+    /// Print each line of a [`PrintTerminalLine`] containing newlines as its own scrollback line
+    ///
+    /// Turn this off to keep the newlines inside a single scrollback line.
+    pub split_lines: bool,
+    /// Also log entered commands and printed lines with target `leafwing_terminal`
+    ///
+    /// Commands and lines are logged at info level, errors and failures at warn level.
+    pub mirror_to_log: bool,
+    /// Draw the terminal in another open window once the primary window closes
+    ///
+    /// Otherwise the terminal isn't drawn while there is no primary window, but commands keep running.
+    pub move_to_open_window: bool,
+    /// Printed before each echoed command
+    pub echo_prompt: String,
+    /// Show the command an alias runs after echoed commands starting with the alias, such as `gg 1000 → give_gold 1000`
+    pub echo_aliases: bool,
+    /// Draw the terminal above every other egui window, like a classic console
+    ///
+    /// Otherwise it is stacked with other windows and comes to the front when clicked.
+    pub always_on_top: bool,
+    /// Append how long a command took to its `[ok]` or `[failed]` line when it is slow
+    ///
+    /// The time is measured from parsing the command's arguments until the outcome is printed,
+    /// so only outcomes printed by the same run of the command system are timed.
+    /// Toggled at runtime with the `timing` command.
+    pub report_slow_commands: bool,
+    /// Seconds a command can take before it is reported as slow
+    pub slow_command_threshold: f32,
+    /// Frames to wait for a command to call `ok` or `failed` before it is completed without an outcome,
+    /// or `0` to wait forever
+    ///
+    /// See [`TerminalCommandCompleted`](crate::TerminalCommandCompleted). In debug builds a hint is printed when it happens.
+    pub outcome_timeout: usize,
+    /// Colors of the terminal, read every frame so themes can be swapped at runtime
+    pub style: TerminalStyle,
+    /// Height of the input row, in points
+    ///
+    /// The row grows to fit [`TerminalConfiguration::font_size`] if it is too short.
+    pub input_height: f32,
+    /// Size of the text in the scrollback and input, in points
+    pub font_size: f32,
+    /// Font family of the scrollback and input, or `None` for egui's monospace family
+    ///
+    /// Named families must be added to the egui fonts first, see [`egui::FontDefinitions`](bevy_egui::egui::FontDefinitions).
+    pub font: Option<FontFamily>,
+    /// Space around the scrollback, in points
+    pub scrollback_margin: Margin,
+    /// Space around the input row, in points
+    pub input_margin: Margin,
 }
 
 impl Default for TerminalConfiguration {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
+            keys: vec![KeyCode::Grave],
+            block_game_input: true,
             left_pos: 200.0,
             top_pos: 100.0,
             height: 400.0,
             width: 800.0,
+            layout: TerminalLayout::Window,
+            movable: false,
+            resizable: false,
             commands: BTreeMap::new(),
             history_size: 20,
+            persist_history: false,
+            history_file_size: 1000,
+            scrollback_size: 5000,
+            print_queue_size: 10_000,
+            print_lines_per_frame: 1000,
+            command_batch_size: 1,
+            command_time_budget: None,
+            unfocused_policy: UnfocusedPolicy::AllowAll,
+            history_search_modifier: Modifiers::CTRL,
+            history_wheel: false,
+            key_repeat_delay: 0.4,
+            key_repeat_rate: 15.0,
+            history_browser_key: Some(KeyCode::F7),
+            palette_key: Some(KeyCode::P),
+            palette_modifier: Modifiers::CTRL,
+            reverse_search_key: Some(KeyCode::R),
+            reverse_search_modifier: Modifiers::CTRL,
+            close_on_submit: false,
+            toasts: false,
+            toast_successes: false,
+            toast_duration: 4.0,
+            max_toasts: 3,
+            attention: true,
+            attention_flash_duration: 0.4,
+            attention_threshold: 1,
+            interactive_scrollback: true,
+            line_numbers: false,
+            show_timestamps: false,
+            copy_line_buttons: false,
+            max_line_length: 4000,
+            paste_behavior: PasteBehavior::ExecuteLines,
+            auto_close_quotes: false,
+            tail_size: 100,
+            motd: None,
+            history_exclude: HashSet::new(),
+            notice_duration: 3.0,
+            mirror_notices: false,
+            help_format: HelpFormat::default(),
+            help_suffix: true,
+            help_search: true,
+            app_version: None,
+            split_lines: true,
+            mirror_to_log: false,
+            move_to_open_window: false,
+            echo_prompt: "$ ".to_string(),
+            echo_aliases: false,
+            always_on_top: false,
+            report_slow_commands: true,
+            slow_command_threshold: 0.1,
+            outcome_timeout: 120,
+            style: TerminalStyle::default(),
+            input_height: 24.0,
+            font_size: 12.0,
+            font: None,
+            scrollback_margin: Margin::same(0.0),
+            input_margin: Margin::same(0.0),
+        }
+    }
+}
+
+/// Colors of the terminal, see [`TerminalConfiguration::style`].
+///
+/// The default is [`TerminalStyle::dark`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TerminalStyle {
+    /// Fill behind the scrollback, the input and the popups over them
+    pub background: Color32,
+    /// Color of text without a color of its own, such as command output
+    pub text: Color32,
+    /// Color of echoed commands, so what was typed stands out from the output
+    pub prompt: Color32,
+    /// Color of scrollback lines with each [`Severity`], whose error color also flashes the border
+    pub severity: SeverityColors,
+    /// Line between the scrollback and the input, or `None` to hide it
+    pub separator: Option<SeparatorStyle>,
+}
+
+impl TerminalStyle {
+    /// Light text on black.
+    pub fn dark() -> Self {
+        Self {
+            background: Color32::BLACK,
+            text: Color32::LIGHT_GRAY,
+            prompt: Color32::from_rgb(120, 190, 255),
+            severity: SeverityColors::default(),
+            separator: Some(SeparatorStyle::default()),
+        }
+    }
+
+    /// Dark text on white.
+    pub fn light() -> Self {
+        Self {
+            background: Color32::from_gray(248),
+            text: Color32::from_gray(30),
+            prompt: Color32::from_rgb(0, 70, 160),
+            severity: SeverityColors {
+                info: Color32::from_rgb(0, 100, 140),
+                warn: Color32::from_rgb(150, 100, 0),
+                error: Color32::from_rgb(190, 30, 30),
+                ok: Color32::DARK_GREEN,
+                failed: Color32::DARK_RED,
+            },
+            separator: Some(SeparatorStyle {
+                color: Color32::from_gray(200),
+                thickness: 1.0,
+            }),
+        }
+    }
+}
+
+impl Default for TerminalStyle {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// How the line between the scrollback and the input is drawn, see [`TerminalStyle::separator`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeparatorStyle {
+    /// Line color
+    pub color: Color32,
+    /// Line thickness, in points
+    pub thickness: f32,
+}
+
+impl Default for SeparatorStyle {
+    fn default() -> Self {
+        Self {
+            color: Color32::from_gray(60),
+            thickness: 1.0,
+        }
+    }
+}
+
+/// Color of scrollback lines with each [`Severity`], see [`TerminalStyle::severity`].
+///
+/// Lines without a severity have [`TerminalStyle::text`] color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeverityColors {
+    /// Color of [`Severity::Info`] lines
+    pub info: Color32,
+    /// Color of [`Severity::Warn`] lines
+    pub warn: Color32,
+    /// Color of [`Severity::Error`] lines
+    pub error: Color32,
+    /// Color of [`Severity::Ok`] lines
+    pub ok: Color32,
+    /// Color of [`Severity::Failed`] lines
+    pub failed: Color32,
+}
+
+impl SeverityColors {
+    /// Color of lines with `severity`.
+    pub fn color(&self, severity: Severity) -> Color32 {
+        match severity {
+            Severity::Info => self.info,
+            Severity::Warn => self.warn,
+            Severity::Error => self.error,
+            Severity::Ok => self.ok,
+            Severity::Failed => self.failed,
+        }
+    }
+}
+
+impl Default for SeverityColors {
+    fn default() -> Self {
+        Self {
+            info: Color32::LIGHT_BLUE,
+            warn: Color32::YELLOW,
+            error: Color32::LIGHT_RED,
+            ok: Color32::GREEN,
+            failed: Color32::RED,
         }
     }
 }
 
+/// Where the terminal is drawn, see [`TerminalConfiguration::layout`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TerminalLayout {
+    /// A floating window at [`TerminalConfiguration::left_pos`] and [`TerminalConfiguration::top_pos`],
+    /// sized by [`TerminalConfiguration::width`] and [`TerminalConfiguration::height`]
+    Window,
+    /// A console spanning the width of the window from its top, like in classic shooters
+    ///
+    /// It follows the window as it is resized.
+    TopDropDown {
+        /// Part of the window height the terminal covers, from 0 to 1
+        height_fraction: f32,
+    },
+}
+
+/// How the input handles pasted text spanning several lines.
+///
+/// Newlines inside quoted strings don't split the paste.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PasteBehavior {
+    /// Submit each non-empty line as its own command, in order
+    ///
+    /// While a prompt is waiting for an answer this behaves like `FirstLineOnly`.
+    ExecuteLines,
+    /// Insert only the first line and warn about the rest
+    FirstLineOnly,
+    /// Insert the text as is, newlines included
+    InsertRaw,
+}
+
 /// Add a terminal commands to Bevy app.
 pub trait AddTerminalCommand {
     /// Add a terminal command with a given system.
     ///
     /// This registers the terminal command so it will print with the built-in `help` terminal command.
+    /// Commands whose name can't be typed, see [`validate_command_name`], are logged as errors and not registered.
     ///
     /// # Example
     ///
@@ -451,6 +1860,107 @@ pub trait AddTerminalCommand {
     ) -> &mut Self
     where
         Sys: IntoSystemDescriptor<Params>;
+
+    /// Add a terminal command with a given system in a given stage.
+    ///
+    /// Use this for commands which must run in a particular stage, such as a fixed timestep.
+    /// Entered commands are kept until the command system has seen them,
+    /// even if its stage doesn't run every frame.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy::core::FixedTimestep;
+    /// # use bevy::prelude::*;
+    /// # use leafwing_terminal::{AddTerminalCommand, TerminalCommand};
+    /// #
+    /// App::new()
+    ///     .add_stage_before(
+    ///         CoreStage::Update,
+    ///         "fixed_update",
+    ///         SystemStage::parallel().with_run_criteria(FixedTimestep::step(0.1)),
+    ///     )
+    ///     .add_terminal_command_to_stage::<SpawnWaveCommand, _, _>("fixed_update", spawn_wave_command);
+    /// #
+    /// # /// Spawns a wave of enemies.
+    /// # #[derive(TerminalCommand)]
+    /// # #[terminal_command(name = "spawn_wave")]
+    /// # struct SpawnWaveCommand;
+    /// #
+    /// # fn spawn_wave_command(mut spawn_wave: TerminalCommand<SpawnWaveCommand>) {}
+    /// ```
+    fn add_terminal_command_to_stage<T: CommandName + CommandHelp, Sys, Params>(
+        &mut self,
+        stage: impl StageLabel,
+        system: Sys,
+    ) -> &mut Self
+    where
+        Sys: IntoSystemDescriptor<Params>;
+
+    /// Validate the arguments of a terminal command while they are typed and before it runs.
+    ///
+    /// See [`CommandRegistry::set_validator`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use leafwing_terminal::{AddTerminalCommand, TerminalCommand, ValueRawOwned};
+    /// #
+    /// App::new()
+    ///     .add_terminal_command::<LoadLevelCommand, _, _>(load_level_command)
+    ///     .add_terminal_command_validator::<LoadLevelCommand>(validate_level);
+    ///
+    /// fn validate_level(args: &[ValueRawOwned]) -> Result<(), String> {
+    ///     match args.first() {
+    ///         Some(ValueRawOwned::String(level)) if level != "forest" => {
+    ///             Err(format!("unknown level '{level}'"))
+    ///         }
+    ///         _ => Ok(()),
+    ///     }
+    /// }
+    /// #
+    /// # /// Loads a level.
+    /// # #[derive(TerminalCommand)]
+    /// # #[terminal_command(name = "loadlevel")]
+    /// # struct LoadLevelCommand {
+    /// #     level: String,
+    /// # }
+    /// #
+    /// # fn load_level_command(mut load_level: TerminalCommand<LoadLevelCommand>) {}
+    /// ```
+    fn add_terminal_command_validator<T: CommandName>(
+        &mut self,
+        validator: CommandValidator,
+    ) -> &mut Self;
+
+    /// Complete the arguments of a terminal command with Tab.
+    ///
+    /// See [`CommandCompleters`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use leafwing_terminal::{AddTerminalCommand, PathCompleter, TerminalCommand};
+    /// #
+    /// App::new()
+    ///     .add_terminal_command::<LoadLevelCommand, _, _>(load_level_command)
+    ///     .add_terminal_command_completer::<LoadLevelCommand>(PathCompleter::new("assets/levels"));
+    /// #
+    /// # /// Loads a level.
+    /// # #[derive(TerminalCommand)]
+    /// # #[terminal_command(name = "loadlevel")]
+    /// # struct LoadLevelCommand {
+    /// #     level: String,
+    /// # }
+    /// #
+    /// # fn load_level_command(mut load_level: TerminalCommand<LoadLevelCommand>) {}
+    /// ```
+    fn add_terminal_command_completer<T: CommandName>(
+        &mut self,
+        completer: impl ArgCompleter,
+    ) -> &mut Self;
 }
 
 impl AddTerminalCommand for App {
@@ -461,45 +1971,1849 @@ impl AddTerminalCommand for App {
     where
         Sys: IntoSystemDescriptor<Params>,
     {
-        let sys = move |mut config: ResMut<TerminalConfiguration>| {
+        self.add_terminal_command_to_stage::<T, _, _>(CoreStage::Update, system)
+    }
+
+    fn add_terminal_command_to_stage<T: CommandName + CommandHelp, Sys, Params>(
+        &mut self,
+        stage: impl StageLabel,
+        system: Sys,
+    ) -> &mut Self
+    where
+        Sys: IntoSystemDescriptor<Params>,
+    {
+        #[allow(deprecated)]
+        let sys = move |mut config: ResMut<TerminalConfiguration>,
+                        mut registry: ResMut<CommandRegistry>,
+                        mut cooldowns: ResMut<TerminalCooldowns>| {
             let name = T::command_name();
-            if config.commands.contains_key(name) {
+            if let Err(reason) = validate_command_name(name) {
+                error!(
+                    "terminal command '{}' of {} can't be typed because {}, so it wasn't registered",
+                    name,
+                    std::any::type_name::<T>(),
+                    reason
+                );
+                return;
+            }
+            let help = T::command_help();
+            if let Some(cooldown) = help.as_ref().and_then(|help| help.cooldown) {
+                cooldowns.set_cooldown(name, cooldown);
+            }
+            if help.as_ref().is_some_and(|help| help.exclude_from_history) {
+                config.history_exclude.insert(name.to_string());
+            }
+            config.commands.insert(name, help.clone());
+            if registry.insert(name, help) {
                 warn!(
                     "terminal command '{}' already registered and was overwritten",
                     name
                 );
             }
-            config.commands.insert(name, T::command_help());
         };
 
-        self.add_startup_system(sys).add_system(system)
+        self.add_startup_system(sys)
+            .add_system_to_stage(stage, system)
+    }
+
+    fn add_terminal_command_validator<T: CommandName>(
+        &mut self,
+        validator: CommandValidator,
+    ) -> &mut Self {
+        self.add_startup_system(move |mut registry: ResMut<CommandRegistry>| {
+            registry.set_validator(T::command_name(), validator);
+        })
+    }
+
+    fn add_terminal_command_completer<T: CommandName>(
+        &mut self,
+        completer: impl ArgCompleter,
+    ) -> &mut Self {
+        let mut completer = Some(completer);
+        self.add_startup_system(move |mut completers: ResMut<CommandCompleters>| {
+            if let Some(completer) = completer.take() {
+                completers.set_completer(T::command_name(), completer);
+            }
+        })
     }
 }
 
-pub(crate) struct TerminalState {
+/// Identity of a scrollback line, which unlike its index doesn't change as the oldest lines are trimmed.
+///
+/// Ids count every line added since startup, so they aren't reused after `clear` either.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct LineId(pub(crate) u64);
+
+/// Anchors and selected lines dropped by [`TerminalState::trim_scrollback`], not yet reported in a notice.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct TrimmedReferences {
+    /// Labels of the anchors whose divider was trimmed
+    pub(crate) anchors: Vec<String>,
+    /// Whether the selection was cut short or cleared
+    pub(crate) selection: bool,
+}
+
+/// Terminal input, scrollback and history.
+pub struct TerminalState {
     pub(crate) buf: String,
-    pub(crate) scrollback: Vec<String>,
+    pub(crate) scrollback: VecDeque<TerminalLine>,
+    /// Lines removed from the front of the scrollback since it was last cleared,
+    /// so the numbers of the remaining lines don't change
+    pub(crate) trimmed_lines: usize,
+    /// Id of the first scrollback line, see [`LineId`]
+    pub(crate) first_line: LineId,
+    /// Heights of the scrollback lines as last laid out
+    pub(crate) row_heights: RowHeights,
+    pub(crate) expanded: HashSet<LineId>,
+    pub(crate) selected: Option<LineId>,
+    /// Line Shift+clicked to extend the selection from `selected`
+    pub(crate) selection_end: Option<LineId>,
+    /// Submitted commands, oldest first
     pub(crate) history: VecDeque<String>,
+    /// Commands pushed to the history since it was last cleared, telling the saved history which are new
+    pub(crate) history_pushed: usize,
+    /// Entry shown while navigating history, counted back from the newest, or `0` for `history_scratch`
     pub(crate) history_index: usize,
+    /// Input typed before navigating history, shown again when navigating past the newest entry
+    pub(crate) history_scratch: String,
+    pub(crate) history_browser: Option<HistoryBrowser>,
+    pub(crate) history_search: Option<HistorySearch>,
+    pub(crate) palette: Option<CommandPalette>,
+    /// Candidates listed above the input by the last Tab completion
+    pub(crate) completions: Vec<String>,
+    pub(crate) history_prefix: Option<String>,
+    /// Mouse wheel scrolled over the input and not yet stepped through history
+    pub(crate) history_wheel: f32,
+    pub(crate) prompts: VecDeque<PendingPrompt>,
+    pub(crate) tails: Vec<Tail>,
+    pub(crate) anchors: Vec<ScrollAnchor>,
+    /// Scrollback line to scroll to in the next frame
+    pub(crate) scroll_to: Option<LineId>,
+    pub(crate) trimmed_references: TrimmedReferences,
+    /// Command typed lines are appended to, see `sticky`
+    pub(crate) sticky: Option<String>,
+    pub(crate) motd_printed: bool,
+    /// The notice shown in the status line and when it expires
+    pub(crate) notice: Option<(String, f64)>,
+    /// Whether the pointer was over the terminal when last drawn
+    pub(crate) pointer_over: bool,
+    /// Whether the scrollback was scrolled up from its bottom when last drawn
+    pub(crate) scrolled_up: bool,
+    /// Errors printed while the terminal was closed or scrolled up
+    pub(crate) unseen_errors: usize,
+    /// When the attention flash for the last unseen error ends
+    pub(crate) attention_until: f64,
 }
 
 impl Default for TerminalState {
     fn default() -> Self {
         TerminalState {
             buf: String::default(),
-            scrollback: Vec::new(),
-            history: VecDeque::from([String::new()]),
+            scrollback: VecDeque::new(),
+            trimmed_lines: 0,
+            first_line: LineId::default(),
+            row_heights: RowHeights::default(),
+            expanded: HashSet::new(),
+            selected: None,
+            selection_end: None,
+            history: VecDeque::new(),
+            history_pushed: 0,
             history_index: 0,
+            history_scratch: String::new(),
+            history_browser: None,
+            history_search: None,
+            palette: None,
+            completions: Vec::new(),
+            history_prefix: None,
+            history_wheel: 0.,
+            prompts: VecDeque::new(),
+            tails: Vec::new(),
+            anchors: Vec::new(),
+            scroll_to: None,
+            trimmed_references: TrimmedReferences::default(),
+            sticky: None,
+            motd_printed: false,
+            notice: None,
+            pointer_over: false,
+            scrolled_up: false,
+            unseen_errors: 0,
+            attention_until: 0.0,
         }
     }
 }
 
+/// What the terminal input is currently used for.
+///
+/// While the terminal is in a modal mode, commands queued with [`TerminalCommandQueue`](crate::TerminalCommandQueue)
+/// are held and released in order once it returns to [`TerminalMode::Normal`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TerminalMode {
+    /// Input is submitted as commands
+    Normal,
+    /// Input answers a prompt, see [`TerminalCommand::request_input`]
+    Prompt(PromptId),
+    /// Keys go to the history browser, see [`TerminalConfiguration::history_browser_key`]
+    HistoryBrowser,
+    /// Keys go to the command palette, see [`TerminalConfiguration::palette_key`]
+    CommandPalette,
+    /// Keys go to the reverse history search, see [`TerminalConfiguration::reverse_search_key`]
+    HistorySearch,
+}
+
+/// History navigation.
+///
+/// `history` is stored oldest first, and `history_index` counts back from the newest entry
+/// to the one currently shown in the buffer. At `0` the buffer shows `history_scratch`,
+/// holding what was typed before navigating.
+///
+/// Prefix navigation remembers the text typed when it started in `history_prefix`
+/// and only stops at entries beginning with it. It moves the same `history_index`,
+/// so plain navigation continues from wherever prefix navigation landed.
+/// Plain navigation, editing the buffer or submitting ends prefix navigation.
+impl TerminalState {
+    /// What the terminal input is currently used for.
+    pub fn mode(&self) -> TerminalMode {
+        if self.history_browser.is_some() {
+            return TerminalMode::HistoryBrowser;
+        }
+        if self.palette.is_some() {
+            return TerminalMode::CommandPalette;
+        }
+        if self.history_search.is_some() {
+            return TerminalMode::HistorySearch;
+        }
+        match self.prompts.front() {
+            Some(prompt) => TerminalMode::Prompt(prompt.id),
+            None => TerminalMode::Normal,
+        }
+    }
+
+    /// Lines printed to the terminal, oldest first.
+    ///
+    /// Only the last [`TerminalConfiguration::scrollback_size`] lines are kept.
+    pub fn scrollback(&self) -> &VecDeque<TerminalLine> {
+        &self.scrollback
+    }
+
+    /// The number of the scrollback line at `index`, counted from 1 at the first line printed since the last `clear`.
+    ///
+    /// See [`TerminalConfiguration::line_numbers`].
+    pub fn line_number(&self, index: usize) -> usize {
+        self.trimmed_lines + index + 1
+    }
+
+    /// Id of the scrollback line at `index`, or of a line yet to be added past the end.
+    pub(crate) fn line_id(&self, index: usize) -> LineId {
+        LineId(self.first_line.0 + index as u64)
+    }
+
+    /// Index of the scrollback line with `id`, or of the first or last line if the scrollback no longer reaches it.
+    pub(crate) fn clamp_line(&self, id: LineId) -> usize {
+        let index = usize::try_from(id.0.saturating_sub(self.first_line.0)).unwrap_or(usize::MAX);
+        index.min(self.scrollback.len().saturating_sub(1))
+    }
+
+    /// Scroll to the scrollback line at `index` in the next frame.
+    pub(crate) fn scroll_to_line(&mut self, index: usize) {
+        self.scroll_to = Some(self.line_id(index));
+    }
+
+    /// Number of errors printed while the terminal was closed or scrolled up, see [`TerminalConfiguration::attention`].
+    pub fn unseen_errors(&self) -> usize {
+        self.unseen_errors
+    }
+
+    /// The most recent line of command output, skipping echoed commands, outcomes and blank lines.
+    pub(crate) fn last_output(&self, strings: &TerminalStrings) -> Option<&str> {
+        self.scrollback
+            .iter()
+            .rev()
+            .filter(|line| line.get_meta::<Echo>().is_none())
+            .map(TerminalLine::as_str)
+            .find(|line| {
+                !line.trim().is_empty()
+                    && !line.starts_with(&strings.ok)
+                    && !line.starts_with(&strings.failed)
+            })
+    }
+
+    /// Submitted commands, most recent first.
+    pub fn history(&self) -> impl Iterator<Item = &str> {
+        self.history.iter().rev().map(String::as_str)
+    }
+
+    /// Add a submitted line to the history unless its command is in `history_exclude`.
+    pub(crate) fn record_submission(&mut self, line: String, config: &TerminalConfiguration) {
+        let excluded = line
+            .split_whitespace()
+            .next()
+            .is_some_and(|command| config.history_exclude.contains(command));
+        if excluded {
+            self.history_prefix = None;
+        } else {
+            self.push_history(line, config.history_size);
+        }
+    }
+
+    /// Add a submitted command to the history, dropping the oldest to keep at most `history_size` commands.
+    pub fn push_history(&mut self, cmd: String, history_size: usize) {
+        while !self.history.is_empty() && self.history.len() >= history_size {
+            self.history.pop_front();
+        }
+        if history_size > 0 {
+            self.history.push_back(cmd);
+            self.history_pushed += 1;
+        }
+        self.history_index = self.history_index.min(self.history.len());
+        self.history_prefix = None;
+    }
+
+    /// Forget all submitted commands and the scratch input.
+    pub(crate) fn clear_history(&mut self) {
+        self.history.clear();
+        self.history_pushed = 0;
+        self.history_index = 0;
+        self.history_scratch.clear();
+        self.history_prefix = None;
+    }
+
+    /// The entry `index` back from the newest, or the scratch input for `0`.
+    fn history_entry(&self, index: usize) -> &String {
+        match index {
+            0 => &self.history_scratch,
+            index => &self.history[self.history.len() - index],
+        }
+    }
+
+    /// Show the previous (older) history entry, returning whether the buffer changed.
+    pub(crate) fn history_previous(&mut self) -> bool {
+        self.history_prefix = None;
+        if self.history_index >= self.history.len() {
+            return false;
+        }
+
+        self.save_scratch();
+        self.history_index += 1;
+        self.buf = self.history_entry(self.history_index).clone();
+        true
+    }
+
+    /// Show the next (newer) history entry, returning whether the buffer changed.
+    pub(crate) fn history_next(&mut self) -> bool {
+        self.history_prefix = None;
+        if self.history_index == 0 {
+            return false;
+        }
+
+        self.history_index -= 1;
+        self.buf = self.history_entry(self.history_index).clone();
+        true
+    }
+
+    /// Step through history by mouse wheel `delta`, in points scrolled up, returning whether the buffer changed.
+    ///
+    /// Small deltas from smooth scrolling add up until they reach a step.
+    pub(crate) fn history_wheel(&mut self, delta: f32) -> bool {
+        const STEP: f32 = 24.;
+
+        self.history_wheel += delta;
+        let mut changed = false;
+        while self.history_wheel >= STEP {
+            self.history_wheel -= STEP;
+            changed |= self.history_previous();
+        }
+        while self.history_wheel <= -STEP {
+            self.history_wheel += STEP;
+            changed |= self.history_next();
+        }
+        changed
+    }
+
+    /// Show the previous history entry starting with the typed prefix, returning whether the buffer changed.
+    pub(crate) fn history_previous_with_prefix(&mut self) -> bool {
+        let prefix = self.history_prefix.get_or_insert_with(|| self.buf.clone());
+        let found = (self.history_index + 1..=self.history.len()).find(|&i| {
+            let entry = &self.history[self.history.len() - i];
+            entry.starts_with(prefix.as_str()) && *entry != self.buf
+        });
+
+        match found {
+            Some(index) => {
+                self.save_scratch();
+                self.history_index = index;
+                self.buf = self.history_entry(index).clone();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Show the next history entry starting with the typed prefix, returning whether the buffer changed.
+    ///
+    /// Returns to the typed prefix once there are no newer matches.
+    pub(crate) fn history_next_with_prefix(&mut self) -> bool {
+        let prefix = match &self.history_prefix {
+            Some(prefix) if self.history_index > 0 => prefix,
+            _ => return false,
+        };
+        let found = (1..self.history_index).rev().find(|&i| {
+            let entry = &self.history[self.history.len() - i];
+            entry.starts_with(prefix.as_str()) && *entry != self.buf
+        });
+
+        match found {
+            Some(index) => {
+                self.history_index = index;
+                self.buf = self.history_entry(index).clone();
+            }
+            None => {
+                self.history_index = 0;
+                self.buf = prefix.clone();
+                self.history_prefix = None;
+            }
+        }
+        true
+    }
+
+    fn save_scratch(&mut self) {
+        if self.history_index == 0 && !self.buf.trim().is_empty() {
+            self.history_scratch = self.buf.clone();
+        }
+    }
+}
+
+/// Lines printed since a `tail` command was entered which contain its pattern.
+pub(crate) struct Tail {
+    pub(crate) pattern: String,
+    pub(crate) lines: VecDeque<String>,
+}
+
+impl Tail {
+    pub(crate) fn new(pattern: String) -> Self {
+        Self {
+            pattern,
+            lines: VecDeque::new(),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn receive_terminal_line(
     mut terminal_state: ResMut<TerminalState>,
+    open: Res<TerminalOpen>,
+    config: Res<TerminalConfiguration>,
+    strings: Res<TerminalStrings>,
+    time: Res<Time>,
+    mut boot: ResMut<TerminalBootBuffer>,
+    mut queue: ResMut<PrintQueue>,
     mut events: EventReader<PrintTerminalLine>,
+    mut attention: EventWriter<TerminalAttentionRequested>,
 ) {
-    for event in events.iter() {
-        let event: &PrintTerminalLine = event;
-        terminal_state.scrollback.push(event.line.clone());
+    let now = time.seconds_since_startup();
+    let printed_at = PrintedAt(now);
+    let unseen = !open.0 || terminal_state.scrolled_up;
+    if !unseen {
+        terminal_state.unseen_errors = 0;
+    }
+    // Lines are logged as they arrive, so even those dropped from the queue are in the log.
+    // Echoes aren't, as commands are logged as they run
+    let boot_lines = mem::take(&mut boot.lines);
+    for event in boot_lines.into_iter().chain(events.iter().cloned()) {
+        if config.mirror_to_log && event.get_meta::<Echo>().is_none() {
+            log_line(&event, &strings);
+        }
+        queue.push(event, config.print_queue_size, &strings);
+    }
+    for event in queue.take(config.print_lines_per_frame, &strings) {
+        if config.attention && unseen && is_error_line(&event, &strings) {
+            terminal_state.unseen_errors += 1;
+            terminal_state.attention_until = now + config.attention_flash_duration as f64;
+            let errors = terminal_state.unseen_errors;
+            if errors == config.attention_threshold {
+                attention.send(TerminalAttentionRequested { errors });
+            }
+        }
+        terminal_state.push_printed(event, printed_at, &config);
+    }
+    terminal_state.trim_scrollback(config.scrollback_size);
+}
+
+/// Splits printed text into lines, dropping a single trailing newline but keeping empty lines.
+fn split_printed_line(text: &str) -> impl Iterator<Item = &str> {
+    text.strip_suffix('\n')
+        .unwrap_or(text)
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+impl TerminalState {
+    /// Add a printed line to the scrollback, split into one line per `\n` if [`TerminalConfiguration::split_lines`] is set.
+    pub(crate) fn push_printed(
+        &mut self,
+        event: PrintTerminalLine,
+        printed_at: PrintedAt,
+        config: &TerminalConfiguration,
+    ) {
+        if config.split_lines && event.line.contains('\n') {
+            for line in split_printed_line(&event.line) {
+                let line = TerminalLine::with_line_meta(line.to_string(), event.meta.clone())
+                    .with_meta(printed_at);
+                self.push_line(line, config);
+            }
+        } else {
+            let line = TerminalLine::with_line_meta(event.line, event.meta).with_meta(printed_at);
+            self.push_line(line, config);
+        }
+    }
+
+    /// Add a line to the scrollback and to the tails whose pattern it contains.
+    fn push_line(&mut self, line: TerminalLine, config: &TerminalConfiguration) {
+        for tail in &mut self.tails {
+            if line.text.contains(tail.pattern.as_str()) {
+                tail.lines.push_back(line.text.clone());
+                if tail.lines.len() > config.tail_size {
+                    tail.lines.pop_front();
+                }
+            }
+        }
+        self.scrollback.push_back(line);
+    }
+
+    /// Drop the oldest scrollback lines beyond `limit`, keeping every line if it is 0,
+    /// see [`TerminalConfiguration::scrollback_size`].
+    ///
+    /// Expanded and selected lines, anchors and the pending scroll refer to lines by [`LineId`], so they follow their lines.
+    /// Those on dropped lines are forgotten, except the selection, which is cut short at the first line left,
+    /// and the pending scroll, which goes to the first line left.
+    /// Dropped anchors and selected lines are reported by [`report_trimmed_references`].
+    pub(crate) fn trim_scrollback(&mut self, limit: usize) {
+        if limit == 0 || self.scrollback.len() <= limit {
+            return;
+        }
+        let excess = self.scrollback.len() - limit;
+        self.scrollback.drain(..excess);
+        self.trimmed_lines += excess;
+        self.first_line.0 += excess as u64;
+        self.row_heights.trim(excess);
+
+        let first = self.first_line;
+        self.expanded.retain(|id| *id >= first);
+        if let Some(selected) = self.selected {
+            let end = self.selection_end.unwrap_or(selected);
+            if selected.max(end) < first {
+                self.clear_selection();
+                self.trimmed_references.selection = true;
+            } else if selected < first {
+                self.selected = Some(first);
+                self.trimmed_references.selection = true;
+            } else if end < first {
+                self.selection_end = Some(first);
+                self.trimmed_references.selection = true;
+            }
+        }
+        let trimmed = &mut self.trimmed_references.anchors;
+        self.anchors.retain(|anchor| {
+            if anchor.line < first {
+                trimmed.push(anchor.label.clone());
+            }
+            anchor.line >= first
+        });
+        self.scroll_to = self.scroll_to.map(|line| line.max(first));
+    }
+}
+
+/// Shows a [`TerminalNotice`] when anchors or selected lines were trimmed from the scrollback,
+/// see [`TerminalState::trim_scrollback`].
+pub(crate) fn report_trimmed_references(
+    mut terminal_state: ResMut<TerminalState>,
+    strings: Res<TerminalStrings>,
+    mut notices: EventWriter<TerminalNotice>,
+) {
+    if terminal_state.trimmed_references == TrimmedReferences::default() {
+        return;
+    }
+    let trimmed = mem::take(&mut terminal_state.trimmed_references);
+    if !trimmed.anchors.is_empty() {
+        let labels = trimmed.anchors.join(", ");
+        notices.send(TerminalNotice::new(fill(
+            &strings.trimmed_anchors,
+            &[&labels],
+        )));
+    }
+    if trimmed.selection {
+        notices.send(TerminalNotice::new(strings.trimmed_selection.clone()));
+    }
+}
+
+pub(crate) fn receive_notices(
+    mut terminal_state: ResMut<TerminalState>,
+    config: Res<TerminalConfiguration>,
+    time: Res<Time>,
+    mut notices: EventReader<TerminalNotice>,
+    mut terminal_line: EventWriter<PrintTerminalLine>,
+) {
+    let now = time.seconds_since_startup();
+    for TerminalNotice { text } in notices.iter() {
+        if config.mirror_notices {
+            terminal_line.send(PrintTerminalLine::new(text.clone()));
+        }
+        terminal_state.notice = Some((text.clone(), now + config.notice_duration as f64));
+    }
+
+    if matches!(terminal_state.notice, Some((_, expires)) if expires <= now) {
+        terminal_state.notice = None;
+    }
+}
+
+pub(crate) fn print_motd(
+    mut terminal_state: ResMut<TerminalState>,
+    open: Res<TerminalOpen>,
+    config: Res<TerminalConfiguration>,
+    mut terminal_line: EventWriter<PrintTerminalLine>,
+) {
+    if !open.0 || terminal_state.motd_printed {
+        return;
+    }
+    terminal_state.motd_printed = true;
+    if let Some(motd) = &config.motd {
+        terminal_line.send_batch(
+            motd.lines()
+                .map(|line| PrintTerminalLine::new(line.to_string())),
+        );
+    }
+}
+
+/// Sends [`TerminalOpened`] and [`TerminalClosed`] when [`TerminalOpen`] changes.
+pub(crate) fn send_open_events(
+    open: Res<TerminalOpen>,
+    mut was_open: Local<bool>,
+    mut opened: EventWriter<TerminalOpened>,
+    mut closed: EventWriter<TerminalClosed>,
+) {
+    match (*was_open, open.0) {
+        (false, true) => opened.send(TerminalOpened),
+        (true, false) => closed.send(TerminalClosed),
+        _ => return,
+    }
+    *was_open = open.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use bevy::ecs::event::Events;
+    use bevy::ecs::schedule::ShouldRun;
+    use bevy::prelude::*;
+
+    use super::{split_printed_line, TerminalBootBuffer, TerminalState, TrimmedReferences};
+    use crate as leafwing_terminal;
+    use crate::anchor::AnchorDivider;
+    use crate::harness::{submit_and_collect, test_app, validate_level};
+    use crate::{
+        AddTerminalCommand, CommandRegistry, FromValue, FromValueError, PrintTerminalLine,
+        Severity, TerminalAttentionRequested, TerminalClosed, TerminalCommand,
+        TerminalCommandEntered, TerminalCommandQueue, TerminalConfiguration, TerminalNotice,
+        TerminalOpen, TerminalOpened, ValueRawOwned, ValueType,
+    };
+    use crate::{
+        CommandArgGroup, CommandArgInfo, CommandArgs, CommandExample, CommandHelp, CommandInfo,
+        CommandName, HelpFormat, TerminalLine,
+    };
+
+    fn state_with_history(history: &[&str]) -> TerminalState {
+        let mut state = TerminalState::default();
+        for cmd in history {
+            state.push_history(cmd.to_string(), 20);
+        }
+        state
+    }
+
+    #[test]
+    fn it_trims_history() {
+        let mut state = TerminalState::default();
+        for i in 0..5 {
+            state.push_history(i.to_string(), 3);
+        }
+        assert_eq!(state.history, ["2", "3", "4"]);
+        assert!(state.history().eq(["4", "3", "2"]));
+
+        // Shrinking the history size drops the oldest commands
+        state.push_history("5".to_string(), 2);
+        assert_eq!(state.history, ["4", "5"]);
+
+        state.push_history("6".to_string(), 0);
+        assert!(state.history.is_empty());
+    }
+
+    #[test]
+    fn it_navigates_history() {
+        let mut state = state_with_history(&["spawn 1", "log hi", "spawn 2"]);
+        state.buf = "typed".to_string();
+
+        assert!(state.history_previous());
+        assert_eq!(state.buf, "spawn 2");
+        assert!(state.history_previous());
+        assert!(state.history_previous());
+        assert_eq!(state.buf, "spawn 1");
+        assert!(!state.history_previous());
+
+        assert!(state.history_next());
+        assert!(state.history_next());
+        assert!(state.history_next());
+        assert_eq!(state.buf, "typed");
+        assert!(!state.history_next());
+    }
+
+    #[test]
+    fn it_navigates_history_with_the_wheel() {
+        let mut state = state_with_history(&["spawn 1", "log hi", "spawn 2"]);
+        state.buf = "typed".to_string();
+
+        // Smooth scrolling adds up to a step
+        assert!(!state.history_wheel(10.));
+        assert!(state.history_wheel(20.));
+        assert_eq!(state.buf, "spawn 2");
+        assert!(state.history_wheel(48.));
+        assert_eq!(state.buf, "spawn 1");
+        assert!(!state.history_wheel(100.));
+
+        assert!(state.history_wheel(-72.));
+        assert_eq!(state.buf, "spawn 2");
+        assert!(state.history_wheel(-30.));
+        assert_eq!(state.buf, "typed");
+    }
+
+    #[test]
+    fn it_navigates_history_by_prefix() {
+        let mut state = state_with_history(&["spawn 1", "log hi", "spawn 2", "spawn 2"]);
+        state.buf = "sp".to_string();
+
+        assert!(state.history_previous_with_prefix());
+        assert_eq!(state.buf, "spawn 2");
+        // Duplicates of the shown entry are skipped
+        assert!(state.history_previous_with_prefix());
+        assert_eq!(state.buf, "spawn 1");
+        assert!(!state.history_previous_with_prefix());
+        assert_eq!(state.buf, "spawn 1");
+
+        assert!(state.history_next_with_prefix());
+        assert_eq!(state.buf, "spawn 2");
+        assert!(state.history_next_with_prefix());
+        assert_eq!(state.buf, "sp");
+        assert_eq!(state.history_index, 0);
+        assert!(!state.history_next_with_prefix());
+    }
+
+    #[test]
+    fn it_mixes_plain_and_prefix_navigation() {
+        let mut state = state_with_history(&["spawn 1", "log hi", "spawn 2", "log bye"]);
+        state.buf = "sp".to_string();
+
+        // Prefix navigation skips to the newest match
+        assert!(state.history_previous_with_prefix());
+        assert_eq!(state.buf, "spawn 2");
+
+        // Plain navigation continues from there and ends prefix navigation
+        assert!(state.history_previous());
+        assert_eq!(state.buf, "log hi");
+        assert_eq!(state.history_prefix, None);
+
+        // Prefix navigation now uses the shown entry as its prefix
+        assert!(!state.history_previous_with_prefix());
+        assert!(state.history_next());
+        assert_eq!(state.buf, "spawn 2");
+        assert!(state.history_next());
+        assert!(state.history_next());
+        assert_eq!(state.buf, "sp");
+    }
+
+    /// Spawns a wave
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "spawn_wave")]
+    struct SpawnWaveCommand;
+
+    #[derive(Default)]
+    struct Waves(usize);
+
+    fn spawn_wave_command(
+        mut spawn_wave: TerminalCommand<SpawnWaveCommand>,
+        mut waves: ResMut<Waves>,
+    ) {
+        if spawn_wave.take().is_some() {
+            waves.0 += 1;
+        }
+    }
+
+    /// Run criteria for a fixed timestep three times slower than the frame rate
+    fn every_third_frame(mut frame: Local<u32>) -> ShouldRun {
+        *frame = (*frame + 1) % 3;
+        if *frame == 0 {
+            ShouldRun::Yes
+        } else {
+            ShouldRun::No
+        }
+    }
+
+    fn enter_command(app: &mut App, command: &str) {
+        app.world
+            .resource_mut::<Events<TerminalCommandEntered>>()
+            .send(TerminalCommandEntered {
+                command: command.to_string(),
+                args: Vec::new(),
+            });
+    }
+
+    #[test]
+    fn it_delivers_commands_to_slow_stages() {
+        let mut app = test_app();
+        app.init_resource::<Waves>()
+            .add_stage_after(
+                CoreStage::Update,
+                "fixed_update",
+                SystemStage::parallel().with_run_criteria(every_third_frame),
+            )
+            .add_terminal_command_to_stage::<SpawnWaveCommand, _, _>(
+                "fixed_update",
+                spawn_wave_command,
+            );
+        app.update();
+
+        // The fixed stage next runs two frames from now, after the event has been cleared
+        enter_command(&mut app, "spawn_wave");
+        app.update();
+        app.update();
+        assert_eq!(app.world.resource::<Waves>().0, 1);
+
+        // Commands entered together are each seen once, one per run
+        enter_command(&mut app, "spawn_wave");
+        enter_command(&mut app, "spawn_wave");
+        for _ in 0..9 {
+            app.update();
+        }
+        assert_eq!(app.world.resource::<Waves>().0, 3);
+    }
+
+    static PARSED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Argument which counts how often it is parsed
+    struct Counted;
+
+    impl FromValue for Counted {
+        fn from_value(_value: &ValueRawOwned, _arg_num: u8) -> Result<Self, FromValueError> {
+            PARSED.fetch_add(1, Ordering::Relaxed);
+            Ok(Counted)
+        }
+    }
+
+    /// Parses an expensive argument
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "expensive")]
+    struct ExpensiveCommand {
+        _counted: Counted,
+        _flag: Option<bool>,
+    }
+
+    fn expensive_command(mut expensive: TerminalCommand<ExpensiveCommand>) {
+        expensive.take();
+    }
+
+    #[test]
+    fn it_checks_arg_count_before_parsing() {
+        let mut app = test_app();
+        app.add_terminal_command::<ExpensiveCommand, _, _>(expensive_command);
+        app.update();
+
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.submit("expensive").unwrap();
+        queue.submit("expensive a true b").unwrap();
+        queue.submit("expensive a true").unwrap();
+        for _ in 0..4 {
+            app.update();
+        }
+
+        assert_eq!(PARSED.load(Ordering::Relaxed), 1);
+        let scrollback = app.world.resource::<TerminalState>().scrollback();
+        assert!(scrollback
+            .iter()
+            .any(|line| line == "[error] not enough arguments provided"));
+        assert!(scrollback
+            .iter()
+            .any(|line| line == "[error] too many arguments provided (max 2)"));
+    }
+
+    #[test]
+    fn it_runs_commands_by_alias() {
+        let mut app = test_app();
+        app.init_resource::<Waves>()
+            .add_terminal_command::<SpawnWaveCommand, _, _>(spawn_wave_command);
+        app.world
+            .resource_mut::<CommandRegistry>()
+            .add_alias("sw", "spawn_wave");
+        app.update();
+
+        enter_command(&mut app, "sw");
+        app.update();
+        assert_eq!(app.world.resource::<Waves>().0, 1);
+    }
+
+    /// Loads a level
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "loadlevel")]
+    struct LoadLevelCommand {
+        level: String,
+    }
+
+    #[derive(Default)]
+    struct Level(String);
+
+    fn load_level_command(
+        mut load_level: TerminalCommand<LoadLevelCommand>,
+        mut current: ResMut<Level>,
+    ) {
+        if let Some(LoadLevelCommand { level }) = load_level.take() {
+            current.0 = level;
+        }
+    }
+
+    #[test]
+    fn it_validates_commands_before_running_them() {
+        let mut app = test_app();
+        app.init_resource::<Level>()
+            .add_terminal_command::<LoadLevelCommand, _, _>(load_level_command)
+            .add_terminal_command_validator::<LoadLevelCommand>(validate_level);
+        app.update();
+
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.submit("loadlevel moon").unwrap();
+        app.update();
+        app.update();
+        assert_eq!(app.world.resource::<Level>().0, "");
+        assert_eq!(
+            *app.world.resource::<TerminalState>().scrollback(),
+            ["[error] unknown level 'moon'"]
+        );
+
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.submit("loadlevel forest").unwrap();
+        app.update();
+        assert_eq!(app.world.resource::<Level>().0, "forest");
+    }
+
+    /// Awards bonus points
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "bonus", cooldown = "10s")]
+    struct BonusCommand {
+        points: i64,
+    }
+
+    #[derive(Default)]
+    struct Bonuses {
+        awarded: Vec<i64>,
+        recorded: Vec<i64>,
+    }
+
+    fn bonus_command(mut bonus: TerminalCommand<BonusCommand>, mut bonuses: ResMut<Bonuses>) {
+        if let Some(BonusCommand { points }) = bonus.take() {
+            bonuses.awarded.push(points);
+        }
+    }
+
+    fn bonus_analytics(mut bonus: TerminalCommand<BonusCommand>, mut bonuses: ResMut<Bonuses>) {
+        if let Some(BonusCommand { points }) = bonus.take() {
+            bonuses.recorded.push(points);
+        }
+    }
+
+    #[test]
+    fn it_shares_invocations_between_systems() {
+        let mut app = test_app();
+        app.init_resource::<Bonuses>()
+            .add_terminal_command::<BonusCommand, _, _>(bonus_command)
+            .add_system(bonus_analytics);
+        app.update();
+
+        // Errors are printed once, and neither system sees the invocation
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.submit("bonus lots").unwrap();
+        app.update();
+        let bonuses = app.world.resource::<Bonuses>();
+        assert!(bonuses.awarded.is_empty() && bonuses.recorded.is_empty());
+
+        // Both systems see a valid invocation, the cooldown it triggers doesn't hide it from the second
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.submit("bonus 50").unwrap();
+        app.update();
+        let bonuses = app.world.resource::<Bonuses>();
+        assert_eq!(bonuses.awarded, [50]);
+        assert_eq!(bonuses.recorded, [50]);
+
+        // The cooldown is reported once too
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        queue.submit("bonus 10").unwrap();
+        app.update();
+        let bonuses = app.world.resource::<Bonuses>();
+        assert_eq!((bonuses.awarded.len(), bonuses.recorded.len()), (1, 1));
+
+        app.update();
+        let scrollback = app.world.resource::<TerminalState>().scrollback();
+        let count = |prefix: &str| {
+            scrollback
+                .iter()
+                .filter(|line| line.as_str().starts_with(prefix))
+                .count()
+        };
+        assert_eq!(
+            count("[error] expected 'int' but got 'string' for arg #1"),
+            1
+        );
+        assert_eq!(count("Usage:"), 1);
+        assert_eq!(count("command on cooldown"), 1);
+    }
+
+    #[test]
+    fn it_numbers_lines_from_the_last_clear() {
+        let mut app = test_app();
+        app.update();
+        let mut state = app.world.resource_mut::<TerminalState>();
+        state.scrollback.push_back("first".into());
+        state.scrollback.push_back("second".into());
+        assert_eq!(state.line_number(1), 2);
+
+        // Trimmed lines keep counting, so the remaining lines aren't renumbered
+        state.trim_scrollback(1);
+        assert_eq!(state.line_number(0), 2);
+
+        app.world
+            .resource_mut::<TerminalCommandQueue>()
+            .submit("clear")
+            .unwrap();
+        app.update();
+        app.update();
+        let state = app.world.resource::<TerminalState>();
+        assert!(state.scrollback().is_empty());
+        assert_eq!(state.line_number(0), 1);
+    }
+
+    #[test]
+    fn it_drops_the_oldest_lines_beyond_the_limit() {
+        let mut app = test_app();
+        app.world
+            .resource_mut::<TerminalConfiguration>()
+            .scrollback_size = 3;
+        let mut state = app.world.resource_mut::<TerminalState>();
+        state.mark("old".to_string(), "── old ──".to_string());
+        state.scrollback.push_back("1".into());
+        state.mark("new".to_string(), "── new ──".to_string());
+        state.select_line(2, false);
+        app.world
+            .resource_mut::<Events<PrintTerminalLine>>()
+            .send(PrintTerminalLine::new("2".to_string()));
+        app.update();
+
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(*state.scrollback(), ["1", "── new ──", "2"]);
+        assert_eq!(state.line_number(0), 2);
+        // Anchors and the selection follow their lines, and anchors on dropped lines are forgotten
+        assert_eq!(state.anchors().collect::<Vec<_>>(), [("new", 1)]);
+        assert_eq!(state.selection(), Some(1..=1));
+        assert_eq!(
+            state.notice.as_ref().map(|(notice, _)| notice.as_str()),
+            Some("anchors trimmed from the scrollback: old")
+        );
+
+        app.world
+            .resource_mut::<TerminalConfiguration>()
+            .scrollback_size = 0;
+        let mut events = app.world.resource_mut::<Events<PrintTerminalLine>>();
+        for line in ["3", "4"] {
+            events.send(PrintTerminalLine::new(line.to_string()));
+        }
+        app.update();
+        assert_eq!(app.world.resource::<TerminalState>().scrollback().len(), 5);
+    }
+
+    #[test]
+    fn it_keeps_anchors_and_the_selection_through_a_long_soak() {
+        const LINES: usize = 1_000_000;
+        const LINES_PER_FRAME: usize = 100;
+        let config = TerminalConfiguration {
+            scrollback_size: 2000,
+            ..Default::default()
+        };
+        let mut state = TerminalState::default();
+        // Text of the first and last selected lines
+        let mut selected = (String::new(), String::new());
+        for i in 0..LINES {
+            state.push_line(TerminalLine::from(i.to_string()), &config);
+            if i % 1000 == 999 {
+                let label = state.next_anchor_label();
+                state.mark(label, "──".to_string());
+                let last = state.scrollback.len() - 1;
+                let first = last.saturating_sub(1500);
+                state.select_line(first, false);
+                state.select_line(last, true);
+                selected = (state.scrollback[first].text.clone(), "──".to_string());
+            }
+            if i % LINES_PER_FRAME != LINES_PER_FRAME - 1 {
+                continue;
+            }
+            state.trim_scrollback(config.scrollback_size);
+            assert!(state.scrollback.len() <= config.scrollback_size);
+            for (label, index) in state.anchors() {
+                assert_eq!(
+                    state.scrollback[index].get_meta(),
+                    Some(&AnchorDivider(label.to_string()))
+                );
+            }
+            if let Some(selection) = state.selection() {
+                assert_eq!(state.scrollback[*selection.end()], selected.1);
+                assert!(
+                    *selection.start() == 0 || state.scrollback[*selection.start()] == selected.0
+                );
+            }
+        }
+        assert_eq!(state.line_number(0), LINES + LINES / 1000 - 2000 + 1);
+        assert_eq!(state.anchors().count(), 2);
+        assert_eq!(state.selection(), Some(499..=1999));
+        state.trimmed_references = TrimmedReferences::default();
+
+        // Trimming the first selected lines cuts the selection short
+        for i in 0..600 {
+            state.push_line(TerminalLine::from(i.to_string()), &config);
+        }
+        state.trim_scrollback(config.scrollback_size);
+        assert_eq!(state.selection(), Some(0..=1399));
+        assert!(state.trimmed_references.selection);
+        state.scroll_to_line(0);
+
+        // Trimming every selected line clears it
+        for i in 0..3000 {
+            state.push_line(TerminalLine::from(i.to_string()), &config);
+        }
+        state.trim_scrollback(config.scrollback_size);
+        assert_eq!(state.anchors().count(), 0);
+        assert_eq!(state.selection(), None);
+        assert_eq!(state.scroll_to.map(|line| state.clamp_line(line)), Some(0));
+        assert_eq!(state.trimmed_references.anchors.len(), 2);
+    }
+
+    #[test]
+    fn it_sends_open_and_close_events() {
+        let mut app = test_app();
+        let sent = |app: &App| {
+            let opened = app.world.resource::<Events<TerminalOpened>>();
+            let closed = app.world.resource::<Events<TerminalClosed>>();
+            (
+                opened.get_reader().iter(opened).count(),
+                closed.get_reader().iter(closed).count(),
+            )
+        };
+
+        app.update();
+        assert_eq!(sent(&app), (0, 0));
+        app.world.resource_mut::<TerminalOpen>().0 = true;
+        app.update();
+        assert_eq!(sent(&app), (1, 0));
+        // Setting the same value again isn't a transition
+        app.world.resource_mut::<TerminalOpen>().0 = true;
+        app.update();
+        app.update();
+        assert_eq!(sent(&app), (0, 0));
+
+        // `exit` closes the terminal like any other close
+        app.world
+            .resource_mut::<TerminalCommandQueue>()
+            .submit("exit")
+            .unwrap();
+        app.update();
+        app.update();
+        assert!(!app.world.resource::<TerminalOpen>().0);
+        assert_eq!(sent(&app), (0, 1));
+    }
+
+    #[test]
+    fn it_prints_the_motd_on_first_open() {
+        let mut app = test_app();
+        app.world.resource_mut::<TerminalConfiguration>().motd =
+            Some("Welcome\ntype help to get started".to_string());
+
+        // Lines printed while closed come before the motd
+        app.world
+            .resource_mut::<Events<PrintTerminalLine>>()
+            .send(PrintTerminalLine::new("loaded autoexec".to_string()));
+        app.update();
+        assert_eq!(
+            *app.world.resource::<TerminalState>().scrollback(),
+            ["loaded autoexec"]
+        );
+
+        app.world.resource_mut::<TerminalOpen>().0 = true;
+        app.update();
+        app.world.resource_mut::<TerminalOpen>().0 = false;
+        app.update();
+        app.world.resource_mut::<TerminalOpen>().0 = true;
+        app.update();
+        assert_eq!(
+            *app.world.resource::<TerminalState>().scrollback(),
+            ["loaded autoexec", "Welcome", "type help to get started"]
+        );
+    }
+
+    #[test]
+    fn it_prints_lines_from_startup() {
+        fn boot_diagnostics(mut lines: EventWriter<PrintTerminalLine>) {
+            lines.send(PrintTerminalLine::new("renderer: vulkan".to_string()));
+        }
+
+        let mut app = test_app();
+        app.add_startup_system_to_stage(StartupStage::PreStartup, boot_diagnostics);
+        app.world
+            .resource_mut::<TerminalBootBuffer>()
+            .push(PrintTerminalLine::new("loaded 3 plugins".to_string()));
+        app.update();
+
+        let state = app.world.resource::<TerminalState>();
+        let scrollback: Vec<_> = state
+            .scrollback()
+            .iter()
+            .map(|line| line.as_str())
+            .collect();
+        assert_eq!(scrollback, ["loaded 3 plugins", "renderer: vulkan"]);
+        assert!(app.world.resource::<TerminalBootBuffer>().is_empty());
+    }
+
+    #[test]
+    fn it_counts_errors_printed_while_closed_or_scrolled_up() {
+        let mut app = test_app();
+        app.world
+            .resource_mut::<TerminalConfiguration>()
+            .attention_threshold = 2;
+        let print = |app: &mut App, line: &str| {
+            app.world
+                .resource_mut::<Events<PrintTerminalLine>>()
+                .send(PrintTerminalLine::new(line.to_string()));
+            app.update();
+        };
+        let requested = |app: &App| {
+            let events = app.world.resource::<Events<TerminalAttentionRequested>>();
+            events
+                .get_reader()
+                .iter(events)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        print(&mut app, "[error] first");
+        print(&mut app, "loaded autoexec");
+        assert_eq!(app.world.resource::<TerminalState>().unseen_errors(), 1);
+        assert!(requested(&app).is_empty());
+        print(&mut app, "[failed]");
+        print(&mut app, "[error] third");
+        assert_eq!(app.world.resource::<TerminalState>().unseen_errors(), 3);
+        assert_eq!(requested(&app), [TerminalAttentionRequested { errors: 2 }]);
+
+        // Seen once open and at the bottom
+        app.world.resource_mut::<TerminalOpen>().0 = true;
+        print(&mut app, "[error] seen");
+        assert_eq!(app.world.resource::<TerminalState>().unseen_errors(), 0);
+
+        app.world.resource_mut::<TerminalState>().scrolled_up = true;
+        print(&mut app, "[error] scrolled past");
+        assert_eq!(app.world.resource::<TerminalState>().unseen_errors(), 1);
+    }
+
+    /// Files a bug report.
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "report_bug", exclude_from_history)]
+    struct ReportBugCommand;
+
+    fn report_bug_command(mut report_bug: TerminalCommand<ReportBugCommand>) {
+        report_bug.take();
+    }
+
+    #[test]
+    fn it_leaves_excluded_commands_out_of_history() {
+        let mut app = test_app();
+        app.add_terminal_command::<ReportBugCommand, _, _>(report_bug_command);
+        app.update();
+
+        let config = app.world.resource::<TerminalConfiguration>().clone();
+        assert!(config.history_exclude.contains("report_bug"));
+
+        let mut state = TerminalState::default();
+        state.record_submission("spawn_wave 1".to_string(), &config);
+        state.record_submission("report_bug 'the sky is green'".to_string(), &config);
+        assert!(state.history_previous());
+        assert_eq!(state.buf, "spawn_wave 1");
+        assert_eq!(state.history().collect::<Vec<_>>(), ["spawn_wave 1"]);
+    }
+
+    #[test]
+    fn it_splits_printed_lines() {
+        assert_eq!(
+            split_printed_line("a\nb\r\nc").collect::<Vec<_>>(),
+            ["a", "b", "c"]
+        );
+        assert_eq!(split_printed_line("a\n").collect::<Vec<_>>(), ["a"]);
+        assert_eq!(split_printed_line("a\n\n").collect::<Vec<_>>(), ["a", ""]);
+        assert_eq!(
+            split_printed_line("a\n\nb").collect::<Vec<_>>(),
+            ["a", "", "b"]
+        );
+        assert_eq!(split_printed_line("\n").collect::<Vec<_>>(), [""]);
+        assert_eq!(split_printed_line("").collect::<Vec<_>>(), [""]);
+    }
+
+    // Only read through `Debug`
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct Stats {
+        health: u32,
+        effects: Vec<&'static str>,
+    }
+
+    /// Prints the player's stats
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "stats")]
+    struct StatsCommand;
+
+    fn stats_command(mut stats: TerminalCommand<StatsCommand>) {
+        if stats.take().is_some() {
+            stats.reply_debug(&Stats {
+                health: 80,
+                effects: vec!["haste"],
+            });
+            stats.reply_kv([("hp", "80"), ("effects", "haste\nshield")]);
+        }
+    }
+
+    #[test]
+    fn it_prints_debug_and_key_value_replies() {
+        let mut app = test_app();
+        app.add_terminal_command::<StatsCommand, _, _>(stats_command);
+        app.update();
+
+        let output = submit_and_collect(&mut app, "stats");
+        assert_eq!(
+            output,
+            [
+                "Stats {",
+                "  health: 80,",
+                "  effects: [",
+                "    \"haste\",",
+                "  ],",
+                "}",
+                "       hp: 80",
+                "  effects: haste",
+                "           shield",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_prints_multi_line_replies_as_separate_lines() {
+        let mut app = test_app();
+
+        let mut events = app.world.resource_mut::<Events<PrintTerminalLine>>();
+        events.send(PrintTerminalLine::new("Usage:\n\n  > spawn\n".to_string()));
+        events.send(PrintTerminalLine::new(String::new()));
+        app.update();
+        assert_eq!(
+            *app.world.resource::<TerminalState>().scrollback(),
+            ["Usage:", "", "  > spawn", ""]
+        );
+
+        app.world
+            .resource_mut::<TerminalConfiguration>()
+            .split_lines = false;
+        app.world
+            .resource_mut::<Events<PrintTerminalLine>>()
+            .send(PrintTerminalLine::new("a\nb".to_string()));
+        app.update();
+        assert_eq!(
+            app.world
+                .resource::<TerminalState>()
+                .scrollback()
+                .back()
+                .unwrap(),
+            "a\nb"
+        );
+    }
+
+    #[test]
+    fn it_copies_line_meta_to_the_scrollback() {
+        #[derive(Debug, PartialEq)]
+        struct Source(&'static str);
+
+        let mut app = test_app();
+
+        let mut events = app.world.resource_mut::<Events<PrintTerminalLine>>();
+        events.send(PrintTerminalLine::new("plain".to_string()));
+        events.send(PrintTerminalLine::new("a\nb".to_string()).with_meta(Source("net")));
+        app.update();
+
+        let scrollback = app.world.resource::<TerminalState>().scrollback();
+        assert_eq!(*scrollback, ["plain", "a", "b"]);
+        assert_eq!(scrollback[0].get_meta::<Source>(), None);
+        assert_eq!(scrollback[1].get_meta::<Source>(), Some(&Source("net")));
+        assert_eq!(scrollback[2].get_meta::<Source>(), Some(&Source("net")));
+    }
+
+    /// Sets the volume
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "volume")]
+    struct VolumeCommand {
+        level: u32,
+    }
+
+    fn volume_command(mut volume: TerminalCommand<VolumeCommand>) {
+        if let Some(VolumeCommand { level }) = volume.take() {
+            match level {
+                0..=100 => volume.reply_ok(format!("volume {level}")),
+                101..=200 => {
+                    volume.reply_warn("clipped to 100");
+                    volume.ok();
+                }
+                _ => volume.reply_failed("too loud"),
+            }
+        }
+    }
+
+    #[test]
+    fn it_marks_the_severity_of_replies() {
+        let mut app = test_app();
+        app.add_terminal_command::<VolumeCommand, _, _>(volume_command);
+        app.update();
+
+        let mut queue = app.world.resource_mut::<TerminalCommandQueue>();
+        for line in ["volume 50", "volume 150", "volume 500"] {
+            queue.submit(line).unwrap();
+        }
+        app.world
+            .resource_mut::<Events<PrintTerminalLine>>()
+            .send(PrintTerminalLine::info("autosaved"));
+        for _ in 0..6 {
+            app.update();
+        }
+
+        let severities: Vec<_> = app
+            .world
+            .resource::<TerminalState>()
+            .scrollback()
+            .iter()
+            .map(|line| (line.as_str(), line.severity()))
+            .collect();
+        assert_eq!(
+            severities,
+            [
+                ("autosaved", Some(Severity::Info)),
+                ("volume 50", None),
+                ("[ok]", Some(Severity::Ok)),
+                ("[warning] clipped to 100", Some(Severity::Warn)),
+                ("[ok]", Some(Severity::Ok)),
+                ("too loud", Some(Severity::Error)),
+                ("[failed]", Some(Severity::Failed)),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_keeps_notices_out_of_the_scrollback() {
+        let mut app = test_app();
+
+        app.world
+            .resource_mut::<Events<TerminalNotice>>()
+            .send(TerminalNotice::new("config reloaded".to_string()));
+        app.update();
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(state.notice.as_ref().unwrap().0, "config reloaded");
+        assert!(state.scrollback().is_empty());
+
+        app.world
+            .resource_mut::<TerminalConfiguration>()
+            .mirror_notices = true;
+        app.world
+            .resource_mut::<Events<TerminalNotice>>()
+            .send(TerminalNotice::new("client connected".to_string()));
+        app.update();
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(state.notice.as_ref().unwrap().0, "client connected");
+        // Mirrored notices are printed like other lines, which reach the scrollback in the next frame
+        app.update();
+        let state = app.world.resource::<TerminalState>();
+        assert_eq!(*state.scrollback(), ["client connected"]);
+    }
+
+    fn spawn_wave_info() -> CommandInfo {
+        CommandInfo {
+            name: "spawn_wave".to_string(),
+            description: Some(
+                "Spawns a wave of enemies around the player, scaled by the current difficulty"
+                    .to_string(),
+            ),
+            args: vec![
+                CommandArgInfo {
+                    name: "count".to_string(),
+                    ty: "int".to_string(),
+                    description: Some("Number of enemies to spawn in the wave".to_string()),
+                    optional: false,
+                    default: None,
+                },
+                CommandArgInfo {
+                    name: "kind".to_string(),
+                    ty: "string".to_string(),
+                    description: None,
+                    optional: true,
+                    default: None,
+                },
+            ],
+            min_args: Some(1),
+            max_args: Some(2),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_formats_help_text() {
+        assert_eq!(
+            spawn_wave_info().help_text(),
+            "\
+Usage:
+
+  > spawn_wave <count> [kind]
+
+  Spawns a wave of enemies around the player, scaled by the current difficulty
+
+    count <int>      - Number of enemies to spawn in the wave
+    kind  [string]
+"
+        );
+    }
+
+    #[test]
+    fn it_wraps_help_text() {
+        let format = HelpFormat {
+            max_width: Some(40),
+            ..Default::default()
+        };
+        assert_eq!(
+            spawn_wave_info().help_text_with(&format),
+            "\
+Usage:
+
+  > spawn_wave <count> [kind]
+
+  Spawns a wave of enemies around the
+  player, scaled by the current
+  difficulty
+
+    count <int>      - Number of enemies
+                       to spawn in the
+                       wave
+    kind  [string]
+"
+        );
+    }
+
+    #[test]
+    fn it_formats_help_text_without_types_with_defaults() {
+        let mut info = spawn_wave_info();
+        info.args[1].default = Some("grunt".to_string());
+        let format = HelpFormat {
+            indent: 1,
+            show_types: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            info.help_text_with(&format),
+            "\
+Usage:
+
+ > spawn_wave <count> [kind]
+
+ Spawns a wave of enemies around the player, scaled by the current difficulty
+
+  count    - Number of enemies to spawn in the wave
+  kind     - (default: grunt)
+"
+        );
+
+        let format = HelpFormat {
+            show_defaults: false,
+            ..format
+        };
+        assert!(!info.help_text_with(&format).contains("grunt"));
+    }
+
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "spawn_with_defaults")]
+    struct SpawnWithDefaultsCommand {
+        count: Option<i64>,
+        #[terminal_command(default = "grunt")]
+        kind: Option<String>,
+        #[terminal_command(default = 1.5)]
+        scale: Option<f64>,
+    }
+
+    #[test]
+    fn it_derives_arg_defaults() {
+        let info = SpawnWithDefaultsCommand::command_help().unwrap();
+        let defaults: Vec<_> = info.args.iter().map(|arg| arg.default.as_deref()).collect();
+        assert_eq!(defaults, [None, Some("grunt"), Some("1.5")]);
+    }
+
+    /// Teleports the player
+    #[derive(TerminalCommand)]
+    #[terminal_command(
+        name = "teleport",
+        example("teleport 10 0 5", "move to the tower"),
+        example("teleport 0 0 0")
+    )]
+    struct TeleportCommand {
+        x: i64,
+        y: i64,
+        z: i64,
+    }
+
+    /// Spawns a wave of enemies
+    /// around the player.
+    ///
+    ///
+    /// Waves get harder with the difficulty:
+    ///
+    ///     difficulty  enemies
+    ///     easy        count
+    ///     hard        count * 2
+    ///
+    /// ```
+    /// spawn_wave 10 brute
+    /// ```
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "spawn_wave")]
+    struct DocumentedSpawnWaveCommand {
+        /// Number of enemies,
+        /// before difficulty
+        ///
+        /// At most 100
+        count: i64,
+    }
+
+    #[test]
+    fn it_keeps_paragraphs_and_code_from_doc_comments() {
+        let info = DocumentedSpawnWaveCommand::command_help().unwrap();
+        assert_eq!(
+            info.description.as_deref(),
+            Some(
+                "Spawns a wave of enemies around the player.\n\
+                 \n\
+                 Waves get harder with the difficulty:\n\
+                 \n    \
+                 difficulty  enemies\n    \
+                 easy        count\n    \
+                 hard        count * 2\n\
+                 \n    \
+                 spawn_wave 10 brute"
+            )
+        );
+        assert_eq!(
+            info.summary(),
+            Some("Spawns a wave of enemies around the player.")
+        );
+        assert_eq!(
+            info.args[0].description.as_deref(),
+            Some("Number of enemies, before difficulty\n\nAt most 100")
+        );
+
+        let format = HelpFormat {
+            max_width: Some(40),
+            ..Default::default()
+        };
+        assert_eq!(
+            info.help_text_with(&format),
+            "\
+Usage:
+
+  > spawn_wave <count>
+
+  Spawns a wave of enemies around the
+  player.
+
+  Waves get harder with the difficulty:
+
+      difficulty  enemies
+      easy        count
+      hard        count * 2
+
+      spawn_wave 10 brute
+
+    count <int>   - Number of enemies,
+                    before difficulty
+
+                    At most 100
+"
+        );
+    }
+
+    #[test]
+    fn it_formats_examples() {
+        let info = TeleportCommand::command_help().unwrap();
+        assert_eq!(
+            info.examples[0],
+            CommandExample {
+                command: "teleport 10 0 5".to_string(),
+                description: Some("move to the tower".to_string()),
+            }
+        );
+        assert_eq!(
+            info.help_text(),
+            "\
+Usage:
+
+  > teleport <x> <y> <z>
+
+  Teleports the player
+
+    x <int>
+    y <int>
+    z <int>
+
+Examples:
+
+  > teleport 10 0 5  # move to the tower
+  > teleport 0 0 0
+"
+        );
+
+        let info = spawn_wave_info()
+            .with_example("spawn_wave 3", Some("a few grunts"))
+            .with_example("spawn_wave 20 brute", Some("a wave nobody survives"));
+        let format = HelpFormat {
+            max_width: Some(45),
+            ..Default::default()
+        };
+        assert!(info.help_text_with(&format).ends_with(
+            "\
+Examples:
+
+  > spawn_wave 3         # a few grunts
+  > spawn_wave 20 brute  # a wave nobody
+                           survives
+"
+        ));
+    }
+
+    #[derive(CommandArgGroup, Debug, PartialEq)]
+    struct PosArgs {
+        /// X coordinate
+        x: i64,
+        /// Y coordinate
+        y: i64,
+    }
+
+    #[derive(CommandArgGroup, Debug, PartialEq)]
+    struct PlacementArgs {
+        #[terminal_command(flatten)]
+        pos: PosArgs,
+        facing: Option<f64>,
+    }
+
+    /// Spawns an enemy
+    #[derive(TerminalCommand, Debug, PartialEq)]
+    #[terminal_command(name = "spawn")]
+    struct SpawnCommand {
+        kind: String,
+        #[terminal_command(flatten)]
+        placement: PlacementArgs,
+    }
+
+    /// Draws a line
+    #[derive(TerminalCommand, Debug, PartialEq)]
+    #[terminal_command(name = "line")]
+    struct LineCommand {
+        #[terminal_command(flatten)]
+        from: PosArgs,
+        #[terminal_command(flatten)]
+        to: PosArgs,
+        color: Option<String>,
+    }
+
+    fn values(args: &str) -> Vec<ValueRawOwned> {
+        crate::parse_command(&format!("cmd {args}")).unwrap().args
+    }
+
+    #[test]
+    fn it_parses_flattened_args_in_order() {
+        assert_eq!(
+            SpawnCommand::from_values(values("grunt 1 2 0.5")),
+            Ok(SpawnCommand {
+                kind: "grunt".to_string(),
+                placement: PlacementArgs {
+                    pos: PosArgs { x: 1, y: 2 },
+                    facing: Some(0.5),
+                },
+            })
+        );
+        assert_eq!(
+            LineCommand::from_values(values("1 2 3 4")),
+            Ok(LineCommand {
+                from: PosArgs { x: 1, y: 2 },
+                to: PosArgs { x: 3, y: 4 },
+                color: None,
+            })
+        );
+        assert_eq!(
+            LineCommand::from_values(values("1 2 3 four")),
+            Err(FromValueError::UnexpectedArgType {
+                arg_num: 3,
+                expected: ValueType::Int,
+                received: ValueType::String,
+            })
+        );
+        assert_eq!(
+            SpawnCommand::from_values(values("grunt 1 two")),
+            Err(FromValueError::UnexpectedArgType {
+                arg_num: 2,
+                expected: ValueType::Int,
+                received: ValueType::String,
+            })
+        );
+    }
+
+    #[test]
+    fn it_lists_flattened_args_in_help() {
+        let help = SpawnCommand::command_help().unwrap();
+        let names: Vec<_> = help.args.iter().map(|arg| arg.name.as_str()).collect();
+        assert_eq!(names, ["kind", "x", "y", "facing"]);
+        assert_eq!(help.args[1].description.as_deref(), Some("X coordinate"));
+        assert!(help.args[3].optional);
+        assert_eq!((help.min_args, help.max_args), (Some(3), Some(4)));
+
+        let help = LineCommand::command_help().unwrap();
+        assert_eq!(help.args.len(), 5);
+        assert_eq!((help.min_args, help.max_args), (Some(4), Some(5)));
+        assert_eq!(PlacementArgs::ARG_COUNT, 3);
+    }
+
+    struct GiveGoldCommand;
+
+    impl CommandName for GiveGoldCommand {
+        fn command_name() -> &'static str {
+            "give gold"
+        }
+    }
+
+    impl CommandHelp for GiveGoldCommand {}
+
+    #[test]
+    fn it_rejects_names_which_cant_be_typed() {
+        let mut app = test_app();
+        app.add_terminal_command::<GiveGoldCommand, _, _>(|| {});
+        app.update();
+
+        let registry = app.world.resource::<CommandRegistry>();
+        assert!(!registry.contains("give gold"));
+        assert!(registry.contains("help"));
     }
 }
@@ -0,0 +1,136 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{ParseError, TerminalCommandEntered, TerminalCommandQueue};
+
+type ToolbarItemFn = dyn Fn(&mut egui::Ui, &mut TerminalToolbar) + Send + Sync;
+
+struct ToolbarItem {
+    show: Box<ToolbarItemFn>,
+    panicked: bool,
+}
+
+/// Widgets added with [`AddTerminalToolbar::add_terminal_toolbar`], in the order they were added.
+#[derive(Default)]
+pub(crate) struct TerminalToolbarItems(Vec<ToolbarItem>);
+
+impl TerminalToolbarItems {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Show each item, disabling items which panic and returning their indices.
+    pub(crate) fn show(&mut self, ui: &mut egui::Ui, toolbar: &mut TerminalToolbar) -> Vec<usize> {
+        let mut panicked = Vec::new();
+        for (index, item) in self.0.iter_mut().enumerate() {
+            if item.panicked {
+                continue;
+            }
+            let shown = panic::catch_unwind(AssertUnwindSafe(|| (item.show)(ui, toolbar)));
+            if shown.is_err() {
+                item.panicked = true;
+                panicked.push(index);
+            }
+        }
+        panicked
+    }
+}
+
+/// What toolbar widgets can do to the terminal.
+///
+/// See [`AddTerminalToolbar::add_terminal_toolbar`].
+pub struct TerminalToolbar<'a> {
+    pub(crate) queue: &'a mut TerminalCommandQueue,
+    pub(crate) lines: Vec<String>,
+}
+
+impl TerminalToolbar<'_> {
+    /// Queue a command to run after all previously queued commands.
+    pub fn push(&mut self, command: TerminalCommandEntered) {
+        self.queue.push(command);
+    }
+
+    /// Parse a command line and queue it, see [`TerminalCommandQueue::submit`].
+    pub fn submit(&mut self, line: &str) -> Result<(), ParseError> {
+        self.queue.submit(line).map(|_| ())
+    }
+
+    /// Print a line to the terminal.
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+    }
+}
+
+/// Add widgets to the terminal's toolbar, shown above the input while the terminal is open.
+pub trait AddTerminalToolbar {
+    /// Add a widget to the end of the toolbar.
+    ///
+    /// The closure is called every frame the terminal is open, in a horizontal strip.
+    /// If it panics, the panic is reported in the terminal and the widget is no longer shown.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use leafwing_terminal::AddTerminalToolbar;
+    /// #
+    /// App::new().add_terminal_toolbar(|ui, toolbar| {
+    ///     if ui.button("Respawn").clicked() {
+    ///         toolbar.submit("respawn").unwrap();
+    ///     }
+    /// });
+    /// ```
+    fn add_terminal_toolbar(
+        &mut self,
+        item: impl Fn(&mut egui::Ui, &mut TerminalToolbar) + Send + Sync + 'static,
+    ) -> &mut Self;
+}
+
+impl AddTerminalToolbar for App {
+    fn add_terminal_toolbar(
+        &mut self,
+        item: impl Fn(&mut egui::Ui, &mut TerminalToolbar) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(TerminalToolbarItems::default)
+            .0
+            .push(ToolbarItem {
+                show: Box::new(item),
+                panicked: false,
+            });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_removes_panicking_items() {
+        let mut app = App::new();
+        app.add_terminal_toolbar(|_, toolbar| toolbar.submit("spawn_wave").unwrap())
+            .add_terminal_toolbar(|_, _| panic!("broken widget"));
+        let mut items = app.world.remove_resource::<TerminalToolbarItems>().unwrap();
+
+        let mut queue = TerminalCommandQueue::default();
+        let ctx = egui::Context::default();
+        let mut panicked = Vec::new();
+        for _ in 0..2 {
+            let _ = ctx.run(Default::default(), |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let mut toolbar = TerminalToolbar {
+                        queue: &mut queue,
+                        lines: Vec::new(),
+                    };
+                    panicked.push(items.show(ui, &mut toolbar));
+                });
+            });
+        }
+
+        assert_eq!(panicked, [vec![1], vec![]]);
+        assert_eq!(queue.len(), 2);
+    }
+}
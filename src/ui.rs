@@ -1,123 +1,1822 @@
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::mem;
+
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
-use crate::{TerminalCommandEntered, TerminalConfiguration, TerminalState};
+use crate::anchor::AnchorDivider;
+use crate::echo::{format_echo, Echo, EchoSource, SubmitInfo};
+use crate::expand::{expand_history, expand_placeholders};
+use crate::input::{KeyRepeat, TerminalKeys};
+use crate::log::is_error_line;
+use crate::palette::palette_matches;
+use crate::parse::{normalize_input, only_control_characters, unterminated_quote};
+use crate::selection::{bug_report_snippet, dump_lines, selection_text, timestamp};
+use crate::strings::fill;
+use crate::structured::strip_json_flag;
+use crate::terminal::EXAMPLE_COMMENT;
+use crate::toolbar::{TerminalToolbar, TerminalToolbarItems};
+use crate::watchdog::MissingOutcomeHint;
+use crate::{
+    parse_command, CommandCompleters, CommandRegistry, PasteBehavior, PrintTerminalLine, Severity,
+    TerminalCommandEntered, TerminalCommandQueue, TerminalConfiguration, TerminalLayout,
+    TerminalLine, TerminalMode, TerminalOpen, TerminalParseFailed, TerminalPaths,
+    TerminalPromptAnswered, TerminalState, TerminalStrings, TerminalStyle, ValueRawOwned,
+};
 use bevy_egui::egui::epaint::text::cursor::CCursor;
 use bevy_egui::{
     egui::{
-        self, text_edit::CCursorRange, Color32, Context, Frame, Id, RichText, ScrollArea, TextEdit,
+        self, text_edit::CCursorRange, Align2, Area, Color32, Context, FontFamily, FontId, Frame,
+        Id, Order, RichText, ScrollArea, TextEdit,
     },
     EguiContext,
 };
-use leafwing_terminal_parser::{parse_terminal_command, ValueRawOwned};
 
+/// The egui context of the window the terminal is drawn in.
+///
+/// Every terminal UI system skips the frame while there is no such window, such as once the primary window closes,
+/// or while its context isn't ready yet. Command systems keep running in the meantime.
+#[derive(SystemParam)]
+pub(crate) struct TerminalWindow<'w, 's> {
+    egui_context: Option<ResMut<'w, EguiContext>>,
+    windows: Option<Res<'w, Windows>>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl TerminalWindow<'_, '_> {
+    /// The context of the primary window, or of another open window once it closes
+    /// if [`TerminalConfiguration::move_to_open_window`] is set.
+    fn context(&mut self, config: &TerminalConfiguration) -> Option<Context> {
+        let windows = self.windows.as_ref()?;
+        let window = match windows.get_primary() {
+            Some(primary) => primary.id(),
+            None if config.move_to_open_window => windows.iter().next()?.id(),
+            None => return None,
+        };
+        let egui_context = self.egui_context.as_mut()?;
+        egui_context.try_ctx_for_window_mut(window).cloned()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn terminal_ui(
-    mut egui_context: ResMut<EguiContext>,
-    config: Res<TerminalConfiguration>,
+    mut window: TerminalWindow,
+    keys: Res<TerminalKeys>,
+    mut config: ResMut<TerminalConfiguration>,
+    strings: Res<TerminalStrings>,
     mut state: ResMut<TerminalState>,
-    mut command_entered: EventWriter<TerminalCommandEntered>,
+    mut open: ResMut<TerminalOpen>,
+    mut command_queue: ResMut<TerminalCommandQueue>,
+    registry: Res<CommandRegistry>,
+    mut completers: ResMut<CommandCompleters>,
+    mut toolbar_items: ResMut<TerminalToolbarItems>,
+    mut parse_failed: EventWriter<TerminalParseFailed>,
+    mut prompt_answered: EventWriter<TerminalPromptAnswered>,
+    mut terminal_line: EventWriter<PrintTerminalLine>,
+    time: Res<Time>,
+    paths: Res<TerminalPaths>,
+    (mut composing, mut warned_geometry, mut key_repeat): (
+        Local<bool>,
+        Local<Vec<&'static str>>,
+        Local<KeyRepeat>,
+    ),
 ) {
-    const INPUT_HEIGHT: f32 = 30.;
+    const SEPARATOR_SPACING: f32 = 5.;
+    const NOTICE_HEIGHT: f32 = 20.;
+    const TOOLBAR_HEIGHT: f32 = 25.;
+    const GUTTER_WIDTH: f32 = 12.;
+    const COPY_BUTTON_WIDTH: f32 = 24.;
     const MARGIN: f32 = 10.;
 
-    egui::Window::new("Terminal")
-        .collapsible(false)
-        .fixed_pos([config.left_pos, config.top_pos])
-        .fixed_size([config.width - 2. * MARGIN, config.height - 2. * MARGIN])
-        .title_bar(false)
-        .frame(Frame {
-            fill: Color32::BLACK,
-            ..Default::default()
-        })
-        .show(egui_context.ctx_mut(), |ui| {
-            ui.vertical(|ui| {
-                let scroll_height = ui.available_height() - INPUT_HEIGHT;
+    state.pointer_over = false;
+    let Some(ctx) = window.context(&config) else {
+        return;
+    };
+    let keyboard_input = &keys.0;
+    let toggled = keyboard_input.any_just_pressed(config.keys.iter().copied());
+    if toggled {
+        open.0 = !open.0;
+    }
+    if !open.0 {
+        return;
+    }
+    if toggled {
+        // Errors printed while closed are seen once it opens
+        state.unseen_errors = 0;
 
-                // Scroll area
-                ScrollArea::vertical()
-                    .auto_shrink([false, false])
-                    .stick_to_bottom()
-                    .max_height(scroll_height)
-                    .show(ui, |ui| {
-                        ui.vertical(|ui| {
-                            for line in &state.scrollback {
-                                ui.label(RichText::new(line).monospace());
+        // Don't type the toggle key into the input
+        ctx.input_mut()
+            .events
+            .retain(|event| !matches!(event, egui::Event::Text(_)));
+    }
+
+    // Ctrl+C cancels the remaining runs of a repeating command
+    let ctrl = keyboard_input.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if ctrl && keyboard_input.just_pressed(KeyCode::C) {
+        if let Some((queued, count)) = command_queue.cancel_repeat() {
+            let stopped = fill(&strings.repeat_stopped, &[&queued, &count]);
+            terminal_line.send(PrintTerminalLine::new(stopped));
+        }
+    }
+
+    // Open or close the history browser, unless the input answers a prompt or searches history
+    let browser_key = config.history_browser_key;
+    if browser_key.is_some_and(|key| keyboard_input.just_pressed(key))
+        && !matches!(
+            state.mode(),
+            TerminalMode::Prompt(_) | TerminalMode::HistorySearch
+        )
+    {
+        state.toggle_history_browser();
+    }
+
+    // Open or close the command palette, unless the input answers a prompt or the history browser is open
+    let palette_key = config.palette_key;
+    if palette_key.is_some_and(|key| keyboard_input.just_pressed(key))
+        && ctx.input().modifiers.matches(config.palette_modifier)
+        && matches!(
+            state.mode(),
+            TerminalMode::Normal | TerminalMode::CommandPalette
+        )
+    {
+        state.toggle_palette();
+        // Don't type the key into the filter
+        ctx.input_mut()
+            .events
+            .retain(|event| !matches!(event, egui::Event::Text(_)));
+    }
+
+    // Search history backward, or go to an older match while searching, unless another mode has the input
+    let search_key = config.reverse_search_key;
+    if search_key.is_some_and(|key| keyboard_input.just_pressed(key))
+        && ctx
+            .input()
+            .modifiers
+            .matches(config.reverse_search_modifier)
+        && matches!(
+            state.mode(),
+            TerminalMode::Normal | TerminalMode::HistorySearch
+        )
+    {
+        state.search_history_backward();
+        // Don't type the key into the query
+        ctx.input_mut()
+            .events
+            .retain(|event| !matches!(event, egui::Event::Text(_)));
+    }
+
+    let order = match config.always_on_top {
+        true => Order::Foreground,
+        false => Order::Middle,
+    };
+    // Warn once about each field, such as a width computed from the window going negative during a resize
+    for (field, value) in invalid_geometry(&config) {
+        if !warned_geometry.contains(&field) {
+            warned_geometry.push(field);
+            let line = fill(&strings.invalid_geometry, &[&field, &value]);
+            warn!("{line}");
+            terminal_line.send(PrintTerminalLine::new(line));
+        }
+    }
+    let screen = ctx.available_rect();
+    let rect = terminal_rect(&config, screen);
+    let size = (rect.size() - egui::vec2(2. * MARGIN, 2. * MARGIN)).max(egui::Vec2::ZERO);
+    let floating = config.layout == TerminalLayout::Window;
+    let font = terminal_font(&config);
+    let row_height = ctx.fonts().row_height(&font);
+    // Grow the input row with the font, so large fonts aren't clipped
+    let input_height = config
+        .input_height
+        .max(row_height + 2. * ctx.style().spacing.button_padding.y);
+    let now = time.seconds_since_startup();
+    let area = Area::new("Terminal")
+        .current_pos(rect.min)
+        .movable(floating && config.movable)
+        .order(order)
+        .show(&ctx, |ui| {
+            // Everything monospace in the terminal uses the configured font
+            ui.style_mut()
+                .text_styles
+                .insert(egui::TextStyle::Monospace, font);
+            ui.visuals_mut().override_text_color = Some(config.style.text);
+
+            // Flash the border when an error arrives while scrolled up
+            let flashing = state.attention_until > now;
+            let stroke = match flashing {
+                true => egui::Stroke::new(2., config.style.severity.error),
+                false => egui::Stroke::none(),
+            };
+            let frame = Frame {
+                fill: config.style.background,
+                stroke,
+                ..Default::default()
+            };
+            frame.show(ui, |ui| {
+                ui.set_min_size(size);
+                ui.set_max_size(size);
+                ui.vertical(|ui| {
+                    let notice_height = match state.notice {
+                        Some(_) => NOTICE_HEIGHT,
+                        None => 0.,
+                    };
+                    let toolbar_height = match toolbar_items.is_empty() {
+                        true => 0.,
+                        false => TOOLBAR_HEIGHT,
+                    };
+                    let separator_height = config
+                        .style
+                        .separator
+                        .map_or(0., |separator| separator.thickness + SEPARATOR_SPACING);
+                    let margins = config.scrollback_margin.top
+                        + config.scrollback_margin.bottom
+                        + config.input_margin.top
+                        + config.input_margin.bottom;
+                    // The scrollback shrinks first when the terminal is short
+                    let scroll_height = (ui.available_height()
+                        - input_height
+                        - separator_height
+                        - notice_height
+                        - toolbar_height
+                        - margins)
+                        .max(0.);
+
+                    // Scroll area, with a gutter of anchors beside it
+                    let mut clicked_line = None;
+                    let mut expand_line = None;
+                    let mut select_line = None;
+                    let mut selection_action = None;
+                    let selection = state.selection();
+                    let scroll_to = state.scroll_to.take().map(|line| state.clamp_line(line));
+                    let mut scrolled_up = false;
+                    // First line at least partly shown, for Page Up and Page Down
+                    let mut first_visible = None;
+                    let mut measured = Vec::new();
+                    let gutter_width = match state.anchors.is_empty() {
+                        true => 0.,
+                        false => GUTTER_WIDTH,
+                    };
+                    let gutter = config.line_numbers || config.copy_line_buttons;
+                    let last_index = state.scrollback.len().saturating_sub(1);
+                    let number_width = state.line_number(last_index).to_string().len();
+                    let copy_width = match config.copy_line_buttons {
+                        true => COPY_BUTTON_WIDTH,
+                        false => 0.,
+                    };
+                    let scrollback_frame = Frame {
+                        inner_margin: config.scrollback_margin,
+                        ..Default::default()
+                    };
+                    let gutter_jump = scrollback_frame.show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ScrollArea::vertical()
+                                .auto_shrink([false, false])
+                                .stick_to_bottom()
+                                .max_height(scroll_height)
+                                .max_width(ui.available_width() - gutter_width)
+                                .show(ui, |ui| {
+                                    ui.vertical(|ui| {
+                                        // Only the lines in view are laid out, the rest is left as space
+                                        let spacing = ui.spacing().item_spacing.y;
+                                        let origin = ui.cursor().top();
+                                        let top = ui.clip_rect().top() - origin;
+                                        let bottom = ui.clip_rect().bottom() - origin;
+                                        let lines = state.scrollback.len();
+                                        let width = ui.available_width();
+                                        state.row_heights.sync(lines, width, row_height);
+                                        let visible =
+                                            state.row_heights.visible(top, bottom, spacing);
+                                        if let Some(index) = scroll_to {
+                                            let y =
+                                                origin + state.row_heights.offset(index, spacing);
+                                            let rect = egui::Rect::from_min_size(
+                                                egui::pos2(ui.max_rect().left(), y),
+                                                egui::vec2(ui.max_rect().width(), row_height),
+                                            );
+                                            ui.scroll_to_rect(rect, Some(egui::Align::TOP));
+                                        }
+                                        first_visible = Some(visible.rows.start)
+                                            .filter(|_| !visible.rows.is_empty());
+                                        ui.add_space(visible.above);
+
+                                        let rows = visible.rows;
+                                        for (index, line) in
+                                            rows.clone().zip(state.scrollback.range(rows))
+                                        {
+                                            let row_top = ui.cursor().top();
+                                            let expanded =
+                                                state.expanded.contains(&state.line_id(index));
+                                            let truncated = match expanded {
+                                                true => None,
+                                                false => truncate_line(
+                                                    line.as_str(),
+                                                    config.max_line_length,
+                                                ),
+                                            };
+                                            let shown =
+                                                truncated.map_or(line.as_str(), |(shown, _)| shown);
+
+                                            let shown = match config.show_timestamps {
+                                                true => timestamp(line) + shown,
+                                                false => shown.to_string(),
+                                            };
+                                            let mut text = RichText::new(shown).monospace();
+                                            if let Some(color) = line_color(&config.style, line) {
+                                                text = text.color(color);
+                                            }
+                                            if selection
+                                                .as_ref()
+                                                .is_some_and(|selection| selection.contains(&index))
+                                            {
+                                                text = text.background_color(Color32::DARK_GRAY);
+                                            }
+                                            let label = egui::Label::new(text);
+                                            let label = match config.interactive_scrollback {
+                                                true => label.sense(egui::Sense::click()),
+                                                false => label,
+                                            };
+
+                                            // The command an echoed alias runs is shown dimmed after it
+                                            let expansion = line
+                                                .get_meta::<Echo>()
+                                                .and_then(|echo| echo.expansion.as_deref());
+                                            let add_line =
+                                                |ui: &mut egui::Ui| match (truncated, expansion) {
+                                                    (None, None) => ui.add(label),
+                                                    (None, Some(expansion)) => {
+                                                        ui.horizontal_wrapped(|ui| {
+                                                            let response = ui.add(label);
+                                                            let suffix = RichText::new(format!(
+                                                                "→ {expansion}"
+                                                            ))
+                                                            .monospace()
+                                                            .color(Color32::GRAY);
+                                                            ui.label(suffix);
+                                                            response
+                                                        })
+                                                        .inner
+                                                    }
+                                                    (Some((_, hidden)), _) => {
+                                                        ui.horizontal_wrapped(|ui| {
+                                                            let response = ui.add(label);
+                                                            let suffix = format!(
+                                                                "… [+{} chars]",
+                                                                group_thousands(hidden)
+                                                            );
+                                                            let suffix = RichText::new(suffix)
+                                                                .monospace()
+                                                                .color(Color32::GRAY);
+                                                            if ui
+                                                            .add(
+                                                                egui::Label::new(suffix)
+                                                                    .sense(egui::Sense::click()),
+                                                            )
+                                                            .clicked()
+                                                        {
+                                                            expand_line = Some(index);
+                                                        }
+                                                            response
+                                                        })
+                                                        .inner
+                                                    }
+                                                };
+
+                                            // Line number to the left and copy button to the right, if enabled
+                                            let response = match gutter {
+                                                false => add_line(ui),
+                                                true => {
+                                                    ui.horizontal_top(|ui| {
+                                                        if config.line_numbers {
+                                                            let number = format!(
+                                                                "{:>number_width$}",
+                                                                state.line_number(index)
+                                                            );
+                                                            let number = RichText::new(number)
+                                                                .monospace()
+                                                                .color(Color32::DARK_GRAY);
+                                                            ui.label(number);
+                                                        }
+                                                        let response = ui
+                                                            .vertical(|ui| {
+                                                                ui.set_max_width(
+                                                                    ui.available_width()
+                                                                        - copy_width,
+                                                                );
+                                                                add_line(ui)
+                                                            })
+                                                            .inner;
+                                                        let min = ui.min_rect();
+                                                        let row = egui::Rect::from_min_max(
+                                                            min.min,
+                                                            egui::pos2(
+                                                                ui.max_rect().right(),
+                                                                min.max.y,
+                                                            ),
+                                                        );
+                                                        if config.copy_line_buttons
+                                                            && ui.rect_contains_pointer(row)
+                                                            && ui
+                                                                .small_button("📋")
+                                                                .on_hover_text(&strings.copy_line)
+                                                                .clicked()
+                                                        {
+                                                            ui.output().copied_text =
+                                                                line.command_text().to_string();
+                                                        }
+                                                        response
+                                                    })
+                                                    .inner
+                                                }
+                                            };
+                                            if response.double_clicked() {
+                                                clicked_line =
+                                                    Some(line.command_text().to_string());
+                                            } else if response.clicked() {
+                                                let extend = ui.input().modifiers.shift;
+                                                select_line = Some((index, extend));
+                                            } else if response.secondary_clicked()
+                                                && !selection.as_ref().is_some_and(|selection| {
+                                                    selection.contains(&index)
+                                                })
+                                            {
+                                                select_line = Some((index, false));
+                                            }
+                                            response.context_menu(|ui| {
+                                                for (label, action) in [
+                                                    (
+                                                        &strings.copy_selection,
+                                                        SelectionAction::Copy,
+                                                    ),
+                                                    (
+                                                        &strings.dump_selection,
+                                                        SelectionAction::Dump,
+                                                    ),
+                                                    (
+                                                        &strings.bug_report_selection,
+                                                        SelectionAction::BugReport,
+                                                    ),
+                                                ] {
+                                                    if ui.button(label).clicked() {
+                                                        selection_action = Some(action);
+                                                        ui.close_menu();
+                                                    }
+                                                }
+                                            });
+                                            measured.push((index, ui.cursor().top() - row_top));
+                                        }
+                                        ui.add_space(visible.below);
+                                        scrolled_up =
+                                            ui.min_rect().bottom() > ui.clip_rect().bottom() + 1.;
+                                    });
+                                });
+                            match gutter_width > 0. {
+                                true => anchor_gutter(ui, &state, scroll_height),
+                                false => None,
                             }
-                        });
+                        })
+                        .inner
                     });
+                    for (index, height) in measured {
+                        state.row_heights.measure(index, height);
+                    }
+                    // Anchors clicked in the gutter are scrolled to next frame
+                    if let Some(line) = gutter_jump.inner {
+                        state.scroll_to_line(line);
+                    }
 
-                // Separator
-                ui.separator();
+                    // Count errors printed while scrolled up, jumping to the bottom when clicked
+                    state.scrolled_up = scrolled_up;
+                    if !scrolled_up {
+                        state.unseen_errors = 0;
+                    } else if state.unseen_errors > 0 {
+                        let text = fill(&strings.unseen_errors, &[&state.unseen_errors]);
+                        let text = RichText::new(text)
+                            .monospace()
+                            .color(Color32::WHITE)
+                            .background_color(Color32::DARK_RED);
+                        let pos =
+                            gutter_jump.response.rect.left_bottom() - egui::vec2(0., NOTICE_HEIGHT);
+                        let clicked = Area::new("terminal_unseen_errors")
+                            .fixed_pos(pos)
+                            .order(Order::Foreground)
+                            .show(ui.ctx(), |ui| {
+                                ui.add(egui::Label::new(text).sense(egui::Sense::click()))
+                                    .clicked()
+                            })
+                            .inner;
+                        if clicked {
+                            let last = state.scrollback.len().saturating_sub(1);
+                            state.scroll_to_line(last);
+                        }
+                    }
 
-                // Input
-                let text_edit = TextEdit::singleline(&mut state.buf)
-                    .desired_width(f32::INFINITY)
-                    .lock_focus(true)
-                    .font(egui::TextStyle::Monospace);
+                    // Status line
+                    if let Some((notice, _)) = &state.notice {
+                        ui.label(RichText::new(notice).monospace().color(Color32::LIGHT_BLUE));
+                    }
 
-                // Handle enter
-                let text_edit_response = ui.add(text_edit);
-                if text_edit_response.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
-                    if state.buf.trim().is_empty() {
-                        state.scrollback.push(String::new());
-                    } else {
-                        let msg = format!("$ {}", state.buf);
-                        state.scrollback.push(msg);
-                        let cmd_string = state.buf.clone();
-                        state.history.insert(1, cmd_string);
-                        if state.history.len() > config.history_size + 1 {
-                            state.history.pop_back();
-                        }
-
-                        match parse_terminal_command(&state.buf) {
-                            Ok(cmd) => {
-                                let command = TerminalCommandEntered {
-                                    command: cmd.command.to_string(),
-                                    args: cmd.args.into_iter().map(ValueRawOwned::from).collect(),
-                                };
-
-                                command_entered.send(command);
+                    // Toolbar
+                    if !toolbar_items.is_empty() {
+                        let mut toolbar = TerminalToolbar {
+                            queue: &mut command_queue,
+                            lines: Vec::new(),
+                        };
+                        let panicked = ui
+                            .horizontal(|ui| toolbar_items.show(ui, &mut toolbar))
+                            .inner;
+                        for index in panicked {
+                            toolbar
+                                .lines
+                                .push(fill(&strings.toolbar_panicked, &[&index]));
+                        }
+                        terminal_line
+                            .send_batch(toolbar.lines.into_iter().map(PrintTerminalLine::new));
+                    }
+
+                    // Separator
+                    if let Some(separator) = config.style.separator {
+                        let size = egui::vec2(ui.available_width(), separator_height);
+                        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                        let stroke = egui::Stroke::new(separator.thickness, separator.color);
+                        ui.painter().hline(rect.x_range(), rect.center().y, stroke);
+                    }
+
+                    // Handle pastes spanning several lines before the input sees them
+                    if config.paste_behavior != PasteBehavior::InsertRaw {
+                        let mut pasted = Vec::new();
+                        ui.input_mut().events.retain(|event| match event {
+                            egui::Event::Paste(text) if text.contains('\n') => {
+                                pasted.push(text.clone());
+                                false
+                            }
+                            _ => true,
+                        });
+
+                        for text in pasted {
+                            let lines = split_pasted_lines(&text);
+                            match config.paste_behavior {
+                                PasteBehavior::ExecuteLines if state.prompts.is_empty() => {
+                                    for line in lines {
+                                        submit_line(
+                                            line.to_string(),
+                                            now,
+                                            &config,
+                                            &strings,
+                                            &registry,
+                                            &mut state,
+                                            &mut command_queue,
+                                            &mut parse_failed,
+                                            &mut terminal_line,
+                                        );
+                                    }
+                                }
+                                _ => {
+                                    if let Some((first, rest)) = lines.split_first() {
+                                        ui.input_mut()
+                                            .events
+                                            .push(egui::Event::Paste(first.to_string()));
+                                        if !rest.is_empty() {
+                                            terminal_line.send(PrintTerminalLine::new(fill(
+                                                &strings.paste_first_line_only,
+                                                &[&lines.len()],
+                                            )));
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    // Track IME composition, whose keys confirm or pick candidates rather than edit the input
+                    let mut composition_ended = false;
+                    for event in &ui.input().events {
+                        match event {
+                            egui::Event::CompositionStart | egui::Event::CompositionUpdate(_) => {
+                                *composing = true;
                             }
-                            Err(_) => {
-                                state
-                                    .scrollback
-                                    .push("[error] invalid argument(s)".to_string());
+                            egui::Event::CompositionEnd(_) => {
+                                *composing = false;
+                                composition_ended = true;
                             }
+                            _ => {}
                         }
+                    }
+                    let ime_active = *composing || composition_ended;
 
-                        state.buf.clear();
+                    // Auto-close quotes, typing over the closing quote instead of doubling it
+                    let input_id = Id::new("terminal_input");
+                    let mut close_quote = None;
+                    if config.auto_close_quotes {
+                        let cursor = cursor_index(ui.ctx(), input_id, &state.buf);
+                        let next = state.buf.chars().nth(cursor);
+                        let mut skip = false;
+                        ui.input_mut().events.retain(|event| match event {
+                            egui::Event::Text(text) if text == "\"" || text == "'" => {
+                                let quote = text.chars().next();
+                                skip = next == quote;
+                                close_quote = quote.filter(|_| !skip);
+                                !skip
+                            }
+                            _ => true,
+                        });
+                        if skip {
+                            set_cursor_pos(ui.ctx(), input_id, cursor + 1);
+                        }
+                    }
+
+                    // Input, preceded by the outstanding prompt if there is one
+                    let prompt_message = state.prompts.front().map(|prompt| prompt.message.clone());
+                    let unterminated =
+                        prompt_message.is_none() && unterminated_quote(&state.buf).is_some();
+                    let invalid = match prompt_message {
+                        Some(_) => None,
+                        None => validation_error(&state.with_sticky_prefix(&state.buf), &registry),
+                    };
+                    let sticky = match prompt_message {
+                        Some(_) => None,
+                        None => state.sticky.clone(),
+                    };
+                    let input_frame = Frame {
+                        inner_margin: config.input_margin,
+                        ..Default::default()
+                    };
+                    let text_edit_response = input_frame
+                        .show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.set_min_height(input_height);
+                                if let Some(message) = prompt_message {
+                                    ui.label(
+                                        RichText::new(message).monospace().color(Color32::YELLOW),
+                                    );
+                                }
+                                if let Some(prefix) = sticky {
+                                    let prefix = format!("{prefix}>");
+                                    ui.label(
+                                        RichText::new(prefix)
+                                            .monospace()
+                                            .color(Color32::LIGHT_BLUE),
+                                    );
+                                }
+                                if unterminated {
+                                    ui.label(RichText::new("⚠").color(Color32::YELLOW))
+                                        .on_hover_text(&strings.unterminated_quote);
+                                }
+                                if let Some(invalid) = invalid {
+                                    ui.label(RichText::new("✖").color(Color32::RED))
+                                        .on_hover_text(invalid);
+                                }
+
+                                let text_edit = TextEdit::singleline(&mut state.buf)
+                                    .id(input_id)
+                                    .desired_width(f32::INFINITY)
+                                    .lock_focus(true)
+                                    .font(egui::TextStyle::Monospace);
+                                ui.add(text_edit)
+                            })
+                            .inner
+                        })
+                        .inner;
+
+                    if let Some(quote) = close_quote.filter(|_| text_edit_response.changed()) {
+                        let cursor = cursor_index(ui.ctx(), input_id, &state.buf);
+                        insert_at_cursor(ui.ctx(), input_id, &mut state.buf, &quote.to_string());
+                        set_cursor_pos(ui.ctx(), input_id, cursor);
+                    }
+
+                    if let Some(index) = expand_line {
+                        let line = state.line_id(index);
+                        state.expanded.insert(line);
+                    }
+
+                    // Clicking a line selects it for `%sel%`, Shift+clicking another selects the lines between
+                    if let Some((index, extend)) = select_line {
+                        state.select_line(index, extend);
+                    }
+
+                    // Act on the selection from the context menu
+                    match selection_action {
+                        Some(SelectionAction::Copy) => {
+                            ui.output().copied_text =
+                                selection_text(&state.selected_lines(), config.show_timestamps);
+                        }
+                        Some(SelectionAction::Dump) => {
+                            let lines = state.selected_lines();
+                            let line = match dump_lines(&lines, &paths, config.show_timestamps) {
+                                Ok(path) => fill(
+                                    &strings.selection_dumped,
+                                    &[&lines.len(), &path.display()],
+                                ),
+                                Err(error) => fill(&strings.dump_failed, &[&error]),
+                            };
+                            terminal_line.send(PrintTerminalLine::new(line));
+                        }
+                        Some(SelectionAction::BugReport) => {
+                            let version = config.app_version.as_deref().unwrap_or("unknown");
+                            let header = fill(&strings.bug_report_header, &[&version]);
+                            ui.output().copied_text =
+                                bug_report_snippet(&header, &state.selected_lines());
+                        }
+                        None => {}
+                    }
+
+                    // Copy a double-clicked line, or insert it with Ctrl held
+                    if let Some(line) = clicked_line {
+                        if ui.input().modifiers.command {
+                            insert_at_cursor(
+                                ui.ctx(),
+                                text_edit_response.id,
+                                &mut state.buf,
+                                &line,
+                            );
+                            state.history_prefix = None;
+                        } else {
+                            ui.output().copied_text = line;
+                        }
+                    }
+
+                    // The history browser takes the keys while it's open
+                    if state.history_browser.is_some() {
+                        let above = text_edit_response.rect;
+                        if let Some(entry) =
+                            history_browser(ui, &mut state, &strings, &config.style, above)
+                        {
+                            insert_at_cursor(ui.ctx(), input_id, &mut state.buf, &entry);
+                            state.history_prefix = None;
+                        }
+                        return;
+                    }
+
+                    // So does the command palette
+                    if state.palette.is_some() {
+                        let above = text_edit_response.rect;
+                        if let Some(name) = command_palette(
+                            ui,
+                            &mut state,
+                            &registry,
+                            &strings,
+                            &config.style,
+                            above,
+                        ) {
+                            state.buf = format!("{name} ");
+                            state.history_prefix = None;
+                            set_cursor_pos(ui.ctx(), input_id, state.buf.len());
+                        }
+                        return;
                     }
-                }
 
-                // Handle up and down through history
-                if text_edit_response.has_focus()
-                    && ui.input().key_pressed(egui::Key::ArrowUp)
-                    && state.history.len() > 1
-                    && state.history_index < state.history.len() - 1
-                {
-                    if state.history_index == 0 && !state.buf.trim().is_empty() {
-                        *state.history.get_mut(0).unwrap() = state.buf.clone();
-                    }
-
-                    state.history_index += 1;
-                    let previous_item = state.history.get(state.history_index).unwrap().clone();
-                    state.buf = previous_item;
-
-                    set_cursor_pos(ui.ctx(), text_edit_response.id, state.buf.len());
-                } else if text_edit_response.has_focus()
-                    && ui.input().key_pressed(egui::Key::ArrowDown)
-                    && state.history_index > 0
-                {
-                    state.history_index -= 1;
-                    let next_item = state.history.get(state.history_index).unwrap().clone();
-                    state.buf = next_item;
-
-                    set_cursor_pos(ui.ctx(), text_edit_response.id, state.buf.len());
+                    // And the reverse history search, which submits the match on Enter
+                    if state.history_search.is_some() {
+                        let below = text_edit_response.rect;
+                        if history_search(ui, &mut state, &strings, &config.style, below) {
+                            let line = state.accept_history_search();
+                            let submitted = !line.trim().is_empty()
+                                && submit_line(
+                                    line,
+                                    now,
+                                    &config,
+                                    &strings,
+                                    &registry,
+                                    &mut state,
+                                    &mut command_queue,
+                                    &mut parse_failed,
+                                    &mut terminal_line,
+                                );
+                            if submitted && config.close_on_submit {
+                                open.0 = false;
+                            }
+                        }
+                        return;
+                    }
+
+                    // Handle escape
+                    if ui.input().key_pressed(egui::Key::Escape) {
+                        if let Some((answered, echo)) = state.cancel_prompt() {
+                            terminal_line.send(echo);
+                            prompt_answered.send(answered);
+                            state.buf.clear();
+                        } else {
+                            // Leave sticky mode and drop the selection and completions
+                            state.sticky = None;
+                            state.clear_selection();
+                            state.completions.clear();
+                        }
+                    }
+
+                    // Handle enter
+                    if text_edit_response.lost_focus()
+                        && ui.input().key_pressed(egui::Key::Enter)
+                        && !ime_active
+                    {
+                        state.completions.clear();
+                        if state.mode() != TerminalMode::Normal {
+                            let answer = mem::take(&mut state.buf);
+                            if let Some((answered, echo)) = state.answer_prompt(&answer) {
+                                terminal_line.send(echo);
+                                prompt_answered.send(answered);
+                            }
+                        } else if state.buf.trim().is_empty() {
+                            terminal_line.send(PrintTerminalLine::new(String::new()));
+                        } else if unterminated_quote(&state.buf).is_some() {
+                            // Continue the quoted string on a new line
+                            insert_at_cursor(ui.ctx(), input_id, &mut state.buf, "\n");
+                        } else {
+                            let line = mem::take(&mut state.buf);
+                            let submitted = submit_line(
+                                line,
+                                now,
+                                &config,
+                                &strings,
+                                &registry,
+                                &mut state,
+                                &mut command_queue,
+                                &mut parse_failed,
+                                &mut terminal_line,
+                            );
+
+                            // Shift+Enter inverts `close_on_submit` for this command
+                            let shift = ui.input().modifiers.shift;
+                            if submitted && config.close_on_submit != shift {
+                                open.0 = false;
+                            }
+                        }
+                    }
+
+                    // Typing ends history prefix navigation and hides completions
+                    if text_edit_response.changed() {
+                        state.history_prefix = None;
+                        state.completions.clear();
+                    }
+
+                    // Handle up and down through history, repeating while held
+                    if text_edit_response.has_focus() && !ime_active {
+                        let up = key_repeat.steps(KeyCode::Up, &keys.0, now, &config);
+                        let down = key_repeat.steps(KeyCode::Down, &keys.0, now, &config);
+                        let prefix_search =
+                            ui.input().modifiers.matches(config.history_search_modifier);
+
+                        let mut changed = false;
+                        for _ in 0..up {
+                            changed |= match prefix_search {
+                                true => state.history_previous_with_prefix(),
+                                false => state.history_previous(),
+                            };
+                        }
+                        for _ in 0..down {
+                            changed |= match prefix_search {
+                                true => state.history_next_with_prefix(),
+                                false => state.history_next(),
+                            };
+                        }
+                        if changed {
+                            set_cursor_pos(ui.ctx(), text_edit_response.id, state.buf.len());
+                        }
+                    }
+
+                    // Scroll the scrollback a page at a time, repeating while held
+                    let page_up = key_repeat.steps(KeyCode::PageUp, &keys.0, now, &config);
+                    let page_down = key_repeat.steps(KeyCode::PageDown, &keys.0, now, &config);
+                    if let Some(first) = first_visible.filter(|_| page_up + page_down > 0) {
+                        let page = ((scroll_height / row_height) as usize).max(1);
+                        let last = state.scrollback.len().saturating_sub(1);
+                        let line = (first + page * page_down as usize)
+                            .saturating_sub(page * page_up as usize)
+                            .min(last);
+                        state.scroll_to_line(line);
+                    }
+
+                    // Step through history with the mouse wheel, keeping it from scrolling anything else
+                    if config.history_wheel && text_edit_response.hovered() && !ime_active {
+                        let delta = ui.input().scroll_delta.y;
+                        ui.ctx().input_mut().scroll_delta = egui::Vec2::ZERO;
+                        if state.history_wheel(delta) {
+                            set_cursor_pos(ui.ctx(), text_edit_response.id, state.buf.len());
+                        }
+                    } else {
+                        state.history_wheel = 0.;
+                    }
+
+                    // Complete the argument being typed with Tab, listing the candidates if there are several
+                    // Held Tab keeps completing, such as through nested paths
+                    let tabs = key_repeat.steps(KeyCode::Tab, &keys.0, now, &config);
+                    let normal = state.mode() == TerminalMode::Normal;
+                    if normal && text_edit_response.has_focus() && !ime_active {
+                        for _ in 0..tabs {
+                            let line = state.with_sticky_prefix(&state.buf).into_owned();
+                            let prefix_len = line.len() - state.buf.trim_start().len();
+                            match completers.complete(&line, &registry, &paths) {
+                                Some(completion) => {
+                                    state.buf = completion.line[prefix_len..].to_string();
+                                    state.completions = completion.candidates;
+                                    let end = state.buf.chars().count();
+                                    set_cursor_pos(ui.ctx(), text_edit_response.id, end);
+                                }
+                                None => state.completions.clear(),
+                            }
+                        }
+                    }
+                    if !state.completions.is_empty() {
+                        let input_rect = text_edit_response.rect;
+                        completion_list(
+                            ui,
+                            &state.completions,
+                            &strings,
+                            &config.style,
+                            input_rect,
+                        );
+                    }
+
+                    // Focus on input
+                    ui.memory().request_focus(text_edit_response.id);
+                });
+            });
+
+            // Resize by dragging the bottom right corner
+            (floating && config.resizable).then(|| resize_grip(ui))
+        });
+
+    // Echoes and errors pushed while drawing count toward the limit too
+    state.trim_scrollback(config.scrollback_size);
+    state.pointer_over = ctx
+        .input()
+        .pointer
+        .hover_pos()
+        .is_some_and(|pos| area.response.rect.contains(pos));
+
+    // Keep where the user moved or resized the terminal to
+    let delta = ctx.input().pointer.delta();
+    if area.response.dragged() {
+        config.left_pos = rect.min.x + delta.x;
+        config.top_pos = rect.min.y + delta.y;
+    }
+    if area.inner == Some(true) {
+        config.width = (rect.width() + delta.x).max(MIN_WIDTH);
+        config.height = (rect.height() + delta.y).max(MIN_HEIGHT);
+    }
+}
+
+/// The font of the scrollback and input, see [`TerminalConfiguration::font`].
+fn terminal_font(config: &TerminalConfiguration) -> FontId {
+    let family = config.font.clone().unwrap_or(FontFamily::Monospace);
+    FontId::new(config.font_size, family)
+}
+
+/// Draws a grip in the bottom right corner of `ui`, returning whether it is dragged.
+fn resize_grip(ui: &mut egui::Ui) -> bool {
+    const SIZE: f32 = 12.;
+
+    let corner = ui.min_rect().max;
+    let rect = egui::Rect::from_min_max(corner - egui::vec2(SIZE, SIZE), corner);
+    let response = ui.interact(rect, ui.id().with("resize_grip"), egui::Sense::drag());
+    if response.hovered() || response.dragged() {
+        ui.output().cursor_icon = egui::CursorIcon::ResizeSouthEast;
+    }
+    let stroke = egui::Stroke::new(1., Color32::GRAY);
+    for offset in [SIZE / 3., 2. * SIZE / 3., SIZE] {
+        ui.painter().line_segment(
+            [
+                egui::pos2(corner.x - offset, corner.y),
+                egui::pos2(corner.x, corner.y - offset),
+            ],
+            stroke,
+        );
+    }
+    response.dragged()
+}
+
+pub(crate) struct Toast {
+    text: String,
+    color: Color32,
+    expires: f64,
+}
+
+/// Shows errors and failures printed while the terminal is closed.
+pub(crate) fn terminal_toasts(
+    mut window: TerminalWindow,
+    config: Res<TerminalConfiguration>,
+    strings: Res<TerminalStrings>,
+    open: Res<TerminalOpen>,
+    time: Res<Time>,
+    mut lines: EventReader<PrintTerminalLine>,
+    mut toasts: Local<VecDeque<Toast>>,
+) {
+    const MARGIN: f32 = 10.;
+
+    if open.0 || !config.toasts {
+        for _ in lines.iter() {}
+        toasts.clear();
+        return;
+    }
+
+    let now = time.seconds_since_startup();
+    let mut previous: Option<&str> = None;
+    for printed in lines.iter() {
+        let line = &printed.line;
+        let (ok, failed) = match printed.severity() {
+            Some(severity) => (severity == Severity::Ok, severity == Severity::Failed),
+            None => (
+                line.starts_with(&strings.ok),
+                line.starts_with(&strings.failed),
+            ),
+        };
+        let outcome = ok || failed;
+        let toast = if is_error_line(printed, &strings) {
+            Some(Color32::RED)
+        } else if ok && config.toast_successes {
+            Some(Color32::GREEN)
+        } else {
+            None
+        };
+        if let Some(color) = toast {
+            // Outcomes are sent after the reply they belong to
+            let text = match previous {
+                Some(previous) if outcome => {
+                    format!("{previous} {line}")
                 }
+                _ => line.clone(),
+            };
+            toasts.push_back(Toast {
+                text,
+                color,
+                expires: now + config.toast_duration as f64,
+            });
+        }
+        previous = Some(line);
+    }
 
-                // Focus on input
-                ui.memory().request_focus(text_edit_response.id);
+    toasts.retain(|toast| toast.expires > now);
+    while toasts.len() > config.max_toasts {
+        toasts.pop_front();
+    }
+    if toasts.is_empty() {
+        return;
+    }
+    let Some(ctx) = window.context(&config) else {
+        return;
+    };
+
+    Area::new("terminal_toasts")
+        .anchor(Align2::RIGHT_BOTTOM, [-MARGIN, -MARGIN])
+        .order(Order::Foreground)
+        .interactable(false)
+        .show(&ctx, |ui| {
+            for toast in toasts.iter() {
+                Frame::popup(ui.style())
+                    .fill(config.style.background)
+                    .show(ui, |ui| {
+                        ui.label(RichText::new(&toast.text).monospace().color(toast.color));
+                    });
+            }
+        });
+}
+
+/// Color of a scrollback line, or `None` for [`TerminalStyle::text`].
+fn line_color(style: &TerminalStyle, line: &TerminalLine) -> Option<Color32> {
+    if line.get_meta::<AnchorDivider>().is_some() {
+        Some(Color32::GOLD)
+    } else if line.get_meta::<MissingOutcomeHint>().is_some() {
+        Some(Color32::GRAY)
+    } else if line.get_meta::<Echo>().is_some() {
+        Some(style.prompt)
+    } else {
+        line.severity()
+            .map(|severity| style.severity.color(severity))
+    }
+}
+
+/// Echoes a submitted command at `now`, adds it to the history and queues it,
+/// returning whether it parsed.
+///
+/// The history keeps placeholders such as `%last%` unexpanded so recalling the line expands them again,
+/// but includes the `sticky` prefix so the line can be recalled outside sticky mode.
+/// Lines asking for help with `?` run `help` or list matching commands instead, see [`help_request`].
+///
+/// The echo and any error are printed like command output, so they come after lines printed before them.
+#[allow(clippy::too_many_arguments)]
+fn submit_line(
+    line: String,
+    now: f64,
+    config: &TerminalConfiguration,
+    strings: &TerminalStrings,
+    registry: &CommandRegistry,
+    state: &mut TerminalState,
+    command_queue: &mut TerminalCommandQueue,
+    parse_failed: &mut EventWriter<TerminalParseFailed>,
+    terminal_line: &mut EventWriter<PrintTerminalLine>,
+) -> bool {
+    if only_control_characters(&line) {
+        terminal_line.send(PrintTerminalLine::new(
+            strings.only_control_characters.clone(),
+        ));
+        return false;
+    }
+    let line = state
+        .with_sticky_prefix(normalize_input(&line))
+        .into_owned();
+    let echo = |line: &str, invocation| {
+        let info = SubmitInfo {
+            line,
+            source: EchoSource::Typed,
+            invocation,
+            printed_at: now,
+        };
+        PrintTerminalLine::from(format_echo(config, registry, &info))
+    };
+    // `!!` is echoed and recorded as the line it expands to
+    let expanded = expand_history(&line, state.history().next());
+    let line = match expanded {
+        Ok(line) => line,
+        Err(placeholder) => {
+            terminal_line.send(echo(&line, None));
+            terminal_line.send(PrintTerminalLine::new(fill(
+                &strings.nothing_to_expand,
+                &[&placeholder],
+            )));
+            return false;
+        }
+    };
+    state.record_submission(line.clone(), config);
+    let echoed = line.clone();
+
+    let selection = state
+        .selected
+        .and_then(|line| state.scrollback.get(state.clamp_line(line)));
+    let expanded = expand_placeholders(
+        &line,
+        state.last_output(strings),
+        selection.map(TerminalLine::command_text),
+    );
+    let line = match expanded {
+        Ok(line) => line,
+        Err(placeholder) => {
+            terminal_line.send(echo(&echoed, None));
+            terminal_line.send(PrintTerminalLine::new(fill(
+                &strings.nothing_to_expand,
+                &[&placeholder],
+            )));
+            return false;
+        }
+    };
+
+    match help_request(&line, config, registry) {
+        Some(HelpRequest::Command(command)) => {
+            let invocation = command_queue.push(TerminalCommandEntered {
+                command: "help".to_string(),
+                args: vec![ValueRawOwned::String(command)],
+            });
+            terminal_line.send(echo(&echoed, Some(invocation)));
+            return true;
+        }
+        Some(HelpRequest::Search(prefix)) => {
+            let indent = " ".repeat(config.help_format.indent);
+            let names: Vec<String> = registry
+                .iter()
+                .filter(|(name, _)| name.starts_with(&prefix))
+                .map(|(name, _)| format!("{indent}{name}"))
+                .collect();
+            let heading = match names.is_empty() {
+                true => &strings.no_commands_starting_with,
+                false => &strings.commands_starting_with,
+            };
+            terminal_line.send(echo(&echoed, None));
+            terminal_line.send(PrintTerminalLine::new(fill(heading, &[&prefix])));
+            terminal_line.send_batch(names.into_iter().map(PrintTerminalLine::new));
+            return true;
+        }
+        None => {}
+    }
+
+    let (line, json) = strip_json_flag(&line);
+    match parse_command(line) {
+        Ok(command) => {
+            let invocation = command_queue.push(command);
+            if json {
+                command_queue.structured.insert(invocation);
+            }
+            terminal_line.send(echo(&echoed, Some(invocation)));
+            true
+        }
+        Err(err) => {
+            terminal_line.send(echo(&echoed, None));
+            terminal_line.send(PrintTerminalLine::new(fill(
+                &strings.invalid_arguments,
+                &[&err],
+            )));
+            parse_failed.send(TerminalParseFailed {
+                input: line.to_string(),
+                error: err,
             });
+            false
+        }
+    }
+}
+
+/// The message from the validator of the command being typed, if its arguments are invalid.
+///
+/// See [`CommandRegistry::set_validator`].
+/// Draws the history browser above the input and handles its keys, returning the entry picked.
+///
+/// Up and Down move through the matches shown newest at the bottom, Enter or a click picks one,
+/// Delete removes it from the history and Escape closes the browser.
+fn history_browser(
+    ui: &mut egui::Ui,
+    state: &mut TerminalState,
+    strings: &TerminalStrings,
+    style: &TerminalStyle,
+    input_rect: egui::Rect,
+) -> Option<String> {
+    const HEIGHT: f32 = 200.;
+
+    let (up, down, enter, delete, escape) = {
+        let input = ui.input();
+        (
+            input.key_pressed(egui::Key::ArrowUp),
+            input.key_pressed(egui::Key::ArrowDown),
+            input.key_pressed(egui::Key::Enter),
+            input.key_pressed(egui::Key::Delete),
+            input.key_pressed(egui::Key::Escape),
+        )
+    };
+    if escape {
+        state.history_browser = None;
+        return None;
+    }
+    // Delete removes entries rather than editing the filter
+    ui.input_mut().events.retain(|event| {
+        !matches!(
+            event,
+            egui::Event::Key {
+                key: egui::Key::Delete,
+                ..
+            }
+        )
+    });
+
+    let matches: Vec<String> = state
+        .history_matches()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let browser = state.history_browser.as_mut()?;
+    if up {
+        browser.selected += 1;
+    }
+    if down {
+        browser.selected = browser.selected.saturating_sub(1);
+    }
+    browser.selected = browser.selected.min(matches.len().saturating_sub(1));
+    let selected = browser.selected;
+    if enter {
+        state.history_browser = None;
+        return matches.get(selected).cloned();
+    }
+    if delete {
+        if let Some(entry) = matches.get(selected) {
+            state.remove_history_entry(entry);
+        }
+    }
+
+    let mut picked = None;
+    let filter_id = Id::new("terminal_history_filter");
+    Area::new("terminal_history_browser")
+        .fixed_pos(input_rect.left_top() - egui::vec2(0., HEIGHT))
+        .order(Order::Foreground)
+        .show(ui.ctx(), |ui| {
+            Frame::popup(ui.style())
+                .fill(style.background)
+                .show(ui, |ui| {
+                    ui.visuals_mut().override_text_color = Some(style.text);
+                    ui.set_width(input_rect.width());
+                    ui.set_height(HEIGHT);
+                    ScrollArea::vertical()
+                        .auto_shrink([false, true])
+                        .stick_to_bottom()
+                        .max_height(HEIGHT - 2. * ui.spacing().interact_size.y)
+                        .show(ui, |ui| {
+                            if matches.is_empty() {
+                                let empty = RichText::new(&strings.no_history_matches)
+                                    .monospace()
+                                    .color(Color32::GRAY);
+                                ui.label(empty);
+                            }
+                            for (index, entry) in matches.iter().enumerate().rev() {
+                                let mut text = RichText::new(entry).monospace();
+                                if index == selected {
+                                    text = text.background_color(Color32::DARK_GRAY);
+                                }
+                                let response =
+                                    ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+                                if response.clicked() {
+                                    picked = Some(entry.clone());
+                                }
+                                if index == selected && (up || down) {
+                                    response.scroll_to_me(None);
+                                }
+                            }
+                        });
+
+                    if let Some(browser) = &mut state.history_browser {
+                        let filter = TextEdit::singleline(&mut browser.filter)
+                            .id(filter_id)
+                            .hint_text(&strings.history_filter_hint)
+                            .desired_width(f32::INFINITY)
+                            .font(egui::TextStyle::Monospace);
+                        if ui.add(filter).changed() {
+                            browser.selected = 0;
+                        }
+                    }
+                });
         });
+    ui.memory().request_focus(filter_id);
+
+    if picked.is_some() {
+        state.history_browser = None;
+    }
+    picked
+}
+
+/// Draws the query of the reverse history search below the input and handles its keys,
+/// returning whether Enter was pressed to submit the match shown in the input.
+///
+/// Escape cancels the search.
+fn history_search(
+    ui: &mut egui::Ui,
+    state: &mut TerminalState,
+    strings: &TerminalStrings,
+    style: &TerminalStyle,
+    input_rect: egui::Rect,
+) -> bool {
+    let (enter, escape) = {
+        let input = ui.input();
+        (
+            input.key_pressed(egui::Key::Enter),
+            input.key_pressed(egui::Key::Escape),
+        )
+    };
+    if escape {
+        state.cancel_history_search();
+        return false;
+    }
+    if enter {
+        return true;
+    }
+
+    let Some(search) = &state.history_search else {
+        return false;
+    };
+    let mut query = search.query.clone();
+    let no_match = !query.is_empty() && state.history_search_match().is_none();
+    let query_id = Id::new("terminal_history_search");
+    let changed = Area::new("terminal_history_search")
+        .fixed_pos(input_rect.left_bottom())
+        .order(Order::Foreground)
+        .show(ui.ctx(), |ui| {
+            Frame::popup(ui.style())
+                .fill(style.background)
+                .show(ui, |ui| {
+                    ui.visuals_mut().override_text_color = Some(style.text);
+                    ui.set_width(input_rect.width());
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(&strings.reverse_search_label).monospace());
+                        if no_match {
+                            let hint = RichText::new(&strings.no_reverse_search_match)
+                                .monospace()
+                                .color(Color32::GRAY);
+                            ui.label(hint);
+                        }
+                        let edit = TextEdit::singleline(&mut query)
+                            .id(query_id)
+                            .desired_width(f32::INFINITY)
+                            .font(egui::TextStyle::Monospace);
+                        ui.add(edit).changed()
+                    })
+                    .inner
+                })
+                .inner
+        })
+        .inner;
+    ui.memory().request_focus(query_id);
+
+    if changed {
+        state.set_history_search_query(query);
+    }
+    false
+}
+
+/// Draws the command palette above the input and handles its keys, returning the name of the command picked.
+///
+/// Up and Down move through the matches shown best at the top, Enter or a click picks one
+/// and Escape closes the palette. The usage of the highlighted command is shown below the matches.
+fn command_palette(
+    ui: &mut egui::Ui,
+    state: &mut TerminalState,
+    registry: &CommandRegistry,
+    strings: &TerminalStrings,
+    style: &TerminalStyle,
+    input_rect: egui::Rect,
+) -> Option<String> {
+    const HEIGHT: f32 = 240.;
+
+    let (up, down, enter, escape) = {
+        let input = ui.input();
+        (
+            input.key_pressed(egui::Key::ArrowUp),
+            input.key_pressed(egui::Key::ArrowDown),
+            input.key_pressed(egui::Key::Enter),
+            input.key_pressed(egui::Key::Escape),
+        )
+    };
+    if escape {
+        state.palette = None;
+        return None;
+    }
+
+    let palette = state.palette.as_mut()?;
+    let matches = palette_matches(&palette.filter, registry);
+    if up {
+        palette.selected = palette.selected.saturating_sub(1);
+    }
+    if down {
+        palette.selected += 1;
+    }
+    palette.selected = palette.selected.min(matches.len().saturating_sub(1));
+    let selected = palette.selected;
+    if enter {
+        state.palette = None;
+        return matches.get(selected).map(|(name, _)| name.to_string());
+    }
+
+    let mut picked = None;
+    let filter_id = Id::new("terminal_palette_filter");
+    Area::new("terminal_command_palette")
+        .fixed_pos(input_rect.left_top() - egui::vec2(0., HEIGHT))
+        .order(Order::Foreground)
+        .show(ui.ctx(), |ui| {
+            Frame::popup(ui.style())
+                .fill(style.background)
+                .show(ui, |ui| {
+                    ui.visuals_mut().override_text_color = Some(style.text);
+                    ui.set_width(input_rect.width());
+                    ui.set_height(HEIGHT);
+                    if let Some(palette) = &mut state.palette {
+                        let filter = TextEdit::singleline(&mut palette.filter)
+                            .id(filter_id)
+                            .hint_text(&strings.palette_filter_hint)
+                            .desired_width(f32::INFINITY)
+                            .font(egui::TextStyle::Monospace);
+                        if ui.add(filter).changed() {
+                            palette.selected = 0;
+                        }
+                    }
+
+                    ScrollArea::vertical()
+                        .auto_shrink([false, true])
+                        .max_height(HEIGHT - 3. * ui.spacing().interact_size.y)
+                        .show(ui, |ui| {
+                            if matches.is_empty() {
+                                let empty = RichText::new(&strings.no_palette_matches)
+                                    .monospace()
+                                    .color(Color32::GRAY);
+                                ui.label(empty);
+                            }
+                            for (index, (name, info)) in matches.iter().enumerate() {
+                                let description =
+                                    info.and_then(|info| info.summary()).unwrap_or_default();
+                                let mut text =
+                                    RichText::new(format!("{name:<16} {description}")).monospace();
+                                if index == selected {
+                                    text = text.background_color(Color32::DARK_GRAY);
+                                }
+                                let response =
+                                    ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+                                if response.clicked() {
+                                    picked = Some(name.to_string());
+                                }
+                                if index == selected && (up || down) {
+                                    response.scroll_to_me(None);
+                                }
+                            }
+                        });
+
+                    if let Some((_, Some(info))) = matches.get(selected) {
+                        ui.separator();
+                        let usage = RichText::new(format!("> {}", info.signature()))
+                            .monospace()
+                            .color(Color32::GRAY);
+                        ui.label(usage);
+                    }
+                });
+        });
+    ui.memory().request_focus(filter_id);
+
+    if picked.is_some() {
+        state.palette = None;
+    }
+    picked
+}
+
+/// Lists Tab completions above the input, cut short after `MAX_SHOWN`.
+fn completion_list(
+    ui: &mut egui::Ui,
+    completions: &[String],
+    strings: &TerminalStrings,
+    style: &TerminalStyle,
+    input_rect: egui::Rect,
+) {
+    const MAX_SHOWN: usize = 10;
+
+    let shown = completions.len().min(MAX_SHOWN);
+    let rows = shown + usize::from(completions.len() > MAX_SHOWN);
+    let row_height =
+        ui.text_style_height(&egui::TextStyle::Monospace) + ui.spacing().item_spacing.y;
+    let height = rows as f32 * row_height + 2. * ui.spacing().window_margin.top;
+    Area::new("terminal_completions")
+        .fixed_pos(input_rect.left_top() - egui::vec2(0., height))
+        .order(Order::Foreground)
+        .interactable(false)
+        .show(ui.ctx(), |ui| {
+            Frame::popup(ui.style())
+                .fill(style.background)
+                .show(ui, |ui| {
+                    ui.visuals_mut().override_text_color = Some(style.text);
+                    for completion in &completions[..shown] {
+                        ui.label(RichText::new(completion).monospace());
+                    }
+                    if completions.len() > MAX_SHOWN {
+                        let more = fill(
+                            &strings.more_completions,
+                            &[&(completions.len() - MAX_SHOWN)],
+                        );
+                        ui.label(RichText::new(more).monospace().color(Color32::GRAY));
+                    }
+                });
+        });
+}
+
+/// Draws a tick for each anchor at its position in the scrollback, returning the line of a clicked one.
+fn anchor_gutter(ui: &mut egui::Ui, state: &TerminalState, height: f32) -> Option<usize> {
+    const TICK_HEIGHT: f32 = 4.;
+
+    let size = egui::vec2(ui.available_width(), height);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let lines = state.scrollback.len().max(1) as f32;
+    let mut clicked = None;
+    for (label, line) in state.anchors() {
+        let top = rect.top() + rect.height() * line as f32 / lines;
+        let top = top.min(rect.bottom() - TICK_HEIGHT);
+        let tick = egui::Rect::from_min_size(
+            egui::pos2(rect.left(), top),
+            egui::vec2(rect.width(), TICK_HEIGHT),
+        );
+        let response = ui
+            .interact(tick, ui.id().with(("anchor", label)), egui::Sense::click())
+            .on_hover_text(label);
+        let color = match response.hovered() {
+            true => Color32::WHITE,
+            false => Color32::GOLD,
+        };
+        ui.painter().rect_filled(tick, 0., color);
+        if response.clicked() {
+            clicked = Some(line);
+        }
+    }
+    clicked
+}
+
+/// Help asked for with `?` instead of a command to run.
+/// What to do with the selected scrollback lines, picked from their context menu
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SelectionAction {
+    Copy,
+    Dump,
+    BugReport,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum HelpRequest {
+    /// `spawn ?` prints the help of `spawn`
+    Command(String),
+    /// `?sp` lists the commands starting with `sp`
+    Search(String),
+}
+
+/// Finds a request for help in a submitted line, as enabled by [`TerminalConfiguration::help_suffix`]
+/// and [`TerminalConfiguration::help_search`].
+///
+/// Only an unquoted `?` after a registered command or alias asks for its help, whatever its other arguments.
+fn help_request(
+    line: &str,
+    config: &TerminalConfiguration,
+    registry: &CommandRegistry,
+) -> Option<HelpRequest> {
+    let line = line.trim();
+    if let Some(prefix) = line.strip_prefix('?') {
+        let prefix = prefix.trim_start();
+        if config.help_search && !prefix.is_empty() && !prefix.contains(char::is_whitespace) {
+            return Some(HelpRequest::Search(prefix.to_string()));
+        }
+    }
+
+    let rest = line.strip_suffix('?')?;
+    if !config.help_suffix
+        || !rest.ends_with(char::is_whitespace)
+        || unterminated_quote(rest).is_some()
+    {
+        return None;
+    }
+    let command = rest.split_whitespace().next()?;
+    registry
+        .contains(command)
+        .then(|| HelpRequest::Command(command.to_string()))
+}
+
+fn validation_error(buf: &str, registry: &CommandRegistry) -> Option<String> {
+    let command = parse_command(buf).ok()?;
+    registry.validate(&command.command, &command.args).err()
+}
+
+/// Splits pasted text into its non-empty lines, keeping newlines inside quoted strings.
+fn split_pasted_lines(text: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    // The quote character of the string the text is in
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quote.is_some() => escaped = true,
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            _ if quote == Some(c) => quote = None,
+            '\n' if quote.is_none() => {
+                lines.push(&text[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    lines.push(&text[start..]);
+
+    lines
+        .into_iter()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .filter(|line| !line.trim().is_empty())
+        .collect()
+}
+
+/// Strips the echoed `$ ` prompt from a scrollback line,
+/// and the `> ` and comment around usage and examples in command help.
+pub(crate) fn strip_decorations(line: &str) -> &str {
+    if let Some(example) = line.trim_start().strip_prefix("> ") {
+        return example
+            .split_once(EXAMPLE_COMMENT)
+            .map_or(example, |(command, _)| command);
+    }
+    line.strip_prefix("$ ").unwrap_or(line)
+}
+
+/// Splits off the part of `line` shown when it is longer than `max_chars`,
+/// returning it along with the number of hidden chars.
+fn truncate_line(line: &str, max_chars: usize) -> Option<(&str, usize)> {
+    if max_chars == 0 || line.len() <= max_chars {
+        return None;
+    }
+    let (end, _) = line.char_indices().nth(max_chars)?;
+    Some((&line[..end], line[end..].chars().count()))
+}
+
+/// Sizes must be finite and not negative.
+fn is_valid_size(size: f32) -> bool {
+    size.is_finite() && size >= 0.
+}
+
+/// Geometry fields of `config` which can't be drawn, with their value.
+fn invalid_geometry(config: &TerminalConfiguration) -> impl Iterator<Item = (&'static str, f32)> {
+    let positions = [("left_pos", config.left_pos), ("top_pos", config.top_pos)];
+    let sizes = [("width", config.width), ("height", config.height)];
+    positions
+        .into_iter()
+        .filter(|(_, pos)| !pos.is_finite())
+        .chain(sizes.into_iter().filter(|(_, size)| !is_valid_size(*size)))
+}
+
+/// Smallest usable terminal size, in points.
+const MIN_WIDTH: f32 = 240.;
+const MIN_HEIGHT: f32 = 120.;
+
+/// Where the terminal is drawn, in points, kept inside `screen`.
+///
+/// The configured rect is shrunk to fit and moved back on screen, unless the terminal drops down from the top.
+/// Invalid sizes are replaced by the minimum usable size, and invalid positions by the top left of the screen.
+/// Screens smaller than the minimum usable size get the whole screen instead.
+fn terminal_rect(config: &TerminalConfiguration, screen: egui::Rect) -> egui::Rect {
+    if screen.width() < MIN_WIDTH || screen.height() < MIN_HEIGHT {
+        return screen;
+    }
+    if let TerminalLayout::TopDropDown { height_fraction } = config.layout {
+        let fraction = match height_fraction.is_finite() {
+            true => height_fraction.clamp(0., 1.),
+            false => 0.,
+        };
+        let height = (screen.height() * fraction).clamp(MIN_HEIGHT, screen.height());
+        return egui::Rect::from_min_size(screen.min, egui::vec2(screen.width(), height));
+    }
+    let size_or = |size: f32, min: f32| match is_valid_size(size) {
+        true => size,
+        false => min,
+    };
+    let pos_or = |pos: f32, min: f32| match pos.is_finite() {
+        true => pos,
+        false => min,
+    };
+    let size = egui::vec2(
+        size_or(config.width, MIN_WIDTH).min(screen.width()),
+        size_or(config.height, MIN_HEIGHT).min(screen.height()),
+    );
+    let pos = egui::pos2(
+        pos_or(config.left_pos, screen.min.x).clamp(screen.min.x, screen.max.x - size.x),
+        pos_or(config.top_pos, screen.min.y).clamp(screen.min.y, screen.max.y - size.y),
+    );
+    egui::Rect::from_min_size(pos, size)
+}
+
+/// Formats `n` with commas between groups of three digits.
+pub(crate) fn group_thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let first = match digits.len() % 3 {
+        0 => 3,
+        len => len,
+    };
+    let mut grouped = digits[..first].to_string();
+    for group in digits.as_bytes()[first..].chunks(3) {
+        grouped.push(',');
+        grouped.push_str(std::str::from_utf8(group).unwrap());
+    }
+    grouped
+}
+
+/// The char index of the input's cursor, or the end of `buf` if it has none.
+fn cursor_index(ctx: &Context, id: Id, buf: &str) -> usize {
+    TextEdit::load_state(ctx, id)
+        .and_then(|state| state.ccursor_range())
+        .map(|range| range.primary.index)
+        .unwrap_or_else(|| buf.chars().count())
+}
+
+fn insert_at_cursor(ctx: &Context, id: Id, buf: &mut String, text: &str) {
+    let cursor = cursor_index(ctx, id, buf);
+    let byte_index = buf
+        .char_indices()
+        .nth(cursor)
+        .map(|(index, _)| index)
+        .unwrap_or(buf.len());
+    buf.insert_str(byte_index, text);
+    set_cursor_pos(ctx, id, cursor + text.chars().count());
+}
+
+/// Shows a pane for each `tail` command, which stays up while the terminal is closed.
+pub(crate) fn terminal_tails(
+    mut window: TerminalWindow,
+    config: Res<TerminalConfiguration>,
+    mut state: ResMut<TerminalState>,
+) {
+    const TAIL_WIDTH: f32 = 400.;
+    const TAIL_HEIGHT: f32 = 200.;
+    const MARGIN: f32 = 10.;
+
+    let Some(ctx) = window.context(&config) else {
+        return;
+    };
+    let terminal = terminal_rect(&config, ctx.available_rect());
+    let mut closed = None;
+    for (index, tail) in state.tails.iter().enumerate() {
+        let mut open = true;
+        let offset = index as f32 * (TAIL_HEIGHT + 3. * MARGIN);
+        egui::Window::new(format!("tail {}", tail.pattern))
+            .open(&mut open)
+            .collapsible(false)
+            .default_pos([terminal.max.x + MARGIN, terminal.min.y + offset])
+            .default_size([TAIL_WIDTH, TAIL_HEIGHT])
+            .frame(Frame::window(&ctx.style()).fill(config.style.background))
+            .show(&ctx, |ui| {
+                ui.visuals_mut().override_text_color = Some(config.style.text);
+                ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom()
+                    .show(ui, |ui| {
+                        for line in &tail.lines {
+                            ui.label(RichText::new(line).monospace());
+                        }
+                    });
+            });
+        if !open {
+            closed = Some(index);
+        }
+    }
+
+    if let Some(index) = closed {
+        state.tails.remove(index);
+    }
 }
 
 fn set_cursor_pos(ctx: &Context, id: Id, pos: usize) {
@@ -126,3 +1825,281 @@ fn set_cursor_pos(ctx: &Context, id: Id, pos: usize) {
         state.store(ctx, id);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::{test_app, validate_level};
+
+    fn screen(width: f32, height: f32) -> egui::Rect {
+        egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(width, height))
+    }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> egui::Rect {
+        egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(width, height))
+    }
+
+    #[test]
+    fn keeps_terminal_rect_on_screen() {
+        let config = TerminalConfiguration::default();
+        assert_eq!(
+            terminal_rect(&config, screen(1920., 1080.)),
+            rect(200., 100., 800., 400.)
+        );
+        assert_eq!(
+            terminal_rect(&config, screen(900., 450.)),
+            rect(100., 50., 800., 400.)
+        );
+        assert_eq!(
+            terminal_rect(&config, screen(640., 360.)),
+            rect(0., 0., 640., 360.)
+        );
+        assert_eq!(
+            terminal_rect(&config, screen(200., 100.)),
+            screen(200., 100.)
+        );
+    }
+
+    #[test]
+    fn recovers_from_invalid_geometry() {
+        let config = TerminalConfiguration {
+            width: -120.,
+            height: f32::NAN,
+            left_pos: f32::NAN,
+            top_pos: -50.,
+            ..Default::default()
+        };
+        assert_eq!(
+            invalid_geometry(&config)
+                .map(|(field, _)| field)
+                .collect::<Vec<_>>(),
+            ["left_pos", "width", "height"]
+        );
+        assert_eq!(
+            terminal_rect(&config, screen(1920., 1080.)),
+            rect(0., 0., 240., 120.)
+        );
+
+        let config = TerminalConfiguration {
+            width: f32::INFINITY,
+            height: 400.,
+            left_pos: f32::NEG_INFINITY,
+            top_pos: 5000.,
+            ..Default::default()
+        };
+        assert_eq!(invalid_geometry(&config).count(), 2);
+        assert_eq!(
+            terminal_rect(&config, screen(1920., 1080.)),
+            rect(0., 680., 240., 400.)
+        );
+        assert_eq!(
+            invalid_geometry(&TerminalConfiguration::default()).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn validates_typed_commands() {
+        let mut registry = CommandRegistry::default();
+        registry.insert("loadlevel", None);
+        registry.set_validator("loadlevel", validate_level);
+
+        assert_eq!(validation_error("loadlevel forest", &registry), None);
+        assert_eq!(
+            validation_error("loadlevel moon", &registry),
+            Some("unknown level 'moon'".to_string())
+        );
+        assert_eq!(validation_error("loadlevel 'moo", &registry), None);
+        assert_eq!(validation_error("teleport moon", &registry), None);
+    }
+
+    #[test]
+    fn finds_help_requests() {
+        let mut registry = CommandRegistry::default();
+        registry.insert("say", None);
+        registry.insert("spawn_enemy", None);
+        registry.add_alias("se", "spawn_enemy");
+        let config = TerminalConfiguration::default();
+        let help = |line| help_request(line, &config, &registry);
+
+        let spawn_enemy = Some(HelpRequest::Command("spawn_enemy".to_string()));
+        assert_eq!(help("spawn_enemy ?"), spawn_enemy);
+        assert_eq!(help("spawn_enemy goblin 3 ?"), spawn_enemy);
+        assert_eq!(help("se ?"), Some(HelpRequest::Command("se".to_string())));
+        assert_eq!(help("teleport ?"), None);
+        assert_eq!(help("spawn_enemy"), None);
+
+        // A quoted question mark is an argument
+        assert_eq!(help(r#"say "?""#), None);
+        assert_eq!(help("say '?'"), None);
+        assert_eq!(help(r#"say "what ?""#), None);
+        assert_eq!(help("say why?"), None);
+
+        assert_eq!(help("?spa"), Some(HelpRequest::Search("spa".to_string())));
+        assert_eq!(help("?"), None);
+        assert_eq!(help("? spa wn"), None);
+
+        let config = TerminalConfiguration {
+            help_suffix: false,
+            help_search: false,
+            ..Default::default()
+        };
+        assert_eq!(help_request("spawn_enemy ?", &config, &registry), None);
+        assert_eq!(help_request("?spa", &config, &registry), None);
+    }
+
+    #[test]
+    fn strips_echoed_prompt() {
+        assert_eq!(strip_decorations("$ spawn_wave 3"), "spawn_wave 3");
+        assert_eq!(strip_decorations("[ok]"), "[ok]");
+        assert_eq!(strip_decorations("$spawn"), "$spawn");
+        assert_eq!(
+            strip_decorations("  > teleport 10 0 5  # move to the tower"),
+            "teleport 10 0 5"
+        );
+        assert_eq!(strip_decorations("  > teleport 0 0 0"), "teleport 0 0 0");
+    }
+
+    #[test]
+    fn colors_lines_with_the_style() {
+        let style = TerminalStyle::light();
+        let echo = TerminalLine::from("$ spawn_wave 3").with_meta(Echo {
+            command: "spawn_wave 3".to_string(),
+            expansion: None,
+            source: EchoSource::Typed,
+            invocation: None,
+        });
+        assert_eq!(line_color(&style, &echo), Some(style.prompt));
+        let error = TerminalLine::from("[error] no wave 3").with_meta(crate::Severity::Error);
+        assert_eq!(line_color(&style, &error), Some(style.severity.error));
+        assert_eq!(line_color(&style, &TerminalLine::from("3 waves")), None);
+        assert_ne!(TerminalStyle::dark(), style);
+    }
+
+    #[test]
+    fn truncates_long_lines_at_char_boundaries() {
+        assert_eq!(truncate_line("short", 10), None);
+        assert_eq!(truncate_line("short", 0), None);
+        assert_eq!(truncate_line("abcdef", 4), Some(("abcd", 2)));
+        assert_eq!(truncate_line("ééééé", 3), Some(("ééé", 2)));
+        // Fits in chars even though it's longer in bytes
+        assert_eq!(truncate_line("éé", 2), None);
+    }
+
+    #[test]
+    fn drops_down_from_the_top() {
+        let mut config = TerminalConfiguration {
+            layout: TerminalLayout::TopDropDown {
+                height_fraction: 0.4,
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            terminal_rect(&config, screen(1920., 1080.)),
+            rect(0., 0., 1920., 432.)
+        );
+        // Resized windows keep the same fraction, but not less than the minimum height
+        assert_eq!(
+            terminal_rect(&config, screen(800., 200.)),
+            rect(0., 0., 800., 120.)
+        );
+        config.layout = TerminalLayout::TopDropDown {
+            height_fraction: f32::NAN,
+        };
+        assert_eq!(
+            terminal_rect(&config, screen(1920., 1080.)),
+            rect(0., 0., 1920., 120.)
+        );
+    }
+
+    #[test]
+    fn splits_pasted_lines() {
+        let text = "set_difficulty 3\r\n\n  \nspawn_wave 2\n";
+        assert_eq!(
+            split_pasted_lines(text),
+            vec!["set_difficulty 3", "spawn_wave 2"]
+        );
+    }
+
+    #[test]
+    fn pasted_quotes_are_not_split() {
+        let text = "log \"first\nsecond\"\nlog \"escaped \\\" quote\nstill quoted\"";
+        assert_eq!(
+            split_pasted_lines(text),
+            vec![
+                "log \"first\nsecond\"",
+                "log \"escaped \\\" quote\nstill quoted\""
+            ]
+        );
+    }
+
+    #[test]
+    fn pasted_single_quotes_are_not_split() {
+        let text = "log 'first\nsecond'\nlog 'say \"hi\nthere\"'\nlog \"it's\"\nspawn_wave 2";
+        assert_eq!(
+            split_pasted_lines(text),
+            vec![
+                "log 'first\nsecond'",
+                "log 'say \"hi\nthere\"'",
+                "log \"it's\"",
+                "spawn_wave 2"
+            ]
+        );
+    }
+
+    #[test]
+    fn uses_the_configured_font() {
+        let mut config = TerminalConfiguration {
+            font_size: 20.,
+            ..Default::default()
+        };
+        assert_eq!(terminal_font(&config), FontId::monospace(20.));
+        config.font = Some(FontFamily::Name("Fira Code".into()));
+        assert_eq!(
+            terminal_font(&config),
+            FontId::new(20., FontFamily::Name("Fira Code".into()))
+        );
+    }
+
+    #[test]
+    fn groups_thousands() {
+        assert_eq!(group_thousands(7), "7");
+        assert_eq!(group_thousands(999), "999");
+        assert_eq!(group_thousands(18203), "18,203");
+        assert_eq!(group_thousands(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn runs_commands_without_a_window() {
+        // Like a closed primary window, or a headless app without `EguiPlugin`
+        let mut app = test_app();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<Windows>()
+            .add_system(terminal_ui)
+            .add_system(terminal_toasts.after(terminal_ui))
+            .add_system(terminal_tails.after(terminal_ui));
+        app.update();
+        app.world
+            .resource_mut::<TerminalConfiguration>()
+            .move_to_open_window = true;
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Grave);
+
+        let invocation = app
+            .world
+            .resource_mut::<TerminalCommandQueue>()
+            .submit("help clear")
+            .unwrap();
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert!(!app.world.resource::<TerminalOpen>().0);
+        let output = invocation.collect_output(&app);
+        assert!(
+            output.iter().any(|line| line.contains("clear")),
+            "{output:?}"
+        );
+    }
+}
@@ -13,6 +13,10 @@ pub enum ValueType {
     Float,
     /// Bool arg type
     Bool,
+    /// Socket address arg type
+    Addr,
+    /// IP address arg type
+    Ip,
 }
 
 impl fmt::Display for ValueType {
@@ -22,6 +26,8 @@ impl fmt::Display for ValueType {
             ValueType::Int => write!(f, "int"),
             ValueType::Float => write!(f, "float"),
             ValueType::Bool => write!(f, "bool"),
+            ValueType::Addr => write!(f, "addr"),
+            ValueType::Ip => write!(f, "ip"),
         }
     }
 }
@@ -31,6 +37,11 @@ impl fmt::Display for ValueType {
 pub enum FromValueError {
     /// Not enough arguments provided
     NotEnoughArgs,
+    /// Too many arguments provided
+    TooManyArgs {
+        /// Maximum number of arguments
+        max: usize,
+    },
     /// Unexpected argument type
     UnexpectedArgType {
         /// Argument number, starting from 0
@@ -55,6 +66,9 @@ impl fmt::Display for FromValueError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             FromValueError::NotEnoughArgs => write!(f, "[error] not enough arguments provided"),
+            FromValueError::TooManyArgs { max } => {
+                write!(f, "[error] too many arguments provided (max {max})")
+            }
             FromValueError::UnexpectedArgType {
                 arg_num,
                 expected,
@@ -78,29 +92,89 @@ impl fmt::Display for FromValueError {
 }
 
 /// Parse from argument value.
-pub trait FromValue<'a>: Sized {
+///
+/// Implement [`FromValue::from_value`], and [`FromValue::from_value_owned`] too if `Self`
+/// can take the string data of the value instead of copying it.
+pub trait FromValue: Sized {
     /// Parse a [`ValueRawOwned`] into `Self`.
-    fn from_value(value: &'a ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError>;
+    fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError>;
+
+    /// Parse a [`ValueRawOwned`] into `Self`, taking ownership of it.
+    ///
+    /// Commands own their arguments while parsing, so this is what they call.
+    /// Defaults to [`FromValue::from_value`].
+    fn from_value_owned(value: ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        Self::from_value(&value, arg_num)
+    }
 
     /// Parse a iterator of [`ValueRawOwned`] into `Self`.
-    fn from_value_iter<I>(value: &mut I, arg_num: u8) -> Result<Self, FromValueError>
+    fn from_value_iter<I>(values: &mut I, arg_num: u8) -> Result<Self, FromValueError>
     where
-        I: Iterator<Item = &'a ValueRawOwned>,
+        I: Iterator<Item = ValueRawOwned>,
     {
-        Self::from_value(value.next().ok_or(FromValueError::NotEnoughArgs)?, arg_num)
+        Self::from_value_owned(values.next().ok_or(FromValueError::NotEnoughArgs)?, arg_num)
     }
 }
 
-impl<'a> FromValue<'a> for Value {
-    fn from_value(value: &'a ValueRawOwned, _arg_num: u8) -> Result<Self, FromValueError> {
+/// Convert into argument values, the reverse of [`FromValue`].
+///
+/// Used by [`IntoValues`](crate::IntoValues) to turn typed commands back into the values they are parsed from.
+/// Values are the ones typing `Self` in the terminal would give, so formatting them gives a line
+/// which parses back to the same values.
+///
+/// # Example
+///
+/// ```
+/// # use leafwing_terminal::{IntoValue, ValueRawOwned};
+/// #
+/// struct Team(String);
+///
+/// impl IntoValue for Team {
+///     fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+///         self.0.push_values(values);
+///     }
+/// }
+/// ```
+pub trait IntoValue {
+    /// Push the values of `self`, which [`FromValue::from_value_iter`] parses back into `self`.
+    fn push_values(self, values: &mut Vec<ValueRawOwned>);
+}
+
+impl FromValue for Value {
+    fn from_value(value: &ValueRawOwned, _arg_num: u8) -> Result<Self, FromValueError> {
         Ok(value.clone().into())
     }
+
+    fn from_value_owned(value: ValueRawOwned, _arg_num: u8) -> Result<Self, FromValueError> {
+        Ok(value.into())
+    }
 }
 
-impl<'a> FromValue<'a> for ValueRawOwned {
-    fn from_value(value: &'a ValueRawOwned, _arg_num: u8) -> Result<Self, FromValueError> {
+impl IntoValue for Value {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        match self {
+            Value::String(s) => s.push_values(values),
+            Value::Int(num) => num.push_values(values),
+            Value::Float(num) => num.push_values(values),
+            Value::Bool(b) => b.push_values(values),
+        }
+    }
+}
+
+impl FromValue for ValueRawOwned {
+    fn from_value(value: &ValueRawOwned, _arg_num: u8) -> Result<Self, FromValueError> {
         Ok(value.clone())
     }
+
+    fn from_value_owned(value: ValueRawOwned, _arg_num: u8) -> Result<Self, FromValueError> {
+        Ok(value)
+    }
+}
+
+impl IntoValue for ValueRawOwned {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        values.push(self);
+    }
 }
 
 macro_rules! unexpected_arg_type {
@@ -113,7 +187,7 @@ macro_rules! unexpected_arg_type {
     };
 }
 
-impl FromValue<'_> for String {
+impl FromValue for String {
     fn from_value(value: &ValueRawOwned, _arg_num: u8) -> Result<Self, FromValueError> {
         match value {
             ValueRawOwned::String(s) => Ok(s.clone()),
@@ -122,44 +196,96 @@ impl FromValue<'_> for String {
             | ValueRawOwned::Bool(_, raw) => Ok(raw.to_string()),
         }
     }
+
+    fn from_value_owned(value: ValueRawOwned, _arg_num: u8) -> Result<Self, FromValueError> {
+        match value {
+            ValueRawOwned::String(s)
+            | ValueRawOwned::Int(_, s)
+            | ValueRawOwned::Float(_, s)
+            | ValueRawOwned::Bool(_, s) => Ok(s),
+        }
+    }
+}
+
+impl IntoValue for String {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        values.push(ValueRawOwned::String(self));
+    }
 }
 
-macro_rules! impl_from_int_value {
+macro_rules! impl_int_value {
     ($ty: ty) => {
-        impl FromValue<'_> for $ty {
+        /// Negative numbers and numbers too large for an `i64` are typed as words,
+        /// so strings holding one of those are accepted too.
+        impl FromValue for $ty {
             fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
-                match value {
-                    ValueRawOwned::String(_) => Err(unexpected_arg_type!(Int, String, arg_num)),
-                    ValueRawOwned::Int(num, _) => {
-                        let max = <$ty>::MAX as i64;
-                        if *num > max {
-                            Err(FromValueError::ValueTooLarge { arg_num, max })
-                        } else {
-                            Ok(*num as $ty)
-                        }
+                let num = match value {
+                    ValueRawOwned::Int(num, _) => i128::from(*num),
+                    ValueRawOwned::String(s) if s.starts_with('-') || s.parse::<i64>().is_err() => {
+                        s.parse::<i128>()
+                            .map_err(|_| unexpected_arg_type!(Int, String, arg_num))?
                     }
-                    ValueRawOwned::Float(_, _) => Err(unexpected_arg_type!(Int, Float, arg_num)),
-                    ValueRawOwned::Bool(_, _) => Err(unexpected_arg_type!(Int, Bool, arg_num)),
-                }
+                    ValueRawOwned::String(_) => {
+                        return Err(unexpected_arg_type!(Int, String, arg_num))
+                    }
+                    ValueRawOwned::Float(_, _) => {
+                        return Err(unexpected_arg_type!(Int, Float, arg_num))
+                    }
+                    ValueRawOwned::Bool(_, _) => {
+                        return Err(unexpected_arg_type!(Int, Bool, arg_num))
+                    }
+                };
+                <$ty>::try_from(num).map_err(|_| match num < 0 {
+                    true if <$ty>::MIN == 0 => FromValueError::Custom(format!(
+                        "number must not be negative for arg #{}",
+                        arg_num + 1
+                    )),
+                    true => FromValueError::Custom(format!(
+                        "number is too small for arg #{} (min {})",
+                        arg_num + 1,
+                        <$ty>::MIN
+                    )),
+                    false => {
+                        let max = i64::try_from(<$ty>::MAX).unwrap_or(i64::MAX);
+                        FromValueError::ValueTooLarge { arg_num, max }
+                    }
+                })
+            }
+        }
+
+        /// Negative numbers and numbers too large for an `i64` are strings, as if they were typed.
+        impl IntoValue for $ty {
+            fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+                let raw = self.to_string();
+                values.push(match i64::try_from(self as i128) {
+                    Ok(num) if num >= 0 => ValueRawOwned::Int(num, raw),
+                    _ => ValueRawOwned::String(raw),
+                });
             }
         }
     };
 }
 
-impl_from_int_value!(i8);
-impl_from_int_value!(i16);
-impl_from_int_value!(i32);
-impl_from_int_value!(i64);
-impl_from_int_value!(isize);
-impl_from_int_value!(u8);
-impl_from_int_value!(u16);
-impl_from_int_value!(u32);
-impl_from_int_value!(u64);
-impl_from_int_value!(usize);
+impl_int_value!(i8);
+impl_int_value!(i16);
+impl_int_value!(i32);
+impl_int_value!(i64);
+impl_int_value!(isize);
+impl_int_value!(u8);
+impl_int_value!(u16);
+impl_int_value!(u32);
+impl_int_value!(u64);
+impl_int_value!(usize);
 
-impl FromValue<'_> for f64 {
+/// Negative numbers are typed as words, so strings holding a finite negative number, such as `-0.5`, are accepted too.
+impl FromValue for f64 {
     fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
         match value {
+            ValueRawOwned::String(s) if s.starts_with('-') => s
+                .parse()
+                .ok()
+                .filter(|num: &f64| num.is_finite())
+                .ok_or(unexpected_arg_type!(Float, String, arg_num)),
             ValueRawOwned::String(_) => Err(unexpected_arg_type!(Float, String, arg_num)),
             ValueRawOwned::Int(num, _) => Ok(*num as f64),
             ValueRawOwned::Float(num, _) => Ok(*num),
@@ -168,7 +294,22 @@ impl FromValue<'_> for f64 {
     }
 }
 
-impl FromValue<'_> for bool {
+/// Negative numbers are strings, as if they were typed.
+///
+/// Non-finite numbers are floats, which typed values never are,
+/// so they parse back from these values but not from a typed `NaN` or `-inf`.
+impl IntoValue for f64 {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        // `{:?}` keeps the shortest form which parses back to the same number, such as `1e20`
+        let raw = format!("{self:?}");
+        values.push(match self.is_finite() && self.is_sign_negative() {
+            true => ValueRawOwned::String(raw),
+            false => ValueRawOwned::Float(self, raw),
+        });
+    }
+}
+
+impl FromValue for bool {
     fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
         match value {
             ValueRawOwned::String(_) => Err(unexpected_arg_type!(Bool, String, arg_num)),
@@ -179,21 +320,131 @@ impl FromValue<'_> for bool {
     }
 }
 
-impl<'a, T> FromValue<'a> for Option<T>
-where
-    T: FromValue<'a>,
-{
-    fn from_value(value: &'a ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+impl IntoValue for bool {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        values.push(ValueRawOwned::Bool(self, self.to_string()));
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
         Ok(Some(T::from_value(value, arg_num)?))
     }
 
-    fn from_value_iter<I>(value: &mut I, arg_num: u8) -> Result<Self, FromValueError>
+    fn from_value_owned(value: ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        Ok(Some(T::from_value_owned(value, arg_num)?))
+    }
+
+    fn from_value_iter<I>(values: &mut I, arg_num: u8) -> Result<Self, FromValueError>
     where
-        I: Iterator<Item = &'a ValueRawOwned>,
+        I: Iterator<Item = ValueRawOwned>,
     {
-        value
+        values
             .next()
-            .map(|value| T::from_value(value, arg_num))
+            .map(|value| T::from_value_owned(value, arg_num))
             .transpose()
     }
 }
+
+/// Pushes nothing for `None`.
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        if let Some(value) = self {
+            value.push_values(values);
+        }
+    }
+}
+
+/// Takes all the remaining arguments.
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        Ok(vec![T::from_value(value, arg_num)?])
+    }
+
+    fn from_value_owned(value: ValueRawOwned, arg_num: u8) -> Result<Self, FromValueError> {
+        Ok(vec![T::from_value_owned(value, arg_num)?])
+    }
+
+    fn from_value_iter<I>(values: &mut I, arg_num: u8) -> Result<Self, FromValueError>
+    where
+        I: Iterator<Item = ValueRawOwned>,
+    {
+        values
+            .enumerate()
+            .map(|(i, value)| T::from_value_owned(value, arg_num.saturating_add(i as u8)))
+            .collect()
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn push_values(self, values: &mut Vec<ValueRawOwned>) {
+        for value in self {
+            value.push_values(values);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_reports_ints_out_of_range() {
+        let negative = ValueRawOwned::String("-3".to_string());
+        assert_eq!(
+            u32::from_value(&negative, 0),
+            Err(FromValueError::Custom(
+                "number must not be negative for arg #1".to_string()
+            ))
+        );
+        assert_eq!(
+            i8::from_value(&ValueRawOwned::String("-300".to_string()), 1),
+            Err(FromValueError::Custom(
+                "number is too small for arg #2 (min -128)".to_string()
+            ))
+        );
+        assert_eq!(
+            u8::from_value(&ValueRawOwned::Int(300, "300".to_string()), 0),
+            Err(FromValueError::ValueTooLarge {
+                arg_num: 0,
+                max: 255
+            })
+        );
+    }
+
+    #[test]
+    fn it_only_takes_numbers_from_strings_typed_as_words() {
+        let string = |s: &str| ValueRawOwned::String(s.to_string());
+        assert_eq!(i32::from_value(&string("-5"), 0), Ok(-5));
+        assert_eq!(
+            u64::from_value(&string("18446744073709551615"), 0),
+            Ok(u64::MAX)
+        );
+        assert_eq!(f64::from_value(&string("-0.5"), 0), Ok(-0.5));
+
+        let not_a_number = |received| {
+            Err(FromValueError::UnexpectedArgType {
+                arg_num: 0,
+                expected: ValueType::Float,
+                received,
+            })
+        };
+        assert_eq!(
+            f64::from_value(&string("nan"), 0),
+            not_a_number(ValueType::String)
+        );
+        assert_eq!(
+            f64::from_value(&string("inf"), 0),
+            not_a_number(ValueType::String)
+        );
+        assert_eq!(
+            f64::from_value(&string("-inf"), 0),
+            not_a_number(ValueType::String)
+        );
+        assert_eq!(
+            f64::from_value(&string("5"), 0),
+            not_a_number(ValueType::String)
+        );
+        assert!(i32::from_value(&string("5"), 0).is_err());
+    }
+}
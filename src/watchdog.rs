@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use bevy::ecs::event::{Events, ManualEventReader};
+use bevy::prelude::*;
+
+use crate::strings::fill;
+use crate::terminal::TerminalCommandBuffer;
+use crate::{PrintTerminalLine, TerminalConfiguration, TerminalInvocation, TerminalStrings};
+
+/// How an invocation of a command ended, see [`TerminalCommandCompleted`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandOutcome {
+    /// The command printed `[ok]`, or was marked done with [`TerminalCommand::done`](crate::TerminalCommand::done)
+    Ok,
+    /// The command printed `[failed]`
+    Failed,
+    /// The command printed neither within [`TerminalConfiguration::outcome_timeout`] frames
+    NoExplicitOutcome,
+}
+
+/// Event sent when an invocation accepted by its command system ends.
+///
+/// Invocations whose arguments were rejected, or which were on cooldown, are never accepted and aren't reported.
+/// Outcomes printed after an invocation was completed without one are ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TerminalCommandCompleted {
+    /// The invocation which ended
+    pub invocation: TerminalInvocation,
+    /// How it ended
+    pub outcome: CommandOutcome,
+}
+
+/// Invocations accepted by their command system which haven't printed their outcome yet.
+#[derive(Default)]
+pub(crate) struct PendingOutcomes {
+    /// Name of the command and frames waited so far, by invocation
+    pending: HashMap<TerminalInvocation, (&'static str, usize)>,
+}
+
+impl PendingOutcomes {
+    /// Returns true if `invocation` was accepted and hasn't completed yet.
+    pub(crate) fn is_pending(&self, invocation: TerminalInvocation) -> bool {
+        self.pending.contains_key(&invocation)
+    }
+}
+
+/// Metadata of the hint printed for a command which didn't print its outcome, which is drawn dimmed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct MissingOutcomeHint;
+
+/// Sends [`TerminalCommandCompleted`] as invocations print their outcome,
+/// completing those which take longer than [`TerminalConfiguration::outcome_timeout`] frames without one.
+pub(crate) fn watch_outcomes(
+    config: Res<TerminalConfiguration>,
+    strings: Res<TerminalStrings>,
+    mut buffer: ResMut<TerminalCommandBuffer>,
+    mut outcomes: ResMut<PendingOutcomes>,
+    mut terminal_line: ResMut<Events<PrintTerminalLine>>,
+    mut printed: Local<ManualEventReader<PrintTerminalLine>>,
+    mut completed: EventWriter<TerminalCommandCompleted>,
+) {
+    let pending = &mut outcomes.pending;
+    for (invocation, command) in buffer.take_started() {
+        pending.insert(invocation, (command, 0));
+    }
+
+    let mut ended: Vec<_> = buffer
+        .take_finished()
+        .into_iter()
+        .map(|invocation| (invocation, CommandOutcome::Ok))
+        .collect();
+    for line in printed.iter(&terminal_line) {
+        // Matched by text, like toasts, so outcomes printed by hand count too
+        let outcome = match &line.line {
+            line if line.starts_with(&strings.ok) => CommandOutcome::Ok,
+            line if line.starts_with(&strings.failed) => CommandOutcome::Failed,
+            _ => continue,
+        };
+        if let Some(invocation) = line.get_meta::<TerminalInvocation>() {
+            ended.push((*invocation, outcome));
+        }
+    }
+    for (invocation, outcome) in ended {
+        if pending.remove(&invocation).is_some() {
+            completed.send(TerminalCommandCompleted {
+                invocation,
+                outcome,
+            });
+        }
+    }
+
+    if config.outcome_timeout == 0 {
+        return;
+    }
+    let mut timed_out = Vec::new();
+    pending.retain(|invocation, (command, frames)| {
+        *frames += 1;
+        if *frames < config.outcome_timeout {
+            return true;
+        }
+        timed_out.push((*invocation, *command));
+        false
+    });
+    for (invocation, command) in timed_out {
+        completed.send(TerminalCommandCompleted {
+            invocation,
+            outcome: CommandOutcome::NoExplicitOutcome,
+        });
+        if cfg!(debug_assertions) {
+            let hint = fill(&strings.missing_outcome, &[&command]);
+            terminal_line.send(
+                PrintTerminalLine::new(hint)
+                    .with_meta(MissingOutcomeHint)
+                    .with_meta(invocation),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as leafwing_terminal;
+    use crate::harness::test_app;
+    use crate::{AddTerminalCommand, TerminalCommand, TerminalCommandQueue};
+
+    /// Rebuilds the navigation mesh
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "rebuild_navmesh")]
+    struct RebuildNavmeshCommand;
+
+    fn rebuild_navmesh_command(mut rebuild: TerminalCommand<RebuildNavmeshCommand>) {
+        if rebuild.take().is_some() {
+            rebuild.reply("rebuilt 12 tiles");
+        }
+    }
+
+    /// Saves the game
+    #[derive(TerminalCommand)]
+    #[terminal_command(name = "save")]
+    struct SaveCommand;
+
+    /// The save in progress, with the frames since it started
+    #[derive(Default)]
+    struct PendingSave(Option<(TerminalInvocation, usize)>);
+
+    fn save_command(mut save: TerminalCommand<SaveCommand>, mut pending: ResMut<PendingSave>) {
+        if save.take().is_some() {
+            pending.0 = save.invocation().map(|invocation| (invocation, 0));
+        }
+    }
+
+    /// Fails the save a few frames after it runs, like a command waiting on a save task
+    fn finish_save(
+        mut pending: ResMut<PendingSave>,
+        mut terminal_line: EventWriter<PrintTerminalLine>,
+    ) {
+        if let Some((invocation, frames)) = pending.0.as_mut() {
+            *frames += 1;
+            if *frames == 3 {
+                terminal_line
+                    .send(PrintTerminalLine::new("[failed]".to_string()).with_meta(*invocation));
+                pending.0 = None;
+            }
+        }
+    }
+
+    /// Every completion so far, as events only last two frames
+    #[derive(Default)]
+    struct Completed(Vec<TerminalCommandCompleted>);
+
+    fn record_completed(
+        mut events: EventReader<TerminalCommandCompleted>,
+        mut completed: ResMut<Completed>,
+    ) {
+        completed.0.extend(events.iter().copied());
+    }
+
+    fn app() -> App {
+        let mut app = test_app();
+        app.add_terminal_command::<RebuildNavmeshCommand, _, _>(rebuild_navmesh_command)
+            .add_terminal_command::<SaveCommand, _, _>(save_command)
+            .init_resource::<PendingSave>()
+            .add_system(finish_save.after(save_command))
+            .init_resource::<Completed>()
+            .add_system_to_stage(CoreStage::Last, record_completed);
+        app.world
+            .resource_mut::<TerminalConfiguration>()
+            .outcome_timeout = 5;
+        app.update();
+        app
+    }
+
+    fn completed(app: &App) -> &[TerminalCommandCompleted] {
+        &app.world.resource::<Completed>().0
+    }
+
+    #[test]
+    fn it_completes_commands_without_an_outcome() {
+        let mut app = app();
+        let invocation = app
+            .world
+            .resource_mut::<TerminalCommandQueue>()
+            .submit("rebuild_navmesh")
+            .unwrap();
+        for _ in 0..4 {
+            app.update();
+        }
+        assert_eq!(completed(&app), []);
+
+        // The hint reaches the scrollback in the frame after the timeout
+        app.update();
+        app.update();
+        assert_eq!(
+            completed(&app),
+            [TerminalCommandCompleted {
+                invocation,
+                outcome: CommandOutcome::NoExplicitOutcome,
+            }]
+        );
+        let output = invocation.collect_output(&app);
+        assert_eq!(output[0], "rebuilt 12 tiles");
+        if cfg!(debug_assertions) {
+            assert_eq!(
+                output[1],
+                "hint: 'rebuild_navmesh' printed no outcome, call ok() or failed() once it is done"
+            );
+        }
+    }
+
+    #[test]
+    fn it_waits_for_outcomes_printed_later() {
+        let mut app = app();
+        let invocation = app
+            .world
+            .resource_mut::<TerminalCommandQueue>()
+            .submit("save")
+            .unwrap();
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(
+            completed(&app),
+            [TerminalCommandCompleted {
+                invocation,
+                outcome: CommandOutcome::Failed,
+            }]
+        );
+
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(invocation.collect_output(&app), ["[failed]"]);
+    }
+}
@@ -0,0 +1,124 @@
+//! Catches accidental clones in the hot paths for printed lines and command arguments.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use bevy::ecs::event::Events;
+use bevy::prelude::*;
+use leafwing_terminal::{
+    AddTerminalCommand, PrintTerminalLine, TerminalCommand, TerminalCommandQueue,
+    TerminalConfiguration, TerminalCorePlugin, TerminalState,
+};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Tests run in parallel, so each holds this while counting to keep the others' allocations out
+static COUNTING: Mutex<()> = Mutex::new(());
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[test]
+fn printing_lines_stays_within_allocation_budget() {
+    const LINES: usize = 10_000;
+    let _counting = COUNTING.lock().unwrap();
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(TerminalCorePlugin);
+    // Keep every line and add them all at once, so none are dropped or left queued while counting
+    let mut config = app.world.resource_mut::<TerminalConfiguration>();
+    config.scrollback_size = 0;
+    config.print_queue_size = 0;
+    config.print_lines_per_frame = 0;
+    app.update();
+    app.update();
+
+    let mut events = app.world.resource_mut::<Events<PrintTerminalLine>>();
+    for i in 0..LINES {
+        events.send(PrintTerminalLine::new(format!("line {i}")));
+    }
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    app.update();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    assert_eq!(
+        app.world.resource::<TerminalState>().scrollback().len(),
+        LINES
+    );
+    // Storing each line costs one allocation; leave generous room for the rest of the frame
+    assert!(
+        allocations < LINES * 2 + 1_000,
+        "{allocations} allocations to print {LINES} lines"
+    );
+}
+
+/// Keeps a note
+#[derive(TerminalCommand)]
+#[terminal_command(name = "note")]
+struct NoteCommand {
+    text: String,
+}
+
+#[derive(Default)]
+struct Notes(Vec<String>);
+
+fn note_command(mut note: TerminalCommand<NoteCommand>, mut notes: ResMut<Notes>) {
+    if let Some(NoteCommand { text }) = note.take() {
+        notes.0.push(text);
+    }
+}
+
+#[test]
+fn string_arguments_are_copied_once() {
+    const LEN: usize = 1 << 20;
+    let _counting = COUNTING.lock().unwrap();
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(TerminalCorePlugin)
+        .init_resource::<Notes>()
+        .add_terminal_command::<NoteCommand, _, _>(note_command);
+    app.update();
+    app.update();
+
+    let line = format!("note \"{}\"", "a".repeat(LEN));
+    app.world
+        .resource_mut::<TerminalCommandQueue>()
+        .submit(&line)
+        .unwrap();
+
+    let before = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    app.update();
+    let allocated = ALLOCATED_BYTES.load(Ordering::Relaxed) - before;
+
+    assert_eq!(app.world.resource::<Notes>().0[0].len(), LEN);
+    // The event keeps the parsed argument, and the command system takes the buffered copy
+    assert!(
+        allocated < LEN + LEN / 2,
+        "{allocated} bytes allocated to run a command with a {LEN} byte argument"
+    );
+}
@@ -0,0 +1,8 @@
+//! Compiles uses of the derive macros from outside the crate, to catch generated code
+//! naming items outside of `leafwing_terminal::__private`.
+
+#[test]
+fn derives_compile_from_other_crates() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/*.rs");
+}
@@ -0,0 +1,45 @@
+use leafwing_terminal::{CommandArgGroup, TerminalCommand};
+
+/// A position in the world
+#[derive(CommandArgGroup)]
+struct Position {
+    /// East-west
+    x: f64,
+    /// North-south
+    z: f64,
+}
+
+/// Moves the player
+#[derive(TerminalCommand)]
+#[terminal_command(
+    name = "teleport",
+    cooldown = "2s",
+    destructive,
+    deprecated = "use 'warp' instead",
+    example("teleport 10 5", "go to the tower")
+)]
+struct TeleportCommand {
+    /// Where to go
+    #[terminal_command(flatten)]
+    to: Position,
+    /// Floor to arrive on
+    #[terminal_command(default = 0)]
+    floor: Option<i64>,
+}
+
+fn main() {
+    // Imported only here, so the derives above can't rely on them being in scope
+    use leafwing_terminal::{CommandArgs, CommandHelp, CommandName, IntoValues};
+
+    assert_eq!(TeleportCommand::command_name(), "teleport");
+    let help = TeleportCommand::command_help().unwrap();
+    assert_eq!(help.args.len(), 3);
+    assert_eq!(help.args[2].default.as_deref(), Some("0"));
+    assert_eq!(help.deprecated.as_deref(), Some("use 'warp' instead"));
+    let values = TeleportCommand {
+        to: Position { x: 10., z: 5. },
+        floor: None,
+    }
+    .into_values();
+    assert!(TeleportCommand::from_values(values).is_ok());
+}